@@ -42,7 +42,7 @@ fn common(initial_buffer: &mut dyn WriteBuffer) -> Result<(), Box<dyn std::error
                 GET_ADAPTERS_ADDRESSES_FLAGS(0),
                 None,
                 Some(argument.pointer()),
-                argument.size(),
+                argument.size_mut(),
             )
         });
 
@@ -50,7 +50,7 @@ fn common(initial_buffer: &mut dyn WriteBuffer) -> Result<(), Box<dyn std::error
         let fill_buffer_action = rv.to_result(&mut argument)?;
 
         // Apply the action
-        if argument.apply(fill_buffer_action) {
+        if argument.try_apply(fill_buffer_action)? {
             break;
         }
     }
@@ -58,11 +58,12 @@ fn common(initial_buffer: &mut dyn WriteBuffer) -> Result<(), Box<dyn std::error
     let frozen_buffer = growable_buffer.freeze();
     let p = frozen_buffer.pointer();
     println!("pointer = {:?}, size = {}", p, frozen_buffer.size());
-    if let Some(mut p) = p {
-        while p != std::ptr::null() {
-            println!("FriendlyName = {}", unsafe { (*p).FriendlyName.display() });
-            p = unsafe { (*p).Next };
-        }
+    // IP_ADAPTER_ADDRESSES_LH is integers, pointers, and unions.
+    let adapters = unsafe { frozen_buffer.iter_linked(|adapter| adapter.Next) };
+    for adapter in adapters {
+        println!("FriendlyName = {}", unsafe {
+            adapter.FriendlyName.display()
+        });
     }
     println!();
 
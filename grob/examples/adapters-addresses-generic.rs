@@ -27,19 +27,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     GET_ADAPTERS_ADDRESSES_FLAGS(0),
                     None,
                     Some(argument.pointer()),
-                    argument.size(),
+                    argument.size_mut(),
                 )
             })
         },
         |frozen_buffer| {
-            let mut rv = Vec::new();
-            if let Some(mut p) = frozen_buffer.pointer() {
-                while p != std::ptr::null() {
-                    rv.push(format!("{}", unsafe { (*p).FriendlyName.display() }));
-                    p = unsafe { (*p).Next };
-                }
-            }
-            Ok(rv)
+            // IP_ADAPTER_ADDRESSES_LH is integers, pointers, and unions.
+            let adapters = unsafe { frozen_buffer.iter_linked(|adapter| adapter.Next) };
+            Ok(adapters
+                .map(|adapter| format!("{}", unsafe { adapter.FriendlyName.display() }))
+                .collect::<Vec<String>>())
         },
     )?;
     names.sort();
@@ -25,7 +25,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Get the current computer name so it can be restored
     let original_name = winapi_string(true, |argument| {
-        RvIsError::new(unsafe { GetComputerNameW(argument.pointer(), argument.size()) })
+        RvIsError::new(unsafe { GetComputerNameW(argument.pointer(), argument.size_mut()) })
     })?
     .unwrap();
     println!("GetComputerNameW returned {}", original_name);
@@ -17,8 +17,8 @@ use windows::Win32::Foundation::HMODULE;
 use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
 
 use grob::{
-    FillBufferAction, GrowForStoredIsReturned, GrowStrategy, GrowableBuffer, RvIsSize, StackBuffer,
-    ToResult, WriteBuffer, CAPACITY_FOR_PATHS,
+    grob_call, FillBufferAction, GrowForStoredIsReturned, GrowStrategy, GrowableBuffer, RvIsSize,
+    StackBuffer, WriteBuffer, CAPACITY_FOR_PATHS,
 };
 
 struct PrintNextCapacity {
@@ -53,27 +53,25 @@ fn common(initial_buffer: &mut dyn WriteBuffer) -> Result<(), Box<dyn std::error
 
     // Loop until the call to GetModuleFileNameW fails with an error or succeeds because the buffer
     // has enough space.
-    let mut growable_buffer = GrowableBuffer::<u16, PWSTR>::new(initial_buffer, &grow_strategy);
-    loop {
-        let mut argument = growable_buffer.argument();
-        let rv = unsafe { GetModuleFileNameW(HMODULE(0), argument.as_mut_slice()) };
-        let rv: RvIsSize = rv.into();
-        let result = rv.to_result(&mut argument);
-        match result? {
+    let frozen_buffer = grob_call!(
+        GrowableBuffer::<u16, PWSTR>::new(initial_buffer, &grow_strategy),
+        RvIsSize::new,
+        |argument| unsafe { GetModuleFileNameW(HMODULE(0), argument.as_mut_slice()) },
+        |action| match action {
             FillBufferAction::Commit => {
                 argument.commit();
-                break;
+                true
             }
             FillBufferAction::Grow => {
-                argument.grow();
+                argument.grow()?;
+                false
             }
             FillBufferAction::NoData => {
                 argument.commit_no_data();
-                break;
+                true
             }
         }
-    }
-    let frozen_buffer = growable_buffer.freeze();
+    );
     let path = frozen_buffer.to_path_buf().unwrap();
     println!("GetModuleFileNameW returned \"{}\"", path.display());
     Ok(())
@@ -26,7 +26,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("GetModuleFileNameW returned {}", path.display());
 
     let path = winapi_path_buf(|argument| {
-        RvIsSize::new(unsafe { GetModuleFileNameExW(GetCurrentProcess(), HMODULE(0), argument.as_mut_slice()) })
+        RvIsSize::new(unsafe {
+            GetModuleFileNameExW(GetCurrentProcess(), HMODULE(0), argument.as_mut_slice())
+        })
     })?;
     println!("GetModuleFileNameExW returned {}", path.display());
 
@@ -36,7 +36,7 @@ fn common(initial_buffer: &mut dyn WriteBuffer) -> Result<(), Box<dyn std::error
             GetLogicalProcessorInformationEx(
                 RelationGroup,
                 Some(argument.pointer()),
-                argument.size(),
+                argument.size_mut(),
             )
         });
 
@@ -44,28 +44,28 @@ fn common(initial_buffer: &mut dyn WriteBuffer) -> Result<(), Box<dyn std::error
         let fill_buffer_action = rv.to_result(&mut argument)?;
 
         // Apply the action
-        if argument.apply(fill_buffer_action) {
+        if argument.try_apply(fill_buffer_action)? {
             break;
         }
     }
     // Do something with the data
     let frozen_buffer = growable_buffer.freeze();
-    if let Some(p) = frozen_buffer.pointer() {
-        let r = unsafe { (*p).Relationship };
-        println!("Relationship = {:?}", r); // Has to be RelationGroup
-        println!("Size = {:?}", unsafe { (*p).Size });
-        println!("MaximumGroupCount = {:?}", unsafe {
-            (*p).Anonymous.Group.MaximumGroupCount
-        });
-        println!("ActiveGroupCount = {:?}", unsafe {
-            (*p).Anonymous.Group.ActiveGroupCount
-        });
-        println!("ActiveProcessorCount = {:?}", unsafe {
-            (*p).Anonymous.Group.GroupInfo[0].ActiveProcessorCount
-        });
-        println!("MaximumProcessorCount = {:?}", unsafe {
-            (*p).Anonymous.Group.GroupInfo[0].MaximumProcessorCount
-        });
+    // SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX is integers and unions.
+    let infos = unsafe { frozen_buffer.iter_sized(|info| info.Size as usize) };
+    for info in infos {
+        let group = unsafe { &info.Anonymous.Group };
+        println!("Relationship = {:?}", info.Relationship); // Has to be RelationGroup
+        println!("Size = {:?}", info.Size);
+        println!("MaximumGroupCount = {:?}", group.MaximumGroupCount);
+        println!("ActiveGroupCount = {:?}", group.ActiveGroupCount);
+        println!(
+            "ActiveProcessorCount = {:?}",
+            group.GroupInfo[0].ActiveProcessorCount
+        );
+        println!(
+            "MaximumProcessorCount = {:?}",
+            group.GroupInfo[0].MaximumProcessorCount
+        );
         println!();
     }
     Ok(())
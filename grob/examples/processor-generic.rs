@@ -23,31 +23,32 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 GetLogicalProcessorInformationEx(
                     RelationGroup,
                     Some(argument.pointer()),
-                    argument.size(),
+                    argument.size_mut(),
                 )
             })
         },
         |frozen_buffer| {
-            if let Some(p) = frozen_buffer.pointer() {
-                let r = unsafe { (*p).Relationship };
-                let mpc = unsafe { (*p).Anonymous.Group.GroupInfo[0].MaximumProcessorCount };
-                println!("Relationship = {:?}", r); // Has to be RelationGroup
-                println!("Size = {:?}", unsafe { (*p).Size });
-                println!("MaximumGroupCount = {:?}", unsafe {
-                    (*p).Anonymous.Group.MaximumGroupCount
-                });
-                println!("ActiveGroupCount = {:?}", unsafe {
-                    (*p).Anonymous.Group.ActiveGroupCount
-                });
-                println!("ActiveProcessorCount = {:?}", unsafe {
-                    (*p).Anonymous.Group.GroupInfo[0].ActiveProcessorCount
-                });
-                println!("MaximumProcessorCount = {:?}", mpc);
+            let mut mpc = None;
+            // SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX is integers and unions.
+            let infos = unsafe { frozen_buffer.iter_sized(|info| info.Size as usize) };
+            for info in infos {
+                let group = unsafe { &info.Anonymous.Group };
+                println!("Relationship = {:?}", info.Relationship); // Has to be RelationGroup
+                println!("Size = {:?}", info.Size);
+                println!("MaximumGroupCount = {:?}", group.MaximumGroupCount);
+                println!("ActiveGroupCount = {:?}", group.ActiveGroupCount);
+                println!(
+                    "ActiveProcessorCount = {:?}",
+                    group.GroupInfo[0].ActiveProcessorCount
+                );
+                println!(
+                    "MaximumProcessorCount = {:?}",
+                    group.GroupInfo[0].MaximumProcessorCount
+                );
                 println!();
-                Ok(Some(mpc))
-            } else {
-                Ok(None)
+                mpc = mpc.or(Some(group.GroupInfo[0].MaximumProcessorCount));
             }
+            Ok(mpc)
         },
     )?;
 
@@ -34,14 +34,14 @@ fn common(initial_buffer: &mut dyn WriteBuffer) -> Result<(), Box<dyn std::error
 
         // Make the API call indicating what the return value means
         let rv = RvIsError::new(unsafe {
-            GetTcpTable2(Some(argument.pointer()), argument.size(), FALSE)
+            GetTcpTable2(Some(argument.pointer()), argument.size_mut(), FALSE)
         });
 
         // Convert the return value to an action
         let fill_buffer_action = rv.to_result(&mut argument)?;
 
         // Apply the action
-        if argument.apply(fill_buffer_action) {
+        if argument.try_apply(fill_buffer_action)? {
             break;
         }
     }
@@ -23,7 +23,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     winapi_large_binary(
         |argument| {
             RvIsError::new(unsafe {
-                GetTcpTable2(Some(argument.pointer()), argument.size(), FALSE)
+                GetTcpTable2(Some(argument.pointer()), argument.size_mut(), FALSE)
             })
         },
         |frozen_buffer| {
@@ -0,0 +1,60 @@
+// Copyright 2023 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{Duration, Instant};
+
+use windows::core::PWSTR;
+use windows::Win32::System::WindowsProgramming::GetUserNameW;
+
+use grob::{run_call, Argument, FrozenBuffer, GrobCall, GrowForStaticText, GrowableBuffer};
+use grob::{RvIsError, StackBuffer};
+
+// Holds the timing of every try so the finish step can report it.
+struct TimedUserName {
+    timings: Vec<Duration>,
+}
+
+impl GrobCall for TimedUserName {
+    type Ft = u16;
+    type It = PWSTR;
+    type Rv = RvIsError;
+    type Output = (String, Vec<Duration>);
+
+    fn call(&mut self, argument: &mut Argument<PWSTR>) -> RvIsError {
+        let start = Instant::now();
+        let rv = RvIsError::new(unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) });
+        self.timings.push(start.elapsed());
+        rv
+    }
+    fn finish(self, frozen_buffer: FrozenBuffer<u16>) -> Result<Self::Output, std::io::Error> {
+        let username = frozen_buffer.try_to_string(true)?.unwrap_or_default();
+        Ok((username, self.timings))
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Start small to force at least one grow.
+    let mut initial_buffer = StackBuffer::<4>::new();
+    let grow_strategy = GrowForStaticText::new();
+    let growable_buffer = GrowableBuffer::new(&mut initial_buffer, &grow_strategy);
+    let timed_user_name = TimedUserName {
+        timings: Vec::new(),
+    };
+    let (username, timings) = run_call(growable_buffer, timed_user_name)?;
+    println!("GetUserNameW returned {}", username);
+    for (i, timing) in timings.iter().enumerate() {
+        println!("  try {} took {:?}", i + 1, timing);
+    }
+    Ok(())
+}
@@ -34,7 +34,7 @@ fn common(initial_buffer: &mut dyn WriteBuffer) -> Result<(), Box<dyn std::error
         let mut argument = growable_buffer.argument();
 
         // Make the API call
-        let rv = unsafe { GetUserNameW(argument.pointer(), argument.size()) };
+        let rv = unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) };
 
         // Convert the return value to an error code
         let rv: RvIsError = rv.into();
@@ -49,7 +49,7 @@ fn common(initial_buffer: &mut dyn WriteBuffer) -> Result<(), Box<dyn std::error
                 break;
             }
             FillBufferAction::Grow => {
-                argument.grow();
+                argument.grow()?;
             }
             FillBufferAction::NoData => {
                 argument.commit_no_data();
@@ -18,7 +18,7 @@ use grob::{winapi_string, RvIsError};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let username = winapi_string(true, |argument| {
-        RvIsError::new(unsafe { GetUserNameW(argument.pointer(), argument.size()) })
+        RvIsError::new(unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) })
     })?
     .unwrap();
     println!("GetUserNameW returned {}", username);
@@ -68,20 +68,19 @@ impl From<(u32, u32)> for PeVersion {
     fn from(value: (u32, u32)) -> Self {
         Self {
             major: ((value.0 >> 16) & 0xFFFF) as u16,
-            minor: ((value.0 >> 0) & 0xFFFF) as u16,
+            minor: (value.0 & 0xFFFF) as u16,
             patch: ((value.1 >> 16) & 0xFFFF) as u16,
-            build: ((value.1 >> 0) & 0xFFFF) as u16,
+            build: (value.1 & 0xFFFF) as u16,
         }
     }
 }
 
 impl From<*const VS_FIXEDFILEINFO> for PeVersion {
     fn from(value: *const VS_FIXEDFILEINFO) -> PeVersion {
-        assert!(unsafe { (*(value as *const VS_FIXEDFILEINFO)).dwSignature } == 0xFEEF04BD);
-        (
-            unsafe { (*(value as *const VS_FIXEDFILEINFO)).dwProductVersionMS },
-            unsafe { (*(value as *const VS_FIXEDFILEINFO)).dwProductVersionLS },
-        )
+        assert!(unsafe { (*value).dwSignature } == 0xFEEF04BD);
+        (unsafe { (*value).dwProductVersionMS }, unsafe {
+            (*value).dwProductVersionLS
+        })
             .into()
     }
 }
@@ -97,9 +96,9 @@ where
             if needed == 0 {
                 return RvIsError::new(FALSE);
             }
-            let s = unsafe { *argument.size() };
+            let s = *argument.size_mut();
             if s < needed {
-                unsafe { *argument.size() = needed };
+                *argument.size_mut() = needed;
                 return RvIsError::new(ERROR_INSUFFICIENT_BUFFER.0);
             }
             RvIsError::new(unsafe { GetFileVersionInfoW(a.ffi(), 0, s, argument.pointer()) })
@@ -124,23 +123,16 @@ where
 
 fn recurse(path: PathBuf) {
     if let Ok(rd) = std::fs::read_dir(path) {
-        for e in rd {
-            match e {
-                Ok(e) => {
-                    if let Ok(ft) = e.file_type() {
-                        let p = e.path();
-                        if ft.is_dir() {
-                            if p.ancestors().count() < 2 {
-                                recurse(p);
-                            }
-                        } else {
-                            if let Ok(Some(pev)) = get_pe_version(&p) {
-                                println!("{:<18} {:?}", pev, e.file_name());
-                            }
-                        }
+        for e in rd.flatten() {
+            if let Ok(ft) = e.file_type() {
+                let p = e.path();
+                if ft.is_dir() {
+                    if p.ancestors().count() < 2 {
+                        recurse(p);
                     }
+                } else if let Ok(Some(pev)) = get_pe_version(&p) {
+                    println!("{:<18} {:?}", pev, e.file_name());
                 }
-                Err(_) => {}
             }
         }
     }
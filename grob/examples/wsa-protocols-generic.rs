@@ -0,0 +1,63 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ffi::OsString;
+use std::mem::size_of;
+use std::os::windows::ffi::OsStringExt;
+use std::slice::from_raw_parts;
+
+use windows::Win32::Networking::WinSock::{
+    WSACleanup, WSAEnumProtocolsW, WSAStartup, SOCKET_ERROR, WSADATA, WSAPROTOCOL_INFOW,
+};
+
+use grob::{winapi_large_binary, RvIsWsa};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut data = WSADATA::default();
+    let rv = unsafe { WSAStartup(0x0202, &mut data) };
+    if rv != 0 {
+        return Err(std::io::Error::from_raw_os_error(rv).into());
+    }
+    let protocols = winapi_large_binary(
+        |argument| {
+            let rv =
+                unsafe { WSAEnumProtocolsW(None, Some(argument.pointer()), argument.size_mut()) };
+            // The number of protocols is returned.  Commit only those.
+            if rv != SOCKET_ERROR {
+                *argument.size_mut() = rv as u32 * size_of::<WSAPROTOCOL_INFOW>() as u32;
+            }
+            RvIsWsa::new(rv)
+        },
+        |frozen_buffer| {
+            let count = frozen_buffer.size() as usize / size_of::<WSAPROTOCOL_INFOW>();
+            let protocols = match frozen_buffer.try_pointer()? {
+                Some(p) => unsafe { from_raw_parts(p, count) },
+                None => &[],
+            };
+            Ok(protocols
+                .iter()
+                .map(|protocol| {
+                    let name = &protocol.szProtocol;
+                    let end = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+                    OsString::from_wide(&name[..end])
+                })
+                .collect::<Vec<_>>())
+        },
+    );
+    unsafe { WSACleanup() };
+    for protocol in protocols? {
+        println!("{:?}", protocol);
+    }
+    Ok(())
+}
@@ -0,0 +1,202 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::{Deref, DerefMut};
+
+use crate::base::{FillBufferAction, GrowError};
+use crate::traits::{RawToInternal, ToResult};
+use crate::{Argument, GrowableBuffer};
+
+/// Drives the call-operating-system-grow-buffer loop one [`Attempt`] at a time.
+///
+/// Returned by [`GrowableBuffer::attempts`].  [`next_attempt`](Attempts::next_attempt) returns an
+/// [`Attempt`] until one of them finishes with the data in the buffer.  The manual loop...
+///
+/// ```text
+/// loop {
+///     let mut argument = growable_buffer.argument();
+///     // ...
+///     if argument.try_apply(rv.to_result(&mut argument)?)? {
+///         break;
+///     }
+/// }
+/// ```
+///
+/// ...becomes...
+///
+/// ```text
+/// let mut attempts = growable_buffer.attempts();
+/// while let Some(mut attempt) = attempts.next_attempt() {
+///     let rv = /* operating system call using attempt.pointer() and attempt.size_mut() */;
+///     attempt.finish(rv)?;
+/// }
+/// ```
+///
+/// An [`Attempt`] is resolved by [`finish`](Attempt::finish) so an early `break` or a forgotten
+/// [`try_apply`][ta] is caught by the compiler (an unused `Attempt` is a warning) instead of at
+/// runtime.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::core::PWSTR;
+/// use windows::Win32::System::WindowsProgramming::GetUserNameW;
+///
+/// use grob::{GrowForStaticText, GrowableBuffer, RvIsError, StackBuffer, CAPACITY_FOR_NAMES};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut initial_buffer = StackBuffer::<CAPACITY_FOR_NAMES>::new();
+///     let grow_strategy = GrowForStaticText::new();
+///     let mut growable_buffer = GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+///     let mut attempts = growable_buffer.attempts();
+///     while let Some(mut attempt) = attempts.next_attempt() {
+///         let rv = RvIsError::new(unsafe { GetUserNameW(attempt.pointer(), attempt.size_mut()) });
+///         attempt.finish(rv)?;
+///     }
+///     let frozen_buffer = growable_buffer.freeze();
+///     println!("GetUserNameW returned {:?}", frozen_buffer.to_os_string());
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [ta]: crate::Argument::try_apply
+///
+pub struct Attempts<'a, 'gs, 'sb, FT, IT> {
+    growable_buffer: &'a mut GrowableBuffer<'gs, 'sb, FT, IT>,
+    done: bool,
+}
+
+impl<'a, 'gs, 'sb, FT, IT> Attempts<'a, 'gs, 'sb, FT, IT>
+where
+    IT: RawToInternal,
+    IT: Copy,
+{
+    pub(crate) fn new(growable_buffer: &'a mut GrowableBuffer<'gs, 'sb, FT, IT>) -> Self {
+        Self {
+            growable_buffer,
+            done: false,
+        }
+    }
+    /// Returns the next [`Attempt`] or [`None`] after an [`Attempt`] finished with the data in
+    /// the buffer.
+    ///
+    /// An [`Attempt`] that finishes with an error does not end the attempts.  The buffer is left as
+    /// it was before that [`Attempt`] so the call can be retried.
+    ///
+    pub fn next_attempt(&mut self) -> Option<Attempt<'_, IT>> {
+        if self.done {
+            return None;
+        }
+        Some(Attempt {
+            argument: self.growable_buffer.argument(),
+            done: &mut self.done,
+        })
+    }
+    /// Returns `true` if an [`Attempt`] finished with the data in the buffer.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+impl<'a, 'gs, 'sb, FT, IT> std::fmt::Debug for Attempts<'a, 'gs, 'sb, FT, IT> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Attempts")
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+/// One try of an operating system call.  Returned by [`Attempts::next_attempt`].
+///
+/// `Attempt` dereferences to [`Argument`] so [`pointer`](Argument::pointer),
+/// [`size`](Argument::size), [`tries`](Argument::tries), and the rest are available for the call.
+/// The return value from the call is passed to [`finish`](Attempt::finish).
+///
+#[must_use = "an Attempt has to be resolved with finish or dismiss"]
+pub struct Attempt<'a, IT> {
+    argument: Argument<'a, IT>,
+    done: &'a mut bool,
+}
+
+impl<'a, IT> Attempt<'a, IT>
+where
+    IT: Copy,
+{
+    /// Resolve the `Attempt` using the return value from the operating system call.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(()))` when the data is in the buffer; [`Attempts::next_attempt`] returns [`None`]
+    ///
+    /// * `Ok(None)` when the buffer was grown and the call has to be made again
+    ///
+    /// * `Err(`[`std::io::Error`]`)` when the operating system call failed or the buffer could not
+    ///   grow
+    ///
+    pub fn finish<WR>(mut self, rv: WR) -> Result<Option<()>, std::io::Error>
+    where
+        WR: ToResult,
+    {
+        let fill_buffer_action = match rv.to_result(&mut self.argument) {
+            Ok(fill_buffer_action) => fill_buffer_action,
+            Err(error) => {
+                self.dismiss();
+                return Err(error);
+            }
+        };
+        Ok(self.resolve(fill_buffer_action)?)
+    }
+    /// Give up on the `Attempt` leaving the buffer as it was before the `Attempt`.
+    pub fn dismiss(self) {
+        self.argument.dismiss();
+    }
+    // Apply an action that has already been determined.
+    pub(crate) fn resolve(
+        self,
+        fill_buffer_action: FillBufferAction,
+    ) -> Result<Option<()>, GrowError> {
+        if self.argument.try_apply(fill_buffer_action)? {
+            *self.done = true;
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<'a, IT> Deref for Attempt<'a, IT> {
+    type Target = Argument<'a, IT>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.argument
+    }
+}
+
+impl<'a, IT> DerefMut for Attempt<'a, IT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.argument
+    }
+}
+
+impl<'a, IT> std::fmt::Debug for Attempt<'a, IT> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Attempt")
+            .field("argument", &self.argument)
+            .finish_non_exhaustive()
+    }
+}
@@ -15,6 +15,7 @@
 /// What action to take after an operating system call: Commit, Grow, or NoData
 ///
 #[derive(Debug)]
+#[must_use]
 pub enum FillBufferAction {
     /// The operating system call was successful and there is usable data in the buffer.  Normally,
     /// [`freeze`][f] is called to turn the buffer into a [`FrozenBuffer`][fb] so the data can be
@@ -37,6 +38,17 @@ pub enum FillBufferAction {
     NoData,
 }
 
+impl std::fmt::Display for FillBufferAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Commit => "commit",
+            Self::Grow => "grow",
+            Self::NoData => "no data",
+        };
+        f.write_str(s)
+    }
+}
+
 /// The result of an operating system call.
 ///
 /// On success, the [`FillBufferAction`] indicates what should happen next.  There are three
@@ -56,3 +68,305 @@ pub enum FillBufferAction {
 /// [n]: crate::FillBufferAction::NoData
 ///
 pub type FillBufferResult = Result<FillBufferAction, std::io::Error>;
+
+/// The reason a buffer could not grow.
+///
+/// A [`GrowError`] is returned from [`try_apply`][ta] and [`grow`][g] when the [`GrowStrategy`][gs]
+/// asks for a larger buffer that cannot be provided.  The generic functions, like
+/// [`winapi_generic`][wg], convert a [`GrowError`] to a [`std::io::Error`] so it can be returned
+/// with the errors from the operating system call.
+///
+/// [ta]: crate::Argument::try_apply
+/// [g]: crate::Argument::grow
+/// [gs]: crate::GrowStrategy
+/// [wg]: crate::winapi_generic
+///
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GrowError {
+    /// The heap buffer could not be allocated.  `capacity` is the number of bytes requested.
+    AllocationFailed { capacity: u32 },
+    /// The [`GrowStrategy`][gs] returned a capacity no larger than the current capacity.
+    /// `strategy` is the [`name`][n] of the offending strategy.
+    ///
+    /// [gs]: crate::GrowStrategy
+    /// [n]: crate::GrowStrategy::name
+    StrategyDidNotGrow {
+        strategy: &'static str,
+        current_capacity: u32,
+        next_capacity: u32,
+    },
+    /// The operating system reported storing more data than fits in the buffer.
+    /// `needed_capacity` and `capacity` are in bytes.
+    SizeExceedsCapacity { needed_capacity: u32, capacity: u32 },
+    /// The capacity needed, in bytes, is larger than the [`max_capacity`][mc] of the
+    /// [`GrowStrategy`][gs].  `strategy` is the [`name`][n] of the strategy.
+    ///
+    /// [gs]: crate::GrowStrategy
+    /// [mc]: crate::GrowStrategy::max_capacity
+    /// [n]: crate::GrowStrategy::name
+    CapacityLimitExceeded {
+        strategy: &'static str,
+        desired_capacity: u32,
+        max_capacity: u32,
+    },
+    /// The [`GrowStrategy`][gs] stopped growing after `tries` operating system calls.  `strategy`
+    /// is the [`name`][n] of the strategy.
+    ///
+    /// [gs]: crate::GrowStrategy
+    /// [n]: crate::GrowStrategy::name
+    StrategyGaveUp {
+        strategy: &'static str,
+        tries: usize,
+    },
+    /// The operating system returned a value that is not documented.  `returned` is the value
+    /// returned, `capacity` is the buffer capacity in elements, and `last_error` is the value
+    /// from `GetLastError`.
+    UndocumentedReturnValue {
+        returned: u32,
+        capacity: u32,
+        last_error: u32,
+    },
+    /// A heap buffer is needed but [`no_heap`][nh] was used.  `capacity` is the number of bytes
+    /// the heap buffer would have had.
+    ///
+    /// [nh]: crate::GrowableBuffer::no_heap
+    HeapNotAllowed { capacity: u32 },
+}
+
+impl std::fmt::Display for GrowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AllocationFailed { capacity } => {
+                write!(f, "unable to allocate a {} byte buffer", capacity)
+            }
+            Self::StrategyDidNotGrow {
+                strategy,
+                current_capacity,
+                next_capacity,
+            } => write!(
+                f,
+                "{} returned a capacity of {} bytes which is not larger than the current capacity of {} bytes",
+                strategy, next_capacity, current_capacity
+            ),
+            Self::SizeExceedsCapacity {
+                needed_capacity,
+                capacity,
+            } => write!(
+                f,
+                "the operating system reported storing {} bytes in a {} byte buffer",
+                needed_capacity, capacity
+            ),
+            Self::CapacityLimitExceeded {
+                strategy,
+                desired_capacity,
+                max_capacity,
+            } => write!(
+                f,
+                "{} bytes are needed which is more than the {} byte limit of {}",
+                desired_capacity, max_capacity, strategy
+            ),
+            Self::StrategyGaveUp { strategy, tries } => {
+                write!(f, "{} gave up after {} tries", strategy, tries)
+            }
+            Self::UndocumentedReturnValue {
+                returned,
+                capacity,
+                last_error,
+            } => write!(
+                f,
+                "the operating system returned {} for a buffer of {} elements with a last error of {}; that combination is undocumented",
+                returned, capacity, last_error
+            ),
+            Self::HeapNotAllowed { capacity } => write!(
+                f,
+                "a {} byte heap buffer is needed but heap buffers are not allowed",
+                capacity
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GrowError {}
+
+/// The call loop ran past its deadline.
+///
+/// A [`GrobTimeout`] is wrapped in the [`std::io::Error`], with a kind of
+/// [`TimedOut`](std::io::ErrorKind::TimedOut), returned from
+/// [`winapi_generic_with_deadline`][d] and from a [`GrobBuilder`][b] with a deadline.
+///
+/// [d]: crate::winapi_generic_with_deadline
+/// [b]: crate::GrobBuilder::deadline
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GrobTimeout {
+    /// Number of operating system calls made before the deadline passed.
+    pub tries: usize,
+    /// Time spent in the call loop.
+    pub elapsed: std::time::Duration,
+}
+
+impl std::fmt::Display for GrobTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the deadline passed after {} tries and {:?}",
+            self.tries, self.elapsed
+        )
+    }
+}
+
+impl std::error::Error for GrobTimeout {}
+
+impl From<GrobTimeout> for std::io::Error {
+    fn from(value: GrobTimeout) -> Self {
+        std::io::Error::new(std::io::ErrorKind::TimedOut, value)
+    }
+}
+
+/// How the buffer behaved during a call loop.
+///
+/// [`GrowableBuffer::stats`][s] returns the statistics so far and [`FrozenBuffer::stats`][f]
+/// returns them as they were when the buffer was frozen so the finalize closure can log them.
+/// The `*_with_stats` wrappers, like [`winapi_generic_with_stats`][g], return them alongside the
+/// value.  They are meant for tuning a [`GrowStrategy`][gs] or the size of the initial buffer.
+///
+/// [s]: crate::GrowableBuffer::stats
+/// [f]: crate::FrozenBuffer::stats
+/// [g]: crate::winapi_generic_with_stats
+/// [gs]: crate::GrowStrategy
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GrowStats {
+    /// Number of operating system calls made; one per [`Argument`](crate::Argument).
+    pub tries: usize,
+    /// `true` if the initial buffer was too small and the data went to a heap buffer.
+    pub switched_to_heap: bool,
+    /// Largest capacity, in bytes, passed to an operating system call.
+    pub peak_capacity: u32,
+    /// Size, in bytes, stored by the last operating system call.
+    pub final_size: u32,
+}
+
+/// Context about the operating system call that failed.
+///
+/// Errors from the operating system call leave the call loop as a [`std::io::Error`] with the
+/// same [`kind`](std::io::Error::kind) wrapping a [`GrobCallError`].  The original error is
+/// available from [`source`](std::error::Error::source) and the Windows error code from
+/// [`raw_os_error`](GrobCallError::raw_os_error).  Because [`std::io::Error::new`] is used,
+/// [`std::io::Error::raw_os_error`] returns `None` for the wrapping error;
+/// [`as_win32_err`][a] and [`is_win32`][i] look inside the [`GrobCallError`] so they work either
+/// way.  [`from_io_error`](GrobCallError::from_io_error) finds the [`GrobCallError`] and
+/// [`into_source`](GrobCallError::into_source) recovers the original error.
+///
+/// Enable the `raw_os_errors` feature to return the original error unwrapped.
+///
+/// [a]: crate::as_win32_err
+/// [i]: crate::IoErrorExt::is_win32
+///
+#[derive(Debug)]
+pub struct GrobCallError {
+    source: std::io::Error,
+    /// Number of operating system calls made, including the one that failed.
+    pub tries: usize,
+    /// Capacity, in bytes, of the buffer passed to the call that failed.
+    pub capacity_at_failure: u32,
+    /// `true` if the buffer grew before the call that failed.
+    pub grew: bool,
+    /// Size, in bytes, requested by the last call that asked to grow the buffer.  Zero if the
+    /// buffer never grew.
+    pub last_requested: u32,
+}
+
+impl GrobCallError {
+    pub(crate) fn new(
+        source: std::io::Error,
+        tries: usize,
+        capacity_at_failure: u32,
+        grew: bool,
+        last_requested: u32,
+    ) -> Self {
+        Self {
+            source,
+            tries,
+            capacity_at_failure,
+            grew,
+            last_requested,
+        }
+    }
+    /// Returns the [`GrobCallError`] wrapped in `error` or `None` if `error` does not wrap one.
+    ///
+    /// # Examples
+    ///
+    /// ``` ignore
+    /// if let Some(call_error) = GrobCallError::from_io_error(&error) {
+    ///     eprintln!("failed after {} tries", call_error.tries);
+    /// }
+    /// ```
+    ///
+    pub fn from_io_error(error: &std::io::Error) -> Option<&Self> {
+        error.get_ref()?.downcast_ref::<Self>()
+    }
+    /// Returns the operating system error code from the original error.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        self.source.raw_os_error()
+    }
+    /// Returns the original error.
+    pub fn into_source(self) -> std::io::Error {
+        self.source
+    }
+}
+
+impl std::fmt::Display for GrobCallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (try {} with a capacity of {} bytes{})",
+            self.source,
+            self.tries,
+            self.capacity_at_failure,
+            if self.grew { " after growing" } else { "" }
+        )
+    }
+}
+
+impl std::error::Error for GrobCallError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<GrobCallError> for std::io::Error {
+    fn from(value: GrobCallError) -> Self {
+        #[cfg(feature = "raw_os_errors")]
+        return value.source;
+        #[cfg(not(feature = "raw_os_errors"))]
+        std::io::Error::new(value.source.kind(), value)
+    }
+}
+
+// Report a broken invariant.  Without the no_panic feature this panics.  With the no_panic feature
+// the error is returned from the enclosing function.
+macro_rules! fault {
+    ($e:expr) => {{
+        #[cfg(not(feature = "no_panic"))]
+        panic!("{}", $e);
+        #[cfg(feature = "no_panic")]
+        return Err($e.into());
+    }};
+}
+
+impl From<GrowError> for std::io::Error {
+    fn from(value: GrowError) -> Self {
+        let kind = match value {
+            GrowError::AllocationFailed { .. } => std::io::ErrorKind::OutOfMemory,
+            GrowError::StrategyDidNotGrow { .. } => std::io::ErrorKind::Other,
+            GrowError::SizeExceedsCapacity { .. } => std::io::ErrorKind::InvalidData,
+            GrowError::CapacityLimitExceeded { .. } => std::io::ErrorKind::OutOfMemory,
+            GrowError::StrategyGaveUp { .. } => std::io::ErrorKind::Other,
+            GrowError::UndocumentedReturnValue { .. } => std::io::ErrorKind::InvalidData,
+            GrowError::HeapNotAllowed { .. } => std::io::ErrorKind::OutOfMemory,
+        };
+        std::io::Error::new(kind, value)
+    }
+}
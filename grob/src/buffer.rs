@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::alloc::{alloc, dealloc, Layout};
+use std::alloc::{alloc, alloc_zeroed, dealloc, realloc, Layout};
 use std::mem::MaybeUninit;
 
 #[cfg(windows)]
@@ -36,6 +36,7 @@ pub(crate) mod os {
     pub const ALIGNMENT: usize = 8;
 }
 
+use crate::base::GrowError;
 use crate::traits::{ReadBuffer, WriteBuffer};
 
 /// Initial buffer placed on the stack to improve performance.
@@ -45,12 +46,26 @@ use crate::traits::{ReadBuffer, WriteBuffer};
 ///
 /// A [`StackBuffer`] can be zero-sized.  When the [`StackBuffer`] is zero-sized,
 /// [`GrowableBuffer`][gb] makes an operating system call to determine a best guess for the initial
-/// heap buffer size.
+/// heap buffer size.  The storage is aligned to `ALIGN` bytes, [`ALIGNMENT`][a] unless stated
+/// otherwise, so every one of the `CAPACITY` bytes is available.
+///
+/// Some structures need more than [`ALIGNMENT`][a]; for example, ones holding AVX registers or
+/// an `XSAVE` area.  Declare the buffer with a larger `ALIGN` (16, 32, 64, or 128 are supported)
+/// and every heap buffer the [`GrowableBuffer`][gb] switches to is allocated with the same
+/// alignment.  `ALIGN` cannot be less than [`ALIGNMENT`][a] so 8 is only supported where
+/// [`ALIGNMENT`][a] is 8, like 32-bit Windows.
+///
+/// ```compile_fail
+/// // 24 is not a supported alignment.
+/// let _ = grob::StackBuffer::<64, 24>::new();
+/// ```
 ///
 /// Ideally, a [`StackBuffer`] is sized so switching to a heap buffer is rarely necessary.  The
 /// [grob crate][gc] provides two constants to help avoid switching to a heap buffer:
 /// [`CAPACITY_FOR_NAMES`][cfn] and [`CAPACITY_FOR_PATHS`][cfp]
 ///
+/// With the `zeroize` feature a [`StackBuffer`] overwrites its contents with zeros when dropped.
+///
 /// # Examples
 ///
 /// ``` ignore
@@ -68,54 +83,146 @@ use crate::traits::{ReadBuffer, WriteBuffer};
 ///
 /// [gc]: https://crates.io/crates/grob
 /// [gb]: crate::GrowableBuffer
+/// [a]: crate::ALIGNMENT
 /// [cfn]: crate::CAPACITY_FOR_NAMES
 /// [cfp]: crate::CAPACITY_FOR_PATHS
 ///
-pub struct StackBuffer<const CAPACITY: usize> {
+pub struct StackBuffer<const CAPACITY: usize, const ALIGN: usize = { os::ALIGNMENT }>
+where
+    Alignment<ALIGN>: SupportedAlignment,
+{
     final_size: u32,
-    stack: MaybeUninit<[u8; CAPACITY]>,
+    stack: MaybeUninit<Aligned<<Alignment<ALIGN> as SupportedAlignment>::Unit, CAPACITY>>,
+}
+
+/// An alignment, in bytes, for a [`StackBuffer`].
+///
+/// Only used to name the alignments a [`StackBuffer`] supports.  See [`SupportedAlignment`].
+///
+pub struct Alignment<const ALIGN: usize>;
+
+/// Implemented for each [`Alignment`] a [`StackBuffer`] can be declared with; 16, 32, 64, and 128
+/// bytes, plus 8 bytes where [`ALIGNMENT`](os::ALIGNMENT) is 8.
+///
+/// This trait is sealed.
+///
+pub trait SupportedAlignment: sealed::Sealed {
+    #[doc(hidden)]
+    type Unit;
 }
 
-impl<const CAPACITY: usize> StackBuffer<CAPACITY> {
+mod sealed {
+    pub trait Sealed {}
+}
+
+// repr(align) only takes a literal so each supported alignment has a zero-sized type that carries
+// it.  Placing one at the start of the storage gives the storage that alignment without taking
+// any space.
+mod units {
+    #[cfg(any(not(windows), target_pointer_width = "32"))]
+    #[repr(align(8))]
+    pub struct A8;
+    #[repr(align(16))]
+    pub struct A16;
+    #[repr(align(32))]
+    pub struct A32;
+    #[repr(align(64))]
+    pub struct A64;
+    #[repr(align(128))]
+    pub struct A128;
+}
+
+macro_rules! supported_alignment {
+    ($($align:literal => $unit:ident),*) => {
+        $(
+            impl sealed::Sealed for Alignment<$align> {}
+            impl SupportedAlignment for Alignment<$align> {
+                type Unit = units::$unit;
+            }
+        )*
+    };
+}
+
+// 8 is only supported where it is not less than ALIGNMENT.
+#[cfg(any(not(windows), target_pointer_width = "32"))]
+supported_alignment!(8 => A8);
+supported_alignment!(16 => A16, 32 => A32, 64 => A64, 128 => A128);
+
+// Storage for StackBuffer.  The bytes start at offset zero and have the alignment of U.
+#[repr(C)]
+struct Aligned<U, const CAPACITY: usize> {
+    _alignment: U,
+    bytes: [u8; CAPACITY],
+}
+
+impl<const CAPACITY: usize, const ALIGN: usize> StackBuffer<CAPACITY, ALIGN>
+where
+    Alignment<ALIGN>: SupportedAlignment,
+{
+    // Evaluated when new is instantiated so an ALIGN below ALIGNMENT does not compile.
+    const ALIGN_IS_ENOUGH: () = assert!(
+        ALIGN >= os::ALIGNMENT,
+        "a StackBuffer cannot be aligned to less than ALIGNMENT"
+    );
     /// Constructs a stack buffer of size `CAPACITY`.
     pub fn new() -> Self {
+        let () = Self::ALIGN_IS_ENOUGH;
         Self {
             final_size: 0,
             stack: MaybeUninit::uninit(),
         }
     }
-    fn as_mut_ptr(&mut self) -> (*mut u8, usize) {
-        // nfx: Future enhancement...
-        // https://github.com/rust-lang/rust/issues/95228
-        let p = self.stack.as_mut_ptr() as *mut u8;
-        let offset = p.align_offset(os::ALIGNMENT);
-        (unsafe { p.add(offset) }, offset)
+    fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.stack.as_mut_ptr() as *mut u8
     }
-    fn as_ptr(&self) -> (*const u8, usize) {
-        // nfx: Future enhancement...
-        // https://github.com/rust-lang/rust/issues/95228
-        let p = self.stack.as_ptr() as *const u8;
-        let offset = p.align_offset(os::ALIGNMENT);
-        (unsafe { p.add(offset) }, offset)
-    }
-    fn offset(&self) -> usize {
-        let p = self.stack.as_ptr() as *const u8;
-        p.align_offset(os::ALIGNMENT)
+    fn as_ptr(&self) -> *const u8 {
+        self.stack.as_ptr() as *const u8
     }
 }
 
-impl<const CAPACITY: usize> Default for StackBuffer<CAPACITY> {
+impl<const CAPACITY: usize, const ALIGN: usize> Default for StackBuffer<CAPACITY, ALIGN>
+where
+    Alignment<ALIGN>: SupportedAlignment,
+{
     /// Constructs a stack buffer of size `CAPACITY`.
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<const CAPACITY: usize> ReadBuffer for StackBuffer<CAPACITY> {
+impl<const CAPACITY: usize, const ALIGN: usize> std::fmt::Debug for StackBuffer<CAPACITY, ALIGN>
+where
+    Alignment<ALIGN>: SupportedAlignment,
+{
+    /// Formats the shape of the buffer.  The buffer contents are never read.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StackBuffer")
+            .field("CAPACITY", &CAPACITY)
+            .field("ALIGN", &ALIGN)
+            .field("final_size", &self.final_size)
+            .finish()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const CAPACITY: usize, const ALIGN: usize> Drop for StackBuffer<CAPACITY, ALIGN>
+where
+    Alignment<ALIGN>: SupportedAlignment,
+{
+    fn drop(&mut self) {
+        wipe(self.as_mut_ptr(), CAPACITY);
+    }
+}
+
+impl<const CAPACITY: usize, const ALIGN: usize> ReadBuffer for StackBuffer<CAPACITY, ALIGN>
+where
+    Alignment<ALIGN>: SupportedAlignment,
+{
     /// Returns a read-only pointer to the buffer and the number of elements stored in the buffer.
     ///
-    /// If the buffer is too small to meet the alignment needed by the operating system then
-    /// `(none, 0)` is returned.
+    /// If the buffer is zero-sized then `(None, final_size)` is returned.  The size is reported
+    /// as-is so a caller can tell the difference between "nothing stored" and "something stored
+    /// but not accessible".
     ///
     /// `read_buffer` is used by [`FrozenBuffer`][fb] to provide access to the data stored by the
     /// operating system.
@@ -123,15 +230,18 @@ impl<const CAPACITY: usize> ReadBuffer for StackBuffer<CAPACITY> {
     /// [fb]: crate::FrozenBuffer
     ///
     fn read_buffer(&self) -> (Option<*const u8>, u32) {
-        if CAPACITY >= os::ALIGNMENT {
-            (Some(self.as_ptr().0), self.final_size)
+        if CAPACITY > 0 {
+            (Some(self.as_ptr()), self.final_size)
         } else {
-            (None, 0)
+            (None, self.final_size)
         }
     }
 }
 
-impl<const CAPACITY: usize> WriteBuffer for StackBuffer<CAPACITY> {
+impl<const CAPACITY: usize, const ALIGN: usize> WriteBuffer for StackBuffer<CAPACITY, ALIGN>
+where
+    Alignment<ALIGN>: SupportedAlignment,
+{
     /// Returns the [`ReadBuffer`] for this [`StackBuffer`].
     ///
     /// `as_read_buffer` is used internally when converting to a [`FrozenBuffer`][fb].
@@ -143,21 +253,18 @@ impl<const CAPACITY: usize> WriteBuffer for StackBuffer<CAPACITY> {
     }
     /// Returns the available capacity for this [`StackBuffer`].
     ///
-    /// The operating system expects buffers to be aligned on [`ALIGNMENT`][a] boundaries.  Rust
-    /// guarentees alignment to the size of each array element.  Internally [`StackBuffer`] uses an
-    /// array of [`u8`] so the buffer is aligned to the nearest byte (not aligned).  `capacity` may
-    /// be reduced so a correctly aligned buffer can be presented to the operating system.  In other
-    /// words, a 256 byte buffer may be reduced to a capacity of 241 bytes
-    /// (256 - ([`ALIGNMENT`][a] - 1)).
+    /// The operating system expects buffers to be aligned on [`ALIGNMENT`][a] boundaries.  The
+    /// storage is declared with `ALIGN`, which is at least that, so the capacity is always exactly
+    /// `CAPACITY`.
     ///
     /// [a]: os::ALIGNMENT
     ///
     fn capacity(&self) -> u32 {
-        if CAPACITY >= os::ALIGNMENT {
-            (CAPACITY - self.offset()).try_into().unwrap()
-        } else {
-            0
-        }
+        CAPACITY.try_into().unwrap()
+    }
+    /// Returns `ALIGN`.
+    fn alignment(&self) -> usize {
+        ALIGN
     }
     /// Called from [`freeze`][f] to set the amount of data provided by the operating system.
     ///
@@ -180,59 +287,698 @@ impl<const CAPACITY: usize> WriteBuffer for StackBuffer<CAPACITY> {
     /// [a]: crate::Argument
     ///
     fn write_buffer(&mut self) -> (*mut u8, u32) {
-        if CAPACITY >= os::ALIGNMENT {
-            let (p, o) = self.as_mut_ptr();
-            (p, (CAPACITY - o).try_into().unwrap())
+        (self.as_mut_ptr(), self.capacity())
+    }
+}
+
+/// Initial buffer over memory the caller already owns.
+///
+/// Wraps a `&mut [MaybeUninit<u8>]` so an embedded-style caller, or one carving buffers out of a
+/// large arena, can have grob fill memory that was never allocated by grob.  Like the original
+/// [`StackBuffer`], the start of the slice is skipped as needed to meet [`ALIGNMENT`][a] so the
+/// capacity may be a little less than the length of the slice.
+///
+/// Pair it with [`GrowableBuffer::no_heap`][nh] to guarantee grob never allocates.  Otherwise
+/// [`GrowableBuffer`][gb] switches to a heap buffer when the slice is too small.
+///
+/// # Examples
+///
+/// ```
+/// use std::mem::MaybeUninit;
+///
+/// use grob::{BorrowedBuffer, GrowForSmallBinary, GrowableBuffer, WriteBuffer, ALIGNMENT};
+///
+/// let mut arena = [MaybeUninit::<u8>::uninit(); 256];
+/// let mut initial_buffer = BorrowedBuffer::new(&mut arena);
+/// assert!(initial_buffer.capacity() as usize > 256 - ALIGNMENT);
+/// let grow_strategy = GrowForSmallBinary::new();
+/// let growable_buffer =
+///     GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy).no_heap();
+/// # let _ = growable_buffer;
+/// ```
+///
+/// [gb]: crate::GrowableBuffer
+/// [nh]: crate::GrowableBuffer::no_heap
+/// [a]: crate::ALIGNMENT
+///
+pub struct BorrowedBuffer<'b> {
+    final_size: u32,
+    slice: &'b mut [MaybeUninit<u8>],
+}
+
+impl<'b> BorrowedBuffer<'b> {
+    /// Constructs a [`BorrowedBuffer`] that writes into `slice`.
+    pub fn new(slice: &'b mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            final_size: 0,
+            slice,
+        }
+    }
+    // The number of bytes skipped to meet ALIGNMENT.  Never more than the length of the slice.
+    fn offset(&self) -> usize {
+        let offset = self.slice.as_ptr().align_offset(os::ALIGNMENT);
+        offset.min(self.slice.len())
+    }
+}
+
+impl<'b> std::fmt::Debug for BorrowedBuffer<'b> {
+    /// Formats the shape of the buffer.  The buffer contents are never read.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BorrowedBuffer")
+            .field("len", &self.slice.len())
+            .field("offset", &self.offset())
+            .field("final_size", &self.final_size)
+            .finish()
+    }
+}
+
+impl<'b> ReadBuffer for BorrowedBuffer<'b> {
+    /// Returns a read-only pointer to the aligned part of the slice and the number of bytes
+    /// stored.
+    ///
+    /// `(None, final_size)` is returned if the slice is too small to provide an aligned buffer.
+    ///
+    fn read_buffer(&self) -> (Option<*const u8>, u32) {
+        if self.capacity() > 0 {
+            let p = unsafe { self.slice.as_ptr().add(self.offset()) };
+            (Some(p as *const u8), self.final_size)
+        } else {
+            (None, self.final_size)
+        }
+    }
+}
+
+impl<'b> WriteBuffer for BorrowedBuffer<'b> {
+    /// Returns the [`ReadBuffer`] for this [`BorrowedBuffer`].
+    fn as_read_buffer(&self) -> &dyn ReadBuffer {
+        self as &dyn ReadBuffer
+    }
+    /// Returns the length of the slice less the bytes skipped to meet [`ALIGNMENT`][a].
+    ///
+    /// [a]: os::ALIGNMENT
+    ///
+    fn capacity(&self) -> u32 {
+        (self.slice.len() - self.offset())
+            .try_into()
+            .unwrap_or(u32::MAX)
+    }
+    /// Called from [`freeze`][f] to set the amount of data provided by the operating system.
+    ///
+    /// [f]: crate::GrowableBuffer::freeze
+    ///
+    fn set_final_size(&mut self, final_size: u32) {
+        self.final_size = final_size;
+    }
+    /// Returns a pointer to the aligned part of the slice and its capacity.
+    fn write_buffer(&mut self) -> (*mut u8, u32) {
+        let offset = self.offset();
+        let p = unsafe { self.slice.as_mut_ptr().add(offset) };
+        (p as *mut u8, self.capacity())
+    }
+}
+
+/// Initial buffer backed by a [`Vec<u8>`] owned by the caller.
+///
+/// The data ends up in the caller's [`Vec`] instead of a heap buffer owned by the
+/// [`GrowableBuffer`][gb].  The [`Vec`] can be reused for the next call or sent to another thread.
+/// The spare capacity of the [`Vec`] is presented to the operating system.  When more room is
+/// needed the [`Vec`] itself grows (see [`WriteBuffer::try_grow`]) so a heap buffer is never
+/// created.  After [`freeze`][f] the length of the [`Vec`] is the number of bytes stored, even
+/// when the [`GrowableBuffer`][gb] measures its size in elements like `WCHAR`s.
+///
+/// [`new`](VecBuffer::new) clears the [`Vec`] but keeps its capacity.  Memory from the global
+/// allocator is expected to meet [`ALIGNMENT`][a].  If it does not, the [`VecBuffer`] reports no
+/// capacity and [`GrowableBuffer`][gb] uses a heap buffer as usual.
+///
+/// # Examples
+///
+/// ```
+/// use grob::{GrowForSmallBinary, GrowableBuffer, VecBuffer};
+///
+/// let mut data = Vec::with_capacity(256);
+/// {
+///     let mut initial_buffer = VecBuffer::new(&mut data);
+///     let grow_strategy = GrowForSmallBinary::new();
+///     let mut growable_buffer =
+///         GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+///     // ... call loop ...
+///     # let _ = growable_buffer.argument().dismiss();
+///     let frozen_buffer = growable_buffer.freeze();
+///     # let _ = frozen_buffer;
+/// }
+/// assert!(data.is_empty());
+/// ```
+///
+/// [gb]: crate::GrowableBuffer
+/// [f]: crate::GrowableBuffer::freeze
+/// [a]: crate::ALIGNMENT
+///
+pub struct VecBuffer<'v> {
+    vec: &'v mut Vec<u8>,
+    final_size: u32,
+}
+
+impl<'v> VecBuffer<'v> {
+    /// Constructs a [`VecBuffer`] that stores data in `vec`.  `vec` is cleared.
+    pub fn new(vec: &'v mut Vec<u8>) -> Self {
+        vec.clear();
+        Self { vec, final_size: 0 }
+    }
+    fn is_aligned(&self) -> bool {
+        self.vec.as_ptr().align_offset(os::ALIGNMENT) == 0
+    }
+}
+
+impl<'v> std::fmt::Debug for VecBuffer<'v> {
+    /// Formats the shape of the buffer.  The buffer contents are never read.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VecBuffer")
+            .field("len", &self.vec.len())
+            .field("capacity", &self.vec.capacity())
+            .finish()
+    }
+}
+
+impl<'v> ReadBuffer for VecBuffer<'v> {
+    /// Returns a read-only pointer to the [`Vec`] and the final size.
+    ///
+    /// `(None, final size)` is returned if the [`Vec`] has no usable capacity.
+    ///
+    fn read_buffer(&self) -> (Option<*const u8>, u32) {
+        if self.capacity() > 0 {
+            (Some(self.vec.as_ptr()), self.final_size)
         } else {
-            // This pointer may not be aligned but we're indicating there's zero capacity available
-            // so the caller had better not be using it.
-            let p = self.stack.as_mut_ptr() as *mut u8;
-            (p, 0)
+            (None, self.final_size)
         }
     }
 }
 
-pub(crate) struct HeapBuffer {
+impl<'v> WriteBuffer for VecBuffer<'v> {
+    /// Returns the [`ReadBuffer`] for this [`VecBuffer`].
+    fn as_read_buffer(&self) -> &dyn ReadBuffer {
+        self as &dyn ReadBuffer
+    }
+    /// Returns the capacity of the [`Vec`] or zero if it is not aligned to [`ALIGNMENT`][a].
+    ///
+    /// [a]: os::ALIGNMENT
+    ///
+    fn capacity(&self) -> u32 {
+        if self.is_aligned() {
+            self.vec.capacity().try_into().unwrap_or(u32::MAX)
+        } else {
+            0
+        }
+    }
+    /// Sets the length of the [`Vec`] to `final_size` bytes.
+    fn set_final_size(&mut self, final_size: u32) {
+        self.set_final_size_and_bytes(final_size, final_size);
+    }
+    /// Sets the length of the [`Vec`] to the number of bytes stored by the operating system.
+    fn set_final_size_and_bytes(&mut self, final_size: u32, bytes: u32) {
+        let length = bytes.min(self.capacity()) as usize;
+        // The operating system initialized the first `bytes` bytes.
+        unsafe { self.vec.set_len(length) };
+        self.final_size = final_size;
+    }
+    /// Returns a pointer to the [`Vec`] and its capacity.
+    fn write_buffer(&mut self) -> (*mut u8, u32) {
+        (self.vec.as_mut_ptr(), self.capacity())
+    }
+    /// Replaces the allocation of the [`Vec`] with one of `capacity` bytes keeping the contents.
+    ///
+    /// Returns `false`, leaving the [`Vec`] as it was, if the new allocation is not aligned to
+    /// [`ALIGNMENT`][a] or cannot be made.
+    ///
+    /// [a]: os::ALIGNMENT
+    ///
+    fn try_grow(&mut self, capacity: u32) -> bool {
+        let mut grown = Vec::<u8>::new();
+        if grown.try_reserve_exact(capacity as usize).is_err() {
+            return false;
+        }
+        if grown.as_ptr().align_offset(os::ALIGNMENT) != 0 {
+            return false;
+        }
+        // The length is zero while the operating system writes so copy the whole capacity.
+        let length = self.capacity() as usize;
+        unsafe { std::ptr::copy_nonoverlapping(self.vec.as_ptr(), grown.as_mut_ptr(), length) };
+        #[cfg(feature = "zeroize")]
+        wipe(self.vec.as_mut_ptr(), self.vec.capacity());
+        *self.vec = grown;
+        true
+    }
+}
+
+/// Where [`GrowableBuffer`][gb] gets its heap buffers.
+///
+/// Some Windows API functions take ownership of a buffer provided by the caller and free it with
+/// [`HeapFree`][hf] or [`CoTaskMemFree`][ctmf].  Memory from Rust's global allocator must never be
+/// given to them.  Choose the matching source with [`GrowableBuffer::alloc_source`][as] then hand
+/// the data over with [`FrozenBuffer::release_to_os`][rto].
+///
+/// [gb]: crate::GrowableBuffer
+/// [as]: crate::GrowableBuffer::alloc_source
+/// [rto]: crate::FrozenBuffer::release_to_os
+/// [hf]: https://learn.microsoft.com/en-us/windows/win32/api/heapapi/nf-heapapi-heapfree
+/// [ctmf]: https://learn.microsoft.com/en-us/windows/win32/api/combaseapi/nf-combaseapi-cotaskmemfree
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AllocSource {
+    /// Rust's global allocator.  The default.
+    #[default]
+    RustGlobal,
+    /// [`HeapAlloc`][ha] using the heap returned from [`GetProcessHeap`][gph].
+    ///
+    /// [ha]: https://learn.microsoft.com/en-us/windows/win32/api/heapapi/nf-heapapi-heapalloc
+    /// [gph]: https://learn.microsoft.com/en-us/windows/win32/api/heapapi/nf-heapapi-getprocessheap
+    ///
+    ProcessHeap,
+    /// [`CoTaskMemAlloc`][ctma].
+    ///
+    /// [ctma]: https://learn.microsoft.com/en-us/windows/win32/api/combaseapi/nf-combaseapi-cotaskmemalloc
+    ///
+    CoTaskMem,
+}
+
+impl AllocSource {
+    /// Returns the [`BufferAllocator`] for this source.
+    pub fn allocator(self) -> &'static dyn BufferAllocator {
+        match self {
+            Self::RustGlobal => &StdAlloc,
+            Self::ProcessHeap => &ProcessHeapAlloc,
+            Self::CoTaskMem => &CoTaskMemAlloc,
+        }
+    }
+}
+
+/// Provides the memory for a [`HeapBuffer`].
+///
+/// [`AllocSource`] covers the allocators built into Windows.  Implement [`BufferAllocator`] to use
+/// any other allocator then choose it with [`GrowableBuffer::allocator`][ga] or
+/// [`HeapBuffer::with_capacity_in`].  The built-in implementations are [`StdAlloc`],
+/// [`ProcessHeapAlloc`], and [`CoTaskMemAlloc`].
+///
+/// # Safety
+///
+/// `alloc` must return null or a pointer to at least `capacity` writable bytes aligned to `align`.
+/// `realloc` must do the same for the new capacity and keep the contents up to the smaller of the
+/// two capacities.  [`HeapBuffer`] never asks for zero bytes and only passes pointers returned
+/// from the same allocator, along with the [`Layout`] used to allocate them, to `realloc` and
+/// `dealloc`.
+///
+/// [ga]: crate::GrowableBuffer::allocator
+///
+pub unsafe trait BufferAllocator: std::fmt::Debug + Sync {
+    /// Allocate `capacity` bytes aligned to `align`.  Returns null on failure.
+    fn alloc(&self, capacity: usize, align: usize) -> *mut u8;
+    /// Free memory returned from [`alloc`](BufferAllocator::alloc) or
+    /// [`realloc`](BufferAllocator::realloc).
+    ///
+    /// # Safety
+    ///
+    /// `pointer` must have been allocated by this allocator with `layout`.
+    ///
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout);
+    /// Change the capacity of an allocation keeping the contents.  Returns null on failure in
+    /// which case the original allocation is unchanged.
+    ///
+    /// The default allocates a new block, copies the contents, then frees the old block.
+    ///
+    /// # Safety
+    ///
+    /// `pointer` must have been allocated by this allocator with `layout`.
+    ///
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, capacity: usize) -> *mut u8 {
+        let new_pointer = self.alloc(capacity, layout.align());
+        if !new_pointer.is_null() {
+            std::ptr::copy_nonoverlapping(pointer, new_pointer, layout.size().min(capacity));
+            self.dealloc(pointer, layout);
+        }
+        new_pointer
+    }
+    /// Returns `true` if the memory can be handed to other code that frees it.
+    ///
+    /// [`FrozenBuffer::release_to_os`][rto] only gives up ownership of a heap buffer when this
+    /// returns `true`.  The default is `false`; the memory is always freed by `dealloc`.
+    ///
+    /// [rto]: crate::FrozenBuffer::release_to_os
+    ///
+    fn can_release(&self) -> bool {
+        false
+    }
+}
+
+/// [`BufferAllocator`] for Rust's global allocator ([`AllocSource::RustGlobal`]).  The default.
+///
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdAlloc;
+
+unsafe impl BufferAllocator for StdAlloc {
+    fn alloc(&self, capacity: usize, align: usize) -> *mut u8 {
+        match Layout::from_size_align(capacity, align) {
+            Ok(layout) if layout.size() > 0 => unsafe { alloc(layout) },
+            _ => std::ptr::null_mut(),
+        }
+    }
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        dealloc(pointer, layout)
+    }
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, capacity: usize) -> *mut u8 {
+        if capacity == 0 {
+            return std::ptr::null_mut();
+        }
+        realloc(pointer, layout, capacity)
+    }
+}
+
+/// [`BufferAllocator`] for [`HeapAlloc`][ha] using the heap returned from [`GetProcessHeap`][gph]
+/// ([`AllocSource::ProcessHeap`]).  The memory can be freed with [`HeapFree`][hf].
+///
+/// [ha]: https://learn.microsoft.com/en-us/windows/win32/api/heapapi/nf-heapapi-heapalloc
+/// [gph]: https://learn.microsoft.com/en-us/windows/win32/api/heapapi/nf-heapapi-getprocessheap
+/// [hf]: https://learn.microsoft.com/en-us/windows/win32/api/heapapi/nf-heapapi-heapfree
+///
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProcessHeapAlloc;
+
+unsafe impl BufferAllocator for ProcessHeapAlloc {
+    fn alloc(&self, capacity: usize, align: usize) -> *mut u8 {
+        if align > os::ALIGNMENT {
+            return std::ptr::null_mut();
+        }
+        unsafe { os_alloc::alloc(AllocSource::ProcessHeap, capacity) }
+    }
+    unsafe fn dealloc(&self, pointer: *mut u8, _layout: Layout) {
+        os_alloc::free(AllocSource::ProcessHeap, pointer)
+    }
+    unsafe fn realloc(&self, pointer: *mut u8, _layout: Layout, capacity: usize) -> *mut u8 {
+        os_alloc::realloc(AllocSource::ProcessHeap, pointer, capacity)
+    }
+    fn can_release(&self) -> bool {
+        true
+    }
+}
+
+/// [`BufferAllocator`] for [`CoTaskMemAlloc`][ctma] ([`AllocSource::CoTaskMem`]).  The memory can
+/// be freed with [`CoTaskMemFree`][ctmf].
+///
+/// [ctma]: https://learn.microsoft.com/en-us/windows/win32/api/combaseapi/nf-combaseapi-cotaskmemalloc
+/// [ctmf]: https://learn.microsoft.com/en-us/windows/win32/api/combaseapi/nf-combaseapi-cotaskmemfree
+///
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CoTaskMemAlloc;
+
+unsafe impl BufferAllocator for CoTaskMemAlloc {
+    fn alloc(&self, capacity: usize, align: usize) -> *mut u8 {
+        if align > os::ALIGNMENT {
+            return std::ptr::null_mut();
+        }
+        unsafe { os_alloc::alloc(AllocSource::CoTaskMem, capacity) }
+    }
+    unsafe fn dealloc(&self, pointer: *mut u8, _layout: Layout) {
+        os_alloc::free(AllocSource::CoTaskMem, pointer)
+    }
+    unsafe fn realloc(&self, pointer: *mut u8, _layout: Layout, capacity: usize) -> *mut u8 {
+        os_alloc::realloc(AllocSource::CoTaskMem, pointer, capacity)
+    }
+    fn can_release(&self) -> bool {
+        true
+    }
+}
+
+// The operating system allocators.  Both return memory aligned to MEMORY_ALLOCATION_ALIGNMENT.
+mod os_alloc {
+    use std::ffi::c_void;
+
+    use windows::Win32::System::Com::{CoTaskMemAlloc, CoTaskMemFree, CoTaskMemRealloc};
+    use windows::Win32::System::Memory::{
+        GetProcessHeap, HeapAlloc, HeapFree, HeapReAlloc, HEAP_FLAGS,
+    };
+
+    use super::AllocSource;
+
+    pub(super) unsafe fn alloc(source: AllocSource, size: usize) -> *mut u8 {
+        match source {
+            AllocSource::ProcessHeap => match GetProcessHeap() {
+                Ok(heap) => HeapAlloc(heap, HEAP_FLAGS(0), size) as *mut u8,
+                Err(_) => std::ptr::null_mut(),
+            },
+            AllocSource::CoTaskMem => CoTaskMemAlloc(size) as *mut u8,
+            AllocSource::RustGlobal => unreachable!(),
+        }
+    }
+
+    pub(super) unsafe fn realloc(source: AllocSource, pointer: *mut u8, size: usize) -> *mut u8 {
+        let pointer = Some(pointer as *const c_void);
+        match source {
+            AllocSource::ProcessHeap => match GetProcessHeap() {
+                Ok(heap) => HeapReAlloc(heap, HEAP_FLAGS(0), pointer, size) as *mut u8,
+                Err(_) => std::ptr::null_mut(),
+            },
+            AllocSource::CoTaskMem => CoTaskMemRealloc(pointer, size) as *mut u8,
+            AllocSource::RustGlobal => unreachable!(),
+        }
+    }
+
+    pub(super) unsafe fn free(source: AllocSource, pointer: *mut u8) {
+        let pointer = Some(pointer as *const c_void);
+        match source {
+            AllocSource::ProcessHeap => {
+                if let Ok(heap) = GetProcessHeap() {
+                    HeapFree(heap, HEAP_FLAGS(0), pointer);
+                }
+            }
+            AllocSource::CoTaskMem => CoTaskMemFree(pointer),
+            AllocSource::RustGlobal => unreachable!(),
+        }
+    }
+}
+
+/// Initial buffer placed on the heap.
+///
+/// [`GrowableBuffer`][gb] switches to a [`HeapBuffer`] when the initial buffer is too small.  A
+/// [`HeapBuffer`] can also be created directly and passed as the initial buffer (it is a
+/// [`WriteBuffer`]).  This allocates a specific starting capacity without wasting an operating
+/// system call on a zero-sized [`StackBuffer`].
+///
+/// The memory is always aligned to [`ALIGNMENT`][a], or more with
+/// [`with_align`](HeapBuffer::with_align).  [`with_capacity`](HeapBuffer::with_capacity) and
+/// [`zeroed`](HeapBuffer::zeroed) allocate from Rust's global allocator ([`StdAlloc`]).  The
+/// memory is requested from the [`BufferAllocator`] with a [`Layout`] of `capacity` bytes (at
+/// least one) and the alignment.  The same [`Layout`] is passed to the [`BufferAllocator`] to
+/// free the memory when the [`HeapBuffer`] is dropped.
+///
+/// # Secrets
+///
+/// With the `zeroize` feature a [`HeapBuffer`] overwrites its memory with zeros before the memory
+/// is freed, both when dropped and when the buffer grows.  Growing copies the contents to a new
+/// allocation instead of using `realloc` so no copy is left behind.  A [`FrozenBuffer`][fb] that
+/// holds a [`HeapBuffer`] wipes it when dropped.  Memory handed over with
+/// [`release_to_os`][rto] is not wiped.
+///
+/// # Examples
+///
+/// ```
+/// use grob::{GrowForSmallBinary, GrowableBuffer, HeapBuffer, WriteBuffer, ALIGNMENT};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut initial_buffer = HeapBuffer::with_capacity(4096)?;
+///     assert!(initial_buffer.capacity() == 4096);
+///     assert!(initial_buffer.write_buffer().0 as usize % ALIGNMENT == 0);
+///     let grow_strategy = GrowForSmallBinary::new();
+///     let _growable_buffer =
+///         GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+///     Ok(())
+/// }
+/// ```
+///
+/// [gb]: crate::GrowableBuffer
+/// [a]: crate::ALIGNMENT
+/// [fb]: crate::FrozenBuffer
+/// [rto]: crate::FrozenBuffer::release_to_os
+///
+pub struct HeapBuffer {
     capacity: u32,
     final_size: u32,
     layout: Layout,
     pointer: *mut u8,
+    allocator: &'static dyn BufferAllocator,
+}
+
+// A HeapBuffer is the only owner of its memory and every BufferAllocator is Sync.
+unsafe impl Send for HeapBuffer {}
+
+// Through a shared reference a HeapBuffer only reads its fields; the memory is written through
+// &mut self.
+unsafe impl Sync for HeapBuffer {}
+
+// Rust's global allocator does not allow zero sized allocations so at least one byte is allocated.
+// The alignment is never less than ALIGNMENT.
+fn layout_for(capacity: u32, align: usize) -> Result<Layout, GrowError> {
+    let align = align.max(os::ALIGNMENT);
+    usize::try_from(capacity)
+        .ok()
+        .and_then(|size| Layout::from_size_align(size.max(1), align).ok())
+        .ok_or(GrowError::AllocationFailed { capacity })
 }
 
 impl HeapBuffer {
-    pub(crate) fn new(capacity: u32) -> Self {
-        let layout = Layout::from_size_align(capacity.try_into().unwrap(), os::ALIGNMENT).unwrap();
-        let pointer = unsafe { alloc(layout) };
+    /// Allocate a [`HeapBuffer`] with a capacity of `capacity` bytes.
+    ///
+    /// The contents are uninitialized.  A [`GrowError::AllocationFailed`] is returned if the
+    /// memory cannot be allocated.
+    ///
+    pub fn with_capacity(capacity: u32) -> Result<Self, GrowError> {
+        Self::with_capacity_in(capacity, &StdAlloc)
+    }
+    /// Allocate a [`HeapBuffer`] with a capacity of `capacity` bytes from `allocator`.
+    ///
+    /// The contents are uninitialized.  A [`GrowError::AllocationFailed`] is returned if the
+    /// memory cannot be allocated.
+    ///
+    pub fn with_capacity_in(
+        capacity: u32,
+        allocator: &'static dyn BufferAllocator,
+    ) -> Result<Self, GrowError> {
+        Self::try_new_in(capacity, allocator)
+    }
+    /// Allocate a [`HeapBuffer`] with a capacity of `capacity` bytes aligned to `align` bytes.
+    ///
+    /// For data that needs more than [`ALIGNMENT`][a].  `align` has to be a power of two; an
+    /// `align` less than [`ALIGNMENT`][a] is raised to it.  The alignment is kept when the buffer
+    /// grows.  The contents are uninitialized.  A [`GrowError::AllocationFailed`] is returned if
+    /// `align` is not a power of two or the memory cannot be allocated.
+    ///
+    /// [a]: crate::ALIGNMENT
+    ///
+    pub fn with_align(capacity: u32, align: usize) -> Result<Self, GrowError> {
+        Self::try_new_aligned_in(capacity, align, &StdAlloc)
+    }
+    /// Allocate a [`HeapBuffer`] with a capacity of `capacity` bytes filled with zeros.
+    ///
+    /// The memory comes from [`alloc_zeroed`] so the allocator can hand out pages that are
+    /// already zero instead of writing them.  A [`GrowError::AllocationFailed`] is returned if the
+    /// memory cannot be allocated.
+    ///
+    pub fn zeroed(capacity: u32) -> Result<Self, GrowError> {
+        let layout = layout_for(capacity, os::ALIGNMENT)?;
+        let pointer = unsafe { alloc_zeroed(layout) };
         if pointer.is_null() {
-            std::alloc::handle_alloc_error(layout);
+            return Err(GrowError::AllocationFailed { capacity });
         }
-        Self {
+        Ok(Self {
             capacity,
             final_size: 0,
             layout,
             pointer,
+            allocator: &StdAlloc,
+        })
+    }
+    pub(crate) fn try_new_in(
+        capacity: u32,
+        allocator: &'static dyn BufferAllocator,
+    ) -> Result<Self, GrowError> {
+        Self::try_new_aligned_in(capacity, os::ALIGNMENT, allocator)
+    }
+    pub(crate) fn try_new_aligned_in(
+        capacity: u32,
+        align: usize,
+        allocator: &'static dyn BufferAllocator,
+    ) -> Result<Self, GrowError> {
+        let layout = layout_for(capacity, align)?;
+        let pointer = allocator.alloc(layout.size(), layout.align());
+        if pointer.is_null() {
+            return Err(GrowError::AllocationFailed { capacity });
         }
+        Ok(Self {
+            capacity,
+            final_size: 0,
+            layout,
+            pointer,
+            allocator,
+        })
+    }
+    pub(crate) fn allocator(&self) -> &'static dyn BufferAllocator {
+        self.allocator
+    }
+    // Give up ownership of the memory.  The caller is responsible for freeing it in a way that
+    // matches the allocator.
+    pub(crate) fn into_raw(mut self) -> *mut u8 {
+        std::mem::replace(&mut self.pointer, std::ptr::null_mut())
     }
 }
 
-impl Drop for HeapBuffer {
-    fn drop(&mut self) {
-        if !self.pointer.is_null() {
-            unsafe { dealloc(self.pointer, self.layout) };
+impl HeapBuffer {
+    /// Grow to `new_capacity` bytes.
+    ///
+    /// The memory is reallocated with the [`BufferAllocator`] which can often extend it in place.
+    /// The contents are kept and the alignment is unchanged.  Nothing is done if `new_capacity` is
+    /// not larger than the current capacity.  A [`GrowError::AllocationFailed`] is returned if the
+    /// memory cannot be reallocated in which case the buffer is unchanged.
+    ///
+    pub fn grow_to(&mut self, new_capacity: u32) -> Result<(), GrowError> {
+        if new_capacity <= self.capacity {
+            return Ok(());
+        }
+        self.try_resize(new_capacity)
+    }
+    // Grow to `capacity` bytes keeping the contents.  On failure the buffer is unchanged.
+    pub(crate) fn try_resize(&mut self, capacity: u32) -> Result<(), GrowError> {
+        let layout = layout_for(capacity, self.layout.align())?;
+        #[cfg(not(feature = "zeroize"))]
+        let pointer = unsafe {
+            self.allocator
+                .realloc(self.pointer, self.layout, layout.size())
+        };
+        // realloc could leave a copy behind.  Move the contents by hand and wipe the old memory.
+        #[cfg(feature = "zeroize")]
+        let pointer = unsafe {
+            let pointer = self.allocator.alloc(layout.size(), layout.align());
+            if !pointer.is_null() {
+                let length = self.layout.size().min(layout.size());
+                std::ptr::copy_nonoverlapping(self.pointer, pointer, length);
+                wipe(self.pointer, self.layout.size());
+                self.allocator.dealloc(self.pointer, self.layout);
+            }
+            pointer
+        };
+        if pointer.is_null() {
+            return Err(GrowError::AllocationFailed { capacity });
         }
+        self.capacity = capacity;
+        self.layout = layout;
+        self.pointer = pointer;
+        Ok(())
     }
 }
 
-impl HeapBuffer {
-    pub(crate) fn read_buffer(&self) -> (Option<*const u8>, u32) {
-        assert!(self.final_size > 0);
-        (Some(self.pointer), self.final_size)
+impl std::fmt::Debug for HeapBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HeapBuffer")
+            .field("capacity", &self.capacity)
+            .field("final_size", &self.final_size)
+            .field("allocator", &self.allocator)
+            .finish()
+    }
+}
+
+impl Drop for HeapBuffer {
+    fn drop(&mut self) {
+        if !self.pointer.is_null() {
+            #[cfg(feature = "zeroize")]
+            wipe(self.pointer, self.layout.size());
+            unsafe { self.allocator.dealloc(self.pointer, self.layout) }
+        }
     }
 }
 
 impl ReadBuffer for HeapBuffer {
+    /// Returns a read-only pointer to the buffer and the number of elements stored in the buffer.
+    ///
+    /// A [`HeapBuffer`] is always aligned so the pointer is always provided.  If nothing was
+    /// stored the size is zero and the pointer must not be dereferenced.
+    ///
     fn read_buffer(&self) -> (Option<*const u8>, u32) {
-        assert!(self.final_size > 0);
         (Some(self.pointer), self.final_size)
     }
 }
@@ -250,4 +996,16 @@ impl WriteBuffer for HeapBuffer {
     fn write_buffer(&mut self) -> (*mut u8, u32) {
         (self.pointer, self.capacity)
     }
+    fn alignment(&self) -> usize {
+        self.layout.align()
+    }
+}
+
+// Overwrite `length` bytes starting at `p` with zeros in a way the optimizer cannot remove.
+#[cfg(feature = "zeroize")]
+pub(crate) fn wipe(p: *mut u8, length: usize) {
+    for i in 0..length {
+        unsafe { std::ptr::write_volatile(p.add(i), 0) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
 }
@@ -0,0 +1,269 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Instant;
+
+use windows::core::{PSTR, PWSTR};
+
+use crate::buffer::StackBuffer;
+use crate::generic::{winapi_loop, LoopLimits};
+use crate::retry::{RetryPolicy, RetryWithBackoff};
+use crate::strategy::GrowForSmallBinary;
+use crate::traits::{GrowStrategy, RawToInternal, ToResult};
+use crate::{Argument, FrozenBuffer, GrowableBuffer};
+
+const DEFAULT_STACK_CAPACITY: usize = 1024;
+
+/// Entry point for configuring a call loop one piece at a time.
+///
+/// [`Grob::builder`] returns a [`GrobBuilder`] configured like [`winapi_small_binary`][sb]: a 1 KiB
+/// stack buffer and [`GrowForSmallBinary`].  Each builder method changes one piece.  The loop is
+/// run by calling [`run`](GrobBuilder::run) for binary data or [`run_wide`](GrobBuilder::run_wide)
+/// for UTF-16 text.
+///
+/// # Examples
+///
+/// This is the [`adapters-addresses-full`][aaf] example rewritten to use the builder.
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::NetworkManagement::IpHelper::{
+///     GetAdaptersAddresses, GET_ADAPTERS_ADDRESSES_FLAGS, IP_ADAPTER_ADDRESSES_LH,
+/// };
+/// use windows::Win32::Networking::WinSock::AF_UNSPEC;
+///
+/// use grob::{Grob, GrowToNearestQuarterKibi, RvIsError};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     Grob::builder()
+///         .stack::<16384>()
+///         .strategy(GrowToNearestQuarterKibi::new())
+///         .max_tries(8)
+///         .run(
+///             |argument| {
+///                 RvIsError::new(unsafe {
+///                     GetAdaptersAddresses(
+///                         AF_UNSPEC.0 as u32,
+///                         GET_ADAPTERS_ADDRESSES_FLAGS(0),
+///                         None,
+///                         Some(argument.pointer()),
+///                         argument.size_mut(),
+///                     )
+///                 })
+///             },
+///             |frozen_buffer| {
+///                 let mut p: *const IP_ADAPTER_ADDRESSES_LH =
+///                     frozen_buffer.pointer().unwrap_or(std::ptr::null());
+///                 while !p.is_null() {
+///                     println!("FriendlyName = {}", unsafe { (*p).FriendlyName.display() });
+///                     p = unsafe { (*p).Next };
+///                 }
+///                 Ok(())
+///             },
+///         )?;
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [sb]: crate::winapi_small_binary
+/// [aaf]: https://github.com/Coding-Badly/grob/blob/main/grob/examples/adapters-addresses-full.rs
+///
+pub struct Grob {}
+
+impl Grob {
+    /// Returns a [`GrobBuilder`] with a 1 KiB stack buffer and [`GrowForSmallBinary`].
+    pub fn builder() -> GrobBuilder<GrowForSmallBinary, DEFAULT_STACK_CAPACITY> {
+        GrobBuilder {
+            grow_strategy: GrowForSmallBinary::new(),
+            limits: LoopLimits::default(),
+            zeroed: false,
+            heap_capacity: None,
+        }
+    }
+}
+
+/// A configured call loop.  See [`Grob`].
+///
+/// `S` is the [`GrowStrategy`].  `STACK_CAPACITY` is the capacity, in bytes, of the initial
+/// [`StackBuffer`].
+///
+#[derive(Debug)]
+pub struct GrobBuilder<S, const STACK_CAPACITY: usize> {
+    grow_strategy: S,
+    limits: LoopLimits,
+    zeroed: bool,
+    heap_capacity: Option<u32>,
+}
+
+impl<S, const STACK_CAPACITY: usize> GrobBuilder<S, STACK_CAPACITY>
+where
+    S: GrowStrategy,
+{
+    /// Use a [`StackBuffer`] with a capacity of `N` bytes as the initial buffer.
+    pub fn stack<const N: usize>(self) -> GrobBuilder<S, N> {
+        GrobBuilder {
+            grow_strategy: self.grow_strategy,
+            limits: self.limits,
+            zeroed: self.zeroed,
+            heap_capacity: self.heap_capacity,
+        }
+    }
+    /// Do not use a stack buffer.  The first call is made with a zero capacity buffer.
+    pub fn heap_only(self) -> GrobBuilder<S, 0> {
+        self.stack::<0>()
+    }
+    /// Use `grow_strategy` when the buffer has to grow.
+    pub fn strategy<T>(self, grow_strategy: T) -> GrobBuilder<T, STACK_CAPACITY>
+    where
+        T: GrowStrategy,
+    {
+        GrobBuilder {
+            grow_strategy,
+            limits: self.limits,
+            zeroed: self.zeroed,
+            heap_capacity: self.heap_capacity,
+        }
+    }
+    /// Make at most `max_tries` operating system calls.
+    ///
+    /// If the buffer still needs to grow after `max_tries` calls the loop ends with an
+    /// [`std::io::Error`] that has a kind of [`Other`](std::io::ErrorKind::Other).  The default is
+    /// [`DEFAULT_MAX_TRIES`](crate::DEFAULT_MAX_TRIES).
+    ///
+    pub fn max_tries(mut self, max_tries: usize) -> Self {
+        self.limits.max_tries = Some(max_tries);
+        self
+    }
+    /// Give up when `deadline` passes.
+    ///
+    /// The deadline is checked before each operating system call.  If it has passed the loop ends
+    /// with an [`std::io::Error`] that has a kind of [`TimedOut`](std::io::ErrorKind::TimedOut)
+    /// and wraps a [`GrobTimeout`][t].  A deadline and [`max_tries`](GrobBuilder::max_tries) can
+    /// be used together; whichever is reached first ends the loop.
+    ///
+    /// [t]: crate::GrobTimeout
+    ///
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.limits.deadline = Some(deadline);
+        self
+    }
+    /// Retry operating system calls that fail with a transient error.
+    ///
+    /// `retry_policy` decides which errors are transient and how long to wait before each retry.
+    /// When combined with a [`deadline`](GrobBuilder::deadline), the deadline is checked before
+    /// each wait; if the wait would pass the deadline the loop ends with a [`GrobTimeout`][t].
+    ///
+    /// [t]: crate::GrobTimeout
+    ///
+    pub fn retry(mut self, retry_policy: RetryPolicy) -> Self {
+        self.limits.retry = Some(retry_policy);
+        self
+    }
+    /// Wait before calling again when the size needed keeps changing.
+    ///
+    /// After the second and later times the buffer grows the loop waits as decided by
+    /// `grow_backoff`.  When combined with a [`deadline`](GrobBuilder::deadline), the deadline is
+    /// checked before each wait.  The default is to never wait.  See [`RetryWithBackoff`].
+    ///
+    pub fn grow_backoff(mut self, grow_backoff: RetryWithBackoff) -> Self {
+        self.limits.grow_backoff = Some(grow_backoff);
+        self
+    }
+    /// Skip the stack buffer and start with a heap buffer of `capacity` bytes.
+    ///
+    /// Use this when the data is known to be large.  See
+    /// [`GrowableBuffer::with_heap_capacity`].  If the heap buffer cannot be allocated the loop
+    /// ends with an [`std::io::Error`] that has a kind of
+    /// [`OutOfMemory`](std::io::ErrorKind::OutOfMemory).
+    ///
+    pub fn initial_capacity(mut self, capacity: u32) -> Self {
+        self.heap_capacity = Some(capacity);
+        self
+    }
+    /// Fill the buffer with zeros before every operating system call.
+    pub fn zeroed(mut self) -> Self {
+        self.zeroed = true;
+        self
+    }
+    /// Run the loop for an operating system call that returns binary data.
+    ///
+    /// `run` behaves like [`winapi_generic`][wg] with the configured buffer and strategy.
+    ///
+    /// [wg]: crate::winapi_generic
+    ///
+    pub fn run<FT, W, WR, F, U>(self, api_wrapper: W, finalize: F) -> Result<U, std::io::Error>
+    where
+        WR: ToResult,
+        W: FnMut(&mut Argument<*mut FT>) -> WR,
+        F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+    {
+        self.run_generic(api_wrapper, finalize)
+    }
+    /// Run the loop for an operating system call that returns UTF-16 text.
+    ///
+    /// `run_wide` behaves like [`winapi_generic`][wg] with the configured buffer and strategy.
+    ///
+    /// [wg]: crate::winapi_generic
+    ///
+    pub fn run_wide<W, WR, F, U>(self, api_wrapper: W, finalize: F) -> Result<U, std::io::Error>
+    where
+        WR: ToResult,
+        W: FnMut(&mut Argument<PWSTR>) -> WR,
+        F: FnMut(FrozenBuffer<u16>) -> Result<U, std::io::Error>,
+    {
+        self.run_generic(api_wrapper, finalize)
+    }
+    /// Run the loop for an operating system call that returns ANSI text.
+    ///
+    /// `run_narrow` behaves like [`winapi_generic`][wg] with the configured buffer and strategy.
+    ///
+    /// [wg]: crate::winapi_generic
+    ///
+    pub fn run_narrow<W, WR, F, U>(self, api_wrapper: W, finalize: F) -> Result<U, std::io::Error>
+    where
+        WR: ToResult,
+        W: FnMut(&mut Argument<PSTR>) -> WR,
+        F: FnMut(FrozenBuffer<u8>) -> Result<U, std::io::Error>,
+    {
+        self.run_generic(api_wrapper, finalize)
+    }
+
+    fn run_generic<FT, IT, W, WR, F, U>(
+        self,
+        api_wrapper: W,
+        finalize: F,
+    ) -> Result<U, std::io::Error>
+    where
+        IT: RawToInternal,
+        IT: Copy,
+        WR: ToResult,
+        W: FnMut(&mut Argument<IT>) -> WR,
+        F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+    {
+        if let Some(capacity) = self.heap_capacity {
+            let mut growable_buffer =
+                GrowableBuffer::<FT, IT>::with_heap_capacity(capacity, &self.grow_strategy)?;
+            growable_buffer.set_zeroed(self.zeroed);
+            return winapi_loop(growable_buffer, self.limits, api_wrapper, finalize);
+        }
+        let mut initial_buffer = StackBuffer::<STACK_CAPACITY>::new();
+        let mut growable_buffer =
+            GrowableBuffer::<FT, IT>::new(&mut initial_buffer, &self.grow_strategy);
+        growable_buffer.set_zeroed(self.zeroed);
+        winapi_loop(growable_buffer, self.limits, api_wrapper, finalize)
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ffi::OsString;
+
+use windows::core::PWSTR;
+use windows::Win32::Foundation::BOOL;
+use windows::Win32::System::SystemInformation::{GetComputerNameExW, COMPUTER_NAME_FORMAT};
+
+use crate::factory::{default_strategy, StrategyKind};
+use crate::generic::winapi_generic;
+use crate::strategy::{GrowForStaticText, GrowWithSpareWchar};
+use crate::win::{RvIsError, CAPACITY_FOR_NAMES};
+use crate::{Argument, GrowableBuffer, StackBuffer};
+
+/// Returns the name of the local computer in the `format` requested using
+/// [`GetComputerNameExW`][gcne].
+///
+/// `GetComputerNameExW` fails with `ERROR_MORE_DATA` when the buffer is too small.  The size it
+/// reports may not include the `NULL` terminator so one extra WCHAR is always reserved.
+///
+/// # Returns
+///
+/// * `Ok(`[`OsString`]`)` when `GetComputerNameExW` returns success.  Some formats are
+///   legitimately empty, like `ComputerNameDnsDomain` on a computer that is not in a domain.  An
+///   empty [`OsString`] is returned for those.
+///
+/// * `Err(`[`std::io::Error`]`)` when `GetComputerNameExW` fails
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::System::SystemInformation::{
+///     ComputerNameDnsDomain, ComputerNameDnsFullyQualified,
+/// };
+///
+/// use grob::computer_name_ex;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     println!("FQDN is {:?}", computer_name_ex(ComputerNameDnsFullyQualified)?);
+///     println!("DNS domain is {:?}", computer_name_ex(ComputerNameDnsDomain)?);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [gcne]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getcomputernameexw
+///
+pub fn computer_name_ex(format: COMPUTER_NAME_FORMAT) -> Result<OsString, std::io::Error> {
+    computer_name_ex_with(|argument| unsafe {
+        GetComputerNameExW(format, argument.pointer(), argument.size_mut())
+    })
+}
+
+/// [`computer_name_ex`] with the operating system call made by `api_wrapper`.
+///
+/// `api_wrapper` returns the value from `GetComputerNameExW` as-is.  This is useful for testing.
+///
+pub fn computer_name_ex_with<W>(mut api_wrapper: W) -> Result<OsString, std::io::Error>
+where
+    W: FnMut(&mut Argument<PWSTR>) -> BOOL,
+{
+    let mut initial_buffer = StackBuffer::<CAPACITY_FOR_NAMES>::new();
+    let grow_strategy = GrowWithSpareWchar(default_strategy(
+        StrategyKind::String,
+        GrowForStaticText::new(),
+    ));
+    let growable_buffer = GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+    winapi_generic(
+        growable_buffer,
+        |argument| RvIsError::new(api_wrapper(argument)),
+        // On success the size is the number of WCHARs stored without the NULL terminator.  Zero
+        // is an empty name.
+        |frozen_buffer| Ok(frozen_buffer.try_to_os_string()?.unwrap_or_default()),
+    )
+}
@@ -0,0 +1,242 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ffi::OsString;
+
+use windows::core::{GUID, PCWSTR};
+use windows::Win32::Devices::DeviceAndDriverInstallation::{
+    CM_Get_Device_Interface_ListW, CM_Get_Device_Interface_List_SizeW, CONFIGRET, CR_BUFFER_SMALL,
+    CR_SUCCESS,
+};
+use windows::Win32::Foundation::{
+    GetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, ERROR_MORE_DATA, HANDLE,
+};
+use windows::Win32::System::IO::DeviceIoControl;
+
+use crate::generic::{winapi_multi_sz, winapi_small_binary};
+use crate::win::{RvIsConfigRet, RvIsError};
+use crate::{Argument, FrozenBuffer};
+
+/// Wrapper for [`DeviceIoControl`][dic] with an output buffer that grows.
+///
+/// Control codes with variable size output, like `FSCTL_GET_NTFS_VOLUME_DATA` or
+/// `IOCTL_STORAGE_QUERY_PROPERTY`, fail with `ERROR_INSUFFICIENT_BUFFER` or `ERROR_MORE_DATA` when
+/// the output buffer is too small.  `winapi_device_io` doubles the buffer on both and calls again
+/// with the same `input`.  On success the bytes returned becomes the
+/// [`size`](FrozenBuffer::size) of the [`FrozenBuffer`] passed to `finalize`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::w;
+/// use windows::Win32::Foundation::CloseHandle;
+/// use windows::Win32::Storage::FileSystem::{
+///     CreateFileW, FILE_FLAGS_AND_ATTRIBUTES, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+/// };
+/// use windows::Win32::System::Ioctl::{
+///     PropertyStandardQuery, StorageDeviceProperty, IOCTL_STORAGE_QUERY_PROPERTY,
+///     STORAGE_PROPERTY_QUERY,
+/// };
+///
+/// use grob::winapi_device_io;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let device = unsafe {
+///         CreateFileW(
+///             w!("\\\\.\\PhysicalDrive0"),
+///             0,
+///             FILE_SHARE_READ | FILE_SHARE_WRITE,
+///             None,
+///             OPEN_EXISTING,
+///             FILE_FLAGS_AND_ATTRIBUTES(0),
+///             None,
+///         )
+///     }?;
+///     let query = STORAGE_PROPERTY_QUERY {
+///         PropertyId: StorageDeviceProperty,
+///         QueryType: PropertyStandardQuery,
+///         AdditionalParameters: [0],
+///     };
+///     let input = unsafe {
+///         std::slice::from_raw_parts(
+///             (&query as *const STORAGE_PROPERTY_QUERY).cast::<u8>(),
+///             std::mem::size_of::<STORAGE_PROPERTY_QUERY>(),
+///         )
+///     };
+///     let size = winapi_device_io(
+///         device,
+///         IOCTL_STORAGE_QUERY_PROPERTY,
+///         input,
+///         |frozen_buffer: grob::FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+///     )?;
+///     println!("The device descriptor is {} bytes", size);
+///     unsafe { CloseHandle(device) };
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [dic]: https://learn.microsoft.com/en-us/windows/win32/api/ioapiset/nf-ioapiset-deviceiocontrol
+///
+pub fn winapi_device_io<FT, F, U>(
+    device: HANDLE,
+    code: u32,
+    input: &[u8],
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    winapi_device_io_with(
+        input,
+        |input, argument, bytes_returned| unsafe {
+            DeviceIoControl(
+                device,
+                code,
+                (!input.is_empty()).then(|| input.as_ptr().cast()),
+                input.len() as u32,
+                Some(argument.pointer().cast()),
+                *argument.size_mut(),
+                Some(bytes_returned),
+                None,
+            )
+        },
+        finalize,
+    )
+}
+
+/// [`winapi_device_io`] with the operating system call made by `api_wrapper`.
+///
+/// `api_wrapper` is passed the input, the [`Argument`] for the output buffer, and where to store
+/// the bytes returned.  The input is a copy owned by `winapi_device_io_with` so every call gets
+/// exactly the bytes in `input`.  This is useful for `DeviceIoControl` variations or testing.
+///
+pub fn winapi_device_io_with<FT, W, F, U>(
+    input: &[u8],
+    mut api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    W: FnMut(&[u8], &mut Argument<*mut FT>, &mut u32) -> BOOL,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    let input: Box<[u8]> = input.into();
+    winapi_small_binary(
+        |argument| {
+            let mut bytes_returned = 0;
+            let rv = api_wrapper(&input, argument, &mut bytes_returned);
+            if rv.as_bool() {
+                *argument.size_mut() = bytes_returned;
+                return RvIsError::new(rv);
+            }
+            // Partial data and ERROR_MORE_DATA also means the buffer is too small.  The size needed
+            // is not reported so the buffer doubles.
+            let gle = unsafe { GetLastError() };
+            if gle == ERROR_MORE_DATA || gle == ERROR_INSUFFICIENT_BUFFER {
+                let size = argument.size_mut();
+                *size = size.saturating_mul(2);
+                RvIsError::new(ERROR_INSUFFICIENT_BUFFER.0)
+            } else {
+                RvIsError::new(gle.0)
+            }
+        },
+        finalize,
+    )
+}
+
+/// Wrapper for [`CM_Get_Device_Interface_ListW`][gdil]; the symbolic link names of the device
+/// interfaces in the class `guid`.
+///
+/// The size of the list is queried with [`CM_Get_Device_Interface_List_SizeW`][gdils] before every
+/// try.  Devices can arrive between the two calls.  When that happens the list call fails with
+/// `CR_BUFFER_SMALL` and both calls are made again.
+///
+/// # Arguments
+///
+/// * `guid` - The device interface class like `GUID_DEVINTERFACE_DISK`.
+///
+/// * `flags` - `CM_GET_DEVICE_INTERFACE_LIST_PRESENT` for only the interfaces that are present or
+///   `CM_GET_DEVICE_INTERFACE_LIST_ALL_DEVICES` for all of them.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::Devices::DeviceAndDriverInstallation::CM_GET_DEVICE_INTERFACE_LIST_PRESENT;
+/// use windows::Win32::System::Ioctl::GUID_DEVINTERFACE_DISK;
+///
+/// use grob::device_interface_list;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let disks =
+///         device_interface_list(&GUID_DEVINTERFACE_DISK, CM_GET_DEVICE_INTERFACE_LIST_PRESENT)?;
+///     for disk in disks {
+///         println!("{:?}", disk);
+///     }
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [gdil]: https://learn.microsoft.com/en-us/windows/win32/api/cfgmgr32/nf-cfgmgr32-cm_get_device_interface_listw
+/// [gdils]: https://learn.microsoft.com/en-us/windows/win32/api/cfgmgr32/nf-cfgmgr32-cm_get_device_interface_list_sizew
+///
+pub fn device_interface_list(guid: &GUID, flags: u32) -> Result<Vec<OsString>, std::io::Error> {
+    device_interface_list_with(
+        |length| unsafe { CM_Get_Device_Interface_List_SizeW(length, guid, PCWSTR::null(), flags) },
+        |buffer| unsafe { CM_Get_Device_Interface_ListW(guid, PCWSTR::null(), buffer, flags) },
+    )
+}
+
+/// [`device_interface_list`] with the operating system calls made by `size_wrapper` and
+/// `list_wrapper`.
+///
+/// `size_wrapper` stores the length of the list, in WCHARs, like
+/// [`CM_Get_Device_ID_List_SizeW`][gdids].  `list_wrapper` fills the buffer like
+/// [`CM_Get_Device_ID_ListW`][gdid].  Useful for the other Configuration Manager lists and for
+/// testing.
+///
+/// [gdids]: https://learn.microsoft.com/en-us/windows/win32/api/cfgmgr32/nf-cfgmgr32-cm_get_device_id_list_sizew
+/// [gdid]: https://learn.microsoft.com/en-us/windows/win32/api/cfgmgr32/nf-cfgmgr32-cm_get_device_id_listw
+///
+pub fn device_interface_list_with<S, L>(
+    mut size_wrapper: S,
+    mut list_wrapper: L,
+) -> Result<Vec<OsString>, std::io::Error>
+where
+    S: FnMut(&mut u32) -> CONFIGRET,
+    L: FnMut(&mut [u16]) -> CONFIGRET,
+{
+    winapi_multi_sz(|argument| {
+        let mut length = 0;
+        let cr = size_wrapper(&mut length);
+        if cr != CR_SUCCESS {
+            return RvIsConfigRet::new(cr);
+        }
+        if *argument.size_mut() < length {
+            *argument.size_mut() = length;
+            return RvIsConfigRet::new(CR_BUFFER_SMALL);
+        }
+        let cr = list_wrapper(argument.as_mut_slice());
+        if cr == CR_SUCCESS {
+            *argument.size_mut() = length;
+        }
+        RvIsConfigRet::new(cr)
+    })
+}
@@ -0,0 +1,210 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ffi::{OsStr, OsString};
+use std::os::windows::ffi::OsStringExt;
+
+use windows::core::PWSTR;
+use windows::Win32::Foundation::{GetLastError, ERROR_ENVVAR_NOT_FOUND, WIN32_ERROR};
+use windows::Win32::System::Environment::{ExpandEnvironmentStringsW, GetEnvironmentVariableW};
+
+use crate::base::{FillBufferAction, FillBufferResult};
+use crate::factory::{default_strategy, StrategyKind};
+use crate::generic::winapi_generic;
+use crate::strategy::{GrowForStaticText, GrowWithSpareWchar};
+use crate::traits::{NeededSize, ToResult};
+use crate::win::{win32_err, AsPCWSTR, IoErrorExt, RvIsLength, CAPACITY_FOR_PATHS};
+use crate::winstr::WindowsString;
+use crate::{Argument, Grob, GrowableBuffer, StackBuffer};
+
+// Most variable names fit on the stack.
+const NAME_STACK_WCHARS: usize = 64;
+// Strings to expand are often paths.
+const INPUT_STACK_WCHARS: usize = 260;
+
+/// Returns the value of the environment variable `name` using [`GetEnvironmentVariableW`][gev].
+///
+/// Unlike [`std::env::var_os`] the value is read directly from the process environment block.
+///
+/// # Returns
+///
+/// * `Ok(Some(`[`OsString`]`))` when the variable exists.  The [`OsString`] is empty if the
+///   variable is empty.
+///
+/// * `Ok(None)` when the variable does not exist
+///
+/// * `Err(`[`std::io::Error`]`)` when `name` contains a `NULL` or the operating system call fails
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use grob::winapi_env_var;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = winapi_env_var("PATH")?;
+///     println!("PATH is {:?}", path);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [gev]: https://learn.microsoft.com/en-us/windows/win32/api/processenv/nf-processenv-getenvironmentvariablew
+///
+pub fn winapi_env_var<S>(name: S) -> Result<Option<OsString>, std::io::Error>
+where
+    S: AsRef<OsStr>,
+{
+    let name = WindowsString::<NAME_STACK_WCHARS>::new(name)?;
+    winapi_env_var_with(|argument| {
+        RvIsLength::new(unsafe {
+            GetEnvironmentVariableW(name.as_param(), Some(argument.as_mut_slice()))
+        })
+    })
+}
+
+/// [`winapi_env_var`] with the operating system call made by `api_wrapper`.
+///
+/// The return value from `api_wrapper` is an [`RvIsLength`].  `ERROR_ENVVAR_NOT_FOUND` is
+/// returned as `Ok(None)`.  This is useful for reading a variable from something other than the
+/// current process, or for testing.
+///
+pub fn winapi_env_var_with<W>(api_wrapper: W) -> Result<Option<OsString>, std::io::Error>
+where
+    W: FnMut(&mut Argument<PWSTR>) -> RvIsLength,
+{
+    let rv = Grob::builder()
+        .stack::<CAPACITY_FOR_PATHS>()
+        .strategy(default_strategy(
+            StrategyKind::String,
+            GrowForStaticText::new(),
+        ))
+        .run_wide(api_wrapper, |frozen_buffer| {
+            Ok(frozen_buffer.try_to_os_string()?.unwrap_or_default())
+        });
+    match rv {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.is_win32(ERROR_ENVVAR_NOT_FOUND) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Expands the environment variables in `input` using [`ExpandEnvironmentStringsW`][ees].
+///
+/// `ExpandEnvironmentStringsW` returns the number of `WCHAR`s needed including the `NULL`
+/// terminator but versions of Windows do not agree on the exact count.  The buffer always has
+/// room for one `WCHAR` more than requested and the result ends at the first `NULL`, not at the
+/// count, so the last character is not lost.  A count of exactly one more than the capacity is
+/// treated as a guess and the buffer at least doubles.
+///
+/// # Returns
+///
+/// * `Ok(`[`OsString`]`)` with the variables expanded.  Variables that do not exist are left
+///   as-is.
+///
+/// * `Err(`[`std::io::Error`]`)` when `input` contains a `NULL` or the operating system call fails
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use grob::expand_environment_strings;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let expanded = expand_environment_strings("%SystemRoot%\\System32")?;
+///     println!("{:?}", expanded);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [ees]: https://learn.microsoft.com/en-us/windows/win32/api/processenv/nf-processenv-expandenvironmentstringsw
+///
+pub fn expand_environment_strings<S>(input: S) -> Result<OsString, std::io::Error>
+where
+    S: AsRef<OsStr>,
+{
+    let input = WindowsString::<INPUT_STACK_WCHARS>::new(input)?;
+    expand_environment_strings_with(|argument| unsafe {
+        ExpandEnvironmentStringsW(input.as_param(), Some(argument.as_mut_slice()))
+    })
+}
+
+/// [`expand_environment_strings`] with the operating system call made by `api_wrapper`.
+///
+/// `api_wrapper` returns the value from `ExpandEnvironmentStringsW` as-is.  This is useful for
+/// testing.
+///
+pub fn expand_environment_strings_with<W>(mut api_wrapper: W) -> Result<OsString, std::io::Error>
+where
+    W: FnMut(&mut Argument<PWSTR>) -> u32,
+{
+    let mut initial_buffer = StackBuffer::<CAPACITY_FOR_PATHS>::new();
+    let grow_strategy = GrowWithSpareWchar(default_strategy(
+        StrategyKind::String,
+        GrowForStaticText::new(),
+    ));
+    let growable_buffer = GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+    winapi_generic(
+        growable_buffer,
+        |argument| RvIsNeeded::new(api_wrapper(argument)),
+        // The count may or may not include the terminator so the result ends at the first NULL.
+        // The expanded string cannot contain a NULL because the input cannot.
+        |frozen_buffer| {
+            let wide = match frozen_buffer.try_pointer()? {
+                Some(p) => unsafe { std::slice::from_raw_parts(p, frozen_buffer.size() as usize) },
+                None => &[],
+            };
+            let end = wide.iter().position(|c| *c == 0).unwrap_or(wide.len());
+            Ok(OsString::from_wide(&wide[..end]))
+        },
+    )
+}
+
+// The return value from ExpandEnvironmentStringsW; the number of WCHARs needed or stored with zero
+// meaning failure.
+struct RvIsNeeded(u32, WIN32_ERROR);
+
+impl RvIsNeeded {
+    fn new(value: u32) -> Self {
+        let gle = unsafe { GetLastError() };
+        Self(value, gle)
+    }
+}
+
+impl ToResult for RvIsNeeded {
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        needed_size.set_os_error_hint(self.1 .0);
+        let ns = needed_size.needed_size();
+        if self.0 == 0 {
+            needed_size.set_failed();
+            Err(win32_err(self.1))
+        // Some versions return one more than the capacity for any buffer that is too small so
+        // that count is not trusted; the buffer at least doubles.
+        } else if self.0 == ns.saturating_add(1) {
+            needed_size.set_needed_size(self.0.max(ns.saturating_mul(2)));
+            Ok(FillBufferAction::Grow)
+        } else if self.0 > ns {
+            needed_size.set_needed_size(self.0);
+            Ok(FillBufferAction::Grow)
+        } else {
+            needed_size.set_needed_size(self.0);
+            Ok(FillBufferAction::Commit)
+        }
+    }
+}
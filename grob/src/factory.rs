@@ -0,0 +1,162 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+use crate::generic::LARGE_BINARY_FLOOR;
+use crate::strategy::{
+    GrowForMultiSz, GrowForSmallBinary, GrowForStaticText, GrowForStoredIsReturned,
+    GrowToNearestQuarterKibi,
+};
+use crate::traits::GrowStrategy;
+use crate::win::CAPACITY_FOR_PATHS;
+
+/// Identifies which generic wrapper function is asking for a [`GrowStrategy`].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StrategyKind {
+    /// [`winapi_small_binary`](crate::winapi_small_binary).
+    SmallBinary,
+    /// [`winapi_large_binary`](crate::winapi_large_binary) and its variants.
+    LargeBinary,
+    /// [`winapi_path_buf`](crate::winapi_path_buf).
+    PathBuf,
+    /// [`winapi_string`](crate::winapi_string).
+    String,
+    /// [`winapi_multi_sz`](crate::winapi_multi_sz).
+    MultiSz,
+}
+
+impl StrategyKind {
+    /// Returns the [`GrowStrategy`] the generic wrapper uses when no factory is set.
+    ///
+    /// A factory can use this to change only some kinds or to wrap the built-in strategy.
+    ///
+    pub fn built_in(self) -> Box<dyn GrowStrategy + Send + Sync> {
+        const CFP: u64 = CAPACITY_FOR_PATHS as u64;
+        match self {
+            Self::SmallBinary => Box::new(GrowForSmallBinary::new()),
+            Self::LargeBinary => {
+                Box::new(GrowToNearestQuarterKibi::<LARGE_BINARY_FLOOR>::with_const_floor())
+            }
+            Self::PathBuf => Box::new(GrowForStoredIsReturned::<CFP>::new()),
+            Self::String => Box::new(GrowForStaticText::new()),
+            Self::MultiSz => Box::new(GrowForMultiSz::new()),
+        }
+    }
+}
+
+/// Creates the [`GrowStrategy`] for a generic wrapper function.  See
+/// [`set_default_strategy_factory`].
+pub type StrategyFactory = fn(StrategyKind) -> Box<dyn GrowStrategy + Send + Sync>;
+
+static STRATEGY_FACTORY: OnceLock<RwLock<Option<StrategyFactory>>> = OnceLock::new();
+
+fn strategy_factory() -> &'static RwLock<Option<StrategyFactory>> {
+    STRATEGY_FACTORY.get_or_init(|| RwLock::new(None))
+}
+
+/// Replace the grow strategies used by the generic wrapper functions for the entire process.
+///
+/// [`winapi_small_binary`][sb], [`winapi_large_binary`][lb] (and its variants),
+/// [`winapi_path_buf`][pb], and [`winapi_string`][ws] call `factory` for every call loop instead
+/// of using their built-in strategy.  The intent is to let an application change the behaviour of
+/// crates that it does not control; for example, to cap buffer capacities when memory is limited.
+/// [`StrategyKind::built_in`] returns the strategy that would otherwise have been used.
+///
+/// # Composition
+///
+/// There is one factory for the process and the last one set wins.  A library that calls this
+/// function replaces any factory the application set.  Libraries should leave this function to
+/// applications.
+///
+/// [sb]: crate::winapi_small_binary
+/// [lb]: crate::winapi_large_binary
+/// [pb]: crate::winapi_path_buf
+/// [ws]: crate::winapi_string
+///
+pub fn set_default_strategy_factory(factory: StrategyFactory) {
+    let mut guard = strategy_factory()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+    *guard = Some(factory);
+}
+
+/// Remove the factory set by [`set_default_strategy_factory`] so the generic wrapper functions go
+/// back to their built-in strategies.
+///
+/// Mostly useful in tests.
+///
+pub fn reset_default_strategy_factory() {
+    let mut guard = strategy_factory()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+    *guard = None;
+}
+
+// The strategy used by a generic wrapper function.  The built-in strategy is kept inline so there
+// is no heap allocation unless a factory has been set.
+pub(crate) enum DefaultStrategy<S> {
+    BuiltIn(S),
+    Custom(Box<dyn GrowStrategy + Send + Sync>),
+}
+
+impl<S> GrowStrategy for DefaultStrategy<S>
+where
+    S: GrowStrategy,
+{
+    fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+        match self {
+            Self::BuiltIn(s) => s.next_capacity(tries, desired_capacity),
+            Self::Custom(s) => s.next_capacity(tries, desired_capacity),
+        }
+    }
+    fn name(&self) -> &'static str {
+        match self {
+            Self::BuiltIn(s) => s.name(),
+            Self::Custom(s) => s.name(),
+        }
+    }
+    fn max_capacity(&self) -> Option<u32> {
+        match self {
+            Self::BuiltIn(s) => s.max_capacity(),
+            Self::Custom(s) => s.max_capacity(),
+        }
+    }
+    fn should_continue(&self, tries: usize) -> bool {
+        match self {
+            Self::BuiltIn(s) => s.should_continue(tries),
+            Self::Custom(s) => s.should_continue(tries),
+        }
+    }
+    fn initial_capacity(&self) -> Option<u32> {
+        match self {
+            Self::BuiltIn(s) => s.initial_capacity(),
+            Self::Custom(s) => s.initial_capacity(),
+        }
+    }
+}
+
+// Returns the strategy from the factory, if one is set, otherwise `built_in`.
+pub(crate) fn default_strategy<S>(kind: StrategyKind, built_in: S) -> DefaultStrategy<S> {
+    // Copy the function pointer so the lock is not held while the factory runs.
+    let factory = *strategy_factory()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+    match factory {
+        Some(factory) => DefaultStrategy::Custom(factory(kind)),
+        None => DefaultStrategy::BuiltIn(built_in),
+    }
+}
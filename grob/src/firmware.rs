@@ -0,0 +1,83 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use windows::Win32::System::SystemInformation::{
+    GetSystemFirmwareTable, FIRMWARE_TABLE_ID, FIRMWARE_TABLE_PROVIDER,
+};
+
+use crate::generic::{copy_to_vec, winapi_small_binary};
+use crate::win::RvIsRequiredBytes;
+use crate::Argument;
+
+/// Returns the firmware table `id` from the firmware table `provider` using
+/// [`GetSystemFirmwareTable`][gsft].
+///
+/// `GetSystemFirmwareTable` returns the number of bytes in the table even when the buffer is too
+/// small so the buffer is grown to exactly that size.  [`RvIsRequiredBytes`][r] handles the return
+/// value.
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` when `GetSystemFirmwareTable` returns success.  The [`Vec`] is the raw table.
+///
+/// * `Err(`[`std::io::Error`]`)` when `GetSystemFirmwareTable` fails.  For example, when the
+///   table does not exist.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::System::SystemInformation::RSMB;
+///
+/// use grob::system_firmware_table;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let smbios = system_firmware_table(RSMB, 0)?;
+///     println!("The raw SMBIOS data is {} bytes", smbios.len());
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [r]: crate::RvIsRequiredBytes
+/// [gsft]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getsystemfirmwaretable
+///
+pub fn system_firmware_table(
+    provider: FIRMWARE_TABLE_PROVIDER,
+    id: u32,
+) -> Result<Vec<u8>, std::io::Error> {
+    system_firmware_table_with(|argument| {
+        RvIsRequiredBytes::new(unsafe {
+            GetSystemFirmwareTable(
+                provider,
+                FIRMWARE_TABLE_ID(id),
+                Some(argument.pointer().cast()),
+                *argument.size_mut(),
+            )
+        })
+    })
+}
+
+/// [`system_firmware_table`] with the operating system call made by `api_wrapper`.
+///
+/// This is useful for testing.
+///
+pub fn system_firmware_table_with<W>(api_wrapper: W) -> Result<Vec<u8>, std::io::Error>
+where
+    W: FnMut(&mut Argument<*mut u8>) -> RvIsRequiredBytes,
+{
+    winapi_small_binary(api_wrapper, |frozen_buffer| copy_to_vec(&frozen_buffer))
+}
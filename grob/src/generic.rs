@@ -13,26 +13,338 @@
 // limitations under the License.
 
 use std::ffi::OsString;
+use std::marker::PhantomData;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::time::{Duration, Instant};
 
-use windows::core::PWSTR;
+use windows::core::{PSTR, PWSTR};
+use windows::Win32::Foundation::MAX_PATH;
 
-use crate::buffer::StackBuffer;
+use crate::base::{FillBufferAction, GrobCallError, GrobTimeout, GrowStats};
+use crate::factory::{default_strategy, StrategyKind};
+use crate::retry::{RetryPolicy, RetryWithBackoff};
 use crate::strategy::{
-    GrowForSmallBinary, GrowForStaticText, GrowForStoredIsReturned, GrowToNearestQuarterKibi,
+    GrowForMultiSz, GrowForSmallBinary, GrowForStaticText, GrowForStoredIsReturned,
+    GrowToNearestQuarterKibi,
 };
-use crate::traits::{GrowStrategy, RawToInternal, ToResult, WriteBuffer};
-use crate::win::{CAPACITY_FOR_NAMES, CAPACITY_FOR_PATHS};
-use crate::{Argument, FrozenBuffer, GrowableBuffer};
+use crate::traits::{
+    GrobCall, GrowStrategy, NeededSize, Pod, RawToInternal, ToResult, WriteBuffer,
+};
+use crate::win::{
+    capacity_for_wchars, ElementCount, RvIsFull, CAPACITY_FOR_NAMES, CAPACITY_FOR_PATHS,
+};
+use crate::{Argument, FrozenBuffer, Grob, GrowableBuffer, StackBuffer};
+
+/// The number of operating system calls the generic functions make before giving up on a buffer
+/// that keeps needing to grow.
+///
+/// A well behaved function needs two or three calls.  A function that reports a larger size on
+/// every call would otherwise be called until an allocation fails.  [`winapi_generic_limited`]
+/// and [`GrobBuilder::max_tries`][m] use a different limit.
+///
+/// [m]: crate::GrobBuilder::max_tries
+///
+pub const DEFAULT_MAX_TRIES: usize = 16;
 
 /// Generic growable buffer loop.
 ///
 /// This generic function implements the call-operating-system-grow-buffer loop.  It is not meant to
 /// be used directly.
 ///
+/// # Panics
+///
+/// `winapi_generic` does not panic on its own.  It is unwind-transparent: if `api_wrapper`,
+/// `finalize`, or the [`GrowStrategy`] panics, the panic passes through unchanged.  While
+/// unwinding, the [`Argument`] restores the [`GrowableBuffer`] and any heap buffer is freed.  The
+/// same is true for the other generic functions.
+///
+/// # Errors
+///
+/// If the buffer still needs to grow after [`DEFAULT_MAX_TRIES`] calls the loop ends with a
+/// [`std::io::Error`] that has a kind of [`Other`](std::io::ErrorKind::Other).  The message
+/// includes the number of calls and the size the last call needed.  The same is true for the
+/// other generic functions.
+///
 pub fn winapi_generic<FT, IT, W, WR, F, U>(
-    mut growable_buffer: GrowableBuffer<FT, IT>,
-    mut api_wrapper: W,
+    growable_buffer: GrowableBuffer<FT, IT>,
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    IT: RawToInternal,
+    IT: Copy,
+    WR: ToResult,
+    W: FnMut(&mut Argument<IT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    run_call(growable_buffer, Closures::new(api_wrapper, finalize))
+}
+
+/// [`winapi_generic`] that also returns the [`GrowStats`] for the call loop.
+///
+/// The statistics are taken from the [`FrozenBuffer`] before it is given to `finalize`.  They are
+/// only returned when `finalize` succeeds.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::System::WindowsProgramming::GetUserNameW;
+///
+/// use grob::{
+///     winapi_generic_with_stats, FrozenBuffer, GrowForStaticText, GrowableBuffer, RvIsError,
+///     StackBuffer,
+/// };
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut initial_buffer = StackBuffer::<16>::new();
+///     let grow_strategy = GrowForStaticText::new();
+///     let growable_buffer = GrowableBuffer::new(&mut initial_buffer, &grow_strategy);
+///     let (size, stats) = winapi_generic_with_stats(
+///         growable_buffer,
+///         |argument| {
+///             RvIsError::new(unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) })
+///         },
+///         |frozen_buffer: FrozenBuffer<u16>| Ok(frozen_buffer.size()),
+///     )?;
+///     println!("{} characters after {} tries", size, stats.tries);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+pub fn winapi_generic_with_stats<FT, IT, W, WR, F, U>(
+    growable_buffer: GrowableBuffer<FT, IT>,
+    api_wrapper: W,
+    finalize: F,
+) -> Result<(U, GrowStats), std::io::Error>
+where
+    IT: RawToInternal,
+    IT: Copy,
+    WR: ToResult,
+    W: FnMut(&mut Argument<IT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    winapi_generic(growable_buffer, api_wrapper, keep_stats(finalize))
+}
+
+// Wrap a finalize closure so the statistics from the FrozenBuffer are returned with its value.
+fn keep_stats<FT, F, U>(
     mut finalize: F,
+) -> impl FnMut(FrozenBuffer<FT>) -> Result<(U, GrowStats), std::io::Error>
+where
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    move |frozen_buffer| {
+        let stats = frozen_buffer.stats();
+        finalize(frozen_buffer).map(|value| (value, stats))
+    }
+}
+
+/// Run the call-operating-system-grow-buffer loop for a [`GrobCall`].
+///
+/// `run_call` is [`winapi_generic`] with the `api_wrapper` and `finalize` closures replaced by
+/// one object that holds the state they share.
+///
+pub fn run_call<C>(
+    growable_buffer: GrowableBuffer<C::Ft, C::It>,
+    call: C,
+) -> Result<C::Output, std::io::Error>
+where
+    C: GrobCall,
+{
+    call_loop(growable_buffer, LoopLimits::default(), call)
+}
+
+/// Generic growable buffer loop that uses a buffer owned by the caller.
+///
+/// `winapi_generic_in` is [`winapi_generic`] except `growable_buffer` is borrowed instead of
+/// consumed.  Before the first operating system call the state left by any previous call is
+/// cleared; the buffer and its capacity are kept.  The [`FrozenBuffer`] given to `finalize`
+/// borrows `growable_buffer`.  See [`winapi_large_binary_in`].
+///
+pub fn winapi_generic_in<FT, IT, W, WR, F, U>(
+    growable_buffer: &mut GrowableBuffer<FT, IT>,
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    IT: RawToInternal,
+    IT: Copy,
+    WR: ToResult,
+    W: FnMut(&mut Argument<IT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    growable_buffer.reset();
+    let mut call = Closures::new(api_wrapper, finalize);
+    try_until_done(growable_buffer, LoopLimits::default(), &mut call)?;
+    call.finish(growable_buffer.freeze_in_place())
+}
+
+// Adapts the api_wrapper and finalize closures to a GrobCall.
+struct Closures<IT, W, F, FT, U> {
+    api_wrapper: W,
+    finalize: F,
+    types: PhantomData<fn(IT, FT) -> U>,
+}
+
+impl<IT, W, F, FT, U> Closures<IT, W, F, FT, U> {
+    fn new(api_wrapper: W, finalize: F) -> Self {
+        Self {
+            api_wrapper,
+            finalize,
+            types: PhantomData,
+        }
+    }
+}
+
+impl<IT, W, WR, F, FT, U> GrobCall for Closures<IT, W, F, FT, U>
+where
+    IT: RawToInternal,
+    IT: Copy,
+    WR: ToResult,
+    W: FnMut(&mut Argument<IT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    type Ft = FT;
+    type It = IT;
+    type Rv = WR;
+    type Output = U;
+
+    fn call(&mut self, argument: &mut Argument<IT>) -> WR {
+        (self.api_wrapper)(argument)
+    }
+    fn finish(mut self, frozen_buffer: FrozenBuffer<FT>) -> Result<U, std::io::Error> {
+        (self.finalize)(frozen_buffer)
+    }
+}
+
+/// Generic growable buffer loop that gives up after `max_tries` operating system calls.
+///
+/// `winapi_generic_limited` is [`winapi_generic`] with `max_tries` in place of
+/// [`DEFAULT_MAX_TRIES`].  If the buffer still needs to grow after `max_tries` calls the loop ends
+/// with a [`std::io::Error`] that has a kind of [`Other`](std::io::ErrorKind::Other).
+///
+/// [`Grob::builder`] provides the same limit through [`max_tries`][m].
+///
+/// [m]: crate::GrobBuilder::max_tries
+///
+pub fn winapi_generic_limited<FT, IT, W, WR, F, U>(
+    max_tries: usize,
+    growable_buffer: GrowableBuffer<FT, IT>,
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    IT: RawToInternal,
+    IT: Copy,
+    WR: ToResult,
+    W: FnMut(&mut Argument<IT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    let limits = LoopLimits {
+        max_tries: Some(max_tries),
+        ..LoopLimits::default()
+    };
+    winapi_loop(growable_buffer, limits, api_wrapper, finalize)
+}
+
+/// Generic growable buffer loop that gives up at a deadline.
+///
+/// `winapi_generic_with_deadline` is [`winapi_generic`] with a check of `deadline` before each
+/// operating system call.  If the deadline has passed the loop ends with a [`std::io::Error`] that
+/// has a kind of [`TimedOut`](std::io::ErrorKind::TimedOut) and wraps a [`GrobTimeout`].
+///
+/// The deadline takes the place of [`DEFAULT_MAX_TRIES`]; the number of calls is not limited.
+///
+/// [`Grob::builder`] provides the same check through [`deadline`][d], along with a limit on the
+/// number of calls.
+///
+/// [d]: crate::GrobBuilder::deadline
+///
+pub fn winapi_generic_with_deadline<FT, IT, W, WR, F, U>(
+    deadline: Instant,
+    growable_buffer: GrowableBuffer<FT, IT>,
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    IT: RawToInternal,
+    IT: Copy,
+    WR: ToResult,
+    W: FnMut(&mut Argument<IT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    // The deadline bounds the loop so DEFAULT_MAX_TRIES does not apply.
+    let limits = LoopLimits {
+        max_tries: None,
+        deadline: Some(deadline),
+        ..LoopLimits::default()
+    };
+    winapi_loop(growable_buffer, limits, api_wrapper, finalize)
+}
+
+/// Generic growable buffer loop that retries transient errors.
+///
+/// `winapi_generic_with_retry` is [`winapi_generic`] except that an error from the operating
+/// system call is checked with `retry_policy`.  If the error is transient the loop waits as
+/// determined by the [`Backoff`][b] then calls the operating system again.  Otherwise, or once the
+/// [`RetryPolicy`] gives up, the error is returned.
+///
+/// [`Grob::builder`] provides the same retries through [`retry`][r], along with a deadline and a
+/// limit on the number of calls.
+///
+/// [b]: crate::Backoff
+/// [r]: crate::GrobBuilder::retry
+///
+pub fn winapi_generic_with_retry<FT, IT, W, WR, F, U>(
+    retry_policy: RetryPolicy,
+    growable_buffer: GrowableBuffer<FT, IT>,
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    IT: RawToInternal,
+    IT: Copy,
+    WR: ToResult,
+    W: FnMut(&mut Argument<IT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    let limits = LoopLimits {
+        retry: Some(retry_policy),
+        ..LoopLimits::default()
+    };
+    winapi_loop(growable_buffer, limits, api_wrapper, finalize)
+}
+
+// Limits on the call-operating-system-grow-buffer loop.  Only max_tries is on by default.
+#[derive(Debug)]
+pub(crate) struct LoopLimits {
+    pub(crate) max_tries: Option<usize>,
+    pub(crate) deadline: Option<Instant>,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) grow_backoff: Option<RetryWithBackoff>,
+}
+
+impl Default for LoopLimits {
+    fn default() -> Self {
+        Self {
+            max_tries: Some(DEFAULT_MAX_TRIES),
+            deadline: None,
+            retry: None,
+            grow_backoff: None,
+        }
+    }
+}
+
+// The call-operating-system-grow-buffer loop with optional limits.
+pub(crate) fn winapi_loop<FT, IT, W, WR, F, U>(
+    growable_buffer: GrowableBuffer<FT, IT>,
+    limits: LoopLimits,
+    api_wrapper: W,
+    finalize: F,
 ) -> Result<U, std::io::Error>
 where
     IT: RawToInternal,
@@ -41,15 +353,120 @@ where
     W: FnMut(&mut Argument<IT>) -> WR,
     F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
 {
+    call_loop(
+        growable_buffer,
+        limits,
+        Closures::new(api_wrapper, finalize),
+    )
+}
+
+// The call-operating-system-grow-buffer loop for a GrobCall with optional limits.
+fn call_loop<C>(
+    mut growable_buffer: GrowableBuffer<C::Ft, C::It>,
+    limits: LoopLimits,
+    mut call: C,
+) -> Result<C::Output, std::io::Error>
+where
+    C: GrobCall,
+{
+    try_until_done(&mut growable_buffer, limits, &mut call)?;
+    call.finish(growable_buffer.freeze())
+}
+
+// Call the operating system until the data fits or there is an error.  The buffer is borrowed so
+// it can be used again after the data is finalized.
+fn try_until_done<C>(
+    growable_buffer: &mut GrowableBuffer<C::Ft, C::It>,
+    limits: LoopLimits,
+    call: &mut C,
+) -> Result<(), std::io::Error>
+where
+    C: GrobCall,
+{
+    let started = Instant::now();
+    let mut tries = 0;
+    let mut retries = 0;
+    let mut grew = false;
+    let mut grows = 0;
+    let mut last_requested = 0;
+    let mut attempts = growable_buffer.attempts();
     loop {
-        let mut argument = growable_buffer.argument();
-        let rv = api_wrapper(&mut argument);
-        let fill_buffer_action = rv.to_result(&mut argument)?;
-        if argument.apply(fill_buffer_action) {
+        if let Some(deadline) = limits.deadline {
+            if Instant::now() >= deadline {
+                let elapsed = started.elapsed();
+                return Err(GrobTimeout { tries, elapsed }.into());
+            }
+        }
+        let Some(mut attempt) = attempts.next_attempt() else {
             break;
+        };
+        tries += 1;
+        let rv = call.call(&mut attempt);
+        let fill_buffer_action = match rv.to_result(&mut *attempt) {
+            Ok(fill_buffer_action) => fill_buffer_action,
+            Err(error) => {
+                // The loop owns the Attempt so it is given up here; a ToResult implementation does
+                // not have to call set_failed.
+                let capacity = attempt.capacity;
+                attempt.dismiss();
+                let retry = limits.retry.as_ref().and_then(|retry| {
+                    let delay = retry.delay(retries + 1, &error)?;
+                    Some((retry, delay))
+                });
+                let Some((retry, delay)) = retry else {
+                    let call_error =
+                        GrobCallError::new(error, tries, capacity, grew, last_requested);
+                    return Err(call_error.into());
+                };
+                check_wait(limits.deadline, delay, started, tries)?;
+                retries += 1;
+                retry.sleep(delay);
+                continue;
+            }
+        };
+        if let (FillBufferAction::Grow, Some(max_tries)) = (&fill_buffer_action, limits.max_tries) {
+            if tries >= max_tries {
+                let message = format!(
+                    "gave up after {} tries; the last call needed a size of {}",
+                    tries,
+                    attempt.needed_size()
+                );
+                attempt.dismiss();
+                return Err(std::io::Error::other(message));
+            }
+        }
+        let grow = matches!(fill_buffer_action, FillBufferAction::Grow);
+        if grow {
+            grew = true;
+            grows += 1;
+            last_requested = C::It::size_to_capacity(attempt.needed_size());
+        }
+        attempt.resolve(fill_buffer_action)?;
+        // The first grow is the expected size probe.  A later grow means the size changed.
+        if let (true, Some(grow_backoff)) = (grows > 1 && grow, &limits.grow_backoff) {
+            if let Some(delay) = grow_backoff.delay(grows - 1) {
+                check_wait(limits.deadline, delay, started, tries)?;
+                grow_backoff.sleep(delay);
+            }
         }
     }
-    finalize(growable_buffer.freeze())
+    Ok(())
+}
+
+// No point sleeping if the deadline passes before the next try.
+fn check_wait(
+    deadline: Option<Instant>,
+    delay: Duration,
+    started: Instant,
+    tries: usize,
+) -> Result<(), GrobTimeout> {
+    match deadline {
+        Some(deadline) if Instant::now() + delay >= deadline => {
+            let elapsed = started.elapsed();
+            Err(GrobTimeout { tries, elapsed })
+        }
+        _ => Ok(()),
+    }
 }
 
 /// Generic growable buffer loop for binary data (the result datatype is implied).
@@ -77,21 +494,21 @@ where
 /// # Arguments
 ///
 /// * `api_wrapper` - The Windows API call is made inside this closure.  The argument for the call
-///     is provided.  The return value from the closure is either an [`RvIsError`][e] or an
-///     [`RvIsSize`][s].
+///   is provided.  The return value from the closure is either an [`RvIsError`][e] or an
+///   [`RvIsSize`][s].  Create it in the same expression as the call to preserve the last error.
 ///
 /// * `finalize` - If the Windows API call is successful, this closure is passed a [`FrozenBuffer`]
-///     that allows access to the data.
+///   that allows access to the data.
 ///
 /// # Returns
 ///
 /// The return value from `winapi_small_binary` is...
 ///
 /// * `Ok( /* success value */ )` when the operating system call and the `finalize` closure return
-///     success where `success value` is the value returned from the `finalize` closure
+///   success where `success value` is the value returned from the `finalize` closure
 ///
 /// * `Err(`[`std::io::Error`]`)` when the operating system call fails or the `finalize` closure
-///     returns an error
+///   returns an error
 ///
 /// # Examples
 ///
@@ -112,7 +529,7 @@ where
 ///                 GetLogicalProcessorInformationEx(
 ///                     RelationGroup,
 ///                     Some(argument.pointer()),
-///                     argument.size(),
+///                     argument.size_mut(),
 ///                 )
 ///             })
 ///         },
@@ -161,31 +578,137 @@ where
     W: FnMut(&mut Argument<*mut FT>) -> WR,
     F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
 {
-    let mut initial_buffer = StackBuffer::<1024>::new();
-    let grow_strategy = GrowForSmallBinary::new();
-    winapi_binary(&mut initial_buffer, &grow_strategy, api_wrapper, finalize)
+    winapi_small_binary_with::<SMALL_BINARY_STACK_CAPACITY, _, _, _, _, _>(api_wrapper, finalize)
+}
+
+/// Initial stack buffer capacity, in bytes, used by [`winapi_small_binary`].
+///
+const SMALL_BINARY_STACK_CAPACITY: usize = 1024;
+
+/// Generic wrapper function for a Windows API call that returns binary data and needs a relatively
+/// small buffer with the stack buffer capacity chosen by the caller.
+///
+/// This function is identical to [`winapi_small_binary`] except the capacity, in bytes, of the
+/// initial stack buffer is `STACK_CAPACITY`.  A `STACK_CAPACITY` of zero skips the stack buffer
+/// entirely; the first operating system call is made with a zero capacity buffer to determine the
+/// size needed.
+///
+/// # Examples
+///
+/// [`GetLogicalProcessorInformationEx`][glpi] with `RelationAll` often needs more than the 1 KiB
+/// used by [`winapi_small_binary`].
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::System::SystemInformation::{GetLogicalProcessorInformationEx, RelationAll};
+///
+/// use grob::{winapi_small_binary_with, RvIsError};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let size = winapi_small_binary_with::<8192, _, _, _, _, _>(
+///         |argument| {
+///             RvIsError::new(unsafe {
+///                 GetLogicalProcessorInformationEx(
+///                     RelationAll,
+///                     Some(argument.pointer()),
+///                     argument.size_mut(),
+///                 )
+///             })
+///         },
+///         |frozen_buffer| Ok(frozen_buffer.size()),
+///     )?;
+///     println!("The processor information is {} bytes.", size);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [glpi]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getlogicalprocessorinformationex
+///
+pub fn winapi_small_binary_with<const STACK_CAPACITY: usize, FT, W, WR, F, U>(
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut FT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    Grob::builder()
+        .stack::<STACK_CAPACITY>()
+        .strategy(default_strategy(
+            StrategyKind::SmallBinary,
+            GrowForSmallBinary::new(),
+        ))
+        .run(api_wrapper, finalize)
+}
+
+/// Generic wrapper function for a Windows API call that returns binary data and needs a relatively
+/// small buffer using a [`GrowStrategy`] chosen by the caller.
+///
+/// This function is identical to [`winapi_small_binary`], including the 1 KiB stack buffer,
+/// except `grow_strategy` decides the heap buffer capacity.  The factory set with
+/// [`set_default_strategy_factory`](crate::set_default_strategy_factory) is not used.  See
+/// [`winapi_large_binary_with_strategy`] for an example.
+///
+pub fn winapi_small_binary_with_strategy<FT, W, WR, F, U>(
+    grow_strategy: &dyn GrowStrategy,
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut FT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    let mut initial_buffer = StackBuffer::<SMALL_BINARY_STACK_CAPACITY>::new();
+    winapi_binary(&mut initial_buffer, grow_strategy, api_wrapper, finalize)
+}
+
+/// [`winapi_small_binary`] that also returns the [`GrowStats`] for the call loop.
+///
+/// A call that often has [`switched_to_heap`](GrowStats::switched_to_heap) set may be better
+/// served by [`winapi_small_binary_with`] and a larger stack buffer.  See
+/// [`winapi_generic_with_stats`].
+///
+pub fn winapi_small_binary_with_stats<FT, W, WR, F, U>(
+    api_wrapper: W,
+    finalize: F,
+) -> Result<(U, GrowStats), std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut FT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    winapi_small_binary(api_wrapper, keep_stats(finalize))
 }
 
 /// Generic wrapper function for a Windows API call that returns binary data and needs a relatively large buffer.
 ///
+/// The initial stack buffer is 16 KiB.  If that is too small, the heap buffer capacity is never
+/// less than 64 KiB.  Use [`winapi_large_binary_with`] to choose a different stack buffer capacity
+/// or [`winapi_large_binary_heap`] to skip the stack buffer.
+///
 /// # Arguments
 ///
 /// * `api_wrapper` - The Windows API call is made inside this closure.  The argument for the call
-///     is provided.  The return value from the closure is either an [`RvIsError`][e] or an
-///     [`RvIsSize`][s].
+///   is provided.  The return value from the closure is either an [`RvIsError`][e] or an
+///   [`RvIsSize`][s].  Create it in the same expression as the call to preserve the last error.
 ///
 /// * `finalize` - If the Windows API call is successful, this closure is passed a [`FrozenBuffer`]
-///     that allows access to the data.
+///   that allows access to the data.
 ///
 /// # Returns
 ///
 /// The return value from `winapi_large_binary` is...
 ///
 /// * `Ok( /* success value */ )` when the operating system call and the `finalize` closure return
-///     success where `success value` is the value returned from the `finalize` closure
+///   success where `success value` is the value returned from the `finalize` closure
 ///
 /// * `Err(`[`std::io::Error`]`)` when the operating system call fails or the `finalize` closure
-///     returns an error
+///   returns an error
 ///
 /// # Examples
 ///
@@ -212,7 +735,7 @@ where
 ///                     GET_ADAPTERS_ADDRESSES_FLAGS(0),
 ///                     None,
 ///                     Some(argument.pointer()),
-///                     argument.size(),
+///                     argument.size_mut(),
 ///                 )
 ///             })
 ///         },
@@ -250,43 +773,687 @@ where
     W: FnMut(&mut Argument<*mut FT>) -> WR,
     F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
 {
-    let mut initial_buffer = StackBuffer::<65536>::new();
-    let grow_strategy = GrowToNearestQuarterKibi::new();
-    winapi_binary(&mut initial_buffer, &grow_strategy, api_wrapper, finalize)
+    winapi_large_binary_with::<LARGE_BINARY_STACK_CAPACITY, _, _, _, _, _>(api_wrapper, finalize)
 }
 
-/// Generic wrapper for a Windows API call that returns a file system path.
-///
-/// # Arguments
-///
-/// * `api_wrapper` - The Windows API call is made inside this closure.  The argument for the call
-///     is provided.  The return value from the closure is an [`RvIsSize`][s].
+/// Initial stack buffer capacity, in bytes, used by [`winapi_large_binary`].
 ///
-/// [s]: crate::RvIsSize
+/// Large enough for typical [`GetAdaptersAddresses`][gaa] results while staying friendly to
+/// threads with small stacks (async runtimes, recursive walkers).
 ///
-/// # Returns
+/// [gaa]: https://learn.microsoft.com/en-us/windows/win32/api/iphlpapi/nf-iphlpapi-getadaptersaddresses
 ///
-/// The return value from `winapi_path_buf` is...
+pub(crate) const LARGE_BINARY_STACK_CAPACITY: usize = 16384;
+
+/// Minimum heap buffer capacity, in bytes, used by the large binary wrappers.
 ///
-/// * `Ok(`[`PathBuf`][pb]`)` when the operating system call returns success
+/// When the stack buffer is too small the first heap buffer is at least this large so the data
+/// that did not fit on the stack has plenty of room.
 ///
-/// * `Err(`[`std::io::Error`]`)` when the operating system call fails
+pub(crate) const LARGE_BINARY_FLOOR: u64 = 65536;
+
+/// Generic wrapper function for a Windows API call that returns binary data and needs a relatively
+/// large buffer with the stack buffer capacity chosen by the caller.
 ///
-/// [pb]: std::path::PathBuf
+/// This function is identical to [`winapi_large_binary`] except the capacity, in bytes, of the
+/// initial stack buffer is `STACK_CAPACITY`.  A `STACK_CAPACITY` of zero skips the stack buffer
+/// entirely; see [`winapi_large_binary_heap`].  Once the stack buffer is too small the heap buffer
+/// capacity is never less than 64 KiB.
 ///
 /// # Examples
 ///
-/// This example calls [`GetModuleFileNameW`][gm] to get the full path to the running program then
-/// prints that path.
-///
-/// [gm]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/LibraryLoader/fn.GetModuleFileNameW.html
-///
 /// ```
 /// # #[cfg(not(miri))]
 /// # mod miri_skip {
 /// #
-/// use windows::Win32::Foundation::HMODULE;
-/// use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
+/// use windows::Win32::{
+///     NetworkManagement::IpHelper::{GetAdaptersAddresses, GET_ADAPTERS_ADDRESSES_FLAGS},
+///     Networking::WinSock::AF_UNSPEC,
+/// };
+///
+/// use grob::{winapi_large_binary_with, RvIsError};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let count = winapi_large_binary_with::<4096, _, _, _, _, _>(
+///         |argument| {
+///             RvIsError::new(unsafe {
+///                 GetAdaptersAddresses(
+///                     AF_UNSPEC.0 as u32,
+///                     GET_ADAPTERS_ADDRESSES_FLAGS(0),
+///                     None,
+///                     Some(argument.pointer()),
+///                     argument.size_mut(),
+///                 )
+///             })
+///         },
+///         |frozen_buffer| {
+///             let mut rv = 0;
+///             if let Some(mut p) = frozen_buffer.pointer() {
+///                 while p != std::ptr::null() {
+///                     rv += 1;
+///                     p = unsafe { (*p).Next };
+///                 }
+///             }
+///             Ok(rv)
+///         },
+///     )?;
+///     println!("There are {} network adapters.", count);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+pub fn winapi_large_binary_with<const STACK_CAPACITY: usize, FT, W, WR, F, U>(
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut FT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    Grob::builder()
+        .stack::<STACK_CAPACITY>()
+        .strategy(default_strategy(
+            StrategyKind::LargeBinary,
+            GrowToNearestQuarterKibi::<LARGE_BINARY_FLOOR>::with_const_floor(),
+        ))
+        .run(api_wrapper, finalize)
+}
+
+/// Generic wrapper function for a Windows API call that returns binary data and needs a relatively
+/// large buffer using a [`GrowStrategy`] chosen by the caller.
+///
+/// This function is identical to [`winapi_large_binary`], including the 16 KiB stack buffer,
+/// except `grow_strategy` decides the heap buffer capacity.  There is no 64 KiB floor unless
+/// `grow_strategy` provides one.  The factory set with
+/// [`set_default_strategy_factory`](crate::set_default_strategy_factory) is not used.
+///
+/// # Examples
+///
+/// This example grows the buffer in 4 KiB pages.
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::{
+///     NetworkManagement::IpHelper::{GetAdaptersAddresses, GET_ADAPTERS_ADDRESSES_FLAGS},
+///     Networking::WinSock::AF_UNSPEC,
+/// };
+///
+/// use grob::{winapi_large_binary_with_strategy, GrowStrategy, RvIsError, ALIGNMENT};
+///
+/// struct GrowByPage {}
+///
+/// impl GrowStrategy for GrowByPage {
+///     fn next_capacity(&self, _tries: usize, desired_capacity: u32) -> u32 {
+///         // Leave room to align the buffer.
+///         (desired_capacity + ALIGNMENT as u32).next_multiple_of(4096)
+///     }
+/// }
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let size = winapi_large_binary_with_strategy(
+///         &GrowByPage {},
+///         |argument| {
+///             RvIsError::new(unsafe {
+///                 GetAdaptersAddresses(
+///                     AF_UNSPEC.0 as u32,
+///                     GET_ADAPTERS_ADDRESSES_FLAGS(0),
+///                     None,
+///                     Some(argument.pointer()),
+///                     argument.size_mut(),
+///                 )
+///             })
+///         },
+///         |frozen_buffer| Ok(frozen_buffer.size()),
+///     )?;
+///     println!("The adapter addresses are {} bytes.", size);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+pub fn winapi_large_binary_with_strategy<FT, W, WR, F, U>(
+    grow_strategy: &dyn GrowStrategy,
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut FT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    let mut initial_buffer = StackBuffer::<LARGE_BINARY_STACK_CAPACITY>::new();
+    winapi_binary(&mut initial_buffer, grow_strategy, api_wrapper, finalize)
+}
+
+/// [`winapi_large_binary`] that also returns the [`GrowStats`] for the call loop.
+///
+/// See [`winapi_generic_with_stats`].
+///
+pub fn winapi_large_binary_with_stats<FT, W, WR, F, U>(
+    api_wrapper: W,
+    finalize: F,
+) -> Result<(U, GrowStats), std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut FT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    winapi_large_binary(api_wrapper, keep_stats(finalize))
+}
+
+/// Generic wrapper function for a Windows API call that returns an array of fixed-size elements
+///
+/// `winapi_vec` is [`winapi_large_binary`] with a `finalize` that copies the data into a
+/// [`Vec<T>`].  The [`Vec`] owns its elements so it can be returned from the caller freely.  The
+/// number of elements is the number of bytes stored divided by `size_of::<T>()`.
+///
+/// The bytes stored are read as `T`s so `T` has to be [`Pod`].  A Windows structure can be
+/// wrapped in a `#[repr(transparent)]` newtype that implements [`Pod`].
+///
+/// # Arguments
+///
+/// * `api_wrapper` - The Windows API call is made inside this closure.  The argument for the call
+///   is provided.  The return value from the closure is either an [`RvIsError`][e] or an
+///   [`RvIsSize`][s].  Create it in the same expression as the call to preserve the last error.
+///
+/// # Returns
+///
+/// * `Ok(Vec<T>)` when the operating system call returns success.  The [`Vec`] is empty if
+///   nothing was stored.
+///
+/// * `Err(`[`std::io::Error`]`)` when the operating system call fails or the number of bytes
+///   stored is not a multiple of `size_of::<T>()`
+///
+/// # Examples
+///
+/// This example counts the processor cores returned from
+/// [`GetLogicalProcessorInformation`][glpi].
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::System::SystemInformation::{
+///     GetLogicalProcessorInformation, RelationProcessorCore, SYSTEM_LOGICAL_PROCESSOR_INFORMATION,
+/// };
+///
+/// use grob::{winapi_vec, Pod, RvIsError};
+///
+/// #[derive(Clone, Copy)]
+/// #[repr(transparent)]
+/// struct Record(SYSTEM_LOGICAL_PROCESSOR_INFORMATION);
+///
+/// // Every field is an integer, an array of integers, or a union of those.
+/// unsafe impl Pod for Record {}
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let records = winapi_vec::<Record, _, _>(|argument| {
+///         RvIsError::new(unsafe {
+///             GetLogicalProcessorInformation(Some(argument.pointer().cast()), argument.size_mut())
+///         })
+///     })?;
+///     let cores = records
+///         .iter()
+///         .filter(|r| r.0.Relationship == RelationProcessorCore)
+///         .count();
+///     println!("{} processor cores", cores);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [e]: crate::RvIsError
+/// [s]: crate::RvIsSize
+/// [glpi]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getlogicalprocessorinformation
+///
+pub fn winapi_vec<T, W, WR>(api_wrapper: W) -> Result<Vec<T>, std::io::Error>
+where
+    T: Pod,
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut T>) -> WR,
+{
+    winapi_large_binary(api_wrapper, |frozen_buffer| copy_to_vec(&frozen_buffer))
+}
+
+/// Generic wrapper function for a Windows API call that fills the buffer with fixed-size elements
+/// without reporting the size needed
+///
+/// Functions like [`EnumProcesses`][ep] store as many elements as fit.  A full buffer means there
+/// may be more so `winapi_filled_until_slack` doubles the buffer and calls again until some space
+/// is left over.  The data is copied into a [`Vec<T>`] like [`winapi_vec`] so `T` has to be
+/// [`Pod`].
+///
+/// # Arguments
+///
+/// * `api_wrapper` - The Windows API call is made inside this closure.  The argument for the call
+///   is provided.  The return value from the closure is an [`RvIsFull`][f].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::System::ProcessStatus::EnumProcesses;
+///
+/// use grob::{winapi_filled_until_slack, RvIsFull};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let process_ids: Vec<u32> = winapi_filled_until_slack(|argument| {
+///         let mut stored = 0;
+///         let rv = unsafe { EnumProcesses(argument.pointer(), *argument.size_mut(), &mut stored) };
+///         RvIsFull::new(rv, stored)
+///     })?;
+///     println!("{} processes", process_ids.len());
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [f]: crate::RvIsFull
+/// [ep]: https://learn.microsoft.com/en-us/windows/win32/api/psapi/nf-psapi-enumprocesses
+///
+pub fn winapi_filled_until_slack<T, W>(api_wrapper: W) -> Result<Vec<T>, std::io::Error>
+where
+    T: Pod,
+    W: FnMut(&mut Argument<*mut T>) -> RvIsFull,
+{
+    winapi_small_binary(api_wrapper, |frozen_buffer| copy_to_vec(&frozen_buffer))
+}
+
+/// Generic wrapper function for a Windows API call that measures the buffer in elements
+///
+/// The [`Argument`] has an [`ElementCount`][ec] pointer so its size is the number of `T`s that
+/// fit.  The [`FrozenBuffer`] passed to `finalize` has a [`size`](FrozenBuffer::size) that is the
+/// number of `T`s stored.  [`RvIsCount`][c] is the return value handler for functions like
+/// [`GetRawInputDeviceList`][gridl].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::UI::Input::{GetRawInputDeviceList, RAWINPUTDEVICELIST};
+///
+/// use grob::{winapi_element_count, RvIsCount};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let count = winapi_element_count(
+///         |argument| {
+///             let (pointer, size) = argument.pointer_and_size_mut();
+///             RvIsCount::new(unsafe {
+///                 GetRawInputDeviceList(
+///                     Some(pointer.0),
+///                     size,
+///                     std::mem::size_of::<RAWINPUTDEVICELIST>() as u32,
+///                 )
+///             })
+///         },
+///         |frozen_buffer| Ok(frozen_buffer.size()),
+///     )?;
+///     println!("{} raw input devices", count);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [c]: crate::RvIsCount
+/// [ec]: crate::ElementCount
+/// [gridl]: https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getrawinputdevicelist
+///
+pub fn winapi_element_count<T, W, WR, F, U>(
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<ElementCount<T>>) -> WR,
+    F: FnMut(FrozenBuffer<T>) -> Result<U, std::io::Error>,
+{
+    let mut initial_buffer = StackBuffer::<SMALL_BINARY_STACK_CAPACITY>::new();
+    let grow_strategy = default_strategy(StrategyKind::SmallBinary, GrowForSmallBinary::new());
+    let growable_buffer =
+        GrowableBuffer::<T, ElementCount<T>>::new(&mut initial_buffer, &grow_strategy);
+    winapi_generic(growable_buffer, api_wrapper, finalize)
+}
+
+// Copy the bytes stored in a binary buffer to a Vec of whole elements.
+pub(crate) fn copy_to_vec<T>(frozen_buffer: &FrozenBuffer<T>) -> Result<Vec<T>, std::io::Error>
+where
+    T: Pod,
+{
+    let size = frozen_buffer.size() as usize;
+    let element_size = std::mem::size_of::<T>();
+    if element_size == 0 || !size.is_multiple_of(element_size) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "{} bytes stored is not a whole number of {} byte elements",
+                size, element_size
+            ),
+        ));
+    }
+    let Some(p) = frozen_buffer.try_pointer()? else {
+        return Ok(Vec::new());
+    };
+    let count = size / element_size;
+    let mut rv = Vec::<T>::with_capacity(count);
+    // Copied as bytes so the alignment of the buffer does not matter.
+    unsafe {
+        std::ptr::copy_nonoverlapping(p as *const u8, rv.as_mut_ptr() as *mut u8, size);
+        rv.set_len(count);
+    }
+    Ok(rv)
+}
+
+/// Generic wrapper function for a Windows API call that returns one fixed-size structure
+///
+/// `winapi_struct` is [`winapi_small_binary`] with a `finalize` that copies the first `T` out of
+/// the buffer.  There is no need to dereference the buffer pointer.  When more than one `T` is
+/// stored the rest are ignored.
+///
+/// The bytes stored are read as a `T` so `T` has to be [`Pod`].  A Windows structure can be
+/// wrapped in a `#[repr(transparent)]` newtype that implements [`Pod`].
+///
+/// # Arguments
+///
+/// * `api_wrapper` - The Windows API call is made inside this closure.  The argument for the call
+///   is provided.  The return value from the closure is either an [`RvIsError`][e] or an
+///   [`RvIsSize`][s].  Create it in the same expression as the call to preserve the last error.
+///
+/// # Returns
+///
+/// * `Ok(Some(T))` when the operating system call returns success and stores at least
+///   `size_of::<T>()` bytes
+///
+/// * `Ok(None)` when the operating system call returns success and stores nothing
+///
+/// * `Err(`[`std::io::Error`]`)` when the operating system call fails or stores fewer than
+///   `size_of::<T>()` bytes
+///
+/// # Examples
+///
+/// This example prints the maximum processor count for the first processor group.
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::System::SystemInformation::{
+///     GetLogicalProcessorInformationEx, RelationGroup, SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
+/// };
+///
+/// use grob::{winapi_struct, Pod, RvIsError};
+///
+/// #[derive(Clone, Copy)]
+/// #[repr(transparent)]
+/// struct Info(SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX);
+///
+/// // Every field is an integer, an array of integers, or a union of those.
+/// unsafe impl Pod for Info {}
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let info = winapi_struct::<Info, _, _>(|argument| {
+///         RvIsError::new(unsafe {
+///             GetLogicalProcessorInformationEx(
+///                 RelationGroup,
+///                 Some(argument.pointer().cast()),
+///                 argument.size_mut(),
+///             )
+///         })
+///     })?;
+///     if let Some(Info(info)) = info {
+///         let mpc = unsafe { info.Anonymous.Group.GroupInfo[0].MaximumProcessorCount };
+///         println!("The maximum processor count is {}.", mpc);
+///     }
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [e]: crate::RvIsError
+/// [s]: crate::RvIsSize
+///
+pub fn winapi_struct<T, W, WR>(api_wrapper: W) -> Result<Option<T>, std::io::Error>
+where
+    T: Pod,
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut T>) -> WR,
+{
+    winapi_small_binary(api_wrapper, |frozen_buffer| copy_struct(&frozen_buffer))
+}
+
+// Copy the first element out of a binary buffer.
+fn copy_struct<T>(frozen_buffer: &FrozenBuffer<T>) -> Result<Option<T>, std::io::Error>
+where
+    T: Pod,
+{
+    let size = frozen_buffer.size() as usize;
+    if size == 0 {
+        return Ok(None);
+    }
+    let needed = std::mem::size_of::<T>();
+    if size < needed {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "{} bytes stored is less than the {} bytes of a {}",
+                size,
+                needed,
+                std::any::type_name::<T>()
+            ),
+        ));
+    }
+    let Some(p) = frozen_buffer.try_pointer()? else {
+        return Ok(None);
+    };
+    Ok(Some(if p.is_aligned() {
+        unsafe { std::ptr::read(p) }
+    } else {
+        unsafe { std::ptr::read_unaligned(p) }
+    }))
+}
+
+/// Generic wrapper function for a Windows API call that returns a `T` followed by data it points
+/// to.
+///
+/// Structures like [`TOKEN_USER`][tu] hold pointers into the same buffer so the data cannot be
+/// copied out.  `winapi_frozen` returns the [`FrozenBuffer`] instead.  A heap buffer is always
+/// used so the [`FrozenBuffer`] does not borrow anything; it can be returned from the caller.
+///
+/// The buffer starts with room for a `T` so data that is always a `T` usually takes one call.
+/// After the loop the number of bytes stored is checked.  An error with a kind of
+/// [`InvalidData`](std::io::ErrorKind::InvalidData) is returned when it is less than
+/// `size_of::<T>()`, including when nothing was stored.  When `Ok` is returned
+/// [`try_pointer`](FrozenBuffer::try_pointer) returns `Ok(Some(p))` and `p` can be dereferenced.
+///
+/// [`winapi_token_info`](crate::winapi_token_info) is built on `winapi_frozen`.
+///
+/// [tu]: https://learn.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-token_user
+///
+pub fn winapi_frozen<T, W, WR>(api_wrapper: W) -> Result<FrozenBuffer<'static, T>, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut T>) -> WR,
+{
+    let grow_strategy = default_strategy(StrategyKind::SmallBinary, GrowForSmallBinary::new());
+    frozen_with(&grow_strategy, std::mem::size_of::<T>(), api_wrapper)
+}
+
+// The body of winapi_frozen with the grow strategy and the minimum number of bytes that must be
+// stored supplied by the caller.
+pub(crate) fn frozen_with<T, W, WR>(
+    grow_strategy: &dyn GrowStrategy,
+    needed: usize,
+    api_wrapper: W,
+) -> Result<FrozenBuffer<'static, T>, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut T>) -> WR,
+{
+    let mut growable_buffer = GrowableBuffer::<T, *mut T>::heap_only(grow_strategy);
+    // Only a hint.  If the allocation fails the loop grows as usual.
+    let _ = growable_buffer.reserve(u32::try_from(needed).unwrap_or(u32::MAX));
+    // The finalize closure of the other wrappers cannot return a FrozenBuffer so the loop is run
+    // here and the buffer frozen afterwards.
+    let mut call = Closures::new(api_wrapper, |_: FrozenBuffer<T>| Ok(()));
+    try_until_done(&mut growable_buffer, LoopLimits::default(), &mut call)?;
+    let frozen_buffer = growable_buffer.freeze();
+    let size = frozen_buffer.size() as usize;
+    if size < needed {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "{} bytes stored is less than the {} bytes of a {}",
+                size,
+                needed,
+                std::any::type_name::<T>()
+            ),
+        ));
+    }
+    // Report an inaccessible buffer now so the caller does not have to.
+    frozen_buffer.try_pointer()?;
+    Ok(frozen_buffer)
+}
+
+/// Generic wrapper function for a Windows API call that returns binary data and needs a relatively
+/// large buffer without using any stack space for the buffer.
+///
+/// This function is identical to [`winapi_large_binary`] except a heap buffer is always used.  The
+/// first operating system call is made with a zero capacity buffer to determine the size needed.
+/// The heap buffer capacity is never less than 64 KiB.
+///
+/// `winapi_large_binary_heap` is a good choice when the caller is running on a thread with a small
+/// stack or when the data is expected to be large anyway.
+///
+pub fn winapi_large_binary_heap<FT, W, WR, F, U>(
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut FT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    winapi_large_binary_with::<0, _, _, _, _, _>(api_wrapper, finalize)
+}
+
+/// Generic wrapper function for a Windows API call that returns binary data using a buffer owned
+/// by the caller.
+///
+/// `winapi_large_binary_in` is for calls made over and over again, like polling
+/// [`GetAdaptersAddresses`][gaa] from a monitoring loop.  The loop runs against `growable_buffer`
+/// without consuming it.  Whatever buffer the previous call ended with, including a heap buffer,
+/// is used for the next call so there is no allocation unless the data outgrows it.
+///
+/// `finalize` is given a [`FrozenBuffer`] that borrows `growable_buffer`.  The data has to be
+/// copied or converted before `finalize` returns.  [`FrozenBuffer::release_to_os`] always fails
+/// for this [`FrozenBuffer`].
+///
+/// The [`GrowStrategy`] is the one `growable_buffer` was created with.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::{
+///     NetworkManagement::IpHelper::{GetAdaptersAddresses, GET_ADAPTERS_ADDRESSES_FLAGS},
+///     Networking::WinSock::AF_UNSPEC,
+/// };
+///
+/// use grob::{winapi_large_binary_in, GrowToNearestQuarterKibi, GrowableBuffer, RvIsError};
+/// use grob::StackBuffer;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut initial_buffer = StackBuffer::<0>::new();
+///     let grow_strategy = GrowToNearestQuarterKibi::new();
+///     let mut growable_buffer = GrowableBuffer::new(&mut initial_buffer, &grow_strategy);
+///     for _ in 0..3 {
+///         let count = winapi_large_binary_in(
+///             &mut growable_buffer,
+///             |argument| {
+///                 RvIsError::new(unsafe {
+///                     GetAdaptersAddresses(
+///                         AF_UNSPEC.0 as u32,
+///                         GET_ADAPTERS_ADDRESSES_FLAGS(0),
+///                         None,
+///                         Some(argument.pointer()),
+///                         argument.size_mut(),
+///                     )
+///                 })
+///             },
+///             |frozen_buffer| {
+///                 let mut rv = 0;
+///                 if let Some(mut p) = frozen_buffer.pointer() {
+///                     while p != std::ptr::null() {
+///                         rv += 1;
+///                         p = unsafe { (*p).Next };
+///                     }
+///                 }
+///                 Ok(rv)
+///             },
+///         )?;
+///         println!("There are {} network adapters.", count);
+///     }
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [gaa]: https://learn.microsoft.com/en-us/windows/win32/api/iphlpapi/nf-iphlpapi-getadaptersaddresses
+///
+pub fn winapi_large_binary_in<FT, W, WR, F, U>(
+    growable_buffer: &mut GrowableBuffer<FT, *mut FT>,
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut FT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    winapi_generic_in(growable_buffer, api_wrapper, finalize)
+}
+
+/// Generic wrapper for a Windows API call that returns a file system path.
+///
+/// # Arguments
+///
+/// * `api_wrapper` - The Windows API call is made inside this closure.  The argument for the call
+///   is provided.  The return value from the closure is an [`RvIsSize`][s].
+///
+/// [s]: crate::RvIsSize
+///
+/// # Returns
+///
+/// The return value from `winapi_path_buf` is...
+///
+/// * `Ok(`[`PathBuf`][pb]`)` when the operating system call returns success
+///
+/// * `Err(`[`std::io::Error`]`)` when the operating system call fails
+///
+/// [pb]: std::path::PathBuf
+///
+/// # Examples
+///
+/// This example calls [`GetModuleFileNameW`][gm] to get the full path to the running program then
+/// prints that path.
+///
+/// [gm]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/LibraryLoader/fn.GetModuleFileNameW.html
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::Foundation::HMODULE;
+/// use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
 ///
 /// use grob::{winapi_path_buf, RvIsSize};
 ///
@@ -332,13 +1499,169 @@ where
     WR: ToResult,
     W: FnMut(&mut Argument<PWSTR>) -> WR,
 {
-    let mut initial_buffer = StackBuffer::<CAPACITY_FOR_PATHS>::new();
+    winapi_path_buf_ex(PathBufOptions::new(), api_wrapper)
+}
+
+/// Options for [`winapi_path_buf_ex`].
+///
+/// The defaults match [`winapi_path_buf`]: the first call is made with room for a `MAX_PATH`
+/// path, an empty result is returned as an empty [`PathBuf`][pb], and a verbatim (`\\?\`)
+/// prefix is kept.
+///
+/// [pb]: std::path::PathBuf
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PathBufOptions {
+    floor: u32,
+    empty_is_error: bool,
+    strip_verbatim: bool,
+}
+
+impl PathBufOptions {
+    pub fn new() -> Self {
+        Self {
+            floor: MAX_PATH - 1,
+            empty_is_error: false,
+            strip_verbatim: false,
+        }
+    }
+    /// Make the first call with room for a path of at least `wchars` [`WCHAR`][wc]s not including
+    /// the `NUL` terminator.
+    ///
+    /// When long paths are enabled, starting at 32767 avoids growing the buffer.  A floor above
+    /// `MAX_PATH` means the first call uses a heap buffer.
+    ///
+    /// [wc]: https://learn.microsoft.com/en-us/windows/win32/extensible-storage-engine/wchar
+    ///
+    pub fn floor(mut self, wchars: u32) -> Self {
+        self.floor = wchars;
+        self
+    }
+    /// Return an error with a kind of [`NotFound`](std::io::ErrorKind::NotFound) instead of an
+    /// empty [`PathBuf`][pb] when the operating system call succeeds but stores nothing.
+    ///
+    /// [pb]: std::path::PathBuf
+    ///
+    pub fn empty_is_error(mut self, empty_is_error: bool) -> Self {
+        self.empty_is_error = empty_is_error;
+        self
+    }
+    /// Remove a verbatim prefix from the returned path.
+    ///
+    /// `\\?\C:\...` becomes `C:\...` and `\\?\UNC\server\share\...` becomes
+    /// `\\server\share\...`.  Other verbatim paths, like `\\?\Volume{...}\...`, have no
+    /// equivalent without the prefix so they are returned unchanged.
+    ///
+    pub fn strip_verbatim(mut self, strip_verbatim: bool) -> Self {
+        self.strip_verbatim = strip_verbatim;
+        self
+    }
+}
+
+impl Default for PathBufOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Generic wrapper for a Windows API call that returns a file system path with the behaviour
+/// chosen by `options`.
+///
+/// `winapi_path_buf_ex` is [`winapi_path_buf`] with a configurable initial capacity, an optional
+/// error for an empty result, and optional removal of a verbatim (`\\?\`) prefix.  See
+/// [`PathBufOptions`].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::Foundation::HMODULE;
+/// use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
+///
+/// use grob::{winapi_path_buf_ex, PathBufOptions, RvIsSize};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let options = PathBufOptions::new()
+///         .floor(32767)
+///         .empty_is_error(true)
+///         .strip_verbatim(true);
+///     let path = winapi_path_buf_ex(options, |argument| {
+///         RvIsSize::new(unsafe { GetModuleFileNameW(HMODULE(0), argument.as_mut_slice()) })
+///     })?;
+///     println!("GetModuleFileNameW returned {}", path.display());
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+pub fn winapi_path_buf_ex<W, WR>(
+    options: PathBufOptions,
+    api_wrapper: W,
+) -> Result<std::path::PathBuf, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<PWSTR>) -> WR,
+{
     const CFP: u64 = CAPACITY_FOR_PATHS as u64;
-    let grow_strategy = GrowForStoredIsReturned::<CFP>::new();
-    let growable_buffer = GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
-    winapi_generic(growable_buffer, api_wrapper, |frozen_buffer| {
-        Ok(frozen_buffer.to_path_buf().unwrap_or_default())
-    })
+    let grow_strategy =
+        default_strategy(StrategyKind::PathBuf, GrowForStoredIsReturned::<CFP>::new());
+    let mut initial_buffer = StackBuffer::<CAPACITY_FOR_PATHS>::new();
+    let mut growable_buffer =
+        GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+    let floor = u32::try_from(capacity_for_wchars(options.floor as usize)).unwrap_or(u32::MAX);
+    // The floor is only a hint.  If the heap buffer cannot be allocated the loop grows as usual.
+    let _ = growable_buffer.reserve(floor);
+    winapi_loop(
+        growable_buffer,
+        LoopLimits::default(),
+        api_wrapper,
+        |frozen_buffer| {
+            let path = frozen_buffer.try_to_os_string()?.unwrap_or_default();
+            if path.is_empty() && options.empty_is_error {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "the operating system call succeeded but did not return a path",
+                ));
+            }
+            Ok(if options.strip_verbatim {
+                strip_verbatim(path)
+            } else {
+                path
+            }
+            .into())
+        },
+    )
+}
+
+// Remove a \\?\ prefix when the rest is a drive or UNC path.
+fn strip_verbatim(path: OsString) -> OsString {
+    // Compare ignoring ASCII case.
+    fn starts_with(wide: &[u16], prefix: &[u8]) -> bool {
+        wide.len() >= prefix.len()
+            && wide
+                .iter()
+                .zip(prefix)
+                .all(|(w, p)| u8::try_from(*w).is_ok_and(|w| w.eq_ignore_ascii_case(p)))
+    }
+    let wide: Vec<u16> = path.encode_wide().collect();
+    if !starts_with(&wide, br"\\?\") {
+        return path;
+    }
+    let rest = &wide[4..];
+    let is_drive = rest.get(1) == Some(&u16::from(b':'))
+        && u8::try_from(rest[0]).is_ok_and(|d| d.is_ascii_alphabetic());
+    if starts_with(rest, br"UNC\") {
+        // UNC\server\share becomes \\server\share by keeping the backslash after UNC.
+        let mut unc = vec![u16::from(b'\\')];
+        unc.extend_from_slice(&rest[3..]);
+        OsString::from_wide(&unc)
+    } else if is_drive {
+        OsString::from_wide(rest)
+    } else {
+        path
+    }
 }
 
 /// Generic wrapper for a Windows API call that returns a text string like the computer or user name.
@@ -347,8 +1670,8 @@ where
 ///
 /// * `lossy_ok` - Is returning a lossy string okay?  See [`to_string`][ts] for details.
 /// * `api_wrapper` - The Windows API call is made inside this closure.  The argument for the call
-///     is provided.  The return value from the closure is either an [`RvIsError`][e] or an
-///     [`RvIsSize`][s].
+///   is provided.  The return value from the closure is either an [`RvIsError`][e] or an
+///   [`RvIsSize`][s].  Create it in the same expression as the call to preserve the last error.
 ///
 /// [e]: crate::RvIsError
 /// [s]: crate::RvIsSize
@@ -361,11 +1684,11 @@ where
 /// * `Ok(Ok(`[`String`]`))` when the operating system call returns success and...
 ///     * Either `lossy_ok` is `true`
 ///     * Or `lossy_ok` is `false` and the data returned from the operating system can be
-///         converted to a UTF-8 string without problems
+///       converted to a UTF-8 string without problems
 ///
 /// * `Ok(Err(`[`OsString`]`))` when the operating system call returns success and `lossy_ok` is
-///     `false` and the data returned from the operating system _cannot_ be converted to a valid
-///     UTF-8 string
+///   `false` and the data returned from the operating system _cannot_ be converted to a valid
+///   UTF-8 string
 ///
 /// * `Err(Err(`[`std::io::Error`]`))` when the operating system call fails
 ///
@@ -391,7 +1714,7 @@ where
 ///
 /// fn main() -> Result<(), Box<dyn std::error::Error>> {
 ///     let username = winapi_string(true, |argument| {
-///         RvIsError::new(unsafe { GetUserNameW(argument.pointer(), argument.size()) })
+///         RvIsError::new(unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) })
 ///     })?
 ///     .unwrap();
 ///     println!("GetUserNameW returned {}", username);
@@ -408,10 +1731,415 @@ where
     WR: ToResult,
     W: FnMut(&mut Argument<PWSTR>) -> WR,
 {
-    let mut initial_buffer = StackBuffer::<CAPACITY_FOR_NAMES>::new();
-    let grow_strategy = GrowForStaticText::new();
-    let growable_buffer = GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
-    winapi_generic(growable_buffer, api_wrapper, |frozen_buffer| {
-        Ok(frozen_buffer.to_string(lossy_ok))
-    })
+    Grob::builder()
+        .stack::<CAPACITY_FOR_NAMES>()
+        .strategy(default_strategy(
+            StrategyKind::String,
+            GrowForStaticText::new(),
+        ))
+        .run_wide(api_wrapper, |frozen_buffer| {
+            frozen_buffer.try_to_string(lossy_ok)
+        })
+}
+
+/// Generic wrapper for an ANSI (`*A`) Windows API call that returns a text string.
+///
+/// `winapi_string_a` is [`winapi_string`] for functions that only exist in an ANSI form, like
+/// exports from third-party DLLs with narrow string interfaces.  The [`Argument`] is a
+/// [`PSTR`](windows::core::PSTR) so sizes are in bytes.  The text is converted with
+/// [`try_to_string_a`][ts] which checks that it is UTF-8.
+///
+/// # Arguments
+///
+/// * `lossy_ok` - Is returning a lossy string okay?  See [`try_to_string_a`][ts] for details.
+/// * `api_wrapper` - The Windows API call is made inside this closure.  The argument for the call
+///   is provided.  The return value from the closure is either an [`RvIsError`][e] or an
+///   [`RvIsSize`][s].  Create it in the same expression as the call to preserve the last error.
+///
+/// # Returns
+///
+/// * `Ok(Ok(`[`String`]`))` when the operating system call returns success and the text is
+///   UTF-8 or `lossy_ok` is `true`
+///
+/// * `Ok(Err(`[`Vec<u8>`]`))` when the operating system call returns success, `lossy_ok` is
+///   `false`, and the text is not UTF-8.  The bytes, without the `NULL` terminator, are returned
+///   so the caller can convert them from the active code page.
+///
+/// * `Err(`[`std::io::Error`]`)` when the operating system call fails
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::System::WindowsProgramming::GetUserNameA;
+///
+/// use grob::{winapi_string_a, RvIsError};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let username = winapi_string_a(true, |argument| {
+///         RvIsError::new(unsafe { GetUserNameA(argument.pointer(), argument.size_mut()) })
+///     })?
+///     .unwrap();
+///     println!("GetUserNameA returned {}", username);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [e]: crate::RvIsError
+/// [s]: crate::RvIsSize
+/// [ts]: crate::FrozenBuffer::try_to_string_a
+///
+pub fn winapi_string_a<W, WR>(
+    lossy_ok: bool,
+    api_wrapper: W,
+) -> Result<Result<String, Vec<u8>>, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<PSTR>) -> WR,
+{
+    Grob::builder()
+        .stack::<CAPACITY_FOR_NAMES>()
+        .strategy(default_strategy(
+            StrategyKind::String,
+            GrowForStaticText::new(),
+        ))
+        .run_narrow(api_wrapper, |frozen_buffer| {
+            frozen_buffer.try_to_string_a(lossy_ok)
+        })
+}
+
+/// Generic wrapper function for a Windows API call that returns a string as an [`OsString`]
+///
+/// `winapi_osstring` is [`winapi_string`] without the conversion to UTF-8.  The data is returned
+/// exactly as the operating system provided it so there is no lossy / strict choice to make.  The
+/// `api_wrapper` is the same so a [`winapi_string`] call can be switched by changing the function
+/// name.
+///
+/// # Arguments
+///
+/// * `api_wrapper` - The Windows API call is made inside this closure.  The argument for the call
+///   is provided.  The return value from the closure is either an [`RvIsError`][e] or an
+///   [`RvIsSize`][s].  Create it in the same expression as the call to preserve the last error.
+///
+/// # Returns
+///
+/// * `Ok(`[`OsString`]`)` when the operating system call returns success.  The [`OsString`] is
+///   empty if nothing or only a `NULL` terminator was stored.
+///
+/// * `Err(`[`std::io::Error`]`)` when the operating system call fails
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::System::WindowsProgramming::GetUserNameW;
+///
+/// use grob::{winapi_osstring, RvIsError};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let username = winapi_osstring(|argument| {
+///         RvIsError::new(unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) })
+///     })?;
+///     println!("GetUserNameW returned {:?}", username);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [e]: crate::RvIsError
+/// [s]: crate::RvIsSize
+///
+pub fn winapi_osstring<W, WR>(api_wrapper: W) -> Result<OsString, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<PWSTR>) -> WR,
+{
+    Grob::builder()
+        .stack::<CAPACITY_FOR_NAMES>()
+        .strategy(default_strategy(
+            StrategyKind::String,
+            GrowForStaticText::new(),
+        ))
+        .run_wide(api_wrapper, |frozen_buffer| {
+            Ok(frozen_buffer.try_to_os_string()?.unwrap_or_default())
+        })
+}
+
+/// Generic wrapper function for a Windows API call that returns two strings, each in its own
+/// buffer with its own size
+///
+/// [`LookupAccountSidW`][las] returns a name and a domain and either buffer may be too small.
+/// `winapi_two_strings` passes an [`Argument`] for each buffer to `api_wrapper`.  When the call
+/// reports that a buffer is too small, only the buffers whose size grew are grown; the other keeps
+/// its capacity.  Neither buffer is committed until a call succeeds for both.
+///
+/// The return value from `api_wrapper` is converted separately for each buffer.  [`RvIsError`][e]
+/// is the expected return value handler.
+///
+/// # Returns
+///
+/// * `Ok((`[`OsString`]`, `[`OsString`]`))` when the operating system call returns success.  The
+///   first [`OsString`] is from the first [`Argument`].
+///
+/// * `Err(`[`std::io::Error`]`)` when the operating system call fails or the buffers still need to
+///   grow after [`DEFAULT_MAX_TRIES`] calls
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::core::PCWSTR;
+/// use windows::Win32::Foundation::{CloseHandle, HANDLE};
+/// use windows::Win32::Security::{
+///     LookupAccountSidW, TokenUser, SID_NAME_USE, TOKEN_QUERY, TOKEN_USER,
+/// };
+/// use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+///
+/// use grob::{winapi_token_info, winapi_two_strings, RvIsError};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut token = HANDLE::default();
+///     unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) }.ok()?;
+///     let user = winapi_token_info::<TOKEN_USER>(token, TokenUser)?;
+///     unsafe { CloseHandle(token) };
+///
+///     if let Some(p) = user.try_pointer()? {
+///         let sid = unsafe { (*p).User.Sid };
+///         let mut sid_name_use = SID_NAME_USE::default();
+///         let (name, domain) = winapi_two_strings(|name, domain| {
+///             RvIsError::new(unsafe {
+///                 LookupAccountSidW(
+///                     PCWSTR::null(),
+///                     sid,
+///                     name.pointer(),
+///                     name.size_mut(),
+///                     domain.pointer(),
+///                     domain.size_mut(),
+///                     &mut sid_name_use,
+///                 )
+///             })
+///         })?;
+///         println!("{:?}\\{:?}", domain, name);
+///     }
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [e]: crate::RvIsError
+/// [las]: https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-lookupaccountsidw
+///
+pub fn winapi_two_strings<W, WR>(mut api_wrapper: W) -> Result<(OsString, OsString), std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<PWSTR>, &mut Argument<PWSTR>) -> WR,
+{
+    let grow_strategy = default_strategy(StrategyKind::String, GrowForStaticText::new());
+    let mut first_initial = StackBuffer::<CAPACITY_FOR_NAMES>::new();
+    let mut second_initial = StackBuffer::<CAPACITY_FOR_NAMES>::new();
+    let mut first = GrowableBuffer::<u16, PWSTR>::new(&mut first_initial, &grow_strategy);
+    let mut second = GrowableBuffer::<u16, PWSTR>::new(&mut second_initial, &grow_strategy);
+    let mut tries = 0;
+    let mut grew = false;
+    let mut last_requested = 0;
+    loop {
+        tries += 1;
+        let mut a = first.argument();
+        let mut b = second.argument();
+        let before = (a.needed_size(), b.needed_size());
+        let rv = api_wrapper(&mut a, &mut b);
+        // Both Arguments are resolved or dismissed before any error is returned.
+        let a_action = match rv.to_result(&mut a) {
+            Ok(action) => action,
+            Err(error) => {
+                let capacity = a.capacity;
+                a.dismiss();
+                b.dismiss();
+                let call_error = GrobCallError::new(error, tries, capacity, grew, last_requested);
+                return Err(call_error.into());
+            }
+        };
+        let b_action = match rv.to_result(&mut b) {
+            Ok(action) => action,
+            Err(error) => {
+                let capacity = b.capacity;
+                a.dismiss();
+                b.dismiss();
+                let call_error = GrobCallError::new(error, tries, capacity, grew, last_requested);
+                return Err(call_error.into());
+            }
+        };
+        let a_grow = matches!(a_action, FillBufferAction::Grow);
+        let b_grow = matches!(b_action, FillBufferAction::Grow);
+        if !a_grow && !b_grow {
+            let a_applied = a.try_apply(a_action);
+            let b_applied = b.try_apply(b_action);
+            a_applied?;
+            b_applied?;
+            break;
+        }
+        if tries >= DEFAULT_MAX_TRIES {
+            let message = format!(
+                "gave up after {} tries; the last call needed sizes of {} and {}",
+                tries,
+                a.needed_size(),
+                b.needed_size()
+            );
+            a.dismiss();
+            b.dismiss();
+            return Err(std::io::Error::other(message));
+        }
+        // Only the buffers that are too small grow.  If neither size changed both grow so the
+        // loop makes progress.
+        let a_too_small = a.needed_size() > before.0;
+        let b_too_small = b.needed_size() > before.1;
+        let both = !a_too_small && !b_too_small;
+        let a_grown = if both || a_too_small {
+            last_requested = PWSTR::size_to_capacity(a.needed_size());
+            a.grow()
+        } else {
+            a.dismiss();
+            Ok(())
+        };
+        let b_grown = if both || b_too_small {
+            last_requested = PWSTR::size_to_capacity(b.needed_size());
+            b.grow()
+        } else {
+            b.dismiss();
+            Ok(())
+        };
+        a_grown?;
+        b_grown?;
+        grew = true;
+    }
+    let first = first.freeze().try_to_os_string()?.unwrap_or_default();
+    let second = second.freeze().try_to_os_string()?.unwrap_or_default();
+    Ok((first, second))
+}
+
+/// Generic wrapper function for a Windows API call that returns a list of strings separated by
+/// `NULL`s and terminated by two `NULL`s
+///
+/// Functions like [`GetLogicalDriveStringsW`][gl] and [`QueryDosDeviceW`][qd] return this
+/// `REG_MULTI_SZ` style list.  `winapi_multi_sz` calls the function until the buffer is large
+/// enough then splits the list with [`try_to_multi_sz`](FrozenBuffer::try_to_multi_sz).
+///
+/// # Arguments
+///
+/// * `api_wrapper` - The Windows API call is made inside this closure.  The argument for the call
+///   is provided.  The return value from the closure is either an [`RvIsError`][e] or an
+///   [`RvIsSize`][s].  Create it in the same expression as the call to preserve the last error.
+///
+/// # Returns
+///
+/// * `Ok(Vec<`[`OsString`]`>)` when the operating system call returns success.  The [`Vec`] is
+///   empty if the list is empty.
+///
+/// * `Err(`[`std::io::Error`]`)` when the operating system call fails
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::Storage::FileSystem::QueryDosDeviceW;
+///
+/// use grob::{winapi_multi_sz, RvIsSize};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let targets = winapi_multi_sz(|argument| {
+///         let capacity = *argument.size_mut();
+///         let buffer = unsafe {
+///             std::slice::from_raw_parts_mut(argument.pointer().0, capacity as usize)
+///         };
+///         RvIsSize::new(unsafe { QueryDosDeviceW(windows::w!("C:"), Some(buffer)) })
+///     })?;
+///     println!("C: is {:?}", targets);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [e]: crate::RvIsError
+/// [s]: crate::RvIsSize
+/// [gl]: https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-getlogicaldrivestringsw
+/// [qd]: https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-querydosdevicew
+///
+pub fn winapi_multi_sz<W, WR>(api_wrapper: W) -> Result<Vec<OsString>, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<PWSTR>) -> WR,
+{
+    Grob::builder()
+        .stack::<CAPACITY_FOR_PATHS>()
+        .strategy(default_strategy(
+            StrategyKind::MultiSz,
+            GrowForMultiSz::new(),
+        ))
+        .run_wide(api_wrapper, |frozen_buffer| frozen_buffer.try_to_multi_sz())
+}
+
+/// Generic wrapper function for a Windows API call that returns a string into a caller-provided
+/// [`String`].
+///
+/// `winapi_string_into` is [`winapi_string`] for tight loops.  The data is converted into `buffer`
+/// with [`to_string_into`](FrozenBuffer::to_string_into) so the capacity of `buffer` is reused
+/// instead of allocating a [`String`] for every call.  `buffer` is cleared first; if the call
+/// fails `buffer` is left empty.
+///
+/// # Returns
+///
+/// The same as [`winapi_string`] with `()` in place of the [`String`].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::System::WindowsProgramming::GetUserNameW;
+///
+/// use grob::{winapi_string_into, RvIsError};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut username = String::new();
+///     for _ in 0..3 {
+///         winapi_string_into(&mut username, true, |argument| {
+///             RvIsError::new(unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) })
+///         })?
+///         .unwrap();
+///         println!("GetUserNameW returned {}", username);
+///     }
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+pub fn winapi_string_into<W, WR>(
+    buffer: &mut String,
+    lossy_ok: bool,
+    api_wrapper: W,
+) -> Result<Result<(), OsString>, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<PWSTR>) -> WR,
+{
+    buffer.clear();
+    Grob::builder()
+        .stack::<CAPACITY_FOR_NAMES>()
+        .strategy(default_strategy(
+            StrategyKind::String,
+            GrowForStaticText::new(),
+        ))
+        .run_wide(api_wrapper, |frozen_buffer| {
+            frozen_buffer.to_string_into(buffer, lossy_ok)
+        })
 }
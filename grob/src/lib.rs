@@ -47,6 +47,7 @@
 //! | elements / WCHARs stored  | path            | [`GetModuleFileNameW`][3]               | [`winapi_path_buf`] + [`RvIsSize`]      |
 //! | elements / WCHARs stored  | path            | [`GetSystemWindowsDirectoryW`][6]       | [`winapi_path_buf`] + [`RvIsSize`]      |
 //! | bytes stored              | large + binary  | [`GetFileVersionInfoSizeW`][7]          | [`winapi_large_binary`] + [see example][e] |
+//! | bytes required            | small + binary  | [`GetSystemFirmwareTable`][8]           | [`winapi_small_binary`] + [`RvIsRequiredBytes`] |
 //!
 //! [`WindowsString`] and [`WindowsPathString`] are available for easily and efficiently passing
 //! string parameters into Windows API functions like [`DeleteFileW`][df], [`ReplaceFileW`][rf], and
@@ -60,97 +61,370 @@
 //! [5]: https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-getusernamew
 //! [6]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getsystemwindowsdirectoryw
 //! [7]: https://learn.microsoft.com/en-us/windows/win32/api/winver/nf-winver-getfileversioninfosizew
+//! [8]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getsystemfirmwaretable
 //! [e]: https://github.com/Coding-Badly/grob/blob/main/grob/examples/version-info-generic.rs
 //! [df]: https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-deletefilew
 //! [rf]: https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-replacefilew
 //! [scn]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-setcomputernamew
 //!
+//! # Features
+//!
+//! * `no_panic` - Broken invariants and undocumented operating system behaviour are returned as
+//!   errors instead of panicking.  Useful when [grob][gc] runs inside a long-lived process like a
+//!   shell, an editor, or a service.
+//! * `raw_os_errors` - Operating system errors are returned exactly as [`win32_err`] creates them
+//!   instead of wrapped in a [`GrobCallError`].
+//! * `skip_null_check` - [`WindowsString`] does not check for embedded `NUL`s.
+//! * `testing` - Enables the `testing` module for exercising call loops without calling the
+//!   operating system.
+//! * `tracing` - `InstrumentedGrowStrategy::traced` emits each [`GrowEvent`] as a
+//!   [`tracing`](https://docs.rs/tracing) `debug` event.
+//! * `zeroize` - [`WindowsString`] overwrites its copy of the string with zeros when dropped.
+//!   [`StackBuffer`] and [`HeapBuffer`] overwrite their contents with zeros before the memory is
+//!   released.
+//!
 
 use std::marker::PhantomData;
 
+use windows::Win32::Foundation::WIN32_ERROR;
+
+// base is first so its macros are available to the other modules.
+#[macro_use]
 mod base;
+
+mod attempts;
 mod buffer;
+mod builder;
+mod computer;
+mod device;
+mod env;
+mod factory;
+mod firmware;
 mod generic;
+mod macros;
+mod net;
+mod owned;
+mod pooled;
+mod registry;
+mod retry;
+mod security;
 mod strategy;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod token;
 mod traits;
 mod win;
 mod winstr;
 
-pub use crate::base::{FillBufferAction, FillBufferResult};
-pub use crate::buffer::{os::ALIGNMENT, StackBuffer};
+pub use crate::attempts::{Attempt, Attempts};
+pub use crate::base::{
+    FillBufferAction, FillBufferResult, GrobCallError, GrobTimeout, GrowError, GrowStats,
+};
+pub use crate::buffer::{
+    os::ALIGNMENT, Alignment, AllocSource, BorrowedBuffer, BufferAllocator, CoTaskMemAlloc,
+    HeapBuffer, ProcessHeapAlloc, StackBuffer, StdAlloc, SupportedAlignment, VecBuffer,
+};
+pub use crate::builder::{Grob, GrobBuilder};
+pub use crate::computer::{computer_name_ex, computer_name_ex_with};
+pub use crate::device::{
+    device_interface_list, device_interface_list_with, winapi_device_io, winapi_device_io_with,
+};
+pub use crate::env::{
+    expand_environment_strings, expand_environment_strings_with, winapi_env_var,
+    winapi_env_var_with,
+};
+pub use crate::factory::{
+    reset_default_strategy_factory, set_default_strategy_factory, StrategyFactory, StrategyKind,
+};
+pub use crate::firmware::{system_firmware_table, system_firmware_table_with};
 pub use crate::generic::{
-    winapi_binary, winapi_generic, winapi_large_binary, winapi_path_buf, winapi_small_binary,
-    winapi_string,
+    run_call, winapi_binary, winapi_element_count, winapi_filled_until_slack, winapi_frozen,
+    winapi_generic, winapi_generic_in, winapi_generic_limited, winapi_generic_with_deadline,
+    winapi_generic_with_retry, winapi_generic_with_stats, winapi_large_binary,
+    winapi_large_binary_heap, winapi_large_binary_in, winapi_large_binary_with,
+    winapi_large_binary_with_stats, winapi_large_binary_with_strategy, winapi_multi_sz,
+    winapi_osstring, winapi_path_buf, winapi_path_buf_ex, winapi_small_binary,
+    winapi_small_binary_with, winapi_small_binary_with_stats, winapi_small_binary_with_strategy,
+    winapi_string, winapi_string_a, winapi_string_into, winapi_struct, winapi_two_strings,
+    winapi_vec, PathBufOptions, DEFAULT_MAX_TRIES,
+};
+pub use crate::net::{extended_tcp_table, extended_tcp_table_with, TcpRow};
+pub use crate::owned::{NameGrowableBuffer, OwnedGrowableBuffer, PathGrowableBuffer};
+pub use crate::pooled::{pooled_bytes, set_pool_cap, winapi_large_binary_pooled, DEFAULT_POOL_CAP};
+pub use crate::registry::{winapi_registry_value, RegistryValue};
+pub use crate::retry::{
+    Backoff, ExponentialBackoff, FixedBackoff, Jittered, RetryPolicy, RetryWithBackoff,
+    DEFAULT_MAX_RETRIES,
+};
+pub use crate::security::{
+    winapi_security_descriptor, winapi_security_descriptor_with, SecurityObject,
 };
 pub use crate::strategy::{
-    GrowByDoubleWithNull, GrowForSmallBinary, GrowForStaticText, GrowForStoredIsReturned,
-    GrowToNearestNibble, GrowToNearestNibbleWithNull, GrowToNearestQuarterKibi,
+    markdown_table, simulate, simulate_with, GrowByDoubleWithNull, GrowByFixedStep, GrowEvent,
+    GrowForMultiSz, GrowForSmallBinary, GrowForStaticText, GrowForStoredIsReturned,
+    GrowToNearestNibble, GrowToNearestNibbleWithDoubleNull, GrowToNearestNibbleWithNull,
+    GrowToNearestPage, GrowToNearestQuarterKibi, GrowWith, GrowWithCap, GrowWithHeadroom,
+    InstrumentedGrowStrategy, MaxTries, SimHandler, SimStep,
 };
+pub use crate::token::winapi_token_info;
 pub use crate::traits::{
-    GrowStrategy, NeededSize, RawToInternal, ReadBuffer, ToResult, WriteBuffer,
+    GrobCall, GrowStrategy, NeededSize, Pod, RawToInternal, ReadBuffer, ToResult, WriteBuffer,
 };
 pub use crate::win::{
-    AsPCWSTR, RvIsError, RvIsSize, WindowsPathString, CAPACITY_FOR_NAMES, CAPACITY_FOR_PATHS,
-    SIZE_OF_WCHAR,
+    as_ntstatus, as_win32_err, bytes_to_wchars, bytes_to_wchars_usize, capacity_for_wchars,
+    capture_last_error, checked_bool, ntstatus_err, wchars_to_bytes, wchars_to_bytes_usize,
+    win32_err, AsPCWSTR, ElementCount, IoErrorExt, NtStatusError, NulPolicy, RvIsConfigRet,
+    RvIsCount, RvIsError, RvIsFull, RvIsHresult, RvIsLength, RvIsLstatus, RvIsNtstatus,
+    RvIsRequiredBytes, RvIsSecStatus, RvIsSize, RvIsWsa, RvWith, WindowsPathString,
+    CAPACITY_FOR_COMPUTER_NAME, CAPACITY_FOR_DOMAIN_NAME, CAPACITY_FOR_GUID_STRING,
+    CAPACITY_FOR_NAMES, CAPACITY_FOR_PATHS, SIZE_OF_WCHAR,
 };
 pub use crate::winstr::WindowsString;
 
-use crate::buffer::HeapBuffer;
 use crate::traits::GrowableBufferAsParent;
 
 enum ActiveBuffer<'sb> {
     Heap(HeapBuffer),
     Initial(&'sb mut dyn WriteBuffer),
-    PendingSwitch,
 }
 
 impl<'sb> ActiveBuffer<'sb> {
-    pub fn set_final_size(&mut self, final_size: u32) {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Heap(_) => "Heap",
+            Self::Initial(_) => "Initial",
+        }
+    }
+    pub fn set_final_size(&mut self, final_size: u32, bytes: u32) {
         match self {
             Self::Heap(h) => h.set_final_size(final_size),
-            Self::Initial(wb) => wb.set_final_size(final_size),
-            Self::PendingSwitch => panic!("PendingSwitch is only valid in grow"),
+            Self::Initial(wb) => wb.set_final_size_and_bytes(final_size, bytes),
+        }
+    }
+}
+
+// The GrowStrategy is either borrowed from the caller or owned by the GrowableBuffer.
+enum StrategyRef<'gs> {
+    Borrowed(&'gs dyn GrowStrategy),
+    Owned(Box<dyn GrowStrategy>),
+}
+
+impl<'gs> std::ops::Deref for StrategyRef<'gs> {
+    type Target = dyn GrowStrategy + 'gs;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Borrowed(gs) => *gs,
+            Self::Owned(gs) => gs.as_ref(),
         }
     }
 }
 
 struct BufferStrategy<'gs, 'sb> {
     active_buffer: ActiveBuffer<'sb>,
-    grow_strategy: &'gs dyn GrowStrategy,
+    grow_strategy: StrategyRef<'gs>,
     tries: usize,
+    preserve_on_grow: bool,
+    resumed_from: u32,
+    allocator: &'static dyn BufferAllocator,
+    heap_allowed: bool,
+    calls: usize,
+    switched_to_heap: bool,
+    peak_capacity: u32,
+    align: usize,
 }
 
 impl<'gs, 'sb> BufferStrategy<'gs, 'sb> {
+    fn rebind<'gs2, 'sb2>(
+        self,
+        initial: &'sb2 mut dyn WriteBuffer,
+        grow_strategy: &'gs2 dyn GrowStrategy,
+    ) -> BufferStrategy<'gs2, 'sb2> {
+        let active_buffer = match self.active_buffer {
+            ActiveBuffer::Heap(h) => ActiveBuffer::Heap(h),
+            ActiveBuffer::Initial(_) => ActiveBuffer::Initial(initial),
+        };
+        BufferStrategy {
+            active_buffer,
+            grow_strategy: StrategyRef::Borrowed(grow_strategy),
+            tries: self.tries,
+            preserve_on_grow: self.preserve_on_grow,
+            resumed_from: self.resumed_from,
+            allocator: self.allocator,
+            heap_allowed: self.heap_allowed,
+            calls: self.calls,
+            switched_to_heap: self.switched_to_heap,
+            peak_capacity: self.peak_capacity,
+            align: self.align,
+        }
+    }
     fn capacity(&self) -> u32 {
         match &self.active_buffer {
             ActiveBuffer::Heap(h) => h.capacity(),
             ActiveBuffer::Initial(wb) => wb.capacity(),
-            ActiveBuffer::PendingSwitch => panic!("PendingSwitch is only valid in grow"),
         }
     }
-    fn grow(&mut self, desired_capacity: u32) {
+    fn grow(&mut self, desired_capacity: u32) -> Result<(), GrowError> {
         let current_capacity = self.capacity();
         // nfx? Do we need this check? A bug elsewhere could cause an infinite loop. `grow` should
         // only be called when we know for certain the buffer needs to grow.
         // nfx? Should it be an assertion?
         if desired_capacity > current_capacity {
+            self.check_limit(desired_capacity)?;
+            // The number of operating system calls so far is one more than the number of grows.
+            if !self.grow_strategy.should_continue(self.tries + 1) {
+                return Err(GrowError::StrategyGaveUp {
+                    strategy: self.grow_strategy.name(),
+                    tries: self.tries + 1,
+                });
+            }
             self.tries += 1;
             let adjusted_capacity = self
                 .grow_strategy
                 .next_capacity(self.tries, desired_capacity);
-            // We were told to grow the buffer.  If that did not happen we have a bug.
-            assert!(adjusted_capacity > current_capacity);
-            // If we're holding a heap allocated buffer then free it now.  This allows the heap
-            // manager to reuse the memory we just released for our larger allocation.
-            self.active_buffer = ActiveBuffer::PendingSwitch;
-            self.active_buffer = ActiveBuffer::Heap(HeapBuffer::new(adjusted_capacity));
+            // We were told to grow the buffer.  If that did not happen the strategy has a bug.
+            if adjusted_capacity <= current_capacity {
+                return Err(GrowError::StrategyDidNotGrow {
+                    strategy: self.grow_strategy.name(),
+                    current_capacity,
+                    next_capacity: adjusted_capacity,
+                });
+            }
+            if self.preserve_on_grow {
+                self.grow_preserving(adjusted_capacity)?;
+                self.resumed_from = current_capacity;
+            } else if let ActiveBuffer::Heap(h) = &mut self.active_buffer {
+                // The contents do not matter but reallocating lets the allocator extend the buffer
+                // in place.
+                h.grow_to(adjusted_capacity)?;
+            } else if self.try_grow_initial(adjusted_capacity) {
+                // The initial buffer grew itself.
+            } else {
+                // Switching from the initial buffer to a heap buffer.  The initial buffer is kept
+                // until the heap buffer is allocated so a failure leaves the buffer usable.
+                let heap_buffer = self.new_heap_buffer(adjusted_capacity)?;
+                self.switch_to_heap(heap_buffer);
+            }
+        }
+        Ok(())
+    }
+    // Grow ahead of the first operating system call.  Unlike grow the current buffer is kept until
+    // the new one is allocated so a failure leaves the buffer usable.
+    fn reserve(&mut self, min_capacity: u32) -> Result<(), GrowError> {
+        let current_capacity = self.capacity();
+        if min_capacity <= current_capacity {
+            return Ok(());
+        }
+        self.check_limit(min_capacity)?;
+        let adjusted_capacity = self
+            .grow_strategy
+            .next_capacity(self.tries + 1, min_capacity);
+        if adjusted_capacity <= current_capacity {
+            return Err(GrowError::StrategyDidNotGrow {
+                strategy: self.grow_strategy.name(),
+                current_capacity,
+                next_capacity: adjusted_capacity,
+            });
+        }
+        if self.preserve_on_grow {
+            self.grow_preserving(adjusted_capacity)?;
+        } else if !self.try_grow_initial(adjusted_capacity) {
+            let heap_buffer = self.new_heap_buffer(adjusted_capacity)?;
+            self.switch_to_heap(heap_buffer);
+        }
+        self.tries += 1;
+        Ok(())
+    }
+    // Before the first operating system call, replace an initial buffer with no capacity with a
+    // heap buffer of the strategy's initial capacity.  This is not a try.  If the allocation fails
+    // the first call is made with the empty buffer as usual.
+    fn prime(&mut self) {
+        if self.tries != 0 {
+            return;
+        }
+        let ActiveBuffer::Initial(wb) = &self.active_buffer else {
+            return;
+        };
+        if wb.capacity() != 0 {
+            return;
+        }
+        let Some(capacity) = self.grow_strategy.initial_capacity() else {
+            return;
+        };
+        if capacity == 0 || self.try_grow_initial(capacity) {
+            return;
+        }
+        if let Ok(heap_buffer) = self.new_heap_buffer(capacity) {
+            self.switch_to_heap(heap_buffer);
+        }
+    }
+    // Count an operating system call and the capacity offered to it.
+    fn note_call(&mut self) {
+        let capacity = self.capacity();
+        self.calls += 1;
+        self.peak_capacity = self.peak_capacity.max(capacity);
+    }
+    // Replace the initial buffer with a heap buffer.
+    fn switch_to_heap(&mut self, heap_buffer: HeapBuffer) {
+        self.active_buffer = ActiveBuffer::Heap(heap_buffer);
+        self.switched_to_heap = true;
+    }
+    // A capacity larger than the strategy allows ends the call loop instead of growing.
+    fn check_limit(&self, desired_capacity: u32) -> Result<(), GrowError> {
+        match self.grow_strategy.max_capacity() {
+            Some(max_capacity) if desired_capacity > max_capacity => {
+                Err(GrowError::CapacityLimitExceeded {
+                    strategy: self.grow_strategy.name(),
+                    desired_capacity,
+                    max_capacity,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+    // Every heap buffer comes from here so heap_allowed is always honoured.
+    fn new_heap_buffer(&self, capacity: u32) -> Result<HeapBuffer, GrowError> {
+        if !self.heap_allowed {
+            return Err(GrowError::HeapNotAllowed { capacity });
+        }
+        HeapBuffer::try_new_aligned_in(capacity, self.align, self.allocator)
+    }
+    // Give the initial buffer a chance to grow itself (see WriteBuffer::try_grow).  Returns false
+    // if the active buffer is not the initial buffer or it cannot grow.
+    fn try_grow_initial(&mut self, capacity: u32) -> bool {
+        match &mut self.active_buffer {
+            ActiveBuffer::Initial(wb) => wb.try_grow(capacity) && wb.capacity() >= capacity,
+            _ => false,
+        }
+    }
+    // Grow keeping the contents of the current buffer.  A heap buffer is reallocated.  The initial
+    // buffer grows itself if it can.  Otherwise it is copied to a new heap buffer.
+    fn grow_preserving(&mut self, adjusted_capacity: u32) -> Result<(), GrowError> {
+        if self.try_grow_initial(adjusted_capacity) {
+            return Ok(());
+        }
+        let heap_allowed = self.heap_allowed;
+        match &mut self.active_buffer {
+            ActiveBuffer::Heap(h) => h.try_resize(adjusted_capacity),
+            ActiveBuffer::Initial(_) if !heap_allowed => Err(GrowError::HeapNotAllowed {
+                capacity: adjusted_capacity,
+            }),
+            ActiveBuffer::Initial(wb) => {
+                let mut heap_buffer =
+                    HeapBuffer::try_new_aligned_in(adjusted_capacity, self.align, self.allocator)?;
+                let (source, length) = wb.write_buffer();
+                let (destination, _) = heap_buffer.write_buffer();
+                unsafe { std::ptr::copy_nonoverlapping(source, destination, length as usize) };
+                self.active_buffer = ActiveBuffer::Heap(heap_buffer);
+                self.switched_to_heap = true;
+                Ok(())
+            }
         }
     }
     fn raw_buffer(&mut self) -> (*mut u8, u32) {
         match &mut self.active_buffer {
             ActiveBuffer::Heap(h) => h.write_buffer(),
             ActiveBuffer::Initial(wb) => wb.write_buffer(),
-            ActiveBuffer::PendingSwitch => panic!("PendingSwitch is only valid in grow"),
         }
     }
 }
@@ -164,17 +438,70 @@ impl ReadBuffer for EmptyReadBuffer {
 }
 const EMPTY_READ_BUFFER: EmptyReadBuffer = EmptyReadBuffer {};
 
+// Zero capacity initial buffer for a thawed FrozenBuffer that has no data.  It is zero-sized so
+// leaking a Box of it does not allocate.
+struct EmptyWriteBuffer {}
+
+impl WriteBuffer for EmptyWriteBuffer {
+    fn as_read_buffer(&self) -> &dyn ReadBuffer {
+        &EMPTY_READ_BUFFER
+    }
+    fn capacity(&self) -> u32 {
+        0
+    }
+    fn set_final_size(&mut self, _final_size: u32) {}
+    fn write_buffer(&mut self) -> (*mut u8, u32) {
+        (std::ptr::NonNull::<u8>::dangling().as_ptr(), 0)
+    }
+}
+
 enum PassiveBuffer<'sb> {
     Heap(HeapBuffer),
-    Initial(&'sb dyn ReadBuffer),
+    Initial(InitialData<'sb>),
+}
+
+// The data in the initial buffer, read when the buffer is frozen, and the initial buffer's
+// capacity.  The initial buffer stays exclusively borrowed for 'sb, through the GrowableBuffer, so
+// nothing else can write it while the pointer is held.
+struct InitialData<'sb> {
+    pointer: Option<*const u8>,
+    size: u32,
+    capacity: u32,
+    borrow: PhantomData<&'sb ()>,
+}
+
+impl<'sb> InitialData<'sb> {
+    fn new(read_buffer: &'sb dyn ReadBuffer, capacity: u32) -> Self {
+        let (pointer, size) = read_buffer.read_buffer();
+        Self {
+            pointer,
+            size,
+            capacity,
+            borrow: PhantomData,
+        }
+    }
+    fn empty() -> Self {
+        Self::new(&EMPTY_READ_BUFFER, 0)
+    }
+}
+
+impl<'sb> PassiveBuffer<'sb> {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Heap(_) => "Heap",
+            Self::Initial(..) => "Initial",
+        }
+    }
 }
 
 impl<'sb> From<ActiveBuffer<'sb>> for PassiveBuffer<'sb> {
     fn from(value: ActiveBuffer<'sb>) -> Self {
         match value {
             ActiveBuffer::Heap(h) => PassiveBuffer::Heap(h),
-            ActiveBuffer::Initial(s) => PassiveBuffer::Initial(s.as_read_buffer()),
-            ActiveBuffer::PendingSwitch => panic!("PendingSwitch is only valid in grow"),
+            ActiveBuffer::Initial(s) => {
+                let capacity = s.capacity();
+                PassiveBuffer::Initial(InitialData::new(s.as_read_buffer(), capacity))
+            }
         }
     }
 }
@@ -188,28 +515,41 @@ impl<'sb> From<ActiveBuffer<'sb>> for PassiveBuffer<'sb> {
 pub struct FrozenBuffer<'sb, FT> {
     passive_buffer: PassiveBuffer<'sb>,
     final_type: PhantomData<FT>,
+    os_error_hint: u32,
+    stats: GrowStats,
 }
 
+// SAFETY: A FrozenBuffer either owns a HeapBuffer or points into the initial buffer.  The owned
+// case is like a Box<[FT]>: nothing else refers to the memory, so it can be sent when FT is Send
+// and shared when FT is Sync.  In the borrowed case the initial buffer stays exclusively borrowed
+// for 'sb, through the GrowableBuffer or the &mut taken by freeze_in_place, so nothing else can
+// read or write it until the FrozenBuffer is dropped.  That is a &'sb mut [FT], which is Send when
+// FT is Send and Sync when FT is Sync.  The borrow checker keeps a borrowed FrozenBuffer from
+// outliving the initial buffer on whichever thread it is sent to.
+unsafe impl<FT: Send> Send for FrozenBuffer<'_, FT> {}
+unsafe impl<FT: Sync> Sync for FrozenBuffer<'_, FT> {}
+
 impl<'sb, FT> FrozenBuffer<'sb, FT> {
     /// Returns a pointer to the data and the number of elements (`FT`s) stored.
     ///
-    /// Do not read past the end of the buffer.  If zero elements were stored do not dereference
-    /// the pointer.  Doing either is undefined behaviour.
+    /// The pointer is aligned for `FT` and to at least [`ALIGNMENT`]; see [`StackBuffer`] for
+    /// larger alignments.  Do not read past the end of the buffer.  If zero elements were stored
+    /// do not dereference the pointer.  Doing either is undefined behaviour.
     ///
-    /// If the initial buffer was frozen and was too small to meet the alignment requirement then
+    /// If the frozen buffer cannot provide a pointer (e.g. a zero-sized initial buffer) then
     /// [`None`] is returned instead of a pointer.
     ///
     // nfx? Return null if the number elements stored is zero? Return None instead?
     pub fn read_buffer(&self) -> (Option<*const FT>, u32) {
         let (p, s) = match &self.passive_buffer {
             PassiveBuffer::Heap(h) => h.read_buffer(),
-            PassiveBuffer::Initial(wb) => wb.read_buffer(),
+            PassiveBuffer::Initial(d) => (d.pointer, d.size),
         };
         (p.map(|p| p as *const FT), s)
     }
     /// Returns a pointer to the data.
     ///
-    /// If the initial buffer was frozen and is too small to meet the alignment requirement then
+    /// If the frozen buffer cannot provide a pointer (e.g. a zero-sized initial buffer) then
     /// [`None`] is returned instead of a pointer.
     ///
     pub fn pointer(&self) -> Option<*const FT> {
@@ -223,6 +563,530 @@ impl<'sb, FT> FrozenBuffer<'sb, FT> {
     pub fn size(&self) -> u32 {
         self.read_buffer().1
     }
+    /// Returns the data as a slice of `FT`s.
+    ///
+    /// The number of elements is the number of bytes committed divided by the size of `FT` so it
+    /// is right whether the operating system call measured the buffer in bytes or in elements.  A
+    /// partial element at the end is not included.  See [`Pod`] for the element types allowed.
+    ///
+    /// An empty slice is returned when nothing was stored, the data cannot be accessed, or the
+    /// data is not aligned for `FT`.  `as_slice` never panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grob::{GrowForSmallBinary, GrowableBuffer, StackBuffer};
+    ///
+    /// let mut initial_buffer = StackBuffer::<64>::new();
+    /// let grow_strategy = GrowForSmallBinary::new();
+    /// let mut growable_buffer =
+    ///     GrowableBuffer::<u32, *mut u32>::new(&mut initial_buffer, &grow_strategy);
+    /// let mut argument = growable_buffer.argument();
+    /// let pointer = argument.pointer();
+    /// unsafe { pointer.write(7) };
+    /// unsafe { pointer.add(1).write(11) };
+    /// // The size is in bytes.
+    /// *argument.size_mut() = 8;
+    /// argument.commit();
+    /// let frozen_buffer = growable_buffer.freeze();
+    /// assert!(frozen_buffer.as_slice() == [7, 11]);
+    /// ```
+    ///
+    pub fn as_slice(&self) -> &[FT]
+    where
+        FT: Pod,
+    {
+        let count = self.committed_bytes() / std::mem::size_of::<FT>().max(1);
+        match self.pointer() {
+            Some(p) if count > 0 && p.align_offset(std::mem::align_of::<FT>()) == 0 => unsafe {
+                std::slice::from_raw_parts(p, count)
+            },
+            _ => &[],
+        }
+    }
+    /// Returns the bytes committed by the operating system call.
+    ///
+    /// This is the raw view of the data whatever `FT` is; for example, for logging a result that
+    /// was not expected.  An empty slice is returned when nothing was stored or the data cannot be
+    /// accessed.
+    ///
+    pub fn bytes(&self) -> &[u8] {
+        let length = self.committed_bytes();
+        match self.read_buffer() {
+            (Some(p), _) if length > 0 => unsafe { std::slice::from_raw_parts(p.cast(), length) },
+            _ => &[],
+        }
+    }
+    /// Walks a linked list of records that starts at the beginning of the buffer.
+    ///
+    /// Operating system calls like [`GetAdaptersAddresses`][gaa] return records linked through a
+    /// `Next` pointer.  `next` returns the pointer to the record after the one it is given; a null
+    /// pointer ends the list.
+    ///
+    /// Every record is checked before it is returned.  A pointer that is not aligned for `FT` or
+    /// that does not leave a whole `FT` inside the committed data ends the iteration, as does a
+    /// list with more records than fit in the data.  Corrupted data cannot make the iterator read
+    /// outside the buffer or loop forever.
+    ///
+    /// # Safety
+    ///
+    /// Each record is returned as a reference to an `FT` made from the bytes the operating system
+    /// call stored.  Those bytes must be a valid `FT`.  The Windows structures, built from
+    /// integers, raw pointers, and unions, always are.  A `bool`, `char`, reference, `NonNull`,
+    /// or enum field is only valid if the operating system call guarantees the value.  For
+    /// element types that are valid for any bytes, see [`Pod`] and
+    /// [`as_slice`](FrozenBuffer::as_slice).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(miri))]
+    /// # mod miri_skip {
+    /// #
+    /// use windows::Win32::NetworkManagement::IpHelper::{
+    ///     GetAdaptersAddresses, GET_ADAPTERS_ADDRESSES_FLAGS,
+    /// };
+    /// use windows::Win32::Networking::WinSock::AF_UNSPEC;
+    ///
+    /// use grob::{winapi_large_binary, RvIsError};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let names = winapi_large_binary(
+    ///         |argument| {
+    ///             RvIsError::new(unsafe {
+    ///                 GetAdaptersAddresses(
+    ///                     AF_UNSPEC.0 as u32,
+    ///                     GET_ADAPTERS_ADDRESSES_FLAGS(0),
+    ///                     None,
+    ///                     Some(argument.pointer()),
+    ///                     argument.size_mut(),
+    ///                 )
+    ///             })
+    ///         },
+    ///         |frozen_buffer| {
+    ///             // IP_ADAPTER_ADDRESSES_LH is integers, pointers, and unions.
+    ///             let adapters = unsafe { frozen_buffer.iter_linked(|adapter| adapter.Next) };
+    ///             Ok(adapters
+    ///                 .map(|adapter| format!("{}", unsafe { adapter.FriendlyName.display() }))
+    ///                 .collect::<Vec<String>>())
+    ///         },
+    ///     )?;
+    ///     println!("{:?}", names);
+    ///     Ok(())
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [gaa]: https://learn.microsoft.com/en-us/windows/win32/api/iphlpapi/nf-iphlpapi-getadaptersaddresses
+    ///
+    pub unsafe fn iter_linked<'a, F>(&'a self, next: F) -> impl Iterator<Item = &'a FT> + 'a
+    where
+        F: Fn(&FT) -> *const FT + 'a,
+    {
+        let (base, _) = self.read_buffer();
+        let committed = self.committed_bytes();
+        let record_size = std::mem::size_of::<FT>();
+        // Each record takes at least one byte so a list longer than this has a cycle.
+        let mut remaining = committed / record_size.max(1);
+        let mut current = base;
+        std::iter::from_fn(move || {
+            let (base, p) = (base?, current?);
+            // The record is reached through the buffer's pointer so the data cannot point the
+            // iterator anywhere else.
+            let offset = (p as usize).checked_sub(base as usize)?;
+            let p = base.cast::<u8>().wrapping_add(offset).cast::<FT>();
+            let fits = offset
+                .checked_add(record_size)
+                .is_some_and(|end| end <= committed);
+            if remaining == 0 || !fits || p.align_offset(std::mem::align_of::<FT>()) != 0 {
+                current = None;
+                return None;
+            }
+            remaining -= 1;
+            let record = unsafe { &*p };
+            current = Some(next(record));
+            Some(record)
+        })
+    }
+    /// Walks records that each start where the previous one ends.
+    ///
+    /// Operating system calls like [`GetLogicalProcessorInformationEx`][glpi] return records of
+    /// varying size, each holding its own size.  `size_of_record` returns the size, in bytes, of
+    /// the record it is given.
+    ///
+    /// Every record, and a whole `FT` at its start, must be inside the committed data.  A size of
+    /// zero, a record that runs past the committed data, or a record that is not aligned for `FT`
+    /// ends the iteration.  A record may be smaller than `FT` but a small record too close to the
+    /// end of the data for a whole `FT` also ends the iteration; use a header type for `FT` when
+    /// that can happen.
+    ///
+    /// # Safety
+    ///
+    /// The bytes of each record must be a valid `FT`.  See
+    /// [`iter_linked`](FrozenBuffer::iter_linked).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(miri))]
+    /// # mod miri_skip {
+    /// #
+    /// use windows::Win32::System::SystemInformation::{GetLogicalProcessorInformationEx, RelationAll};
+    ///
+    /// use grob::{winapi_small_binary, RvIsError};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let relationships = winapi_small_binary(
+    ///         |argument| {
+    ///             RvIsError::new(unsafe {
+    ///                 GetLogicalProcessorInformationEx(
+    ///                     RelationAll,
+    ///                     Some(argument.pointer()),
+    ///                     argument.size_mut(),
+    ///                 )
+    ///             })
+    ///         },
+    ///         |frozen_buffer| {
+    ///             // SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX is integers and unions.
+    ///             let infos = unsafe { frozen_buffer.iter_sized(|info| info.Size as usize) };
+    ///             Ok(infos.map(|info| info.Relationship).collect::<Vec<_>>())
+    ///         },
+    ///     )?;
+    ///     println!("{:?}", relationships);
+    ///     Ok(())
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [glpi]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getlogicalprocessorinformationex
+    ///
+    pub unsafe fn iter_sized<'a, F>(
+        &'a self,
+        size_of_record: F,
+    ) -> impl Iterator<Item = &'a FT> + 'a
+    where
+        F: Fn(&FT) -> usize + 'a,
+    {
+        let (base, _) = self.read_buffer();
+        let committed = self.committed_bytes();
+        let mut offset: usize = 0;
+        std::iter::from_fn(move || {
+            let base = base?;
+            // Only committed bytes are read; anything past them may never have been written.
+            let fits = offset
+                .checked_add(std::mem::size_of::<FT>())
+                .is_some_and(|end| end <= committed);
+            let p = base.cast::<u8>().wrapping_add(offset).cast::<FT>();
+            if offset >= committed || !fits || p.align_offset(std::mem::align_of::<FT>()) != 0 {
+                offset = committed;
+                return None;
+            }
+            let record = unsafe { &*p };
+            match offset.checked_add(size_of_record(record)) {
+                Some(end) if end > offset && end <= committed => {
+                    offset = end;
+                    Some(record)
+                }
+                _ => {
+                    offset = committed;
+                    None
+                }
+            }
+        })
+    }
+    // The committed byte count is kept with the statistics.  It never exceeds the buffer.
+    fn committed_bytes(&self) -> usize {
+        self.stats.final_size.min(self.capacity()) as usize
+    }
+    /// Returns the capacity, in bytes, of the buffer holding the data.
+    ///
+    /// The capacity is what the call loop grew to, which may be far more than the data needs.
+    /// See [`GrowableBuffer::freeze_shrunk`].  An empty [`FrozenBuffer`] has a capacity of zero.
+    ///
+    pub fn capacity(&self) -> u32 {
+        match &self.passive_buffer {
+            PassiveBuffer::Heap(h) => h.capacity(),
+            PassiveBuffer::Initial(d) => d.capacity,
+        }
+    }
+    /// Returns a pointer to the data or an error if data was stored but cannot be accessed.
+    ///
+    /// `Ok(None)` is returned when no data was stored.  `Ok(Some(pointer))` is returned when data
+    /// was stored.  An [`std::io::Error`] with a kind of [`InvalidData`][id] is returned when data
+    /// was stored but the buffer cannot provide a pointer (e.g. a custom initial buffer that cannot
+    /// be read).  Unlike [`pointer`][p], the last case is never confused with the first.
+    ///
+    /// [id]: std::io::ErrorKind::InvalidData
+    /// [p]: crate::FrozenBuffer::pointer
+    ///
+    pub fn try_pointer(&self) -> Result<Option<*const FT>, std::io::Error> {
+        match self.read_buffer() {
+            (_, 0) => Ok(None),
+            (Some(p), _) => Ok(Some(p)),
+            (None, s) => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "{} bytes were stored but the buffer cannot be read{}",
+                    s,
+                    self.os_error_hint_suffix()
+                ),
+            )),
+        }
+    }
+    /// Returns the last error code seen by the return value handler before the buffer was frozen.
+    ///
+    /// [`RvIsError`] and [`RvIsSize`] record the error code from the operating system call when
+    /// converting the return value to a [`FillBufferAction`].  Some Windows API functions set the
+    /// last error even when they succeed.  When the data in the buffer turns out to be unusable
+    /// (e.g. invalid UTF-16) the hint may help explain why.
+    ///
+    /// [`None`] is returned if the error code was [`NO_ERROR`][ne] or no return value handler was
+    /// used.
+    ///
+    /// [ne]: windows::Win32::Foundation::NO_ERROR
+    ///
+    pub fn last_os_error_hint(&self) -> Option<WIN32_ERROR> {
+        if self.os_error_hint == 0 {
+            None
+        } else {
+            Some(WIN32_ERROR(self.os_error_hint))
+        }
+    }
+    /// Returns how the buffer behaved during the call loop that filled it.
+    ///
+    /// The statistics are those of the [`GrowableBuffer`] when it was frozen.  See [`GrowStats`].
+    ///
+    pub fn stats(&self) -> GrowStats {
+        self.stats
+    }
+    /// Give up ownership of the heap buffer and return a pointer to the data with the number of
+    /// elements (`FT`s) stored.
+    ///
+    /// The buffer is not freed.  The caller, typically by handing the pointer to a Windows API
+    /// function, is responsible for freeing it with the allocator that matches the
+    /// [`AllocSource`]; [`HeapFree`][hf] for [`AllocSource::ProcessHeap`] or
+    /// [`CoTaskMemFree`][ctmf] for [`AllocSource::CoTaskMem`].
+    ///
+    /// Only a heap buffer from an allocator that [can release][cr] its memory, like the operating
+    /// system allocators, can be released.  If the data is in the initial buffer, in a heap buffer
+    /// from [`AllocSource::RustGlobal`], or there is no data the [`FrozenBuffer`] is returned
+    /// unchanged as the error.
+    ///
+    /// [cr]: BufferAllocator::can_release
+    /// [hf]: https://learn.microsoft.com/en-us/windows/win32/api/heapapi/nf-heapapi-heapfree
+    /// [ctmf]: https://learn.microsoft.com/en-us/windows/win32/api/combaseapi/nf-combaseapi-cotaskmemfree
+    ///
+    pub fn release_to_os(self) -> Result<(*mut FT, u32), Self> {
+        let releasable = match &self.passive_buffer {
+            PassiveBuffer::Heap(h) => h.allocator().can_release() && self.size() > 0,
+            PassiveBuffer::Initial(..) => false,
+        };
+        if !releasable {
+            return Err(self);
+        }
+        let size = self.size();
+        match self.passive_buffer {
+            PassiveBuffer::Heap(h) => Ok((h.into_raw() as *mut FT, size)),
+            PassiveBuffer::Initial(..) => unreachable!(),
+        }
+    }
+    /// Convert the [`FrozenBuffer`] back to a [`GrowableBuffer`] for another call.
+    ///
+    /// After one successful call the right size for the next is usually known.  `thaw` keeps the
+    /// buffer so polling code can freeze, use the data, thaw, then call again without allocating.
+    ///
+    /// * A heap buffer is reused as-is.
+    /// * Data in the initial buffer is copied to a new heap buffer with the same capacity as the
+    ///   initial buffer.  This is the only case that allocates.
+    /// * If there is no buffer, because the call failed, the thawed buffer starts with zero
+    ///   capacity.
+    ///
+    /// The thawed [`GrowableBuffer`] does not borrow the initial buffer so it can outlive it.  The
+    /// data is kept but the thawed buffer has no final size until the next call commits one.
+    ///
+    /// # Errors
+    ///
+    /// A [`GrowError::AllocationFailed`] is returned when the heap buffer for the data in the
+    /// initial buffer cannot be allocated.
+    ///
+    pub fn thaw<'gs, IT>(
+        self,
+        grow_strategy: &'gs dyn GrowStrategy,
+    ) -> Result<GrowableBuffer<'gs, 'static, FT, IT>, GrowError>
+    where
+        IT: RawToInternal,
+    {
+        let active_buffer = match self.passive_buffer {
+            PassiveBuffer::Heap(mut h) => {
+                h.set_final_size(0);
+                ActiveBuffer::Heap(h)
+            }
+            PassiveBuffer::Initial(InitialData { capacity: 0, .. }) => {
+                ActiveBuffer::Initial(Box::leak(Box::new(EmptyWriteBuffer {})))
+            }
+            PassiveBuffer::Initial(d) => {
+                let align = std::mem::align_of::<FT>();
+                let mut heap_buffer = HeapBuffer::try_new_aligned_in(d.capacity, align, &StdAlloc)?;
+                if let Some(source) = d.pointer {
+                    let (destination, _) = heap_buffer.write_buffer();
+                    let length = d.size.min(d.capacity) as usize;
+                    unsafe { std::ptr::copy_nonoverlapping(source, destination, length) };
+                }
+                ActiveBuffer::Heap(heap_buffer)
+            }
+        };
+        let mut growable_buffer = GrowableBuffer::from_active_buffer(active_buffer, grow_strategy);
+        if let ActiveBuffer::Heap(h) = &growable_buffer.buffer_strategy.active_buffer {
+            growable_buffer.buffer_strategy.allocator = h.allocator();
+        }
+        Ok(growable_buffer)
+    }
+    /// Detach the [`FrozenBuffer`] from the initial buffer so it can be stored or returned.
+    ///
+    /// A [`FrozenBuffer`] borrows the initial buffer when that is where the data is.  `into_owned`
+    /// returns a [`FrozenBuffer`] that borrows nothing so a function that owns its
+    /// [`StackBuffer`] can return the frozen data instead of finishing inside a `finalize` closure.
+    ///
+    /// * A heap buffer is kept as-is.  Nothing is copied.
+    /// * Data in the initial buffer is copied to a new heap buffer just large enough for it.
+    /// * If nothing was stored, or the data cannot be accessed, an empty [`FrozenBuffer`] is
+    ///   returned.
+    ///
+    /// The [last operating system error hint][h] is kept.  The returned [`FrozenBuffer`] owns its
+    /// data so it can outlive the initial buffer and be sent to another thread.
+    ///
+    /// # Errors
+    ///
+    /// A [`GrowError::AllocationFailed`] is returned when the heap buffer for the data in the
+    /// initial buffer cannot be allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(miri))]
+    /// # mod miri_skip {
+    /// #
+    /// use windows::Win32::System::WindowsProgramming::GetUserNameW;
+    ///
+    /// use grob::{FrozenBuffer, GrowForStaticText, GrowableBuffer, RvIsError, StackBuffer};
+    /// use grob::{ToResult, CAPACITY_FOR_NAMES};
+    ///
+    /// fn user_name() -> Result<FrozenBuffer<'static, u16>, Box<dyn std::error::Error>> {
+    ///     let mut initial_buffer = StackBuffer::<CAPACITY_FOR_NAMES>::new();
+    ///     let grow_strategy = GrowForStaticText::new();
+    ///     let mut growable_buffer =
+    ///         GrowableBuffer::<u16, _>::new(&mut initial_buffer, &grow_strategy);
+    ///     loop {
+    ///         let mut argument = growable_buffer.argument();
+    ///         let rv = unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) };
+    ///         let rv = RvIsError::new(rv);
+    ///         let result = rv.to_result(&mut argument)?;
+    ///         if argument.try_apply(result)? {
+    ///             break;
+    ///         }
+    ///     }
+    ///     Ok(growable_buffer.freeze().into_owned()?)
+    /// }
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let frozen_buffer = user_name()?;
+    ///     println!("{:?}", frozen_buffer.to_os_string());
+    ///     Ok(())
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [h]: FrozenBuffer::last_os_error_hint
+    ///
+    pub fn into_owned(self) -> Result<FrozenBuffer<'static, FT>, GrowError> {
+        let passive_buffer = match self.passive_buffer {
+            PassiveBuffer::Heap(h) => PassiveBuffer::Heap(h),
+            PassiveBuffer::Initial(d) => match (d.pointer, d.size) {
+                (Some(source), size) if size > 0 => {
+                    // The size is in elements.  The element may be larger than a byte.
+                    let length = (size as u64 * std::mem::size_of::<FT>().max(1) as u64)
+                        .min(d.capacity as u64) as u32;
+                    let align = std::mem::align_of::<FT>();
+                    let mut heap_buffer = HeapBuffer::try_new_aligned_in(length, align, &StdAlloc)?;
+                    let (destination, _) = heap_buffer.write_buffer();
+                    unsafe { std::ptr::copy_nonoverlapping(source, destination, length as usize) };
+                    heap_buffer.set_final_size(size);
+                    PassiveBuffer::Heap(heap_buffer)
+                }
+                _ => PassiveBuffer::Initial(InitialData::empty()),
+            },
+        };
+        Ok(FrozenBuffer {
+            passive_buffer,
+            final_type: PhantomData,
+            os_error_hint: self.os_error_hint,
+            stats: self.stats,
+        })
+    }
+    // Copy the stored elements into a new Vec.  Nothing stored, or data that cannot be read,
+    // gives an empty Vec.
+    //
+    // The heap buffer is aligned to ALIGNMENT and may come from any BufferAllocator so it can
+    // never be handed to a Vec, which frees with the global allocator and the alignment of FT.
+    pub(crate) fn copy_elements(&self) -> Vec<FT>
+    where
+        FT: Copy,
+    {
+        match self.read_buffer() {
+            (Some(p), s) if s > 0 => unsafe { std::slice::from_raw_parts(p, s as usize) }.to_vec(),
+            _ => Vec::new(),
+        }
+    }
+    fn os_error_hint_suffix(&self) -> String {
+        match self.last_os_error_hint() {
+            Some(e) => format!(" (last operating system error: {})", win32_err(e)),
+            None => String::new(),
+        }
+    }
+}
+
+impl<'sb, FT> std::fmt::Debug for FrozenBuffer<'sb, FT> {
+    /// Formats the shape of the buffer followed by a hex dump of at most the first 64 bytes.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrozenBuffer")
+            .field("element_type", &std::any::type_name::<FT>())
+            .field("backing", &self.passive_buffer.kind())
+            .field("size", &self.size())
+            .field("last_os_error_hint", &self.last_os_error_hint())
+            .field("bytes", &HexDump(self.bytes()))
+            .finish()
+    }
+}
+
+// The number of bytes shown by the Debug output of a FrozenBuffer.
+const DEBUG_BYTES: usize = 64;
+
+// Formats bytes as space separated hex followed by an ellipsis when there are more than
+// DEBUG_BYTES.
+struct HexDump<'a>(&'a [u8]);
+
+impl<'a> std::fmt::Debug for HexDump<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("[")?;
+        for (i, b) in self.0.iter().take(DEBUG_BYTES).enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{:02x}", b)?;
+        }
+        if self.0.len() > DEBUG_BYTES {
+            f.write_str(" ...")?;
+        }
+        f.write_str("]")
+    }
+}
+
+// The state argument captures before the Argument is created.
+pub(crate) struct ArgumentSetup {
+    tries: usize,
+    zeroed: bool,
+    resumed_from: u32,
+    previous_final_size: u32,
 }
 
 /// Wrapper for Windows API arguments.  Typically a pointer to the buffer and a pointer to the
@@ -232,11 +1096,49 @@ impl<'sb, FT> FrozenBuffer<'sb, FT> {
 /// include providing arguments for an operating system call, growing the buffer, and finalizing
 /// the buffer.
 ///
+/// An `Argument` is resolved by calling [`try_apply`][a], [`commit`][c], [`commit_no_data`][n], or
+/// [`grow`][g].  An `Argument` that is dropped without being resolved leaves the
+/// [`GrowableBuffer`] exactly as it was before [`argument`][ga] was called.  For example, data
+/// committed by an earlier `Argument` is still available from [`freeze`][f].
+///
+/// Forgetting to resolve an `Argument` is nearly always a bug; [`freeze`][f] then returns an empty
+/// buffer.  In debug builds, dropping an `Argument` that was not resolved panics unless
+/// [`dismiss`][d] was called or the return value handler reported an operating system error.  The
+/// check is disabled by the `no_panic` feature.
+///
+/// [a]: crate::Argument::try_apply
+/// [c]: crate::Argument::commit
+/// [n]: crate::Argument::commit_no_data
+/// [g]: crate::Argument::grow
+/// [ga]: crate::GrowableBuffer::argument
+/// [f]: crate::GrowableBuffer::freeze
+/// [d]: crate::Argument::dismiss
+///
+/// An `Argument` borrows its [`GrowableBuffer`] and is used on the thread making the operating
+/// system call so it is neither [`Send`] nor [`Sync`].
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>() {}
+/// assert_send::<grob::Argument<'static, *mut u8>>();
+/// ```
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<grob::Argument<'static, *mut u8>>();
+/// ```
+///
+#[must_use = "an Argument has to be resolved with try_apply, commit, commit_no_data, grow, or dismiss"]
 pub struct Argument<'gb, IT> {
     parent: &'gb mut dyn GrowableBufferAsParent,
     pointer: IT,
+    raw_pointer: *mut u8,
+    capacity: u32,
     size: u32,
     tries: usize,
+    resumed_from: u32,
+    previous_final_size: u32,
+    resolved: bool,
+    dismissed: bool,
 }
 
 impl<'gb, IT> Argument<'gb, IT>
@@ -245,82 +1147,153 @@ where
 {
     /// Apply an action ([`FillBufferAction`]) to the underlying buffer.
     ///
-    /// `apply` is called in response to the return value from an operating system call.  If the
-    /// operating system indicates success then `apply` is called with [`FillBufferAction::Commit`]
-    /// if data is available or [`FillBufferAction::NoData`] if no data is available.  If the
-    /// operating system indicates the buffer is too small then `apply` is called with
-    /// [`FillBufferAction::Grow`] to apply the [`GrowStrategy`] to the buffer.
+    /// `apply` is the infallible form of [`try_apply`][ta].  It panics if the buffer cannot grow.
+    ///
+    /// [ta]: crate::Argument::try_apply
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_apply`][ta] returns a [`GrowError`].
+    ///
+    #[deprecated(since = "0.2.0", note = "use try_apply so a GrowError can be handled")]
+    pub fn apply(self, fill_buffer_action: FillBufferAction) -> bool {
+        match self.try_apply(fill_buffer_action) {
+            Ok(rv) => rv,
+            Err(e) => panic!("{}", e),
+        }
+    }
+    /// Apply an action ([`FillBufferAction`]) to the underlying buffer.
+    ///
+    /// `try_apply` is called in response to the return value from an operating system call.  If
+    /// the operating system indicates success then `try_apply` is called with
+    /// [`FillBufferAction::Commit`] if data is available or [`FillBufferAction::NoData`] if no data
+    /// is available.  If the operating system indicates the buffer is too small then `try_apply`
+    /// is called with [`FillBufferAction::Grow`] to apply the [`GrowStrategy`] to the buffer.
     ///
-    /// [`Argument`] is no longer valid after the call to `apply` so `apply` consumes the
+    /// [`Argument`] is no longer valid after the call to `try_apply` so `try_apply` consumes the
     /// [`Argument`].
     ///
-    /// `apply` is only needed when using the low-level code.  The generic functions
+    /// `try_apply` is only needed when using the low-level code.  The generic functions
     /// ([`winapi_large_binary`], [`winapi_path_buf`], [`winapi_small_binary`], and
-    /// [`winapi_string`]) call `apply` automatically.
+    /// [`winapi_string`]) call `try_apply` automatically.
     ///
     /// # Arguments
     ///
     /// * `fill_buffer_action` - What action to apply to the [GrowableBuffer].  This will nearly
-    /// always be the return value from a call to [`to_result`][tr].
+    ///   always be the return value from a call to [`to_result`][tr].
     ///
     /// [tr]: crate::ToResult::to_result
     ///
     /// # Return Value
     ///
-    /// `true` is returned when the operating system call was successful and the buffer was large
-    /// enough to accommodate all the data.
+    /// `Ok(true)` is returned when the operating system call was successful and the buffer was
+    /// large enough to accommodate all the data.  `Ok(false)` is returned when the buffer grew and
+    /// the operating system call should be tried again.
     ///
-    pub fn apply(self, fill_buffer_action: FillBufferAction) -> bool {
+    /// # Errors
+    ///
+    /// A [`GrowError`] is returned if the buffer cannot grow.  The call loop should end.
+    ///
+    /// # Panics
+    ///
+    /// Without the `no_panic` feature, panics if the operating system reported storing more data
+    /// than fits in the buffer.  With the `no_panic` feature that is returned as a
+    /// [`GrowError::SizeExceedsCapacity`].  Use [`try_commit`](Argument::try_commit) to always get
+    /// the error.
+    ///
+    pub fn try_apply(self, fill_buffer_action: FillBufferAction) -> Result<bool, GrowError> {
         match fill_buffer_action {
             FillBufferAction::Commit => {
-                self.commit();
-                true
+                if let Err(e) = self.try_commit() {
+                    fault!(e);
+                }
+                Ok(true)
             }
             FillBufferAction::Grow => {
-                self.grow();
-                false
+                self.grow()?;
+                Ok(false)
             }
             FillBufferAction::NoData => {
                 self.commit_no_data();
-                true
+                Ok(true)
             }
         }
     }
     /// Set the final size of the buffer so the data is ready to be used.
     ///
-    /// Calling this method is rarely necessary.  Normally it's called from [`apply`][1].  Calling
+    /// Calling this method is rarely necessary.  Normally it's called from [`try_apply`][1].  Calling
     /// `commit` directly will be necessary if a return value handler ([`RvIsError`] or
     /// [`RvIsSize`]) is not adequate for converting an operating system return value into a
     /// [`FillBufferAction`].
     ///
-    /// [1]: crate::Argument::apply
+    /// [1]: crate::Argument::try_apply
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operating system reported storing more data than fits in the buffer, even
+    /// with the `no_panic` feature.  Use [`try_commit`](Argument::try_commit) to handle the error.
     ///
     pub fn commit(self) {
-        self.parent.set_final_size(self.size);
+        if let Err(e) = self.try_commit() {
+            panic!("{}", e);
+        }
+    }
+    /// Set the final size of the buffer so the data is ready to be used reporting a size that does
+    /// not fit as an error.
+    ///
+    /// A [`GrowError::SizeExceedsCapacity`] is returned if the operating system reported storing
+    /// more data than fits in the buffer.  Nothing is committed in that case.  The error is
+    /// returned with or without the `no_panic` feature.  Otherwise `try_commit` is identical to
+    /// [`commit`](Argument::commit).
+    ///
+    pub fn try_commit(mut self) -> Result<(), GrowError> {
+        self.resolved = true;
+        self.parent.set_final_size(self.size)
     }
     /// Set the final size of the buffer to zero indicating the operating system call was successful
     /// but did not return any data.
     ///
-    /// Calling this method is rarely necessary.  Normally it's called from [`apply`][1].  Calling
+    /// Calling this method is rarely necessary.  Normally it's called from [`try_apply`][1].  Calling
     /// `commit_no_data` directly will be necessary if a return value handler ([`RvIsError`] or
     /// [`RvIsSize`]) is not adequate for converting an operating system return value into a
     /// [`FillBufferAction`].
     ///
-    /// [1]: crate::Argument::apply
+    /// [1]: crate::Argument::try_apply
     ///
-    pub fn commit_no_data(self) {
-        self.parent.set_final_size(0);
+    pub fn commit_no_data(mut self) {
+        // Zero always fits.
+        let _ = self.parent.set_final_size(0);
+        self.resolved = true;
     }
     /// Increase the amount of space available in the buffer using the [`GrowStrategy`].
     ///
-    /// Calling this method is rarely necessary.  Normally it's called from [`apply`][1].  Calling
+    /// Calling this method is rarely necessary.  Normally it's called from [`try_apply`][1].  Calling
     /// `grow` directly will be necessary if a return value handler ([`RvIsError`] or [`RvIsSize`])
     /// is not adequate for converting an operating system return value into a [`FillBufferAction`].
     ///
-    /// [1]: crate::Argument::apply
+    /// [1]: crate::Argument::try_apply
+    ///
+    /// # Errors
     ///
-    pub fn grow(self) {
-        self.parent.grow(self.size);
+    /// A [`GrowError`] is returned if the buffer cannot grow.  The [`GrowableBuffer`] keeps its
+    /// current buffer, and any data already committed, so the caller may try again with a new
+    /// [`argument`](GrowableBuffer::argument) or stop.
+    ///
+    pub fn grow(mut self) -> Result<(), GrowError> {
+        self.resolved = true;
+        self.parent.grow(self.size)
+    }
+    /// Abandon the `Argument` without resolving it.
+    ///
+    /// The [`GrowableBuffer`] is left exactly as it was before [`argument`][ga] was called.
+    /// `dismiss` states that the abandonment is intentional so the debug build check for a
+    /// forgotten [`try_apply`][a] is not triggered.
+    ///
+    /// [ga]: crate::GrowableBuffer::argument
+    /// [a]: crate::Argument::try_apply
+    ///
+    pub fn dismiss(mut self) {
+        self.dismissed = true;
     }
     /// Returns a correctly typed pointer to the buffer, ready to be used for an operating system
     /// call.
@@ -344,9 +1317,48 @@ where
     /// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/WindowsProgramming/fn.GetUserNameW.html
     /// [grob]: https://crates.io/crates/grob
     ///
+    #[deprecated(since = "0.2.0", note = "use size_mut or size_opt_mut")]
     pub fn size(&mut self) -> *mut u32 {
         &mut self.size
     }
+    /// Returns a reference to the buffer size, ready to be used for an operating system call.
+    ///
+    /// For example, The `pcbbuffer` parameter for [`GetUserNameW`][1] is a `*mut u32`.  A
+    /// `&mut u32` coerces to a `*mut u32` so `size_mut` can be passed as-is.  The referenced value
+    /// is initialized to the current size of the buffer.  Reading or changing the size does not
+    /// need `unsafe`.
+    ///
+    /// The returned reference borrows the `Argument`.  Get the [`pointer`](Argument::pointer)
+    /// first or use [`pointer_and_size_mut`](Argument::pointer_and_size_mut) when the size comes
+    /// before the buffer in the parameter list.
+    ///
+    /// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/WindowsProgramming/fn.GetUserNameW.html
+    ///
+    pub fn size_mut(&mut self) -> &mut u32 {
+        &mut self.size
+    }
+    /// Returns a reference to the buffer size or [`None`] when the buffer has no capacity.
+    ///
+    /// Some operating system calls take an optional buffer and an optional size that have to be
+    /// both present or both missing.  `size_opt_mut` returns [`None`] when there is no buffer to
+    /// pass (the capacity is zero) and `Some(`[`size_mut`](Argument::size_mut)`)` otherwise.
+    ///
+    pub fn size_opt_mut(&mut self) -> Option<&mut u32> {
+        if self.capacity == 0 {
+            None
+        } else {
+            Some(&mut self.size)
+        }
+    }
+    /// Returns the [`pointer`](Argument::pointer) and [`size_mut`](Argument::size_mut) together.
+    ///
+    /// Useful when the size parameter comes before the buffer parameter.  Calling `size_mut` then
+    /// `pointer` in the same parameter list does not compile because the size reference borrows
+    /// the `Argument`.
+    ///
+    pub fn pointer_and_size_mut(&mut self) -> (IT, &mut u32) {
+        (self.pointer, &mut self.size)
+    }
     /// Returns the number of attempts that have been made.
     ///
     /// `tries` is only used by the Miri tests.  It is unstable (e.g. may be removed or changed in
@@ -355,6 +1367,94 @@ where
     pub fn tries(&self) -> usize {
         self.tries
     }
+    /// Returns the number of bytes at the start of the buffer carried over from the previous call.
+    ///
+    /// Always zero unless [`GrowableBuffer::preserve_on_grow`] is used.  After the buffer grows
+    /// with `preserve_on_grow`, the previous buffer was full so the value is the previous
+    /// capacity, in bytes.  The call should write the remainder of the data starting at this
+    /// offset.
+    ///
+    pub fn resumed_from(&self) -> u32 {
+        self.resumed_from
+    }
+    /// Fill the buffer with zeros.
+    ///
+    /// Some Windows API functions, several IOCTLs among them, read the buffer as well as write it.
+    /// `zero_fill` gives them a buffer with known contents.  It also makes the buffer initialized
+    /// so [`as_mut_slice`](Argument::as_mut_slice) can be used soundly.  Data carried over by
+    /// [`GrowableBuffer::preserve_on_grow`] is kept.  To zero the buffer before every call use
+    /// [`GrobBuilder::zeroed`].
+    ///
+    pub fn zero_fill(&mut self) {
+        let kept = self.resumed_from.min(self.capacity) as usize;
+        let length = self.capacity as usize - kept;
+        // An empty buffer may not have a pointer.
+        if length > 0 {
+            unsafe { std::ptr::write_bytes(self.raw_pointer.add(kept), 0, length) };
+        }
+    }
+}
+
+impl<'gb, IT> std::fmt::Debug for Argument<'gb, IT> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Argument")
+            .field("size", &self.size)
+            .field("tries", &self.tries)
+            .field("resolved", &self.resolved)
+            .finish()
+    }
+}
+
+impl<'gb, IT> Argument<'gb, IT>
+where
+    IT: RawToInternal,
+{
+    pub(crate) fn new(parent: &'gb mut dyn GrowableBufferAsParent, setup: ArgumentSetup) -> Self {
+        // The raw pointer is derived through the same reference the Argument holds.  Deriving it
+        // from the GrowableBuffer then reborrowing it for `parent` leaves a pointer whose
+        // provenance predates the reference it is used alongside.
+        let (pointer, capacity) = parent.raw_buffer();
+        if setup.zeroed {
+            // Data carried over by preserve_on_grow is kept.
+            let kept = setup.resumed_from.min(capacity) as usize;
+            let length = capacity as usize - kept;
+            // An empty buffer may not have a pointer.
+            if length > 0 && !pointer.is_null() {
+                unsafe { std::ptr::write_bytes(pointer.add(kept), 0, length) };
+            }
+        }
+        Argument {
+            parent,
+            pointer: IT::convert_pointer(pointer),
+            raw_pointer: pointer,
+            capacity,
+            size: IT::capacity_to_size(capacity),
+            tries: setup.tries,
+            resumed_from: setup.resumed_from,
+            previous_final_size: setup.previous_final_size,
+            resolved: false,
+            dismissed: false,
+        }
+    }
+}
+
+impl<'gb, IT> Drop for Argument<'gb, IT> {
+    /// Restores the [`GrowableBuffer`] to its state before the `Argument` was created if the
+    /// `Argument` was not resolved.
+    ///
+    /// This is also what happens when the `Argument` is dropped while unwinding from a panic.  The
+    /// [`GrowableBuffer`] can be used again or dropped.
+    ///
+    fn drop(&mut self) {
+        if !self.resolved {
+            self.parent.restore_final_size(self.previous_final_size);
+            // Never panic while unwinding.  A second panic aborts the process.
+            #[cfg(all(debug_assertions, not(feature = "no_panic")))]
+            if !self.dismissed && !std::thread::panicking() {
+                panic!("an Argument was dropped without calling try_apply, commit, commit_no_data, grow, or dismiss");
+            }
+        }
+    }
 }
 
 impl<'gb, IT> NeededSize for Argument<'gb, IT> {
@@ -389,6 +1489,22 @@ impl<'gb, IT> NeededSize for Argument<'gb, IT> {
     fn set_needed_size(&mut self, value: u32) {
         self.size = value;
     }
+    /// Called to record the error code from the operating system call.
+    ///
+    /// The value is carried to the [`FrozenBuffer`] and is available from
+    /// [`last_os_error_hint`][h].
+    ///
+    /// [h]: crate::FrozenBuffer::last_os_error_hint
+    ///
+    fn set_os_error_hint(&mut self, value: u32) {
+        self.parent.set_os_error_hint(value);
+    }
+    /// Called when the operating system call failed.  The `Argument` can then be dropped without
+    /// being resolved.
+    ///
+    fn set_failed(&mut self) {
+        self.dismissed = true;
+    }
 }
 
 /// Writable buffer capable of providing an [`Argument`] for a Windows API function then a
@@ -401,6 +1517,8 @@ impl<'gb, IT> NeededSize for Argument<'gb, IT> {
 /// [gc]: https://crates.io/crates/grob
 pub struct GrowableBuffer<'gs, 'sb, FT, IT> {
     final_size: u32,
+    os_error_hint: u32,
+    zeroed: bool,
     buffer_strategy: BufferStrategy<'gs, 'sb>,
     final_type: PhantomData<FT>,
     intermediate_type: PhantomData<IT>,
@@ -415,25 +1533,170 @@ where
     /// # Arguments
     ///
     /// * `initial` - The initial buffer.  Typically this is a reasonably sized [`StackBuffer`].
-    /// A zero sized [`StackBuffer`] can be passed to force the use of a heap buffer.  Using a heap
-    /// buffer allows moving the data more efficiently; the buffer can be easily "carried away".
+    ///   A zero sized [`StackBuffer`] can be passed to force the use of a heap buffer.  Using a
+    ///   heap buffer allows moving the data more efficiently; the buffer can be easily "carried
+    ///   away".
     /// * `grow_strategy` - Determines how the heap buffer should grow.  This crate provides two
-    /// basic strategies: double the size ([`GrowByDoubleWithNull`]) or use the size requested
-    /// ([`GrowToNearestNibble`], [`GrowToNearestNibbleWithNull`], [`GrowToNearestQuarterKibi`]).
+    ///   basic strategies: double the size ([`GrowByDoubleWithNull`]) or use the size requested
+    ///   ([`GrowToNearestNibble`], [`GrowToNearestNibbleWithNull`], [`GrowToNearestQuarterKibi`]).
+    ///
+    /// # Alignment
+    ///
+    /// Heap buffers are allocated with the [`alignment`](WriteBuffer::alignment) of `initial` or
+    /// the alignment of `FT`, whichever is larger, so a 64 byte aligned [`StackBuffer`] stays 64
+    /// byte aligned after switching to the heap.  An `initial` buffer aligned for less than `FT`
+    /// cannot hold `FT`s; it is not used and the first call is made as if `initial` had no
+    /// capacity.
     ///
     pub fn new(initial: &'sb mut dyn WriteBuffer, grow_strategy: &'gs dyn GrowStrategy) -> Self {
+        Self::from_active_buffer(ActiveBuffer::Initial(initial), grow_strategy)
+    }
+    /// Create a [`GrowableBuffer`] that owns its [`GrowStrategy`].
+    ///
+    /// [`new`](GrowableBuffer::new) borrows the strategy which ties the [`GrowableBuffer`] to the
+    /// strategy's lifetime.  Owning the strategy allows a [`GrowableBuffer`] to be stored in a
+    /// struct without also storing the strategy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grob::{GrowForSmallBinary, GrowableBuffer, StackBuffer};
+    ///
+    /// struct Poller<'sb> {
+    ///     buffer: GrowableBuffer<'static, 'sb, u8, *mut u8>,
+    /// }
+    ///
+    /// let mut initial_buffer = StackBuffer::<64>::new();
+    /// let poller = Poller {
+    ///     buffer: GrowableBuffer::with_owned_strategy(
+    ///         &mut initial_buffer,
+    ///         Box::new(GrowForSmallBinary::new()),
+    ///     ),
+    /// };
+    /// # let _ = poller.buffer;
+    /// ```
+    ///
+    pub fn with_owned_strategy(
+        initial: &'sb mut dyn WriteBuffer,
+        grow_strategy: Box<dyn GrowStrategy>,
+    ) -> GrowableBuffer<'static, 'sb, FT, IT> {
+        GrowableBuffer::from_parts(
+            ActiveBuffer::Initial(initial),
+            StrategyRef::Owned(grow_strategy),
+        )
+    }
+    /// Create a [`GrowableBuffer`] that starts with a heap buffer of `capacity` bytes.
+    ///
+    /// When the size of the data is known to be large, starting from a small initial buffer wastes
+    /// an operating system call.  There is no initial buffer; the heap buffer is allocated now and
+    /// the first call is made with all of it.  The [`GrowStrategy`] is only consulted if the
+    /// buffer still has to grow.  The [`GrowableBuffer`] does not borrow an initial buffer so it,
+    /// and the [`FrozenBuffer`] from [`freeze`](GrowableBuffer::freeze), can outlive the caller's
+    /// stack frame.
+    ///
+    /// The heap buffer comes from Rust's global allocator.  [`allocator`][a] and
+    /// [`alloc_source`][as] only apply to buffers allocated when growing.
+    ///
+    /// # Errors
+    ///
+    /// A [`GrowError::AllocationFailed`] is returned when the heap buffer cannot be allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grob::{GrowToNearestQuarterKibi, GrowableBuffer};
+    ///
+    /// let grow_strategy = GrowToNearestQuarterKibi::new();
+    /// let mut growable_buffer =
+    ///     GrowableBuffer::<u8, *mut u8>::with_heap_capacity(2 * 1024 * 1024, &grow_strategy)
+    ///         .unwrap();
+    /// let mut argument = growable_buffer.argument();
+    /// assert!(*argument.size_mut() == 2 * 1024 * 1024);
+    /// # argument.dismiss();
+    /// ```
+    ///
+    /// [a]: GrowableBuffer::allocator
+    /// [as]: GrowableBuffer::alloc_source
+    ///
+    pub fn with_heap_capacity(
+        capacity: u32,
+        grow_strategy: &'gs dyn GrowStrategy,
+    ) -> Result<GrowableBuffer<'gs, 'static, FT, IT>, GrowError> {
+        let align = std::mem::align_of::<FT>();
+        let heap_buffer = HeapBuffer::try_new_aligned_in(capacity, align, &StdAlloc)?;
+        Ok(GrowableBuffer::from_active_buffer(
+            ActiveBuffer::Heap(heap_buffer),
+            grow_strategy,
+        ))
+    }
+    // A GrowableBuffer without an initial buffer.  The first call is made with zero capacity.  The
+    // FrozenBuffer does not borrow anything so it can be returned to the caller.
+    pub(crate) fn heap_only(
+        grow_strategy: &'gs dyn GrowStrategy,
+    ) -> GrowableBuffer<'gs, 'static, FT, IT> {
+        let active_buffer = ActiveBuffer::Initial(Box::leak(Box::new(EmptyWriteBuffer {})));
+        GrowableBuffer::from_active_buffer(active_buffer, grow_strategy)
+    }
+    fn from_active_buffer(
+        active_buffer: ActiveBuffer<'sb>,
+        grow_strategy: &'gs dyn GrowStrategy,
+    ) -> Self {
+        Self::from_parts(active_buffer, StrategyRef::Borrowed(grow_strategy))
+    }
+    fn from_parts(active_buffer: ActiveBuffer<'sb>, grow_strategy: StrategyRef<'gs>) -> Self {
+        // Heap buffers get the alignment of the initial buffer or FT, whichever is larger.  An
+        // initial buffer aligned for less than FT cannot hold FTs so it is not used.
+        let element_align = std::mem::align_of::<FT>();
+        let (active_buffer, align) = match active_buffer {
+            ActiveBuffer::Initial(wb) if wb.alignment() < element_align => (
+                ActiveBuffer::Initial(Box::leak(Box::new(EmptyWriteBuffer {}))),
+                element_align,
+            ),
+            ActiveBuffer::Initial(wb) => {
+                let align = wb.alignment().max(element_align);
+                (ActiveBuffer::Initial(wb), align)
+            }
+            ActiveBuffer::Heap(h) => {
+                let align = h.alignment().max(element_align);
+                (ActiveBuffer::Heap(h), align)
+            }
+        };
         let buffer_strategy = BufferStrategy {
-            active_buffer: ActiveBuffer::Initial(initial),
+            active_buffer,
             grow_strategy,
             tries: 0,
+            preserve_on_grow: false,
+            resumed_from: 0,
+            allocator: &StdAlloc,
+            heap_allowed: true,
+            calls: 0,
+            switched_to_heap: false,
+            peak_capacity: 0,
+            align,
         };
         Self {
             final_size: 0,
+            os_error_hint: 0,
+            zeroed: false,
             buffer_strategy,
             final_type: PhantomData,
             intermediate_type: PhantomData,
         }
     }
+    /// Returns how the buffer has behaved so far.
+    ///
+    /// The statistics cover every [`Argument`] prepared since the [`GrowableBuffer`] was created
+    /// or last [`reset`](GrowableBuffer::reset).  See [`GrowStats`].
+    ///
+    pub fn stats(&self) -> GrowStats {
+        let buffer_strategy = &self.buffer_strategy;
+        GrowStats {
+            tries: buffer_strategy.calls,
+            switched_to_heap: buffer_strategy.switched_to_heap,
+            peak_capacity: buffer_strategy.peak_capacity,
+            final_size: IT::size_to_capacity(self.final_size),
+        }
+    }
     /// Convert a [`GrowableBuffer`] to a [`FrozenBuffer`].
     ///
     /// `freeze` is called after the Windows API function returns success.  While it can be called
@@ -442,27 +1705,234 @@ where
     ///
     /// The data stored by the API function is accessible through the returned [`FrozenBuffer`].
     ///
+    /// If the last resolved [`Argument`] grew the buffer then there is no data; the returned
+    /// [`FrozenBuffer`] is empty.  Unresolved [`Argument`]s do not affect the returned
+    /// [`FrozenBuffer`].
+    ///
+    /// `freeze` consumes the [`GrowableBuffer`] so it can only be frozen once...
+    ///
+    /// ```compile_fail
+    /// # use grob::{GrowableBuffer, GrowForSmallBinary, StackBuffer};
+    /// let mut initial_buffer = StackBuffer::<64>::new();
+    /// let grow_strategy = GrowForSmallBinary::new();
+    /// let growable_buffer = GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+    /// let first = growable_buffer.freeze();
+    /// let second = growable_buffer.freeze();
+    /// ```
+    ///
     /// # Arguments
     ///
     /// * `self` - The [`GrowableBuffer`] used when calling the Windows API function.
     ///
     pub fn freeze(self) -> FrozenBuffer<'sb, FT> {
+        let stats = self.stats();
         let GrowableBuffer {
             final_size,
+            os_error_hint,
             buffer_strategy,
             ..
         } = self;
         let passive_buffer = if final_size > 0 {
             let mut active_buffer = buffer_strategy.active_buffer;
-            active_buffer.set_final_size(final_size);
+            active_buffer.set_final_size(final_size, IT::size_to_capacity(final_size));
             active_buffer.into()
         } else {
-            PassiveBuffer::Initial(&EMPTY_READ_BUFFER)
+            PassiveBuffer::Initial(InitialData::empty())
         };
         FrozenBuffer {
             passive_buffer,
             final_type: PhantomData,
+            os_error_hint,
+            stats,
+        }
+    }
+    /// Like [`freeze`](GrowableBuffer::freeze) except a heap buffer much larger than the data is
+    /// shrunk first.
+    ///
+    /// Strategies that round up or double can leave a large heap buffer holding a little data.
+    /// When the data fills no more than half of a heap buffer, the buffer is reallocated down to
+    /// the size of the data rounded up to [`ALIGNMENT`].  The reallocation keeps the data and the
+    /// alignment.  Use `freeze_shrunk` when the [`FrozenBuffer`] is kept for a long time.
+    ///
+    /// The initial buffer is never shrunk.  If the reallocation fails the [`FrozenBuffer`] keeps
+    /// the larger buffer.
+    ///
+    pub fn freeze_shrunk(mut self) -> FrozenBuffer<'sb, FT> {
+        let needed = IT::size_to_capacity(self.final_size);
+        if let ActiveBuffer::Heap(h) = &mut self.buffer_strategy.active_buffer {
+            if needed > 0 && needed <= h.capacity() / 2 {
+                if let Some(capacity) = needed.checked_next_multiple_of(ALIGNMENT as u32) {
+                    // Ignoring the error is safe; a failed reallocation leaves the buffer as is.
+                    let _ = h.try_resize(capacity);
+                }
+            }
         }
+        self.freeze()
+    }
+    /// Like [`freeze`](GrowableBuffer::freeze) except the [`GrowableBuffer`] is borrowed instead of
+    /// consumed.
+    ///
+    /// Once the returned [`FrozenBuffer`] is dropped, [`reset`](GrowableBuffer::reset) prepares
+    /// the buffer for the next call.  The returned [`FrozenBuffer`] borrows the buffer so it
+    /// cannot take the heap buffer; [`release_to_os`](FrozenBuffer::release_to_os) returns it
+    /// unchanged and [`thaw`](FrozenBuffer::thaw) copies the data.
+    ///
+    pub fn freeze_in_place(&mut self) -> FrozenBuffer<'_, FT> {
+        let stats = self.stats();
+        let final_size = self.final_size;
+        let active_buffer = &mut self.buffer_strategy.active_buffer;
+        let passive_buffer = match active_buffer {
+            _ if final_size == 0 => PassiveBuffer::Initial(InitialData::empty()),
+            ActiveBuffer::Heap(h) => {
+                h.set_final_size(final_size);
+                let capacity = h.capacity();
+                PassiveBuffer::Initial(InitialData::new(&*h, capacity))
+            }
+            ActiveBuffer::Initial(wb) => {
+                wb.set_final_size_and_bytes(final_size, IT::size_to_capacity(final_size));
+                let capacity = wb.capacity();
+                PassiveBuffer::Initial(InitialData::new(wb.as_read_buffer(), capacity))
+            }
+        };
+        FrozenBuffer {
+            passive_buffer,
+            final_type: PhantomData,
+            os_error_hint: self.os_error_hint,
+            stats,
+        }
+    }
+    /// Forget the previous call so the buffer, at its current capacity, can be used for another.
+    ///
+    /// The stored size, the number of tries, the [`stats`](GrowableBuffer::stats), and the last
+    /// operating system error hint are cleared.  The active buffer is kept so the next call loop
+    /// starts at the capacity the previous one reached instead of growing from the initial buffer
+    /// again.  A grow that failed kept the buffer it started with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(not(miri))]
+    /// # mod miri_skip {
+    /// #
+    /// use windows::Win32::{
+    ///     NetworkManagement::IpHelper::{
+    ///         GetAdaptersAddresses, GET_ADAPTERS_ADDRESSES_FLAGS, IP_ADAPTER_ADDRESSES_LH,
+    ///     },
+    ///     Networking::WinSock::AF_UNSPEC,
+    /// };
+    ///
+    /// use grob::{GrowToNearestQuarterKibi, GrowableBuffer, RvIsError, StackBuffer, ToResult};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut initial_buffer = StackBuffer::<0>::new();
+    ///     let grow_strategy = GrowToNearestQuarterKibi::new();
+    ///     let mut growable_buffer =
+    ///         GrowableBuffer::<IP_ADAPTER_ADDRESSES_LH, *mut IP_ADAPTER_ADDRESSES_LH>::new(
+    ///             &mut initial_buffer,
+    ///             &grow_strategy,
+    ///         );
+    ///     for _ in 0..3 {
+    ///         growable_buffer.reset();
+    ///         loop {
+    ///             let mut argument = growable_buffer.argument();
+    ///             let rv = unsafe {
+    ///                 GetAdaptersAddresses(
+    ///                     AF_UNSPEC.0 as u32,
+    ///                     GET_ADAPTERS_ADDRESSES_FLAGS(0),
+    ///                     None,
+    ///                     Some(argument.pointer()),
+    ///                     argument.size_mut(),
+    ///                 )
+    ///             };
+    ///             let result = RvIsError::new(rv).to_result(&mut argument)?;
+    ///             if argument.try_apply(result)? {
+    ///                 break;
+    ///             }
+    ///         }
+    ///         let frozen_buffer = growable_buffer.freeze_in_place();
+    ///         println!("{} bytes", frozen_buffer.size());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// # }
+    /// ```
+    ///
+    pub fn reset(&mut self) {
+        self.buffer_strategy.active_buffer.set_final_size(0, 0);
+        self.buffer_strategy.tries = 0;
+        self.buffer_strategy.resumed_from = 0;
+        self.buffer_strategy.calls = 0;
+        self.buffer_strategy.switched_to_heap = false;
+        self.buffer_strategy.peak_capacity = 0;
+        self.final_size = 0;
+        self.os_error_hint = 0;
+    }
+    /// Keep the contents of the buffer when it grows.
+    ///
+    /// Some Windows API functions fill the buffer with as much data as fits, report that more is
+    /// needed, and can then be called again to fetch only the remainder.  With `preserve_on_grow`
+    /// the bytes already in the buffer are carried over to the larger buffer; a heap buffer is
+    /// reallocated and the initial buffer is copied.  [`Argument::resumed_from`] tells the next
+    /// call where the carried over data ends.
+    ///
+    /// By default the contents are not guaranteed to be kept.  A heap buffer is still reallocated
+    /// so the allocator can extend it in place but [`Argument::resumed_from`] is always zero.
+    ///
+    pub fn preserve_on_grow(mut self) -> Self {
+        self.buffer_strategy.preserve_on_grow = true;
+        self
+    }
+    /// Allocate heap buffers from `alloc_source`.
+    ///
+    /// Use [`AllocSource::ProcessHeap`] or [`AllocSource::CoTaskMem`] when the data is going to be
+    /// handed to a Windows API function that frees it.  See [`FrozenBuffer::release_to_os`].  The
+    /// initial buffer is not affected; pair this with a zero-sized [`StackBuffer`] so the data is
+    /// always in a heap buffer.
+    ///
+    pub fn alloc_source(mut self, alloc_source: AllocSource) -> Self {
+        self.buffer_strategy.allocator = alloc_source.allocator();
+        self
+    }
+    /// Allocate heap buffers from `allocator`.
+    ///
+    /// [`alloc_source`](GrowableBuffer::alloc_source) chooses one of the allocators built into
+    /// Windows.  `allocator` accepts any [`BufferAllocator`].  The initial buffer is not affected.
+    ///
+    pub fn allocator(mut self, allocator: &'static dyn BufferAllocator) -> Self {
+        self.buffer_strategy.allocator = allocator;
+        self
+    }
+    /// Never switch to a heap buffer.
+    ///
+    /// When the initial buffer is too small and cannot grow itself the call loop ends with
+    /// [`GrowError::HeapNotAllowed`] instead of allocating.  Converted to a [`std::io::Error`] the
+    /// kind is [`OutOfMemory`][oom].  Use this with a [`BorrowedBuffer`] so grob only ever writes
+    /// to memory the caller already owns.
+    ///
+    /// [oom]: std::io::ErrorKind::OutOfMemory
+    ///
+    pub fn no_heap(mut self) -> Self {
+        self.buffer_strategy.heap_allowed = false;
+        self
+    }
+    /// Fill the buffer with zeros before every operating system call.
+    ///
+    /// Some Windows API functions, like [`GetFileVersionInfoW`][gfvi] and several IOCTLs, expect
+    /// the output buffer to be zero on input.  With `zeroed` every buffer handed to the operating
+    /// system is filled with zeros first; the initial buffer, each heap buffer the call loop grows
+    /// to, and a buffer reused after [`reset`](GrowableBuffer::reset).  Data carried over by
+    /// [`preserve_on_grow`](GrowableBuffer::preserve_on_grow) is kept.  [`GrobBuilder::zeroed`]
+    /// does the same for the builder.
+    ///
+    /// [gfvi]: https://learn.microsoft.com/en-us/windows/win32/api/winver/nf-winver-getfileversioninfow
+    ///
+    pub fn zeroed(mut self) -> Self {
+        self.zeroed = true;
+        self
+    }
+    // Fill the buffer with zeros before every operating system call.
+    pub(crate) fn set_zeroed(&mut self, zeroed: bool) {
+        self.zeroed = zeroed;
     }
     /// Return an [`Argument`] that provides the argument(s) for calling a Windows API function
     ///
@@ -474,33 +1944,120 @@ where
     /// # Notes
     ///
     /// * The returned [`Argument`] carries a mutable reference to the [`GrowableBuffer`].  This
-    /// ensures the buffer cannot be changed or dropped until after the operating system call (while
-    /// the [`Argument`] instance exists).  It also ensures there can be only zero or one
-    /// [`Argument`] at any moment.
+    ///   ensures the buffer cannot be changed or dropped until after the operating system call
+    ///   (while the [`Argument`] instance exists).  It also ensures there can be only zero or one
+    ///   [`Argument`] at any moment.
     ///
     pub fn argument(&mut self) -> Argument<'_, IT> {
+        let setup = self.begin_argument();
+        Argument::new(self, setup)
+    }
+    // Everything argument does before the Argument borrows the GrowableBuffer.
+    pub(crate) fn begin_argument(&mut self) -> ArgumentSetup {
+        self.buffer_strategy.prime();
+        self.buffer_strategy.note_call();
+        let previous_final_size = self.final_size;
         self.final_size = 0;
-        let (pointer, capacity) = self.buffer_strategy.raw_buffer();
-        let tries = self.buffer_strategy.tries + 1;
-        Argument {
-            parent: self as &mut dyn GrowableBufferAsParent,
-            pointer: IT::convert_pointer(pointer),
-            size: IT::capacity_to_size(capacity),
-            tries,
+        ArgumentSetup {
+            tries: self.buffer_strategy.tries + 1,
+            zeroed: self.zeroed,
+            resumed_from: self.buffer_strategy.resumed_from,
+            previous_final_size,
+        }
+    }
+    // Move the state to another initial buffer and GrowStrategy.  An initial buffer that was
+    // replaced by a heap buffer is not used.  OwnedGrowableBuffer keeps the state detached from
+    // the StackBuffer it owns and attaches it for each call.
+    pub(crate) fn rebind<'gs2, 'sb2>(
+        self,
+        initial: &'sb2 mut dyn WriteBuffer,
+        grow_strategy: &'gs2 dyn GrowStrategy,
+    ) -> GrowableBuffer<'gs2, 'sb2, FT, IT> {
+        GrowableBuffer {
+            final_size: self.final_size,
+            os_error_hint: self.os_error_hint,
+            zeroed: self.zeroed,
+            buffer_strategy: self.buffer_strategy.rebind(initial, grow_strategy),
+            final_type: PhantomData,
+            intermediate_type: PhantomData,
         }
     }
+    /// Prepare an [`Argument`] with a buffer of at least `min_bytes`.
+    ///
+    /// `argument_with_min` is for when something outside the operating system call (a previous
+    /// call, a documented typical size) says the call needs at least `min_bytes`.  If the current
+    /// capacity is less than `min_bytes` the buffer is grown using the [`GrowStrategy`] before the
+    /// [`Argument`] is prepared.  That grow counts as a try.  Otherwise `argument_with_min` is the
+    /// same as [`argument`](GrowableBuffer::argument).
+    ///
+    /// `min_bytes` is only a hint.  If the grow fails the current buffer is used.  When that
+    /// buffer is too small the grow is tried again, and any error reported, after the operating
+    /// system call.
+    ///
+    pub fn argument_with_min(&mut self, min_bytes: u32) -> Argument<'_, IT> {
+        // Ignoring the error is safe; reserve leaves the current buffer in place.
+        let _ = self.reserve(min_bytes);
+        self.argument()
+    }
+    // Grow to at least min_bytes before the first operating system call.  On error the current
+    // buffer is kept.
+    pub(crate) fn reserve(&mut self, min_bytes: u32) -> Result<(), GrowError> {
+        self.buffer_strategy.reserve(min_bytes)
+    }
+    /// Returns a driver that provides one [`Attempt`] per try of the operating system call.
+    ///
+    /// [`Attempts`] is an alternative to calling [`argument`](GrowableBuffer::argument) in a loop.
+    /// See [`Attempts`] for an example.
+    ///
+    pub fn attempts(&mut self) -> Attempts<'_, 'gs, 'sb, FT, IT>
+    where
+        IT: Copy,
+    {
+        Attempts::new(self)
+    }
+}
+
+impl<'gs, 'sb, FT, IT> std::fmt::Debug for GrowableBuffer<'gs, 'sb, FT, IT> {
+    /// Formats the state of the buffer.  The buffer contents are never read.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let buffer_strategy = &self.buffer_strategy;
+        let capacity = buffer_strategy.capacity();
+        f.debug_struct("GrowableBuffer")
+            .field("backing", &buffer_strategy.active_buffer.kind())
+            .field("capacity", &capacity)
+            .field("tries", &buffer_strategy.tries)
+            .field("final_size", &self.final_size)
+            .finish()
+    }
 }
 
 impl<'gs, 'sb, FT, IT> GrowableBufferAsParent for GrowableBuffer<'gs, 'sb, FT, IT>
 where
     IT: RawToInternal,
 {
-    fn grow(&mut self, size: u32) {
-        self.buffer_strategy.grow(IT::size_to_capacity(size));
+    fn grow(&mut self, size: u32) -> Result<(), GrowError> {
+        self.buffer_strategy.grow(IT::size_to_capacity(size))
     }
-    fn set_final_size(&mut self, size: u32) {
+    fn raw_buffer(&mut self) -> (*mut u8, u32) {
+        self.buffer_strategy.raw_buffer()
+    }
+    fn restore_final_size(&mut self, size: u32) {
+        self.final_size = size;
+    }
+    fn set_final_size(&mut self, size: u32) -> Result<(), GrowError> {
         let needed_capacity = IT::size_to_capacity(size);
-        assert!(needed_capacity <= self.buffer_strategy.capacity());
+        let capacity = self.buffer_strategy.capacity();
+        if needed_capacity > capacity {
+            self.final_size = 0;
+            return Err(GrowError::SizeExceedsCapacity {
+                needed_capacity,
+                capacity,
+            });
+        }
         self.final_size = size;
+        Ok(())
+    }
+    fn set_os_error_hint(&mut self, value: u32) {
+        self.os_error_hint = value;
     }
 }
@@ -0,0 +1,162 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Run the call-operating-system-grow-buffer loop and evaluate to the [`FrozenBuffer`][fb].
+///
+/// `grob_call!` is for the cases where the generic functions do not fit.  It expands to the same
+/// loop the `*-full` examples write by hand.  Errors are propagated with `?` so the enclosing
+/// function has to return a [`Result`] whose error type can be built from a
+/// [`std::io::Error`] and a [`GrowError`][ge].
+///
+/// # Arguments
+///
+/// * `buffer` - The [`GrowableBuffer`][gb].  The expression is evaluated once.
+///
+/// * `handler` - Converts the value returned from the operating system call.  Typically
+///   [`RvIsError::new`][e] or [`RvIsSize::new`][s].
+///
+/// * `|argument| call` - The operating system call.  `argument` is bound to the
+///   [`Argument`][a] for each try.  The call expression appears exactly once in the expansion.
+///
+/// * `|action| body` - Optional.  Without it, the [`FillBufferAction`][fba] is applied with
+///   [`Argument::try_apply`][ta].  With it, `action` is bound to the [`FillBufferAction`][fba]
+///   and `body` decides what to do.  `body` evaluates to `true` when the loop is finished.
+///   Every path through `body` has to resolve `argument` with [`try_apply`][ta],
+///   [`commit`][c], [`commit_no_data`][n], [`grow`][g], or [`dismiss`][d], including a path that
+///   evaluates to `false`.  An `argument` dropped without being resolved panics in debug builds.
+///
+/// When the operating system call fails, `argument` is dismissed and the error is returned; the
+/// `body` is not evaluated.
+///
+/// # Examples
+///
+/// Applying the action with [`Argument::try_apply`][ta]...
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::core::PWSTR;
+/// use windows::Win32::System::WindowsProgramming::GetUserNameW;
+///
+/// use grob::{grob_call, GrowForStaticText, GrowableBuffer, RvIsError, StackBuffer};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut initial_buffer = StackBuffer::<64>::new();
+///     let grow_strategy = GrowForStaticText::new();
+///     let frozen_buffer = grob_call!(
+///         GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy),
+///         RvIsError::new,
+///         |argument| unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) }
+///     );
+///     println!("GetUserNameW returned {:?}", frozen_buffer.to_string(true));
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// Matching on the action...
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::core::PWSTR;
+/// use windows::Win32::Foundation::HMODULE;
+/// use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
+///
+/// use grob::{grob_call, FillBufferAction, GrowForStoredIsReturned, GrowableBuffer, RvIsSize};
+/// use grob::{StackBuffer, CAPACITY_FOR_PATHS};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut initial_buffer = StackBuffer::<CAPACITY_FOR_PATHS>::new();
+///     let grow_strategy = GrowForStoredIsReturned::<0>::new();
+///     let frozen_buffer = grob_call!(
+///         GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy),
+///         RvIsSize::new,
+///         |argument| unsafe { GetModuleFileNameW(HMODULE(0), argument.as_mut_slice()) },
+///         |action| match action {
+///             FillBufferAction::Commit => {
+///                 argument.commit();
+///                 true
+///             }
+///             FillBufferAction::Grow => {
+///                 argument.grow()?;
+///                 false
+///             }
+///             FillBufferAction::NoData => {
+///                 argument.commit_no_data();
+///                 true
+///             }
+///         }
+///     );
+///     println!("GetModuleFileNameW returned {:?}", frozen_buffer.to_path_buf());
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [fb]: crate::FrozenBuffer
+/// [ge]: crate::GrowError
+/// [gb]: crate::GrowableBuffer
+/// [e]: crate::RvIsError::new
+/// [s]: crate::RvIsSize::new
+/// [a]: crate::Argument
+/// [fba]: crate::FillBufferAction
+/// [ta]: crate::Argument::try_apply
+/// [c]: crate::Argument::commit
+/// [n]: crate::Argument::commit_no_data
+/// [g]: crate::Argument::grow
+/// [d]: crate::Argument::dismiss
+///
+#[macro_export]
+macro_rules! grob_call {
+    ($buffer:expr, $handler:expr, |$argument:ident| $call:expr $(,)?) => {{
+        let mut growable_buffer = $buffer;
+        loop {
+            let mut $argument = growable_buffer.argument();
+            let rv = ($handler)($call);
+            let fill_buffer_action = match $crate::ToResult::to_result(&rv, &mut $argument) {
+                ::core::result::Result::Ok(fill_buffer_action) => fill_buffer_action,
+                ::core::result::Result::Err(error) => {
+                    $argument.dismiss();
+                    return ::core::result::Result::Err(::core::convert::From::from(error));
+                }
+            };
+            if $argument.try_apply(fill_buffer_action)? {
+                break;
+            }
+        }
+        growable_buffer.freeze()
+    }};
+    ($buffer:expr, $handler:expr, |$argument:ident| $call:expr, |$action:ident| $body:expr $(,)?) => {{
+        let mut growable_buffer = $buffer;
+        loop {
+            let mut $argument = growable_buffer.argument();
+            let rv = ($handler)($call);
+            let $action = match $crate::ToResult::to_result(&rv, &mut $argument) {
+                ::core::result::Result::Ok(fill_buffer_action) => fill_buffer_action,
+                ::core::result::Result::Err(error) => {
+                    $argument.dismiss();
+                    return ::core::result::Result::Err(::core::convert::From::from(error));
+                }
+            };
+            let finished: bool = $body;
+            if finished {
+                break;
+            }
+        }
+        growable_buffer.freeze()
+    }};
+}
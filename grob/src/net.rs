@@ -0,0 +1,226 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+use windows::Win32::Foundation::FALSE;
+use windows::Win32::NetworkManagement::IpHelper::{
+    GetExtendedTcpTable, MIB_TCP_STATE, TCP_TABLE_CLASS, TCP_TABLE_OWNER_PID_ALL,
+    TCP_TABLE_OWNER_PID_CONNECTIONS, TCP_TABLE_OWNER_PID_LISTENER,
+};
+use windows::Win32::Networking::WinSock::{ADDRESS_FAMILY, AF_INET, AF_INET6};
+
+use crate::generic::winapi_large_binary;
+use crate::traits::ToResult;
+use crate::win::RvIsError;
+use crate::{Argument, FrozenBuffer};
+
+// The table starts with a DWORD dwNumEntries followed by the rows.
+const SIZE_OF_HEADER: usize = 4;
+// MIB_TCPROW_OWNER_PID
+const SIZE_OF_ROW_V4: usize = 24;
+// MIB_TCP6ROW_OWNER_PID
+const SIZE_OF_ROW_V6: usize = 56;
+
+/// One connection or listener from [`extended_tcp_table`].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TcpRow {
+    /// The local address and port.
+    pub local: SocketAddr,
+    /// The remote address and port.  Unspecified for listeners.
+    pub remote: SocketAddr,
+    /// The state of the connection like `MIB_TCP_STATE_ESTAB`.
+    pub state: MIB_TCP_STATE,
+    /// The process that owns the connection.
+    pub owning_pid: u32,
+}
+
+/// Wrapper for [`GetExtendedTcpTable`][gett] with one of the owner PID classes.
+///
+/// The rows of the `MIB_TCPTABLE_OWNER_PID` or `MIB_TCP6TABLE_OWNER_PID` are decoded into
+/// [`TcpRow`]s.  The number of entries is checked against the bytes stored so a table that does
+/// not fit the buffer is reported as an error instead of read past the end.
+///
+/// # Arguments
+///
+/// * `family` - `AF_INET` or `AF_INET6`.
+///
+/// * `class` - `TCP_TABLE_OWNER_PID_LISTENER`, `TCP_TABLE_OWNER_PID_CONNECTIONS`, or
+///   `TCP_TABLE_OWNER_PID_ALL`.
+///
+/// # Returns
+///
+/// * `Ok(Vec<`[`TcpRow`]`>)` when `GetExtendedTcpTable` returns success
+///
+/// * `Err(`[`std::io::Error`]`)` when `GetExtendedTcpTable` fails.  A `family` or `class` that is
+///   not supported has a kind of [`InvalidInput`](std::io::ErrorKind::InvalidInput).  A table that
+///   does not fit in the bytes stored has a kind of
+///   [`InvalidData`](std::io::ErrorKind::InvalidData).
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::NetworkManagement::IpHelper::{
+///     MIB_TCP_STATE_LISTEN, TCP_TABLE_OWNER_PID_ALL,
+/// };
+/// use windows::Win32::Networking::WinSock::AF_INET;
+///
+/// use grob::extended_tcp_table;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for row in extended_tcp_table(AF_INET, TCP_TABLE_OWNER_PID_ALL)? {
+///         if row.state == MIB_TCP_STATE_LISTEN {
+///             println!("{} is listening on {}", row.owning_pid, row.local);
+///         }
+///     }
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [gett]: https://learn.microsoft.com/en-us/windows/win32/api/iphlpapi/nf-iphlpapi-getextendedtcptable
+///
+pub fn extended_tcp_table(
+    family: ADDRESS_FAMILY,
+    class: TCP_TABLE_CLASS,
+) -> Result<Vec<TcpRow>, std::io::Error> {
+    if ![
+        TCP_TABLE_OWNER_PID_LISTENER,
+        TCP_TABLE_OWNER_PID_CONNECTIONS,
+        TCP_TABLE_OWNER_PID_ALL,
+    ]
+    .contains(&class)
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("TCP table class {} does not have owner PID rows", class.0),
+        ));
+    }
+    extended_tcp_table_with(family, |argument| {
+        RvIsError::new(unsafe {
+            GetExtendedTcpTable(
+                Some(argument.pointer().cast()),
+                argument.size_mut(),
+                FALSE,
+                family.0 as u32,
+                class,
+                0,
+            )
+        })
+    })
+}
+
+/// [`extended_tcp_table`] with the operating system call made by `api_wrapper`.
+///
+/// `api_wrapper` must store an owner PID table for `family`.  Useful for testing.
+///
+pub fn extended_tcp_table_with<W, WR>(
+    family: ADDRESS_FAMILY,
+    api_wrapper: W,
+) -> Result<Vec<TcpRow>, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut u8>) -> WR,
+{
+    let (size_of_row, decode): (usize, fn(&[u8]) -> TcpRow) = match family {
+        AF_INET => (SIZE_OF_ROW_V4, decode_v4),
+        AF_INET6 => (SIZE_OF_ROW_V6, decode_v6),
+        _ => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("address family {} is not AF_INET or AF_INET6", family.0),
+            ))
+        }
+    };
+    winapi_large_binary(api_wrapper, |frozen_buffer: FrozenBuffer<u8>| {
+        let data = match frozen_buffer.try_pointer()? {
+            Some(p) => unsafe { std::slice::from_raw_parts(p, frozen_buffer.size() as usize) },
+            None => return Ok(Vec::new()),
+        };
+        let entries = match data.get(..SIZE_OF_HEADER) {
+            Some(header) => read_u32(header, 0) as usize,
+            None => return Err(short_table(data.len(), 0)),
+        };
+        let rows = entries
+            .checked_mul(size_of_row)
+            .and_then(|n| data.get(SIZE_OF_HEADER..SIZE_OF_HEADER.checked_add(n)?))
+            .ok_or_else(|| short_table(data.len(), entries))?;
+        Ok(rows.chunks_exact(size_of_row).map(decode).collect())
+    })
+}
+
+fn short_table(size: usize, entries: usize) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!(
+            "{} bytes stored is too small for a TCP table with {} entries",
+            size, entries
+        ),
+    )
+}
+
+// The values are copied out of the buffer so alignment does not matter.
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+// Ports are stored in network byte order in the first two bytes of a DWORD.
+fn read_port(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+// Addresses are stored in network byte order.
+fn read_array<const N: usize>(data: &[u8], offset: usize) -> [u8; N] {
+    data[offset..offset + N].try_into().unwrap()
+}
+
+// MIB_TCPROW_OWNER_PID
+fn decode_v4(row: &[u8]) -> TcpRow {
+    TcpRow {
+        state: MIB_TCP_STATE(read_u32(row, 0) as i32),
+        local: SocketAddr::V4(SocketAddrV4::new(
+            Ipv4Addr::from(read_array::<4>(row, 4)),
+            read_port(row, 8),
+        )),
+        remote: SocketAddr::V4(SocketAddrV4::new(
+            Ipv4Addr::from(read_array::<4>(row, 12)),
+            read_port(row, 16),
+        )),
+        owning_pid: read_u32(row, 20),
+    }
+}
+
+// MIB_TCP6ROW_OWNER_PID
+fn decode_v6(row: &[u8]) -> TcpRow {
+    TcpRow {
+        local: SocketAddr::V6(SocketAddrV6::new(
+            Ipv6Addr::from(read_array::<16>(row, 0)),
+            read_port(row, 20),
+            0,
+            read_u32(row, 16),
+        )),
+        remote: SocketAddr::V6(SocketAddrV6::new(
+            Ipv6Addr::from(read_array::<16>(row, 24)),
+            read_port(row, 44),
+            0,
+            read_u32(row, 40),
+        )),
+        state: MIB_TCP_STATE(read_u32(row, 48) as i32),
+        owning_pid: read_u32(row, 52),
+    }
+}
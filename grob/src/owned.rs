@@ -0,0 +1,187 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use windows::core::PWSTR;
+
+use crate::base::GrowError;
+use crate::buffer::StackBuffer;
+use crate::strategy::{GrowForStaticText, GrowForStoredIsReturned};
+use crate::traits::{GrowStrategy, GrowableBufferAsParent, RawToInternal, WriteBuffer};
+use crate::win::{CAPACITY_FOR_NAMES, CAPACITY_FOR_PATHS};
+use crate::{Argument, EmptyWriteBuffer, FrozenBuffer, GrowableBuffer};
+
+// Stands in for the OwnedGrowableBuffer's strategy while the state is detached.  It is never
+// asked for a capacity.
+struct Detached {}
+
+impl GrowStrategy for Detached {
+    fn next_capacity(&self, _tries: usize, desired_capacity: u32) -> u32 {
+        desired_capacity
+    }
+}
+
+static DETACHED: Detached = Detached {};
+
+/// A [`GrowableBuffer`] that owns its [`StackBuffer`] and [`GrowStrategy`].
+///
+/// [`GrowableBuffer::new`] borrows the initial buffer and the strategy so both have to be declared
+/// by the caller first.  An `OwnedGrowableBuffer` holds both by value so it has no lifetimes; a
+/// helper function can create one and return it ready to use.  [`argument`][a] and [`freeze`][f]
+/// work like they do for a [`GrowableBuffer`].
+///
+/// The `StackBuffer` moves with the `OwnedGrowableBuffer`.  Nothing is allocated until the data
+/// does not fit in `CAPACITY` bytes.
+///
+/// [`PathGrowableBuffer`] and [`NameGrowableBuffer`] are ready-made for paths and names.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::System::WindowsProgramming::GetUserNameW;
+///
+/// use grob::{NameGrowableBuffer, RvIsError, ToResult};
+///
+/// fn name_buffer() -> NameGrowableBuffer {
+///     NameGrowableBuffer::default()
+/// }
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut growable_buffer = name_buffer();
+///     loop {
+///         let mut argument = growable_buffer.argument();
+///         let rv = unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) };
+///         let result = RvIsError::new(rv).to_result(&mut argument)?;
+///         if argument.try_apply(result)? {
+///             break;
+///         }
+///     }
+///     let frozen_buffer = growable_buffer.freeze();
+///     println!("GetUserNameW returned {:?}", frozen_buffer.to_os_string());
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [a]: OwnedGrowableBuffer::argument
+/// [f]: OwnedGrowableBuffer::freeze
+///
+pub struct OwnedGrowableBuffer<FT, IT, const CAPACITY: usize, S> {
+    initial_buffer: StackBuffer<CAPACITY>,
+    grow_strategy: S,
+    // The GrowableBuffer with the initial buffer and strategy swapped for placeholders.  It cannot
+    // borrow the fields next to it because the OwnedGrowableBuffer moves.
+    state: GrowableBuffer<'static, 'static, FT, IT>,
+}
+
+/// An [`OwnedGrowableBuffer`] for operating system calls that return a path.
+pub type PathGrowableBuffer = OwnedGrowableBuffer<
+    u16,
+    PWSTR,
+    CAPACITY_FOR_PATHS,
+    GrowForStoredIsReturned<{ CAPACITY_FOR_PATHS as u64 }>,
+>;
+
+/// An [`OwnedGrowableBuffer`] for operating system calls that return a user or account name.
+pub type NameGrowableBuffer =
+    OwnedGrowableBuffer<u16, PWSTR, CAPACITY_FOR_NAMES, GrowForStaticText>;
+
+impl<FT, IT, const CAPACITY: usize, S> OwnedGrowableBuffer<FT, IT, CAPACITY, S>
+where
+    IT: RawToInternal,
+    S: GrowStrategy,
+{
+    /// Create an [`OwnedGrowableBuffer`] with a `CAPACITY` byte [`StackBuffer`] and
+    /// `grow_strategy`.
+    pub fn new(grow_strategy: S) -> Self {
+        Self {
+            initial_buffer: StackBuffer::new(),
+            grow_strategy,
+            state: GrowableBuffer::heap_only(&DETACHED),
+        }
+    }
+    /// Prepare an [`Argument`] for the next operating system call.
+    ///
+    /// See [`GrowableBuffer::argument`].
+    ///
+    pub fn argument(&mut self) -> Argument<'_, IT> {
+        let setup = self.attached(|gb| gb.begin_argument());
+        Argument::new(self, setup)
+    }
+    /// Convert the buffer to a [`FrozenBuffer`].
+    ///
+    /// Unlike [`GrowableBuffer::freeze`] the [`OwnedGrowableBuffer`] is borrowed; the data may be
+    /// in the [`StackBuffer`] it owns.  Use [`into_owned`](FrozenBuffer::into_owned) to keep the
+    /// data after the [`OwnedGrowableBuffer`] is dropped.  Once the [`FrozenBuffer`] is dropped
+    /// the [`OwnedGrowableBuffer`] starts over from the [`StackBuffer`].
+    ///
+    pub fn freeze(&mut self) -> FrozenBuffer<'_, FT> {
+        let state = std::mem::replace(&mut self.state, GrowableBuffer::heap_only(&DETACHED));
+        let initial = Self::usable(&mut self.initial_buffer);
+        state.rebind(initial, &self.grow_strategy).freeze()
+    }
+    // An initial buffer aligned for less than FT cannot hold FTs.  GrowableBuffer::new makes the
+    // same check.
+    fn usable(initial_buffer: &mut StackBuffer<CAPACITY>) -> &mut dyn WriteBuffer {
+        if initial_buffer.alignment() < std::mem::align_of::<FT>() {
+            Box::leak(Box::new(EmptyWriteBuffer {}))
+        } else {
+            initial_buffer
+        }
+    }
+    // Run f with the state attached to the StackBuffer and strategy then detach it again.
+    fn attached<R>(&mut self, f: impl FnOnce(&mut GrowableBuffer<'_, '_, FT, IT>) -> R) -> R {
+        let state = std::mem::replace(&mut self.state, GrowableBuffer::heap_only(&DETACHED));
+        let initial = Self::usable(&mut self.initial_buffer);
+        let mut growable_buffer = state.rebind(initial, &self.grow_strategy);
+        let rv = f(&mut growable_buffer);
+        self.state = growable_buffer.rebind(Box::leak(Box::new(EmptyWriteBuffer {})), &DETACHED);
+        rv
+    }
+}
+
+impl<FT, IT, const CAPACITY: usize, S> Default for OwnedGrowableBuffer<FT, IT, CAPACITY, S>
+where
+    IT: RawToInternal,
+    S: GrowStrategy + Default,
+{
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<FT, IT, const CAPACITY: usize, S> GrowableBufferAsParent
+    for OwnedGrowableBuffer<FT, IT, CAPACITY, S>
+where
+    IT: RawToInternal,
+    S: GrowStrategy,
+{
+    fn grow(&mut self, size: u32) -> Result<(), GrowError> {
+        self.attached(|gb| gb.grow(size))
+    }
+    fn raw_buffer(&mut self) -> (*mut u8, u32) {
+        self.attached(|gb| gb.raw_buffer())
+    }
+    fn restore_final_size(&mut self, size: u32) {
+        self.attached(|gb| gb.restore_final_size(size))
+    }
+    fn set_final_size(&mut self, size: u32) -> Result<(), GrowError> {
+        self.attached(|gb| gb.set_final_size(size))
+    }
+    fn set_os_error_hint(&mut self, value: u32) {
+        self.attached(|gb| gb.set_os_error_hint(value))
+    }
+}
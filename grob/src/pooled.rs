@@ -0,0 +1,196 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::{Cell, RefCell};
+
+use crate::buffer::HeapBuffer;
+use crate::factory::{default_strategy, StrategyKind};
+use crate::generic::{winapi_generic, LARGE_BINARY_FLOOR, LARGE_BINARY_STACK_CAPACITY};
+use crate::strategy::GrowToNearestQuarterKibi;
+use crate::traits::{ReadBuffer, ToResult, WriteBuffer};
+use crate::{Argument, FrozenBuffer, GrowableBuffer};
+
+/// Number of bytes each thread keeps in its buffer pool unless changed with [`set_pool_cap`].
+pub const DEFAULT_POOL_CAP: usize = 1024 * 1024;
+
+thread_local! {
+    static POOL: RefCell<Vec<HeapBuffer>> = const { RefCell::new(Vec::new()) };
+    static POOL_CAP: Cell<usize> = const { Cell::new(DEFAULT_POOL_CAP) };
+}
+
+// A heap buffer from the pool used as the initial buffer.  It grows itself so a grown buffer goes
+// back to the pool instead of being freed with the GrowableBuffer.
+struct PooledBuffer(HeapBuffer);
+
+impl ReadBuffer for PooledBuffer {
+    fn read_buffer(&self) -> (Option<*const u8>, u32) {
+        self.0.read_buffer()
+    }
+}
+
+impl WriteBuffer for PooledBuffer {
+    fn as_read_buffer(&self) -> &dyn ReadBuffer {
+        self
+    }
+    fn capacity(&self) -> u32 {
+        self.0.capacity()
+    }
+    fn set_final_size(&mut self, final_size: u32) {
+        self.0.set_final_size(final_size);
+    }
+    fn write_buffer(&mut self) -> (*mut u8, u32) {
+        self.0.write_buffer()
+    }
+    fn try_grow(&mut self, capacity: u32) -> bool {
+        self.0.try_resize(capacity).is_ok()
+    }
+    fn alignment(&self) -> usize {
+        self.0.alignment()
+    }
+}
+
+fn pooled_bytes_in(pool: &[HeapBuffer]) -> usize {
+    pool.iter().map(|h| h.capacity() as usize).sum()
+}
+
+// Take the most recently returned buffer or, if the pool is empty, allocate one the size of the
+// stack buffer winapi_large_binary uses.
+fn take() -> Option<HeapBuffer> {
+    let pooled = POOL.with(|pool| pool.borrow_mut().pop());
+    pooled.or_else(|| {
+        let capacity = LARGE_BINARY_STACK_CAPACITY as u32;
+        HeapBuffer::with_capacity(capacity).ok()
+    })
+}
+
+// Return a buffer to the pool.  A buffer larger than the cap is freed.  Otherwise the oldest
+// buffers are freed until the pool fits under the cap.
+fn give_back(mut heap_buffer: HeapBuffer) {
+    heap_buffer.set_final_size(0);
+    let cap = POOL_CAP.with(Cell::get);
+    if heap_buffer.capacity() as usize > cap {
+        return;
+    }
+    POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        pool.push(heap_buffer);
+        while pooled_bytes_in(&pool) > cap {
+            pool.remove(0);
+        }
+    });
+}
+
+/// Sets the number of bytes the buffer pool for the current thread may keep.
+///
+/// Buffers are freed, oldest first, until the pool fits.  A cap of zero turns pooling off for the
+/// thread.  The default is [`DEFAULT_POOL_CAP`].
+///
+pub fn set_pool_cap(bytes: usize) {
+    POOL_CAP.with(|cap| cap.set(bytes));
+    POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        while pooled_bytes_in(&pool) > bytes {
+            pool.remove(0);
+        }
+    });
+}
+
+/// Returns the number of bytes kept in the buffer pool for the current thread.
+pub fn pooled_bytes() -> usize {
+    POOL.with(|pool| pooled_bytes_in(&pool.borrow()))
+}
+
+/// [`winapi_large_binary`][wlb] with the initial buffer taken from a per-thread pool.
+///
+/// Hot paths that call [`winapi_large_binary`][wlb] repeatedly pay for the stack buffer and for a
+/// new heap buffer whenever the data does not fit.  `winapi_large_binary_pooled` starts with the
+/// buffer left by the previous call on the same thread.  The buffer grows in place so, once it is
+/// large enough, every call is made with a single operating system call and no allocation.  After
+/// `finalize` returns the buffer goes back to the pool.
+///
+/// The pool keeps at most [`set_pool_cap`] bytes per thread.  A buffer that grew past the cap is
+/// freed instead of being kept.  The closures are exactly the same as for
+/// [`winapi_large_binary`][wlb].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::{
+///     NetworkManagement::IpHelper::{GetAdaptersAddresses, GET_ADAPTERS_ADDRESSES_FLAGS},
+///     Networking::WinSock::AF_UNSPEC,
+/// };
+///
+/// use grob::{winapi_large_binary_pooled, RvIsError};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for _ in 0..3 {
+///         let count = winapi_large_binary_pooled(
+///             |argument| {
+///                 RvIsError::new(unsafe {
+///                     GetAdaptersAddresses(
+///                         AF_UNSPEC.0 as u32,
+///                         GET_ADAPTERS_ADDRESSES_FLAGS(0),
+///                         None,
+///                         Some(argument.pointer()),
+///                         argument.size_mut(),
+///                     )
+///                 })
+///             },
+///             |frozen_buffer| {
+///                 let mut count = 0;
+///                 if let Some(mut p) = frozen_buffer.pointer() {
+///                     while p != std::ptr::null() {
+///                         count += 1;
+///                         p = unsafe { (*p).Next };
+///                     }
+///                 }
+///                 Ok(count)
+///             },
+///         )?;
+///         println!("{} adapters", count);
+///     }
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [wlb]: crate::winapi_large_binary
+///
+pub fn winapi_large_binary_pooled<FT, W, WR, F, U>(
+    api_wrapper: W,
+    finalize: F,
+) -> Result<U, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut FT>) -> WR,
+    F: FnMut(FrozenBuffer<FT>) -> Result<U, std::io::Error>,
+{
+    let grow_strategy = default_strategy(
+        StrategyKind::LargeBinary,
+        GrowToNearestQuarterKibi::<LARGE_BINARY_FLOOR>::with_const_floor(),
+    );
+    let Some(heap_buffer) = take() else {
+        // Could not allocate a buffer for the pool.  Let the GrowableBuffer try.
+        let growable_buffer = GrowableBuffer::<FT, *mut FT>::heap_only(&grow_strategy);
+        return winapi_generic(growable_buffer, api_wrapper, finalize);
+    };
+    let mut initial_buffer = PooledBuffer(heap_buffer);
+    let growable_buffer = GrowableBuffer::<FT, *mut FT>::new(&mut initial_buffer, &grow_strategy);
+    let rv = winapi_generic(growable_buffer, api_wrapper, finalize);
+    give_back(initial_buffer.0);
+    rv
+}
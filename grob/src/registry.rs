@@ -0,0 +1,173 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::Cell;
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+
+use windows::Win32::Foundation::WIN32_ERROR;
+use windows::Win32::System::Registry::{
+    REG_DWORD, REG_DWORD_BIG_ENDIAN, REG_EXPAND_SZ, REG_MULTI_SZ, REG_NONE, REG_QWORD, REG_SZ,
+    REG_VALUE_TYPE,
+};
+
+use crate::factory::{default_strategy, StrategyKind};
+use crate::strategy::GrowForSmallBinary;
+use crate::win::RvIsError;
+use crate::{Argument, FrozenBuffer, Grob};
+
+/// Data read from the registry converted according to its type.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RegistryValue {
+    /// `REG_DWORD` or `REG_DWORD_BIG_ENDIAN`.  Big endian values are converted.
+    Dword(u32),
+    /// `REG_QWORD`.
+    Qword(u64),
+    /// `REG_SZ` or `REG_EXPAND_SZ`.  Environment variables are not expanded.
+    String(OsString),
+    /// `REG_MULTI_SZ`.
+    MultiString(Vec<OsString>),
+    /// `REG_BINARY`, `REG_NONE`, and any other type.
+    Binary(Vec<u8>),
+}
+
+/// Generic wrapper for a Windows API call that reads a registry value like [`RegQueryValueExW`][q]
+/// or [`RegGetValueW`][g].
+///
+/// The registry functions report a buffer that is too small with `ERROR_MORE_DATA` and store the
+/// size needed.  `winapi_registry_value` grows the buffer and calls again.  The data is converted
+/// to a [`RegistryValue`] based on the type the registry function stored in the second argument
+/// of `api_wrapper`.
+///
+/// Strings are expected to end with a `NULL` terminator but registry data does not always have
+/// one.  Strings without a terminator are returned intact.  Extra terminators are removed.
+///
+/// # Returns
+///
+/// * `Ok(`[`RegistryValue`]`)` when the registry function returns success
+///
+/// * `Err(`[`std::io::Error`]`)` when the registry function fails or the size of a
+///   `REG_DWORD` or `REG_QWORD` is wrong.  The latter has a kind of
+///   [`InvalidData`](std::io::ErrorKind::InvalidData).
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::w;
+/// use windows::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_ANY};
+///
+/// use grob::winapi_registry_value;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let value = winapi_registry_value(|argument, value_type| unsafe {
+///         RegGetValueW(
+///             HKEY_LOCAL_MACHINE,
+///             w!("SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion"),
+///             w!("ProductName"),
+///             RRF_RT_ANY,
+///             Some(value_type),
+///             Some(argument.pointer().cast()),
+///             Some(argument.size_mut()),
+///         )
+///     })?;
+///     println!("ProductName is {:?}", value);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [q]: https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regqueryvalueexw
+/// [g]: https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-reggetvaluew
+///
+pub fn winapi_registry_value<W>(mut api_wrapper: W) -> Result<RegistryValue, std::io::Error>
+where
+    W: FnMut(&mut Argument<*mut u8>, &mut REG_VALUE_TYPE) -> WIN32_ERROR,
+{
+    let value_type = Cell::new(REG_NONE);
+    Grob::builder()
+        .strategy(default_strategy(
+            StrategyKind::SmallBinary,
+            GrowForSmallBinary::new(),
+        ))
+        .run(
+            |argument| {
+                let mut t = value_type.get();
+                let rv = api_wrapper(argument, &mut t);
+                value_type.set(t);
+                // The registry uses ERROR_MORE_DATA for a buffer that is too small.
+                RvIsError::new(rv.0)
+            },
+            |frozen_buffer| to_registry_value(value_type.get(), &frozen_buffer),
+        )
+}
+
+fn to_registry_value(
+    value_type: REG_VALUE_TYPE,
+    frozen_buffer: &FrozenBuffer<u8>,
+) -> Result<RegistryValue, std::io::Error> {
+    let data = match frozen_buffer.try_pointer()? {
+        Some(p) => unsafe { std::slice::from_raw_parts(p, frozen_buffer.size() as usize) },
+        None => &[],
+    };
+    Ok(match value_type {
+        REG_DWORD => RegistryValue::Dword(u32::from_le_bytes(exact(value_type, data)?)),
+        REG_DWORD_BIG_ENDIAN => RegistryValue::Dword(u32::from_be_bytes(exact(value_type, data)?)),
+        REG_QWORD => RegistryValue::Qword(u64::from_le_bytes(exact(value_type, data)?)),
+        REG_SZ | REG_EXPAND_SZ => {
+            let wide = to_wide(data);
+            let end = wide.iter().rposition(|c| *c != 0).map_or(0, |i| i + 1);
+            RegistryValue::String(OsString::from_wide(&wide[..end]))
+        }
+        REG_MULTI_SZ => RegistryValue::MultiString(
+            to_wide(data)
+                .split(|c| *c == 0)
+                .take_while(|s| !s.is_empty())
+                .map(OsString::from_wide)
+                .collect(),
+        ),
+        // REG_BINARY, REG_NONE, and anything else.
+        _ => RegistryValue::Binary(data.to_vec()),
+    })
+}
+
+// The data for a fixed size type.
+fn exact<const N: usize>(
+    value_type: REG_VALUE_TYPE,
+    data: &[u8],
+) -> Result<[u8; N], std::io::Error> {
+    data.try_into().map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "registry value of type {} has {} bytes instead of {}",
+                value_type.0,
+                data.len(),
+                N
+            ),
+        )
+    })
+}
+
+// Registry strings are UTF-16.  An odd trailing byte is ignored.  The bytes are copied so the
+// alignment of the buffer does not matter.
+fn to_wide(data: &[u8]) -> Vec<u16> {
+    data.chunks_exact(2)
+        .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+        .collect()
+}
@@ -0,0 +1,268 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+/// How long to wait before retrying after a transient error.
+///
+/// `attempt` is one for the first retry, two for the second, and so on.  Returning `None` ends the
+/// call loop with the transient error.
+///
+pub trait Backoff {
+    /// Returns how long to wait before retry number `attempt` or `None` to stop retrying.
+    fn delay(&self, attempt: usize) -> Option<Duration>;
+}
+
+/// [`Backoff`] that waits the same amount of time before every retry.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedBackoff {
+    /// Time to wait before each retry.
+    pub delay: Duration,
+}
+
+impl FixedBackoff {
+    /// Create a [`FixedBackoff`] that waits `delay` before every retry.
+    pub fn new(delay: Duration) -> Self {
+        Self { delay }
+    }
+}
+
+impl Backoff for FixedBackoff {
+    fn delay(&self, _attempt: usize) -> Option<Duration> {
+        Some(self.delay)
+    }
+}
+
+/// [`Backoff`] that doubles the wait before each retry starting with `base` and never exceeding
+/// `cap`.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExponentialBackoff {
+    /// Time to wait before the first retry.
+    pub base: Duration,
+    /// Longest time to wait before any retry.
+    pub cap: Duration,
+}
+
+impl ExponentialBackoff {
+    /// Create an [`ExponentialBackoff`] that starts at `base` and doubles up to `cap`.
+    pub fn new(base: Duration, cap: Duration) -> Self {
+        Self { base, cap }
+    }
+}
+
+impl Backoff for ExponentialBackoff {
+    fn delay(&self, attempt: usize) -> Option<Duration> {
+        // Anything past 2^31 is well past any reasonable cap.
+        let shift = attempt.saturating_sub(1).min(31) as u32;
+        let delay = self.base.saturating_mul(1 << shift);
+        Some(delay.min(self.cap))
+    }
+}
+
+/// [`Backoff`] that randomly shortens the delay from another [`Backoff`] by up to half.
+///
+/// Jitter spreads out retries from several threads or processes that failed at the same time.
+///
+#[derive(Debug)]
+pub struct Jittered<B> {
+    inner: B,
+    random_state: RandomState,
+}
+
+impl<B> Jittered<B>
+where
+    B: Backoff,
+{
+    /// Create a [`Jittered`] that shortens the delays from `inner`.
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            random_state: RandomState::new(),
+        }
+    }
+}
+
+impl<B> Backoff for Jittered<B>
+where
+    B: Backoff,
+{
+    fn delay(&self, attempt: usize) -> Option<Duration> {
+        let delay = self.inner.delay(attempt)?;
+        // A fraction between zero and one half.
+        let random = self.random_state.hash_one(attempt) >> 11;
+        let fraction = random as f64 / (1u64 << 53) as f64 / 2.0;
+        Some(delay.mul_f64(1.0 - fraction))
+    }
+}
+
+/// Number of times a [`RetryPolicy`] retries unless changed with
+/// [`max_retries`](RetryPolicy::max_retries).
+pub const DEFAULT_MAX_RETRIES: usize = 8;
+
+/// When and how to retry an operating system call that failed with a transient error.
+///
+/// `is_transient` decides which errors are worth retrying.  The [`Backoff`], which defaults to a
+/// [`FixedBackoff`] of 10 milliseconds, decides how long to wait before each retry.  At most
+/// [`DEFAULT_MAX_RETRIES`] retries are made unless changed with
+/// [`max_retries`](RetryPolicy::max_retries) so an error that is always transient cannot keep the
+/// call loop going forever.  The wait is done by calling the sleeper, which defaults to
+/// [`std::thread::sleep`].  Tests can provide a sleeper that records the delays instead of
+/// sleeping.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use std::time::Duration;
+///
+/// use windows::Win32::Foundation::ERROR_BUSY;
+///
+/// use grob::{ExponentialBackoff, IoErrorExt, RetryPolicy};
+///
+/// fn main() {
+///     let _retry_policy = RetryPolicy::new(|e| e.is_win32(ERROR_BUSY))
+///         .backoff(ExponentialBackoff::new(
+///             Duration::from_millis(5),
+///             Duration::from_millis(100),
+///         ))
+///         .max_retries(4);
+/// }
+/// # }
+/// ```
+///
+pub struct RetryPolicy {
+    is_transient: fn(&std::io::Error) -> bool,
+    backoff: Box<dyn Backoff>,
+    max_retries: usize,
+    sleeper: fn(Duration),
+}
+
+impl RetryPolicy {
+    /// Create a [`RetryPolicy`] that retries errors for which `is_transient` returns `true`.
+    pub fn new(is_transient: fn(&std::io::Error) -> bool) -> Self {
+        Self {
+            is_transient,
+            backoff: Box::new(FixedBackoff::new(Duration::from_millis(10))),
+            max_retries: DEFAULT_MAX_RETRIES,
+            sleeper: std::thread::sleep,
+        }
+    }
+    /// Use `backoff` to determine the wait before each retry.
+    pub fn backoff<B>(mut self, backoff: B) -> Self
+    where
+        B: Backoff + 'static,
+    {
+        self.backoff = Box::new(backoff);
+        self
+    }
+    /// Retry at most `max_retries` times.  The default is [`DEFAULT_MAX_RETRIES`].
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+    /// Wait by calling `sleeper` instead of [`std::thread::sleep`].
+    pub fn sleeper(mut self, sleeper: fn(Duration)) -> Self {
+        self.sleeper = sleeper;
+        self
+    }
+    // Returns how long to wait before retry number `attempt` or None if `error` should be returned.
+    pub(crate) fn delay(&self, attempt: usize, error: &std::io::Error) -> Option<Duration> {
+        if !(self.is_transient)(error) {
+            return None;
+        }
+        if attempt > self.max_retries {
+            return None;
+        }
+        self.backoff.delay(attempt)
+    }
+    pub(crate) fn sleep(&self, delay: Duration) {
+        (self.sleeper)(delay)
+    }
+}
+
+/// How long to wait before calling again after the size needed changed between calls.
+///
+/// Some tables, like the one returned from [`GetTcpTable2`][1], can grow between the call that
+/// reports the size needed and the call that fills the buffer.  When that keeps happening under
+/// load, waiting a moment before the next call gives the table a chance to settle.  The first time
+/// the buffer grows is the expected size probe so there is no wait after it.  After each later
+/// grow the loop waits for the delay from the [`Backoff`].  `attempt` is one for the first wait.
+/// The wait is done by calling the sleeper, which defaults to [`std::thread::sleep`].
+///
+/// Waiting is off unless a [`RetryWithBackoff`] is passed to
+/// [`GrobBuilder::grow_backoff`](crate::GrobBuilder::grow_backoff).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use grob::{FixedBackoff, Grob, GrowForSmallBinary, GrowWithHeadroom, RetryWithBackoff};
+///
+/// let _builder = Grob::builder()
+///     .strategy(GrowWithHeadroom::new(GrowForSmallBinary::new(), 110))
+///     .grow_backoff(RetryWithBackoff::new(FixedBackoff::new(Duration::from_millis(5))));
+/// ```
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/iphlpapi/nf-iphlpapi-gettcptable2
+///
+pub struct RetryWithBackoff {
+    backoff: Box<dyn Backoff>,
+    sleeper: fn(Duration),
+}
+
+impl RetryWithBackoff {
+    /// Create a [`RetryWithBackoff`] that uses `backoff` to determine the wait after each grow.
+    pub fn new<B>(backoff: B) -> Self
+    where
+        B: Backoff + 'static,
+    {
+        Self {
+            backoff: Box::new(backoff),
+            sleeper: std::thread::sleep,
+        }
+    }
+    /// Wait by calling `sleeper` instead of [`std::thread::sleep`].
+    pub fn sleeper(mut self, sleeper: fn(Duration)) -> Self {
+        self.sleeper = sleeper;
+        self
+    }
+    // Returns how long to wait before the next call after wait number `attempt`.
+    pub(crate) fn delay(&self, attempt: usize) -> Option<Duration> {
+        self.backoff.delay(attempt)
+    }
+    pub(crate) fn sleep(&self, delay: Duration) {
+        (self.sleeper)(delay)
+    }
+}
+
+impl std::fmt::Debug for RetryWithBackoff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryWithBackoff").finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_retries", &self.max_retries)
+            .finish_non_exhaustive()
+    }
+}
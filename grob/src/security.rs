@@ -0,0 +1,249 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+use std::path::Path;
+
+use windows::core::PWSTR;
+use windows::Win32::Foundation::{GetLastError, HANDLE, HLOCAL};
+use windows::Win32::Security::Authorization::{
+    ConvertSecurityDescriptorToStringSecurityDescriptorW, SDDL_REVISION_1,
+};
+use windows::Win32::Security::{
+    GetFileSecurityW, GetKernelObjectSecurity, DACL_SECURITY_INFORMATION,
+    GROUP_SECURITY_INFORMATION, OBJECT_SECURITY_INFORMATION, OWNER_SECURITY_INFORMATION,
+    PSECURITY_DESCRIPTOR, SACL_SECURITY_INFORMATION, SECURITY_DESCRIPTOR,
+};
+use windows::Win32::System::Memory::LocalFree;
+
+use crate::buffer::os::ALIGNMENT;
+use crate::factory::{default_strategy, StrategyKind};
+use crate::generic::frozen_with;
+use crate::strategy::GrowForSmallBinary;
+use crate::traits::{GrowStrategy, ToResult};
+use crate::win::{win32_err, RvIsError};
+use crate::{Argument, AsPCWSTR, FrozenBuffer, WindowsPathString};
+
+// A self-relative security descriptor (SECURITY_DESCRIPTOR_RELATIVE) with no owner, group, or ACLs
+// is 20 bytes; smaller than a SECURITY_DESCRIPTOR on 64 bit Windows.
+const SIZE_OF_SELF_RELATIVE: usize = 20;
+
+/// The object [`winapi_security_descriptor`] reads the security descriptor from.
+///
+#[derive(Clone, Copy, Debug)]
+pub enum SecurityObject<'a> {
+    /// A file or directory.  [`GetFileSecurityW`][gfs] is called.
+    ///
+    /// [gfs]: https://learn.microsoft.com/en-us/windows/win32/api/securitybaseapi/nf-securitybaseapi-getfilesecurityw
+    ///
+    File(&'a Path),
+    /// A handle to a kernel object like a process or an event.  [`GetKernelObjectSecurity`][gkos]
+    /// is called.
+    ///
+    /// [gkos]: https://learn.microsoft.com/en-us/windows/win32/api/securitybaseapi/nf-securitybaseapi-getkernelobjectsecurity
+    ///
+    Handle(HANDLE),
+}
+
+/// Wrapper for [`GetFileSecurityW`][gfs] and [`GetKernelObjectSecurity`][gkos].
+///
+/// The security descriptor is self-relative; the owner, group, and ACLs are stored after the
+/// header in the same buffer and located using offsets.  The returned [`FrozenBuffer`] owns the
+/// buffer so the descriptor is valid as long as it is alive.  Pass the pointer to the security
+/// functions as a [`PSECURITY_DESCRIPTOR`].  Do not copy the [`SECURITY_DESCRIPTOR`] out of the
+/// buffer; the header is smaller than a `SECURITY_DESCRIPTOR` on 64 bit Windows.
+///
+/// The buffer capacity is always a multiple of [`ALIGNMENT`](crate::ALIGNMENT) which satisfies
+/// the alignment needed by the descriptor.
+///
+/// # Arguments
+///
+/// * `object` - The file or kernel object.
+///
+/// * `requested_information` - The parts of the descriptor to read like
+///   `OWNER_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use std::path::Path;
+///
+/// use windows::Win32::Foundation::PSID;
+/// use windows::Win32::Security::{
+///     GetSecurityDescriptorOwner, DACL_SECURITY_INFORMATION, OWNER_SECURITY_INFORMATION,
+///     PSECURITY_DESCRIPTOR,
+/// };
+///
+/// use grob::{winapi_security_descriptor, SecurityObject};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = std::env::current_exe()?;
+///     let descriptor = winapi_security_descriptor(
+///         SecurityObject::File(Path::new(&path)),
+///         OWNER_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION,
+///     )?;
+///     if let Some(p) = descriptor.try_pointer()? {
+///         // The SID points into the buffer owned by descriptor.
+///         let mut owner = PSID::default();
+///         let mut defaulted = 0;
+///         unsafe {
+///             GetSecurityDescriptorOwner(
+///                 PSECURITY_DESCRIPTOR(p.cast_mut().cast()),
+///                 &mut owner,
+///                 &mut defaulted,
+///             )
+///         }
+///         .ok()?;
+///         println!("Owner SID at {:?}", owner);
+///     }
+///     println!("SDDL is {:?}", descriptor.to_sddl()?);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [gfs]: https://learn.microsoft.com/en-us/windows/win32/api/securitybaseapi/nf-securitybaseapi-getfilesecurityw
+/// [gkos]: https://learn.microsoft.com/en-us/windows/win32/api/securitybaseapi/nf-securitybaseapi-getkernelobjectsecurity
+///
+pub fn winapi_security_descriptor(
+    object: SecurityObject,
+    requested_information: OBJECT_SECURITY_INFORMATION,
+) -> Result<FrozenBuffer<'static, SECURITY_DESCRIPTOR>, std::io::Error> {
+    match object {
+        SecurityObject::File(path) => {
+            let path = WindowsPathString::new(path)?;
+            winapi_security_descriptor_with(|argument| {
+                let (pointer, size) = argument.pointer_and_size_mut();
+                let length = *size;
+                RvIsError::new(unsafe {
+                    GetFileSecurityW(
+                        path.as_param(),
+                        requested_information.0,
+                        PSECURITY_DESCRIPTOR(pointer.cast()),
+                        length,
+                        size,
+                    )
+                })
+            })
+        }
+        SecurityObject::Handle(handle) => winapi_security_descriptor_with(|argument| {
+            let (pointer, size) = argument.pointer_and_size_mut();
+            let length = *size;
+            RvIsError::new(unsafe {
+                GetKernelObjectSecurity(
+                    handle,
+                    requested_information.0,
+                    PSECURITY_DESCRIPTOR(pointer.cast()),
+                    length,
+                    size,
+                )
+            })
+        }),
+    }
+}
+
+/// [`winapi_security_descriptor`] with the operating system call made by `api_wrapper`.
+///
+/// Useful for other functions that return a self-relative security descriptor like
+/// [`GetUserObjectSecurity`][guos] and for testing.
+///
+/// [guos]: https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getuserobjectsecurity
+///
+pub fn winapi_security_descriptor_with<W, WR>(
+    api_wrapper: W,
+) -> Result<FrozenBuffer<'static, SECURITY_DESCRIPTOR>, std::io::Error>
+where
+    WR: ToResult,
+    W: FnMut(&mut Argument<*mut SECURITY_DESCRIPTOR>) -> WR,
+{
+    let grow_strategy = GrowToAlignment(default_strategy(
+        StrategyKind::SmallBinary,
+        GrowForSmallBinary::new(),
+    ));
+    frozen_with(&grow_strategy, SIZE_OF_SELF_RELATIVE, api_wrapper)
+}
+
+impl FrozenBuffer<'_, SECURITY_DESCRIPTOR> {
+    /// Convert the security descriptor to a [Security Descriptor Definition Language][sddl]
+    /// string using [`ConvertSecurityDescriptorToStringSecurityDescriptorW`][c].
+    ///
+    /// The owner, group, DACL, and SACL are included if present.  An empty buffer returns an
+    /// empty string.
+    ///
+    /// [sddl]: https://learn.microsoft.com/en-us/windows/win32/secauthz/security-descriptor-definition-language
+    /// [c]: https://learn.microsoft.com/en-us/windows/win32/api/sddl/nf-sddl-convertsecuritydescriptortostringsecuritydescriptorw
+    ///
+    pub fn to_sddl(&self) -> Result<OsString, std::io::Error> {
+        let pointer = match self.try_pointer()? {
+            Some(p) => p,
+            None => return Ok(OsString::new()),
+        };
+        let information = OWNER_SECURITY_INFORMATION
+            | GROUP_SECURITY_INFORMATION
+            | DACL_SECURITY_INFORMATION
+            | SACL_SECURITY_INFORMATION;
+        let mut sddl = PWSTR::null();
+        let rv = unsafe {
+            ConvertSecurityDescriptorToStringSecurityDescriptorW(
+                PSECURITY_DESCRIPTOR(pointer.cast_mut().cast()),
+                SDDL_REVISION_1,
+                information.0,
+                &mut sddl,
+                None,
+            )
+        };
+        if !rv.as_bool() {
+            return Err(win32_err(unsafe { GetLastError() }));
+        }
+        let rv = OsString::from_wide(unsafe { sddl.as_wide() });
+        // The string is allocated by the operating system.
+        let _ = unsafe { LocalFree(HLOCAL(sddl.0 as isize)) };
+        Ok(rv)
+    }
+}
+
+// Rounds the capacity up to a multiple of ALIGNMENT.
+struct GrowToAlignment<S>(S);
+
+impl<S> GrowStrategy for GrowToAlignment<S>
+where
+    S: GrowStrategy,
+{
+    fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+        let capacity = self.0.next_capacity(tries, desired_capacity);
+        let alignment = ALIGNMENT as u32;
+        capacity
+            .checked_next_multiple_of(alignment)
+            .unwrap_or(u32::MAX - u32::MAX % alignment)
+    }
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+    fn max_capacity(&self) -> Option<u32> {
+        self.0.max_capacity()
+    }
+    fn should_continue(&self, tries: usize) -> bool {
+        self.0.should_continue(tries)
+    }
+    fn initial_capacity(&self) -> Option<u32> {
+        let alignment = ALIGNMENT as u32;
+        self.0
+            .initial_capacity()?
+            .checked_next_multiple_of(alignment)
+    }
+}
@@ -13,10 +13,13 @@
 // limitations under the License.
 
 use std::marker::PhantomData;
+use std::sync::OnceLock;
+
+use windows::Win32::System::SystemInformation::{GetSystemInfo, SYSTEM_INFO};
 
 use crate::buffer::os::ALIGNMENT;
 use crate::traits::GrowStrategy;
-use crate::win::SIZE_OF_WCHAR;
+use crate::win::{const_wchars_to_bytes, SIZE_OF_WCHAR};
 
 /// Adjustments made by [GrowToNearestNibbleWithExtra] when calculating the next buffer capacity
 ///
@@ -58,6 +61,14 @@ impl<A: NearestNibbleAdjustments> GrowToNearestNibbleWithExtra<A> {
             phantom: PhantomData,
         }
     }
+    // Shared Debug output for the strategies built on this one.
+    fn fmt_as(&self, name: &str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(name)
+            .field("extra", &A::EXTRA)
+            .field("scale", &A::SCALE)
+            .field("floor", &A::FLOOR)
+            .finish()
+    }
 }
 
 impl<A: NearestNibbleAdjustments> GrowStrategy for GrowToNearestNibbleWithExtra<A> {
@@ -67,7 +78,7 @@ impl<A: NearestNibbleAdjustments> GrowStrategy for GrowToNearestNibbleWithExtra<
         let desired_capacity = desired_capacity as u64;
         // Determine the ceiling of the current number of nibbles.  Supports bumping to include
         // space for a NULL terminator (just in case of an API bug).
-        let bumped_nibbles = (desired_capacity + A::EXTRA + 15) / 16;
+        let bumped_nibbles = (desired_capacity + A::EXTRA).div_ceil(16);
         // Convert that to bytes optionally scaling
         let scaled_bytes = bumped_nibbles * 16 * A::SCALE;
         // Use the largest of the doubled value, desired_capacity, or the preconfigured floor.
@@ -110,6 +121,13 @@ impl NearestNibbleAdjustments for NoAdjustments {
 /// Favor the [`GrowForSmallBinary`] alias over using this strategy directly so your code can
 /// naturally take advantage of improvements.
 ///
+/// # Growth
+///
+/// Starting from a zero capacity buffer with the operating system needing 16, 100, then 1008 bytes
+/// (see [`simulate`])...
+///
+#[doc = include_str!("tables/grow-to-nearest-nibble.md")]
+///
 /// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/SystemInformation/fn.GetLogicalProcessorInformationEx.html
 ///
 pub struct GrowToNearestNibble {
@@ -130,6 +148,12 @@ impl Default for GrowToNearestNibble {
     }
 }
 
+impl std::fmt::Debug for GrowToNearestNibble {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt_as("GrowToNearestNibble", f)
+    }
+}
+
 impl GrowStrategy for GrowToNearestNibble {
     fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
         self.inner.next_capacity(tries, desired_capacity)
@@ -150,7 +174,7 @@ pub type GrowForSmallBinary = GrowToNearestNibble;
 struct AdjustForNull {}
 
 impl NearestNibbleAdjustments for AdjustForNull {
-    const EXTRA: u64 = SIZE_OF_WCHAR as u64;
+    const EXTRA: u64 = const_wchars_to_bytes(1) as u64;
     const SCALE: u64 = 1;
     const FLOOR: u64 = 0;
 }
@@ -177,6 +201,13 @@ impl NearestNibbleAdjustments for AdjustForNull {
 /// Favor the [`GrowForStaticText`] alias over using this strategy directly so your code can
 /// naturally take advantage of improvements.
 ///
+/// # Growth
+///
+/// Starting from a zero capacity buffer with the operating system needing 16, 100, then 1008 bytes
+/// (see [`simulate`])...
+///
+#[doc = include_str!("tables/grow-to-nearest-nibble-with-null.md")]
+///
 /// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/WindowsProgramming/fn.GetUserNameW.html
 ///
 pub struct GrowToNearestNibbleWithNull {
@@ -197,6 +228,12 @@ impl Default for GrowToNearestNibbleWithNull {
     }
 }
 
+impl std::fmt::Debug for GrowToNearestNibbleWithNull {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt_as("GrowToNearestNibbleWithNull", f)
+    }
+}
+
 impl GrowStrategy for GrowToNearestNibbleWithNull {
     fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
         self.inner.next_capacity(tries, desired_capacity)
@@ -211,13 +248,93 @@ impl GrowStrategy for GrowToNearestNibbleWithNull {
 ///
 pub type GrowForStaticText = GrowToNearestNibbleWithNull;
 
+/// A [NearestNibbleAdjustments] that rounds the `desired_capacity` up to the next higher value
+/// evenly divisible by 16 after adding space for two `NULL` terminators.
+///
+struct AdjustForDoubleNull {}
+
+impl NearestNibbleAdjustments for AdjustForDoubleNull {
+    const EXTRA: u64 = const_wchars_to_bytes(2) as u64;
+    const SCALE: u64 = 1;
+    const FLOOR: u64 = 0;
+}
+
+/// [`GrowStrategy`] appropriate for Windows API calls that return a list of strings terminated by
+/// two `NULL`s (a `REG_MULTI_SZ` style list).
+///
+/// This [`GrowStrategy`] works best when the operating system indicates the buffer size needed
+/// (`desired_capacity` is known), that size is unlikely to change, and the buffer size is
+/// relatively small.
+///
+/// This [`GrowStrategy`] rounds the buffer size to the next higher value that's evenly divisible by
+/// 16 after adding space for two `NULL` terminators.
+///
+/// The goals are:
+///
+///   * Be heap friendly by avoiding many small odd sized heap allocations
+///   * Avoid any operating system bugs involving the buffer size requested being incorrect because
+///     the final `NULL`s are not considered (some builds under-report an empty list by one)
+///   * For the API call to be successful after at most two attempts
+///
+/// [`QueryDosDeviceW`][1] is a good example for this [`GrowStrategy`].
+///
+/// Favor the [`GrowForMultiSz`] alias over using this strategy directly so your code can
+/// naturally take advantage of improvements.
+///
+/// # Growth
+///
+/// Starting from a zero capacity buffer with the operating system needing 14, 110, then 1006 bytes
+/// (see [`simulate`])...
+///
+#[doc = include_str!("tables/grow-to-nearest-nibble-with-double-null.md")]
+///
+/// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/Storage/FileSystem/fn.QueryDosDeviceW.html
+///
+pub struct GrowToNearestNibbleWithDoubleNull {
+    inner: GrowToNearestNibbleWithExtra<AdjustForDoubleNull>,
+}
+
+impl GrowToNearestNibbleWithDoubleNull {
+    pub fn new() -> Self {
+        Self {
+            inner: GrowToNearestNibbleWithExtra::new(),
+        }
+    }
+}
+
+impl Default for GrowToNearestNibbleWithDoubleNull {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for GrowToNearestNibbleWithDoubleNull {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt_as("GrowToNearestNibbleWithDoubleNull", f)
+    }
+}
+
+impl GrowStrategy for GrowToNearestNibbleWithDoubleNull {
+    fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+        self.inner.next_capacity(tries, desired_capacity)
+    }
+}
+
+/// Alias for the [`GrowToNearestNibbleWithDoubleNull`] [`GrowStrategy`].
+///
+/// The [`GrowForMultiSz`] alias should be favored over using [`GrowToNearestNibbleWithDoubleNull`]
+/// directly.  Future versions may change the strategy for lists of strings.  By using this alias
+/// your code will naturally take advantage of improvements.
+///
+pub type GrowForMultiSz = GrowToNearestNibbleWithDoubleNull;
+
 /// A [NearestNibbleAdjustments] that rounds the `current_size` up to the next higher value evenly
 /// divisible by 16 after adding space for a `NULL` terminator.  The target is that value doubled.
 ///
 struct DoublePlusNull<const FLOOR: u64> {}
 
 impl<const FLOOR: u64> NearestNibbleAdjustments for DoublePlusNull<FLOOR> {
-    const EXTRA: u64 = SIZE_OF_WCHAR as u64;
+    const EXTRA: u64 = const_wchars_to_bytes(1) as u64;
     const SCALE: u64 = 2;
     const FLOOR: u64 = FLOOR;
 }
@@ -243,6 +360,13 @@ impl<const FLOOR: u64> NearestNibbleAdjustments for DoublePlusNull<FLOOR> {
 /// Favor the [`GrowForStoredIsReturned`] alias over using this strategy directly so your code can
 /// naturally take advantage of improvements.
 ///
+/// # Growth
+///
+/// Starting from a zero capacity buffer with a `FLOOR` of zero and the operating system storing
+/// UTF-16 text that needs 600 bytes (see [`simulate_with`])...
+///
+#[doc = include_str!("tables/grow-by-double-with-null.md")]
+///
 /// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/LibraryLoader/fn.GetModuleFileNameW.html
 ///
 pub struct GrowByDoubleWithNull<const FLOOR: u64> {
@@ -263,10 +387,23 @@ impl<const FLOOR: u64> Default for GrowByDoubleWithNull<FLOOR> {
     }
 }
 
+impl<const FLOOR: u64> std::fmt::Debug for GrowByDoubleWithNull<FLOOR> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt_as("GrowByDoubleWithNull", f)
+    }
+}
+
 impl<const FLOOR: u64> GrowStrategy for GrowByDoubleWithNull<FLOOR> {
     fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
         self.inner.next_capacity(tries, desired_capacity)
     }
+    fn initial_capacity(&self) -> Option<u32> {
+        // Without a floor the first call learns the size needed.
+        match FLOOR {
+            0 => None,
+            floor => Some(floor.min(u32::MAX as u64) as u32),
+        }
+    }
 }
 
 /// Alias for the [`GrowByDoubleWithNull`] [`GrowStrategy`].
@@ -288,6 +425,11 @@ pub type GrowForStoredIsReturned<const FLOOR: u64> = GrowByDoubleWithNull<FLOOR>
 /// This [`GrowStrategy`] rounds the buffer size to the next higher value that's evenly divisible by
 /// 256 after adding space for alignment.
 ///
+/// `FLOOR` is an optional minimum value.  If not zero, the buffer capacity is never below this
+/// value.  A non-zero `FLOOR` lets a small (or zero-sized) initial buffer jump straight to a
+/// reasonably sized heap buffer.  `new` constructs the strategy without a floor;
+/// `with_const_floor` constructs the strategy with the floor given by `FLOOR`.
+///
 /// The goals are:
 ///
 ///   * Be heap friendly by avoiding small odd sized heap allocations
@@ -296,30 +438,679 @@ pub type GrowForStoredIsReturned<const FLOOR: u64> = GrowByDoubleWithNull<FLOOR>
 ///
 /// [`GetAdaptersAddresses`][1] is a good example for this [`GrowStrategy`].
 ///
+/// # Growth
+///
+/// Starting from a zero capacity buffer with a `FLOOR` of zero and the operating system needing
+/// 1000 bytes then 1300 bytes (see [`simulate`])...
+///
+#[doc = include_str!("tables/grow-to-nearest-quarter-kibi.md")]
+///
 /// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/NetworkManagement/IpHelper/fn.GetAdaptersAddresses.html
 ///
-pub struct GrowToNearestQuarterKibi {}
+pub struct GrowToNearestQuarterKibi<const FLOOR: u64 = 0> {}
 
 impl GrowToNearestQuarterKibi {
+    /// Create a [`GrowToNearestQuarterKibi`] without a floor.
     pub fn new() -> Self {
         Self {}
     }
 }
 
-impl Default for GrowToNearestQuarterKibi {
+impl<const FLOOR: u64> GrowToNearestQuarterKibi<FLOOR> {
+    /// Create a [`GrowToNearestQuarterKibi`] whose capacity is never below the `FLOOR` const
+    /// parameter; for example, `GrowToNearestQuarterKibi::<65536>::with_const_floor()`.
+    pub fn with_const_floor() -> Self {
+        Self {}
+    }
+}
+
+impl<const FLOOR: u64> Default for GrowToNearestQuarterKibi<FLOOR> {
     fn default() -> Self {
-        Self::new()
+        Self::with_const_floor()
+    }
+}
+
+impl<const FLOOR: u64> std::fmt::Debug for GrowToNearestQuarterKibi<FLOOR> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrowToNearestQuarterKibi")
+            .field("floor", &FLOOR)
+            .finish()
     }
 }
 
-impl GrowStrategy for GrowToNearestQuarterKibi {
+impl<const FLOOR: u64> GrowStrategy for GrowToNearestQuarterKibi<FLOOR> {
     fn next_capacity(&self, _tries: usize, desired_capacity: u32) -> u32 {
         // With desired_capacity a u32, doing the math with u64 prevents all overlow possibilities.
         // Determine the ceiling of the current number of quarter kibis plus some for alignment.
         let quarter_kibis = (desired_capacity as u64 + 255 + ALIGNMENT as u64) / 256;
         // Convert to bytes
         let bytes = quarter_kibis * 256;
+        // Use the larger of the rounded value or the preconfigured floor.  Limit the target to a
+        // value that fits in a u32.
+        bytes.max(FLOOR).min(u32::MAX as u64) as u32
+    }
+}
+
+/// [`GrowStrategy`] that adds a fixed number of bytes, `STEP`, to the capacity needed.
+///
+/// When the operating system indicates the buffer size needed, like [`RegQueryValueExW`][1], the
+/// extra `STEP` bytes absorb a value that grows a little between calls.  When the operating
+/// system only fills the buffer, the capacity grows linearly by `STEP` bytes per try.  For
+/// values that are usually small, a linear bump converges faster than doubling without
+/// overshooting.
+///
+/// # Examples
+///
+/// ```
+/// use grob::{GrowByFixedStep, GrowStrategy};
+///
+/// let grow_strategy = GrowByFixedStep::<256>::new();
+/// assert!(grow_strategy.next_capacity(1, 100) == 356);
+/// assert!(grow_strategy.next_capacity(2, 356) == 612);
+/// ```
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regqueryvalueexw
+///
+pub struct GrowByFixedStep<const STEP: u64> {}
+
+impl<const STEP: u64> GrowByFixedStep<STEP> {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl<const STEP: u64> Default for GrowByFixedStep<STEP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const STEP: u64> std::fmt::Debug for GrowByFixedStep<STEP> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrowByFixedStep")
+            .field("step", &STEP)
+            .finish()
+    }
+}
+
+impl<const STEP: u64> GrowStrategy for GrowByFixedStep<STEP> {
+    fn next_capacity(&self, _tries: usize, desired_capacity: u32) -> u32 {
+        // With desired_capacity a u32, doing the math with u64 prevents all overlow possibilities.
+        let bytes = (desired_capacity as u64).saturating_add(STEP);
         // Limit the target to a value that fits in a u32.
         bytes.min(u32::MAX as u64) as u32
     }
 }
+
+// The page size used when the operating system does not provide one.
+const FALLBACK_PAGE_SIZE: u32 = 4096;
+
+static PAGE_SIZE: OnceLock<u32> = OnceLock::new();
+
+/// [`GrowStrategy`] that rounds the capacity needed up to a whole number of system pages.
+///
+/// Output buffers for functions like [`DeviceIoControl`][1] are naturally measured in pages.  The
+/// page size is queried once using [`GetSystemInfo`][2].  Heap buffers are always aligned so no
+/// space is added for alignment; a capacity needed that is already a whole number of pages is
+/// used as-is.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use grob::{GrowStrategy, GrowToNearestPage};
+///
+/// fn main() {
+///     let page_size = GrowToNearestPage::page_size();
+///     let grow_strategy = GrowToNearestPage::new();
+///     assert!(grow_strategy.next_capacity(1, page_size) == page_size);
+///     assert!(grow_strategy.next_capacity(1, page_size + 1) == 2 * page_size);
+/// }
+/// # }
+/// ```
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/ioapiset/nf-ioapiset-deviceiocontrol
+/// [2]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getsysteminfo
+///
+#[derive(Debug, Default)]
+pub struct GrowToNearestPage {}
+
+impl GrowToNearestPage {
+    pub fn new() -> Self {
+        Self {}
+    }
+    /// Returns the system page size, in bytes, used for rounding.
+    pub fn page_size() -> u32 {
+        *PAGE_SIZE.get_or_init(|| {
+            let mut system_info = SYSTEM_INFO::default();
+            unsafe { GetSystemInfo(&mut system_info) };
+            match system_info.dwPageSize {
+                0 => FALLBACK_PAGE_SIZE,
+                page_size => page_size,
+            }
+        })
+    }
+}
+
+impl GrowStrategy for GrowToNearestPage {
+    fn next_capacity(&self, _tries: usize, desired_capacity: u32) -> u32 {
+        let page_size = Self::page_size() as u64;
+        // With desired_capacity a u32, doing the math with u64 prevents all overlow possibilities.
+        let pages = (desired_capacity as u64).div_ceil(page_size).max(1);
+        let bytes = pages * page_size;
+        // Limit the target to the largest whole number of pages that fits in a u32.
+        bytes.min(u32::MAX as u64 / page_size * page_size) as u32
+    }
+}
+
+/// [`GrowStrategy`] that calls a closure to determine the next capacity.
+///
+/// The closure is passed the same `tries` and `desired_capacity` as
+/// [`next_capacity`](GrowStrategy::next_capacity) and returns the next capacity.  It has to
+/// follow the same contract.  This avoids a struct and an `impl GrowStrategy` for a one-line
+/// policy.
+///
+/// # Examples
+///
+/// The `PrintNextCapacity` wrapper from the [`GrowStrategy`] documentation in three lines...
+///
+/// ```
+/// use grob::{GrowStrategy, GrowToNearestQuarterKibi, GrowWith};
+///
+/// let inner = GrowToNearestQuarterKibi::new();
+/// let grow_strategy = GrowWith::new(|tries, desired_capacity| {
+///     let rv = inner.next_capacity(tries, desired_capacity);
+///     println!("next_capacity(tries={}, desired_capacity={}) = {}", tries, desired_capacity, rv);
+///     rv
+/// });
+/// assert!(grow_strategy.next_capacity(1, 1000) == 1024);
+///
+/// // Exponential growth with the capacity needed as the floor
+/// let grow_strategy = GrowWith::new(|tries, want: u32| want.max(1 << (10 + tries)));
+/// assert!(grow_strategy.next_capacity(1, 100) == 2048);
+/// ```
+///
+pub struct GrowWith<F>(F);
+
+impl<F> GrowWith<F>
+where
+    F: Fn(usize, u32) -> u32,
+{
+    pub fn new(f: F) -> Self {
+        Self(f)
+    }
+}
+
+impl<F> std::fmt::Debug for GrowWith<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("GrowWith")
+    }
+}
+
+impl<F> GrowStrategy for GrowWith<F>
+where
+    F: Fn(usize, u32) -> u32,
+{
+    fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+        (self.0)(tries, desired_capacity)
+    }
+    fn name(&self) -> &'static str {
+        "GrowWith"
+    }
+}
+
+/// A buffer capacity chosen by the [`GrowStrategy`] wrapped in an [`InstrumentedGrowStrategy`] or
+/// a `RecordingGrowStrategy` from the `testing` module.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GrowEvent {
+    /// The number of times the operating system call has been attempted.
+    pub tries: usize,
+    /// The `desired_capacity`, in bytes, passed to [`next_capacity`](GrowStrategy::next_capacity).
+    pub desired: u32,
+    /// The capacity, in bytes, returned from [`next_capacity`](GrowStrategy::next_capacity).
+    pub chosen: u32,
+}
+
+/// Wraps another [`GrowStrategy`] and reports every capacity it chooses to a callback.
+///
+/// This is the `PrintNextCapacity` wrapper from the [`GrowStrategy`] documentation.  `callback`
+/// is called with a [`GrowEvent`] each time the buffer grows.  The callback does not have to be
+/// `'static` so it can, for example, record the events in a local [`Vec`] or forward them to a
+/// logger.  With the `tracing` feature, `traced` emits the events as `tracing` `debug` events
+/// instead.
+///
+/// # Examples
+///
+/// ```
+/// use std::cell::RefCell;
+///
+/// use grob::{GrowStrategy, GrowToNearestQuarterKibi, InstrumentedGrowStrategy};
+///
+/// let events = RefCell::new(Vec::new());
+/// let grow_strategy = InstrumentedGrowStrategy::new(GrowToNearestQuarterKibi::new(), |event| {
+///     events.borrow_mut().push(event)
+/// });
+/// let _ = grow_strategy.next_capacity(1, 1000);
+/// assert!(events.borrow()[0].chosen == 1024);
+/// ```
+///
+pub struct InstrumentedGrowStrategy<S, F> {
+    inner: S,
+    callback: F,
+}
+
+impl<S, F> InstrumentedGrowStrategy<S, F>
+where
+    S: GrowStrategy,
+    F: Fn(GrowEvent),
+{
+    /// Create an [`InstrumentedGrowStrategy`] that calls `callback` with each [`GrowEvent`].
+    pub fn new(inner: S, callback: F) -> Self {
+        Self { inner, callback }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<S> InstrumentedGrowStrategy<S, fn(GrowEvent)>
+where
+    S: GrowStrategy,
+{
+    /// Create an [`InstrumentedGrowStrategy`] that emits each [`GrowEvent`] as a
+    /// [`tracing::debug!`] event with a target of `grob`.
+    ///
+    pub fn traced(inner: S) -> Self {
+        Self::new(inner, trace_grow_event)
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn trace_grow_event(event: GrowEvent) {
+    tracing::debug!(
+        target: "grob",
+        tries = event.tries,
+        desired = event.desired,
+        chosen = event.chosen,
+        "buffer grown"
+    );
+}
+
+impl<S, F> std::fmt::Debug for InstrumentedGrowStrategy<S, F>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InstrumentedGrowStrategy")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, F> GrowStrategy for InstrumentedGrowStrategy<S, F>
+where
+    S: GrowStrategy,
+    F: Fn(GrowEvent),
+{
+    fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+        let chosen = self.inner.next_capacity(tries, desired_capacity);
+        (self.callback)(GrowEvent {
+            tries,
+            desired: desired_capacity,
+            chosen,
+        });
+        chosen
+    }
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+    fn max_capacity(&self) -> Option<u32> {
+        self.inner.max_capacity()
+    }
+    fn should_continue(&self, tries: usize) -> bool {
+        self.inner.should_continue(tries)
+    }
+    fn initial_capacity(&self) -> Option<u32> {
+        self.inner.initial_capacity()
+    }
+}
+
+/// Wraps another [`GrowStrategy`] and limits the buffer capacity to `max_capacity` bytes.
+///
+/// A size reported by a buggy driver or an API hooking product can be absurdly large.
+/// [`GrowWithCap`] ends the call loop with [`GrowError::CapacityLimitExceeded`][cle], which is
+/// converted to a [`std::io::Error`] with a kind of [`OutOfMemory`](std::io::ErrorKind::OutOfMemory),
+/// as soon as more than `max_capacity` bytes are needed.  Capacities chosen by the wrapped
+/// strategy are clamped to `max_capacity`.
+///
+/// # Examples
+///
+/// ```
+/// use grob::{GrowStrategy, GrowToNearestQuarterKibi, GrowWithCap};
+///
+/// // Never allocate more than 16 MiB
+/// let grow_strategy = GrowWithCap::new(GrowToNearestQuarterKibi::new(), 16 * 1024 * 1024);
+/// assert!(grow_strategy.max_capacity() == Some(16 * 1024 * 1024));
+/// assert!(grow_strategy.next_capacity(1, 1000) == 1024);
+/// ```
+///
+/// [cle]: crate::GrowError::CapacityLimitExceeded
+///
+#[derive(Debug)]
+pub struct GrowWithCap<S> {
+    inner: S,
+    max_capacity: u32,
+}
+
+impl<S> GrowWithCap<S>
+where
+    S: GrowStrategy,
+{
+    pub fn new(inner: S, max_capacity: u32) -> Self {
+        Self {
+            inner,
+            max_capacity,
+        }
+    }
+}
+
+impl<S> GrowStrategy for GrowWithCap<S>
+where
+    S: GrowStrategy,
+{
+    fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+        self.inner
+            .next_capacity(tries, desired_capacity)
+            .min(self.max_capacity)
+    }
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+    fn max_capacity(&self) -> Option<u32> {
+        // The tighter of the two limits wins.
+        match self.inner.max_capacity() {
+            Some(inner) => Some(inner.min(self.max_capacity)),
+            None => Some(self.max_capacity),
+        }
+    }
+    fn should_continue(&self, tries: usize) -> bool {
+        self.inner.should_continue(tries)
+    }
+    fn initial_capacity(&self) -> Option<u32> {
+        Some(self.inner.initial_capacity()?.min(self.max_capacity))
+    }
+}
+
+/// Wraps another [`GrowStrategy`] and adds headroom once the size needed has changed between calls.
+///
+/// Functions like [`GetTcpTable2`][1] and [`GetAdaptersAddresses`][2] return a table that can grow
+/// between the call that reports the size needed and the call that fills the buffer.  Under load
+/// even an exact strategy can need a third or fourth try.  The first time the buffer grows the
+/// capacity chosen by the wrapped strategy is used as is.  From the second try on it is multiplied
+/// by `percent` / 100 (rounded up) to leave room for the table to grow again.  The result is never
+/// less than the capacity chosen by the wrapped strategy and never more than its
+/// [`max_capacity`](GrowStrategy::max_capacity) or [`u32::MAX`].
+///
+/// # Examples
+///
+/// ```
+/// use grob::{GrowForSmallBinary, GrowStrategy, GrowWithHeadroom};
+///
+/// let grow_strategy = GrowWithHeadroom::new(GrowForSmallBinary::new(), 110);
+/// // The first grow trusts the size reported.
+/// assert!(grow_strategy.next_capacity(1, 1000) == 1008);
+/// // The table changed size so leave 10% headroom.
+/// assert!(grow_strategy.next_capacity(2, 1000) == 1109);
+/// ```
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/iphlpapi/nf-iphlpapi-gettcptable2
+/// [2]: https://learn.microsoft.com/en-us/windows/win32/api/iphlpapi/nf-iphlpapi-getadaptersaddresses
+///
+#[derive(Debug)]
+pub struct GrowWithHeadroom<S> {
+    inner: S,
+    percent: u32,
+}
+
+impl<S> GrowWithHeadroom<S>
+where
+    S: GrowStrategy,
+{
+    pub fn new(inner: S, percent: u32) -> Self {
+        Self { inner, percent }
+    }
+}
+
+impl<S> GrowStrategy for GrowWithHeadroom<S>
+where
+    S: GrowStrategy,
+{
+    fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+        let chosen = self.inner.next_capacity(tries, desired_capacity);
+        if tries < 2 {
+            return chosen;
+        }
+        // Doing the math with u64 and saturating prevents all overflow possibilities.
+        let with_headroom = (chosen as u64)
+            .saturating_mul(self.percent as u64)
+            .div_ceil(100)
+            .max(chosen as u64);
+        let limit = self.inner.max_capacity().unwrap_or(u32::MAX).max(chosen);
+        with_headroom.min(limit as u64) as u32
+    }
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+    fn max_capacity(&self) -> Option<u32> {
+        self.inner.max_capacity()
+    }
+    fn should_continue(&self, tries: usize) -> bool {
+        self.inner.should_continue(tries)
+    }
+    fn initial_capacity(&self) -> Option<u32> {
+        self.inner.initial_capacity()
+    }
+}
+
+/// Wraps another [`GrowStrategy`] and gives up after `n` operating system calls.
+///
+/// [`MaxTries`] behaves like the wrapped strategy for the first `n - 1` grows.  Instead of growing
+/// after the `n`th call, the call loop ends with [`GrowError::StrategyGaveUp`][sgu] which names the
+/// wrapped strategy.  Unlike [`max_tries`][mt] on the builder, the limit travels with the strategy
+/// so it works with any function that takes a [`GrowStrategy`].
+///
+/// # Examples
+///
+/// ```
+/// use grob::{GrowStrategy, GrowToNearestQuarterKibi, MaxTries};
+///
+/// let grow_strategy = MaxTries::new(3, GrowToNearestQuarterKibi::new());
+/// assert!(grow_strategy.should_continue(2));
+/// assert!(!grow_strategy.should_continue(3));
+/// ```
+///
+/// [sgu]: crate::GrowError::StrategyGaveUp
+/// [mt]: crate::GrobBuilder::max_tries
+///
+#[derive(Debug)]
+pub struct MaxTries<S>(usize, S);
+
+impl<S> MaxTries<S>
+where
+    S: GrowStrategy,
+{
+    pub fn new(n: usize, inner: S) -> Self {
+        Self(n, inner)
+    }
+}
+
+impl<S> GrowStrategy for MaxTries<S>
+where
+    S: GrowStrategy,
+{
+    fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+        self.1.next_capacity(tries, desired_capacity)
+    }
+    fn name(&self) -> &'static str {
+        self.1.name()
+    }
+    fn max_capacity(&self) -> Option<u32> {
+        self.1.max_capacity()
+    }
+    fn should_continue(&self, tries: usize) -> bool {
+        tries < self.0 && self.1.should_continue(tries)
+    }
+    fn initial_capacity(&self) -> Option<u32> {
+        self.1.initial_capacity()
+    }
+}
+
+// Reserves room for one more WCHAR than requested for functions that report one less than they
+// need.
+pub(crate) struct GrowWithSpareWchar<S>(pub(crate) S);
+
+impl<S> GrowStrategy for GrowWithSpareWchar<S>
+where
+    S: GrowStrategy,
+{
+    fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+        self.0
+            .next_capacity(tries, desired_capacity.saturating_add(SIZE_OF_WCHAR))
+    }
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+    fn max_capacity(&self) -> Option<u32> {
+        self.0.max_capacity()
+    }
+    fn should_continue(&self, tries: usize) -> bool {
+        self.0.should_continue(tries)
+    }
+    fn initial_capacity(&self) -> Option<u32> {
+        self.0.initial_capacity()
+    }
+}
+
+/// How the operating system reports the buffer size needed to [`simulate_with`].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimHandler {
+    /// The buffer size needed is returned, like [`RvIsError`][e].
+    ///
+    /// [e]: crate::RvIsError
+    ///
+    NeededIsReturned,
+    /// The number of elements stored is returned, like [`RvIsSize`][s].  The size needed is
+    /// guessed by doubling the current capacity.  `element_size` is the size, in bytes, of each
+    /// element (e.g. 2 for UTF-16 text).
+    ///
+    /// [s]: crate::RvIsSize
+    ///
+    StoredIsReturned { element_size: u32 },
+}
+
+/// One grow step from a simulation.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimStep {
+    /// The `tries` value passed to [`GrowStrategy::next_capacity`].
+    pub try_n: usize,
+    /// The `desired_capacity` passed to [`GrowStrategy::next_capacity`].
+    pub desired: u32,
+    /// The capacity returned from [`GrowStrategy::next_capacity`].
+    pub chosen: u32,
+}
+
+/// Dry-run a [`GrowStrategy`] starting with a zero capacity buffer against operating system calls
+/// that return the buffer size needed.
+///
+/// See [`simulate_with`].
+///
+pub fn simulate(strategy: &dyn GrowStrategy, demands: &[u32]) -> Vec<SimStep> {
+    simulate_with(strategy, SimHandler::NeededIsReturned, 0, demands)
+}
+
+/// Dry-run a [`GrowStrategy`] without making any operating system calls or allocations.
+///
+/// Each entry in `demands` is the number of bytes the operating system needs on one call.  The
+/// first call is made with `initial_capacity` or, when that is zero, the strategy's
+/// [`initial_capacity`](GrowStrategy::initial_capacity).  The same bookkeeping as the real loop
+/// is applied after each call: the `handler` determines the desired capacity and, if the buffer
+/// has to grow, the strategy chooses the next capacity.  The simulation stops when a call succeeds, the demands
+/// are used up, or the strategy does not grow the buffer.
+///
+/// # Examples
+///
+/// ```
+/// use grob::{simulate, GrowToNearestQuarterKibi, SimStep};
+///
+/// let steps = simulate(&GrowToNearestQuarterKibi::new(), &[1000, 1300, 1300]);
+/// assert!(steps.len() == 2);
+/// assert!(steps[1] == SimStep { try_n: 2, desired: 1300, chosen: 1536 });
+/// ```
+///
+pub fn simulate_with(
+    strategy: &dyn GrowStrategy,
+    handler: SimHandler,
+    initial_capacity: u32,
+    demands: &[u32],
+) -> Vec<SimStep> {
+    let mut steps = Vec::new();
+    let mut capacity = match (initial_capacity, strategy.initial_capacity()) {
+        (0, Some(primed)) => primed,
+        _ => initial_capacity,
+    };
+    for &demand in demands {
+        let desired = match handler {
+            SimHandler::NeededIsReturned => {
+                if demand <= capacity {
+                    break;
+                }
+                demand
+            }
+            SimHandler::StoredIsReturned { element_size } => {
+                // There has to be room for the terminator.
+                if demand == 0 || demand < capacity {
+                    break;
+                }
+                if capacity == 0 {
+                    element_size
+                } else {
+                    capacity.saturating_mul(2)
+                }
+            }
+        };
+        // The real loop only grows when the desired capacity is larger.
+        if desired <= capacity {
+            continue;
+        }
+        let try_n = steps.len() + 1;
+        let chosen = strategy.next_capacity(try_n, desired);
+        steps.push(SimStep {
+            try_n,
+            desired,
+            chosen,
+        });
+        if chosen <= capacity {
+            break;
+        }
+        capacity = chosen;
+    }
+    steps
+}
+
+/// Render the steps from a simulation as a Markdown table.
+///
+/// The tables in the documentation for the built-in strategies are rendered with this function.
+///
+pub fn markdown_table(steps: &[SimStep]) -> String {
+    let mut rv = String::from("| Try | Desired | Chosen |\n| --- | ------- | ------ |\n");
+    for step in steps {
+        rv.push_str(&format!(
+            "| {:>3} | {:>7} | {:>6} |\n",
+            step.try_n, step.desired, step.chosen
+        ));
+    }
+    rv
+}
@@ -0,0 +1,427 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Scaffolding for testing call loops without calling the operating system.
+//!
+//! Enabled with the `testing` feature.  [`MockApi`] stands in for a Windows API function.  It
+//! follows a script of responses and records what it observed on each call.
+//! [`RecordingGrowStrategy`] records every capacity a [`GrowStrategy`] chooses.
+//!
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use windows::Win32::Foundation::{
+    SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, FALSE, NO_ERROR, TRUE, WIN32_ERROR,
+};
+
+use crate::traits::RawToInternal;
+use crate::{Argument, GrowEvent, GrowStrategy, RvIsError, RvIsRequiredBytes, RvIsSize};
+
+enum Step {
+    Needs(u32),
+    Writes(Vec<u8>),
+    Fails(WIN32_ERROR),
+}
+
+struct Response {
+    step: Step,
+    last_error: Option<WIN32_ERROR>,
+}
+
+/// What a [`MockApi`] observed when it was called.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Observation {
+    /// Capacity, in bytes, of the buffer provided through the [`Argument`].
+    pub capacity: u32,
+    /// Address of the buffer provided through the [`Argument`].
+    pub pointer: *const u8,
+}
+
+/// A programmable stand-in for a Windows API function.
+///
+/// A [`MockApi`] is built from a script of responses.  Each call consumes one response.
+///
+/// - [`needs`](MockApi::needs) - Report that the buffer is too small and that `bytes` are needed.
+/// - [`writes`](MockApi::writes) - Write a payload and report success.  If the payload does not
+///   fit, report that the buffer is too small.  The response is not consumed until the payload
+///   fits.
+/// - [`fails`](MockApi::fails) - Report an error.
+///
+/// [`with_last_error`](MockApi::with_last_error) changes the value returned from `GetLastError`
+/// for the previous response.
+///
+/// The script is run by calling [`call_with_error_rv`](MockApi::call_with_error_rv), which mimics
+/// functions like [`GetAdaptersAddresses`][1] that take a pointer to the buffer size, or
+/// [`call_with_size_rv`](MockApi::call_with_size_rv), which mimics functions like
+/// [`GetModuleFileNameW`][2] that return the number of elements stored, or
+/// [`call_with_required_bytes_rv`](MockApi::call_with_required_bytes_rv), which mimics functions
+/// like [`GetSystemFirmwareTable`][3] that return the number of bytes required.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use grob::testing::MockApi;
+/// use grob::winapi_small_binary;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut mock_api = MockApi::new().needs(300).writes([42; 300]);
+///     let size = winapi_small_binary(
+///         |argument| mock_api.call_with_error_rv(argument),
+///         |frozen_buffer: grob::FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+///     )?;
+///     assert!(size == 300);
+///     assert!(mock_api.observations().len() == 2);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/iphlpapi/nf-iphlpapi-getadaptersaddresses
+/// [2]: https://learn.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-getmodulefilenamew
+/// [3]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getsystemfirmwaretable
+///
+#[derive(Default)]
+pub struct MockApi {
+    script: VecDeque<Response>,
+    observations: Vec<Observation>,
+}
+
+impl MockApi {
+    /// Create a [`MockApi`] with an empty script.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a response that reports the buffer is too small and `bytes` are needed.
+    ///
+    /// [`call_with_size_rv`](MockApi::call_with_size_rv) cannot report the size needed so it
+    /// reports a full buffer instead.
+    ///
+    pub fn needs(self, bytes: u32) -> Self {
+        self.push(Step::Needs(bytes))
+    }
+    /// Add a response that writes `payload` into the buffer and reports success.
+    pub fn writes<P>(self, payload: P) -> Self
+    where
+        P: Into<Vec<u8>>,
+    {
+        self.push(Step::Writes(payload.into()))
+    }
+    /// Add a response that writes `payload`, a UTF-16 string, into the buffer and reports success.
+    pub fn writes_wide(self, payload: &[u16]) -> Self {
+        let bytes = payload
+            .iter()
+            .flat_map(|c| c.to_ne_bytes())
+            .collect::<Vec<u8>>();
+        self.writes(bytes)
+    }
+    /// Add a response that reports the error `code`.
+    pub fn fails(self, code: WIN32_ERROR) -> Self {
+        self.push(Step::Fails(code))
+    }
+    /// Set the value returned from `GetLastError` for the previous response.
+    ///
+    /// The value is ignored for [`fails`](MockApi::fails) responses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the script is empty.
+    ///
+    pub fn with_last_error(mut self, code: WIN32_ERROR) -> Self {
+        let response = self
+            .script
+            .back_mut()
+            .expect("with_last_error needs a response to modify");
+        response.last_error = Some(code);
+        self
+    }
+    /// Returns what was observed on each call.
+    pub fn observations(&self) -> &[Observation] {
+        &self.observations
+    }
+    /// Returns `true` if every response in the script has been used.
+    pub fn is_finished(&self) -> bool {
+        self.script.is_empty()
+    }
+    /// Mimic a Windows API function that takes a pointer to the buffer size and returns a
+    /// [`BOOL`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the script has been used up.
+    ///
+    pub fn call_with_error_rv<IT>(&mut self, argument: &mut Argument<IT>) -> RvIsError
+    where
+        IT: RawToInternal,
+    {
+        let (pointer, capacity) = self.observe(argument);
+        let response = self.script.front().expect("the MockApi script is used up");
+        let (rv, size, last_error, consume) = match &response.step {
+            Step::Needs(bytes) => (
+                FALSE,
+                IT::capacity_to_size(*bytes),
+                response.last_error.unwrap_or(ERROR_INSUFFICIENT_BUFFER),
+                true,
+            ),
+            Step::Writes(payload) => {
+                let length: u32 = payload.len().try_into().unwrap();
+                if length <= capacity {
+                    unsafe { std::ptr::copy(payload.as_ptr(), pointer, payload.len()) };
+                    let last_error = response.last_error.unwrap_or(NO_ERROR);
+                    (TRUE, IT::capacity_to_size(length), last_error, true)
+                } else {
+                    let size = IT::capacity_to_size(length);
+                    (FALSE, size, ERROR_INSUFFICIENT_BUFFER, false)
+                }
+            }
+            Step::Fails(code) => (FALSE, argument.size, *code, true),
+        };
+        argument.size = size;
+        self.finish(consume, last_error);
+        RvIsError::new::<BOOL>(rv)
+    }
+    /// Mimic a Windows API function that returns the number of elements stored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the script has been used up.
+    ///
+    pub fn call_with_size_rv<IT>(&mut self, argument: &mut Argument<IT>) -> RvIsSize
+    where
+        IT: RawToInternal,
+    {
+        let (pointer, capacity) = self.observe(argument);
+        let available = IT::capacity_to_size(capacity);
+        let response = self.script.front().expect("the MockApi script is used up");
+        let (rv, last_error, consume) = match &response.step {
+            Step::Needs(_) => (
+                available,
+                response.last_error.unwrap_or(ERROR_INSUFFICIENT_BUFFER),
+                true,
+            ),
+            Step::Writes(payload) => {
+                let stored = IT::capacity_to_size(payload.len().try_into().unwrap());
+                // There has to be room for the terminator.
+                if stored < available {
+                    unsafe { std::ptr::copy(payload.as_ptr(), pointer, payload.len()) };
+                    (stored, response.last_error.unwrap_or(NO_ERROR), true)
+                } else {
+                    (available, ERROR_INSUFFICIENT_BUFFER, false)
+                }
+            }
+            Step::Fails(code) => (0, *code, true),
+        };
+        self.finish(consume, last_error);
+        RvIsSize::new(rv)
+    }
+    /// Mimic a Windows API function that returns the number of bytes required.
+    ///
+    /// Nothing is written when the buffer is too small.  Zero is returned for a
+    /// [`fails`](MockApi::fails) response.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the script has been used up.
+    ///
+    pub fn call_with_required_bytes_rv<IT>(
+        &mut self,
+        argument: &mut Argument<IT>,
+    ) -> RvIsRequiredBytes
+    where
+        IT: RawToInternal,
+    {
+        let (pointer, capacity) = self.observe(argument);
+        let response = self.script.front().expect("the MockApi script is used up");
+        let (rv, last_error, consume) = match &response.step {
+            Step::Needs(bytes) => (
+                *bytes,
+                response.last_error.unwrap_or(ERROR_INSUFFICIENT_BUFFER),
+                true,
+            ),
+            Step::Writes(payload) => {
+                let length: u32 = payload.len().try_into().unwrap();
+                if length <= capacity {
+                    unsafe { std::ptr::copy(payload.as_ptr(), pointer, payload.len()) };
+                    (length, response.last_error.unwrap_or(NO_ERROR), true)
+                } else {
+                    (length, ERROR_INSUFFICIENT_BUFFER, false)
+                }
+            }
+            Step::Fails(code) => (0, *code, true),
+        };
+        self.finish(consume, last_error);
+        RvIsRequiredBytes::new(rv)
+    }
+
+    fn push(mut self, step: Step) -> Self {
+        self.script.push_back(Response {
+            step,
+            last_error: None,
+        });
+        self
+    }
+
+    fn observe<IT>(&mut self, argument: &mut Argument<IT>) -> (*mut u8, u32)
+    where
+        IT: RawToInternal,
+    {
+        let pointer = argument.raw_pointer;
+        // The caller may have reduced the size.  Never write more than the caller allows.
+        let capacity = argument.capacity.min(IT::size_to_capacity(argument.size));
+        self.observations.push(Observation {
+            capacity,
+            pointer: pointer as *const u8,
+        });
+        (pointer, capacity)
+    }
+
+    fn finish(&mut self, consume: bool, last_error: WIN32_ERROR) {
+        if consume {
+            self.script.pop_front();
+        }
+        unsafe { SetLastError(last_error) };
+    }
+}
+
+/// Wraps another [`GrowStrategy`] and records every capacity it chooses.
+///
+/// Each call to [`next_capacity`](GrowStrategy::next_capacity) is recorded as a [`GrowEvent`].
+/// Use [`events`](RecordingGrowStrategy::events) to assert exactly what capacities were requested.
+/// Everything else is forwarded to the wrapped strategy.  A [`RecordingGrowStrategy`] can be passed
+/// to the `*_with_strategy` functions or used with a [`GrowableBuffer`][gb] loop.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use grob::testing::{MockApi, RecordingGrowStrategy};
+/// use grob::{winapi_small_binary_with_strategy, GrowToNearestQuarterKibi};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let grow_strategy = RecordingGrowStrategy::new(GrowToNearestQuarterKibi::new());
+///     let mut mock_api = MockApi::new().needs(3000).writes([42; 3000]);
+///     let size = winapi_small_binary_with_strategy(
+///         &grow_strategy,
+///         |argument| mock_api.call_with_error_rv(argument),
+///         |frozen_buffer: grob::FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+///     )?;
+///     assert!(size == 3000);
+///     let events = grow_strategy.events();
+///     assert!(events.len() == 1);
+///     assert!(events[0].desired == 3000);
+///     assert!(events[0].chosen == 3072);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [gb]: crate::GrowableBuffer
+///
+#[derive(Debug)]
+pub struct RecordingGrowStrategy<S> {
+    inner: S,
+    events: RefCell<Vec<GrowEvent>>,
+}
+
+impl<S> RecordingGrowStrategy<S>
+where
+    S: GrowStrategy,
+{
+    /// Create a [`RecordingGrowStrategy`] that has not recorded anything.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            events: RefCell::new(Vec::new()),
+        }
+    }
+    /// Returns a copy of what was recorded on each call to
+    /// [`next_capacity`](GrowStrategy::next_capacity).
+    pub fn events(&self) -> Vec<GrowEvent> {
+        self.events.borrow().clone()
+    }
+    /// Returns just the capacities chosen, in the order they were chosen.
+    pub fn capacities(&self) -> Vec<u32> {
+        self.events
+            .borrow()
+            .iter()
+            .map(|event| event.chosen)
+            .collect()
+    }
+    /// Forget everything recorded so far.
+    pub fn clear(&self) {
+        self.events.borrow_mut().clear();
+    }
+}
+
+impl<S> GrowStrategy for RecordingGrowStrategy<S>
+where
+    S: GrowStrategy,
+{
+    fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+        let chosen = self.inner.next_capacity(tries, desired_capacity);
+        self.events.borrow_mut().push(GrowEvent {
+            tries,
+            desired: desired_capacity,
+            chosen,
+        });
+        chosen
+    }
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+    fn max_capacity(&self) -> Option<u32> {
+        self.inner.max_capacity()
+    }
+    fn should_continue(&self, tries: usize) -> bool {
+        self.inner.should_continue(tries)
+    }
+    fn initial_capacity(&self) -> Option<u32> {
+        self.inner.initial_capacity()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+type DropObserver = fn(&[u16]);
+
+#[cfg(feature = "zeroize")]
+thread_local! {
+    static WINDOWS_STRING_DROP_OBSERVER: std::cell::Cell<Option<DropObserver>> =
+        const { std::cell::Cell::new(None) };
+}
+
+/// Call `observer` with the heap buffer of every [`WindowsString`][ws] dropped on this thread.
+///
+/// The heap buffer is passed after it has been overwritten and before it is freed so a test can
+/// check that nothing was left behind.  Pass [`None`] to stop observing.  Only available with the
+/// `zeroize` feature.
+///
+/// [ws]: crate::WindowsString
+///
+#[cfg(feature = "zeroize")]
+pub fn set_windows_string_drop_observer(observer: Option<DropObserver>) {
+    WINDOWS_STRING_DROP_OBSERVER.with(|o| o.set(observer));
+}
+
+#[cfg(feature = "zeroize")]
+pub(crate) fn observe_windows_string_drop(heap: &[u16]) {
+    if let Some(observer) = WINDOWS_STRING_DROP_OBSERVER.with(|o| o.get()) {
+        observer(heap);
+    }
+}
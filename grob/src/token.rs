@@ -0,0 +1,82 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use windows::Win32::Foundation::HANDLE;
+use windows::Win32::Security::{GetTokenInformation, TOKEN_INFORMATION_CLASS};
+
+use crate::generic::winapi_frozen;
+use crate::win::RvIsError;
+use crate::{Argument, FrozenBuffer};
+
+/// Wrapper for [`GetTokenInformation`][gti].
+///
+/// `T` is the structure that goes with `class` like [`TOKEN_ELEVATION`][te] for `TokenElevation`
+/// or [`TOKEN_USER`][tu] for `TokenUser`.  Many of the structures hold pointers to data stored
+/// after the structure in the same buffer.  The returned [`FrozenBuffer`] owns the buffer so the
+/// pointers are valid as long as it is alive.
+///
+/// An error with a kind of [`InvalidData`](std::io::ErrorKind::InvalidData) is returned if fewer
+/// than `size_of::<T>()` bytes are stored.  That usually means `T` does not match `class`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::Foundation::{CloseHandle, HANDLE};
+/// use windows::Win32::Security::{
+///     TokenElevation, TokenUser, TOKEN_ELEVATION, TOKEN_QUERY, TOKEN_USER,
+/// };
+/// use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+///
+/// use grob::winapi_token_info;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut token = HANDLE::default();
+///     unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) }.ok()?;
+///
+///     let elevation = winapi_token_info::<TOKEN_ELEVATION>(token, TokenElevation)?;
+///     if let Some(p) = elevation.try_pointer()? {
+///         println!("Elevated: {}", unsafe { (*p).TokenIsElevated } != 0);
+///     }
+///
+///     // The SID is stored in the same buffer after the TOKEN_USER.
+///     let user = winapi_token_info::<TOKEN_USER>(token, TokenUser)?;
+///     if let Some(p) = user.try_pointer()? {
+///         println!("SID at {:?}", unsafe { (*p).User.Sid });
+///     }
+///
+///     unsafe { CloseHandle(token) };
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [gti]: https://learn.microsoft.com/en-us/windows/win32/api/securitybaseapi/nf-securitybaseapi-gettokeninformation
+/// [te]: https://learn.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-token_elevation
+/// [tu]: https://learn.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-token_user
+///
+pub fn winapi_token_info<T>(
+    token: HANDLE,
+    class: TOKEN_INFORMATION_CLASS,
+) -> Result<FrozenBuffer<'static, T>, std::io::Error> {
+    winapi_frozen(|argument: &mut Argument<*mut T>| {
+        let (pointer, size) = argument.pointer_and_size_mut();
+        let length = *size;
+        RvIsError::new(unsafe {
+            GetTokenInformation(token, class, Some(pointer.cast()), length, size)
+        })
+    })
+}
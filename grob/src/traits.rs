@@ -12,15 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::base::FillBufferResult;
+use crate::base::{FillBufferResult, GrowError};
+use crate::{Argument, FrozenBuffer};
 
 pub(crate) trait GrowableBufferAsParent {
-    fn grow(&mut self, value: u32);
-    fn set_final_size(&mut self, value: u32);
+    fn grow(&mut self, value: u32) -> Result<(), GrowError>;
+    fn raw_buffer(&mut self) -> (*mut u8, u32);
+    fn restore_final_size(&mut self, value: u32);
+    fn set_final_size(&mut self, value: u32) -> Result<(), GrowError>;
+    fn set_os_error_hint(&mut self, value: u32);
 }
 
 /// How should the buffer grow?  Small bump?  Double in capacity?
 ///
+/// The `PrintNextCapacity` wrapper in the example below is available as
+/// [`InstrumentedGrowStrategy`](crate::InstrumentedGrowStrategy).
+///
 /// # Examples
 ///
 /// ```
@@ -95,7 +102,7 @@ pub(crate) trait GrowableBufferAsParent {
 ///                 break;
 ///             }
 ///             FillBufferAction::Grow => {
-///                 argument.grow();
+///                 argument.grow()?;
 ///             }
 ///             FillBufferAction::NoData => {
 ///                 argument.commit_no_data();
@@ -120,21 +127,79 @@ pub trait GrowStrategy {
     /// # Arguments
     ///
     /// - `tries` - The number of times the operating system call has been attempted.  Count starts
-    /// at 1.
+    ///   at 1.
     /// - `desired_capacity` - There are two possible meanings for this argument...
     ///     - If the operating system returns the number of elements stored, like
-    ///         [`GetUserNameW`][1], then this argument is the number of bytes stored.  The
-    ///         expectation is that `next_capacity` uses this value as the floor and chooses the
-    ///         next capacity to be reasonably larger.
+    ///       [`GetUserNameW`][1], then this argument is the number of bytes stored.  The
+    ///       expectation is that `next_capacity` uses this value as the floor and chooses the
+    ///       next capacity to be reasonably larger.
     ///     - If the operating system returns the capacity needed for success, like
-    ///         [`GetLogicalProcessorInformationEx`][1], then this argument is the capacity, in
-    ///         bytes, needed.  The expectation is that `next_capacity` returns something no less
-    ///         than and not too much greater than this value.
+    ///       [`GetLogicalProcessorInformationEx`][1], then this argument is the capacity, in
+    ///       bytes, needed.  The expectation is that `next_capacity` returns something no less
+    ///       than and not too much greater than this value.
+    ///
+    /// # Contract
+    ///
+    /// `next_capacity` is only called when the buffer has to grow.  The returned value must be
+    /// greater than the current capacity of the buffer.  The current capacity is always less than
+    /// `desired_capacity` so returning a value no less than `desired_capacity` is always safe.
+    ///
+    /// # What happens if the contract is violated
+    ///
+    /// If the returned value is not greater than the current capacity the buffer does not grow.
+    /// Instead, a [`GrowError::StrategyDidNotGrow`][sdng] that includes the strategy's
+    /// [`name`](GrowStrategy::name) is returned from [`try_apply`][ta] and the call loop ends.  The
+    /// generic functions, like [`winapi_string`][ws], return that error as a [`std::io::Error`].
     ///
     /// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/WindowsProgramming/fn.GetUserNameW.html
     /// [2]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/SystemInformation/fn.GetLogicalProcessorInformationEx.html
+    /// [sdng]: crate::GrowError::StrategyDidNotGrow
+    /// [ta]: crate::Argument::try_apply
+    /// [ws]: crate::winapi_string
     ///
     fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32;
+    /// Returns a name for the strategy used in error messages.
+    ///
+    /// The default is the type name of the strategy.
+    ///
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+    /// Returns the largest capacity, in bytes, the buffer is allowed to grow to.
+    ///
+    /// When the capacity needed is larger, growing fails with
+    /// [`GrowError::CapacityLimitExceeded`][cle] instead of calling
+    /// [`next_capacity`](GrowStrategy::next_capacity).  The default is no limit.  Strategies that
+    /// wrap another strategy should forward this method.
+    ///
+    /// [cle]: crate::GrowError::CapacityLimitExceeded
+    ///
+    fn max_capacity(&self) -> Option<u32> {
+        None
+    }
+    /// Returns `false` to stop growing after `tries` operating system calls.
+    ///
+    /// Called before each grow.  When `false` is returned, growing fails with
+    /// [`GrowError::StrategyGaveUp`][sgu] instead of calling
+    /// [`next_capacity`](GrowStrategy::next_capacity).  The default is to always continue.
+    /// Strategies that wrap another strategy should forward this method.
+    ///
+    /// [sgu]: crate::GrowError::StrategyGaveUp
+    ///
+    fn should_continue(&self, _tries: usize) -> bool {
+        true
+    }
+    /// Returns the capacity, in bytes, to allocate before the first operating system call when the
+    /// initial buffer has no capacity.
+    ///
+    /// With a zero capacity initial buffer, like `StackBuffer::<0>`, the first call is normally
+    /// made only to learn the size needed.  Returning `Some` allocates a heap buffer first so the
+    /// first call can succeed.  The allocation does not count as a try.  The default is `None`.
+    /// Strategies that wrap another strategy should forward this method.
+    ///
+    fn initial_capacity(&self) -> Option<u32> {
+        None
+    }
 }
 
 /// Used internally help determine the [`FillBufferAction`][1].
@@ -147,6 +212,23 @@ pub trait GrowStrategy {
 pub trait NeededSize {
     fn needed_size(&self) -> u32;
     fn set_needed_size(&mut self, value: u32);
+    /// Records the error code from the operating system call.
+    ///
+    /// The default implementation discards the value.
+    ///
+    fn set_os_error_hint(&mut self, _value: u32) {}
+    /// Records that the operating system call failed and the error is about to be returned.
+    ///
+    /// A [`ToResult`] implementation may call `set_failed` before returning an error so an
+    /// [`Argument`][a] in a hand-written loop can be dropped without being applied.  The generic
+    /// functions, [`Attempt::finish`][af], and [`grob_call!`][gc] dismiss the [`Argument`][a]
+    /// themselves so calling `set_failed` is optional.  The default implementation does nothing.
+    ///
+    /// [a]: crate::Argument
+    /// [af]: crate::Attempt::finish
+    /// [gc]: crate::grob_call
+    ///
+    fn set_failed(&mut self) {}
 }
 
 /// Conversion between capacity (bytes in the buffer) and size (API units of measure like WCHARs).
@@ -183,6 +265,41 @@ impl<T> RawToInternal for *mut T {
     }
 }
 
+/// Element types where every bit pattern is a valid value.
+///
+/// [`as_slice`][as] reads the bytes stored by the operating system as `FT`s.  That is only sound
+/// when any bytes make a valid `FT`.  `Pod` is implemented for the integer and floating point
+/// primitives, and arrays of them, so [`FrozenBuffer<u8>`][fb] and [`FrozenBuffer<u16>`][fb] work
+/// as is.
+///
+/// # Safety
+///
+/// Implement `Pod` only for a type where every bit pattern of its size is a valid value; for
+/// example, a `#[repr(C)]` struct whose fields are all `Pod`.  `bool`, `char`, references, and
+/// enums are not `Pod`.
+///
+/// ```compile_fail
+/// // Not every byte is a valid bool.
+/// fn flags<'a>(frozen_buffer: &'a grob::FrozenBuffer<bool>) -> &'a [bool] {
+///     frozen_buffer.as_slice()
+/// }
+/// ```
+///
+/// [as]: crate::FrozenBuffer::as_slice
+/// [fb]: crate::FrozenBuffer
+///
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! pod {
+    ($($t:ty),*) => {
+        $(unsafe impl Pod for $t {})*
+    };
+}
+
+pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
 /// Return a read-only pointer to a buffer and the actual number of bytes stored in the buffer.
 ///
 /// This trait is used internally by [`read_buffer`][rb] to provide read-only access to a buffer
@@ -221,6 +338,21 @@ pub trait WriteBuffer {
     /// [fb]: crate::FrozenBuffer
     ///
     fn set_final_size(&mut self, final_size: u32);
+    /// Called from [`freeze`][f] with the amount of data written as `final_size`, in the units of
+    /// the [`GrowableBuffer`][gb], and as `bytes`.
+    ///
+    /// The default calls [`set_final_size`](WriteBuffer::set_final_size) and ignores `bytes`.
+    /// Override it when the buffer needs the number of bytes stored; for example, a
+    /// [`VecBuffer`][vb] sets the length of its [`Vec`] to `bytes`.
+    ///
+    /// [f]: crate::GrowableBuffer::freeze
+    /// [gb]: crate::GrowableBuffer
+    /// [vb]: crate::VecBuffer
+    ///
+    fn set_final_size_and_bytes(&mut self, final_size: u32, bytes: u32) {
+        let _ = bytes;
+        self.set_final_size(final_size);
+    }
     /// Return a raw correctly aligned pointer into the buffer and the capacity of the buffer in bytes.
     ///
     /// `write_buffer` is used by [`GrowableBuffer`][gb], in the [`argument`][am] method, to prepare
@@ -231,6 +363,31 @@ pub trait WriteBuffer {
     /// [a]: crate::Argument
     ///
     fn write_buffer(&mut self) -> (*mut u8, u32);
+    /// Grow this buffer in place to at least `capacity` bytes keeping the contents.
+    ///
+    /// [`GrowableBuffer`][gb] calls `try_grow` before switching from the initial buffer to a heap
+    /// buffer.  If `true` is returned the initial buffer stays in use.  The default returns
+    /// `false` so the heap buffer is used.  [`VecBuffer`][vb] grows the caller's [`Vec`].
+    ///
+    /// [gb]: crate::GrowableBuffer
+    /// [vb]: crate::VecBuffer
+    ///
+    fn try_grow(&mut self, _capacity: u32) -> bool {
+        false
+    }
+    /// Returns the alignment, in bytes, of the buffer.
+    ///
+    /// [`GrowableBuffer`][gb] allocates heap buffers with at least this alignment so data that
+    /// needs it keeps it after the switch from the initial buffer.  The default is
+    /// [`ALIGNMENT`][a].  A [`StackBuffer`][sb] returns its `ALIGN`.
+    ///
+    /// [gb]: crate::GrowableBuffer
+    /// [a]: crate::ALIGNMENT
+    /// [sb]: crate::StackBuffer
+    ///
+    fn alignment(&self) -> usize {
+        crate::ALIGNMENT
+    }
 }
 
 /// Convert an API return value and the needed buffer size into a `FillBufferResult` which is then
@@ -245,3 +402,83 @@ pub trait ToResult {
     /// [s]: crate::RvIsSize::to_result
     fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult;
 }
+
+/// One operating system call packaged with its state.  An alternative to the closures taken by
+/// [`winapi_generic`][wg].
+///
+/// With [`winapi_generic`][wg] the `api_wrapper` and `finalize` closures are alive at the same
+/// time so they cannot both mutably borrow the same state (statistics, a reusable scratch buffer).
+/// A `GrobCall` holds that state once; [`call`](GrobCall::call) borrows it for each try then
+/// [`finish`](GrobCall::finish) consumes it.  Pass it to [`run_call`][rc].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use std::ffi::OsString;
+///
+/// use windows::core::PWSTR;
+/// use windows::Win32::System::WindowsProgramming::GetUserNameW;
+///
+/// use grob::{run_call, Argument, FrozenBuffer, GrobCall, GrowForStaticText, GrowableBuffer};
+/// use grob::{RvIsError, StackBuffer};
+///
+/// struct UserName {
+///     tries: usize,
+/// }
+///
+/// impl GrobCall for UserName {
+///     type Ft = u16;
+///     type It = PWSTR;
+///     type Rv = RvIsError;
+///     type Output = (Option<OsString>, usize);
+///
+///     fn call(&mut self, argument: &mut Argument<PWSTR>) -> RvIsError {
+///         self.tries += 1;
+///         RvIsError::new(unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) })
+///     }
+///     fn finish(self, frozen_buffer: FrozenBuffer<u16>) -> Result<Self::Output, std::io::Error> {
+///         Ok((frozen_buffer.to_os_string(), self.tries))
+///     }
+/// }
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut initial_buffer = StackBuffer::<64>::new();
+///     let grow_strategy = GrowForStaticText::new();
+///     let growable_buffer = GrowableBuffer::new(&mut initial_buffer, &grow_strategy);
+///     let (name, tries) = run_call(growable_buffer, UserName { tries: 0 })?;
+///     println!("GetUserNameW returned {:?} after {} tries", name, tries);
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [wg]: crate::winapi_generic
+/// [rc]: crate::run_call
+///
+pub trait GrobCall {
+    /// The final type; the element type of the [`FrozenBuffer`][fb].
+    ///
+    /// [fb]: crate::FrozenBuffer
+    ///
+    type Ft;
+    /// The intermediate type; the pointer type passed to the operating system.
+    type It: RawToInternal + Copy;
+    /// The return value handler, typically [`RvIsError`][e] or [`RvIsSize`][s].
+    ///
+    /// [e]: crate::RvIsError
+    /// [s]: crate::RvIsSize
+    ///
+    type Rv: ToResult;
+    /// What [`run_call`][rc] returns on success.
+    ///
+    /// [rc]: crate::run_call
+    ///
+    type Output;
+    /// Make the operating system call.  Called once per try.
+    fn call(&mut self, argument: &mut Argument<Self::It>) -> Self::Rv;
+    /// Convert the data to the output.  Called once after the operating system call succeeds.
+    fn finish(self, frozen_buffer: FrozenBuffer<Self::Ft>) -> Result<Self::Output, std::io::Error>;
+}
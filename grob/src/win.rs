@@ -13,25 +13,37 @@
 // limitations under the License.
 
 use std::ffi::{OsStr, OsString};
-use std::mem::size_of;
+use std::mem::{size_of, MaybeUninit};
 use std::os::windows::ffi::OsStringExt;
 use std::path::PathBuf;
 use std::slice::{from_raw_parts, from_raw_parts_mut};
 
-use windows::core::{PCWSTR, PWSTR};
+use windows::core::{HRESULT, PCWSTR, PSTR, PWSTR};
+use windows::Win32::Devices::DeviceAndDriverInstallation::{
+    CM_MapCrToWin32Err, CONFIGRET, CR_BUFFER_SMALL, CR_SUCCESS,
+};
 use windows::Win32::Foundation::{
-    GetLastError, SetLastError, BOOL, ERROR_BUFFER_OVERFLOW, ERROR_INSUFFICIENT_BUFFER,
-    ERROR_NO_DATA, MAX_PATH, NO_ERROR, TRUE, WIN32_ERROR,
+    GetLastError, SetLastError, BOOL, ERROR_BUFFER_OVERFLOW, ERROR_FILE_NOT_FOUND,
+    ERROR_GEN_FAILURE, ERROR_INSUFFICIENT_BUFFER, ERROR_MORE_DATA, ERROR_NO_DATA, MAX_PATH,
+    NO_ERROR, NTE_BUFFER_TOO_SMALL, NTSTATUS, STATUS_ACCESS_DENIED, STATUS_BUFFER_TOO_SMALL,
+    STATUS_INFO_LENGTH_MISMATCH, STATUS_INVALID_PARAMETER, STATUS_NO_MEMORY,
+    STATUS_OBJECT_NAME_NOT_FOUND, TRUE, WIN32_ERROR,
+};
+use windows::Win32::NetworkManagement::NetManagement::{DNLEN, UNLEN};
+use windows::Win32::Networking::WinSock::{
+    WSAGetLastError, SOCKET_ERROR, WSAEFAULT, WSAENOBUFS, WSA_ERROR,
 };
-use windows::Win32::NetworkManagement::NetManagement::UNLEN;
 
-use crate::base::{FillBufferAction, FillBufferResult};
-use crate::buffer::os::ALIGNMENT;
+use crate::base::{FillBufferAction, FillBufferResult, GrobCallError, GrowError};
 use crate::traits::{NeededSize, RawToInternal, ToResult};
 use crate::winstr::WindowsString;
 use crate::{Argument, FrozenBuffer};
 
 const BETTER_MAX_PATH: usize = MAX_PATH as usize;
+// MAX_COMPUTERNAME_LENGTH from the windows crate.  It is in a module grob does not otherwise need.
+const MAX_COMPUTERNAME_LENGTH: usize = 15;
+// Length of a GUID string with braces not including the NUL terminator.
+const GUID_STRING_LENGTH: usize = 38;
 
 /// Size of [`WCHAR`][wc] / [`u16`] (two bytes) cast as a [`u32`].
 ///
@@ -42,6 +54,160 @@ const BETTER_MAX_PATH: usize = MAX_PATH as usize;
 ///
 pub const SIZE_OF_WCHAR: u32 = size_of::<u16>() as u32;
 
+/// Converts a count of [`WCHAR`][wc]s to a count of bytes or `None` if the result does not fit
+/// in a [`u32`].
+///
+/// [wc]: https://learn.microsoft.com/en-us/windows/win32/extensible-storage-engine/wchar
+///
+pub const fn wchars_to_bytes(wchars: u32) -> Option<u32> {
+    wchars.checked_mul(SIZE_OF_WCHAR)
+}
+
+/// Converts a count of bytes to a count of [`WCHAR`][wc]s.
+///
+/// The result is rounded down; an odd trailing byte is not a [`WCHAR`][wc] so it is not counted.
+/// The conversion cannot fail.
+///
+/// [wc]: https://learn.microsoft.com/en-us/windows/win32/extensible-storage-engine/wchar
+///
+pub const fn bytes_to_wchars(bytes: u32) -> u32 {
+    bytes / SIZE_OF_WCHAR
+}
+
+/// [`wchars_to_bytes`] for [`usize`].
+pub const fn wchars_to_bytes_usize(wchars: usize) -> Option<usize> {
+    wchars.checked_mul(SIZE_OF_WCHAR as usize)
+}
+
+/// [`bytes_to_wchars`] for [`usize`].
+pub const fn bytes_to_wchars_usize(bytes: usize) -> usize {
+    bytes / SIZE_OF_WCHAR as usize
+}
+
+// wchars_to_bytes_usize for constants.  Overflowing is a compile error.
+pub(crate) const fn const_wchars_to_bytes(wchars: usize) -> usize {
+    match wchars_to_bytes_usize(wchars) {
+        Some(bytes) => bytes,
+        None => panic!("the WCHAR count overflows usize"),
+    }
+}
+
+/// Converts a Windows error code to a [`std::io::Error`].
+///
+/// This is the conversion [grob][gc] uses for every operating system error.  Errors that leave the
+/// call loop are then wrapped in a [`GrobCallError`].
+///
+/// [gc]: https://crates.io/crates/grob
+///
+pub fn win32_err(code: WIN32_ERROR) -> std::io::Error {
+    std::io::Error::from_raw_os_error(code.0 as i32)
+}
+
+/// Returns the Windows error code from a [`std::io::Error`] or `None` if the error did not come
+/// from the operating system.
+///
+/// The code is found whether or not the error wraps a [`GrobCallError`].
+///
+pub fn as_win32_err(e: &std::io::Error) -> Option<WIN32_ERROR> {
+    let code = e.raw_os_error().or_else(|| {
+        e.get_ref()
+            .and_then(|inner| inner.downcast_ref::<GrobCallError>())
+            .and_then(GrobCallError::raw_os_error)
+    });
+    code.map(|code| WIN32_ERROR(code as u32))
+}
+
+/// An `NTSTATUS` that is not a success code.
+///
+/// Native API functions, like [`NtQuerySystemInformation`][1], return an `NTSTATUS` instead of
+/// setting the last error.  [`RvIsNtstatus`] converts a failure status to a [`std::io::Error`]
+/// wrapping an [`NtStatusError`] with [`ntstatus_err`].  The status is not converted to a Windows
+/// error code because the conversion loses information.  Use [`as_ntstatus`] or
+/// [`is_ntstatus`](IoErrorExt::is_ntstatus) to get it back.
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/winternl/nf-winternl-ntquerysysteminformation
+///
+#[derive(Clone, Copy, Debug)]
+pub struct NtStatusError(pub NTSTATUS);
+
+impl std::fmt::Display for NtStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NTSTATUS {:#010X}", self.0 .0)
+    }
+}
+
+impl std::error::Error for NtStatusError {}
+
+/// Converts a failure `NTSTATUS` to a [`std::io::Error`] wrapping an [`NtStatusError`].
+///
+/// A few common statuses get a matching [`kind`](std::io::Error::kind).  The rest have a kind of
+/// [`Other`](std::io::ErrorKind::Other).
+///
+pub fn ntstatus_err(status: NTSTATUS) -> std::io::Error {
+    let kind = match status {
+        s if s.0 == STATUS_ACCESS_DENIED.0 => std::io::ErrorKind::PermissionDenied,
+        s if s.0 == STATUS_INVALID_PARAMETER.0 => std::io::ErrorKind::InvalidInput,
+        s if s.0 == STATUS_NO_MEMORY.0 => std::io::ErrorKind::OutOfMemory,
+        s if s.0 == STATUS_OBJECT_NAME_NOT_FOUND.0 => std::io::ErrorKind::NotFound,
+        _ => std::io::ErrorKind::Other,
+    };
+    std::io::Error::new(kind, NtStatusError(status))
+}
+
+/// Returns the `NTSTATUS` from a [`std::io::Error`] or `None` if the error was not created by
+/// [`ntstatus_err`].
+///
+/// The status is found whether or not the error wraps a [`GrobCallError`].
+///
+pub fn as_ntstatus(e: &std::io::Error) -> Option<NTSTATUS> {
+    let inner = e.get_ref()?;
+    if let Some(nse) = inner.downcast_ref::<NtStatusError>() {
+        return Some(nse.0);
+    }
+    let source = std::error::Error::source(inner.downcast_ref::<GrobCallError>()?)?;
+    let source = source.downcast_ref::<std::io::Error>()?;
+    source
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<NtStatusError>())
+        .map(|nse| nse.0)
+}
+
+/// Readable matching of a [`std::io::Error`] against a Windows error code.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_NO_DATA};
+///
+/// use grob::{win32_err, IoErrorExt};
+///
+/// fn main() {
+///     let e = win32_err(ERROR_ACCESS_DENIED);
+///     assert!(e.is_win32(ERROR_ACCESS_DENIED));
+///     assert!(!e.is_win32(ERROR_NO_DATA));
+/// }
+/// # }
+/// ```
+///
+pub trait IoErrorExt {
+    /// Returns `true` if the error is the Windows error `code`.
+    fn is_win32(&self, code: WIN32_ERROR) -> bool;
+    /// Returns `true` if the error is the failure `NTSTATUS` `status`.
+    fn is_ntstatus(&self, status: NTSTATUS) -> bool;
+}
+
+impl IoErrorExt for std::io::Error {
+    fn is_win32(&self, code: WIN32_ERROR) -> bool {
+        as_win32_err(self) == Some(code)
+    }
+    fn is_ntstatus(&self, status: NTSTATUS) -> bool {
+        as_ntstatus(self).map(|s| s.0) == Some(status.0)
+    }
+}
+
 /// A good starting buffer capacity, in bytes, for Windows API calls that return the name of something.
 ///
 /// The value is based on [`UNLEN`].  According to the Windows API documentation this value works
@@ -52,7 +218,7 @@ pub const SIZE_OF_WCHAR: u32 = size_of::<u16>() as u32;
 /// [1]: https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-getusernamew
 /// [2]: crate::generic::winapi_string
 ///
-pub const CAPACITY_FOR_NAMES: usize = ((UNLEN + 1) as usize * SIZE_OF_WCHAR as usize) + ALIGNMENT;
+pub const CAPACITY_FOR_NAMES: usize = capacity_for_wchars(UNLEN as usize);
 
 /// A good starting buffer capacity, in bytes, for Windows API calls that return a file system path.
 ///
@@ -65,8 +231,79 @@ pub const CAPACITY_FOR_NAMES: usize = ((UNLEN + 1) as usize * SIZE_OF_WCHAR as u
 /// [3]: crate::generic::winapi_path_buf
 /// [4]: https://learn.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-getmodulefilenamew
 ///
-pub const CAPACITY_FOR_PATHS: usize =
-    (BETTER_MAX_PATH as usize * SIZE_OF_WCHAR as usize) + ALIGNMENT;
+// MAX_PATH includes the NUL terminator.
+pub const CAPACITY_FOR_PATHS: usize = capacity_for_wchars(BETTER_MAX_PATH - 1);
+
+/// A good starting buffer capacity, in bytes, for Windows API calls that return a computer name.
+///
+/// The value is based on [`MAX_COMPUTERNAME_LENGTH`][1].  [`GetComputerNameW`][2] is an example
+/// API call where this value is useful.
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/sysinfo/computer-names
+/// [2]: https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-getcomputernamew
+///
+pub const CAPACITY_FOR_COMPUTER_NAME: usize = capacity_for_wchars(MAX_COMPUTERNAME_LENGTH);
+
+/// A good starting buffer capacity, in bytes, for Windows API calls that return a domain name.
+///
+/// The value is based on [`DNLEN`].  [`LookupAccountSidW`][1] is an example API call where this
+/// value is useful.
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-lookupaccountsidw
+///
+pub const CAPACITY_FOR_DOMAIN_NAME: usize = capacity_for_wchars(DNLEN as usize);
+
+/// Buffer capacity, in bytes, for a GUID string with braces like
+/// `{6B29FC40-CA47-1067-B31D-00DD010662DA}`.
+///
+/// [`StringFromGUID2`][1] is an example API call where this value is useful.
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/combaseapi/nf-combaseapi-stringfromguid2
+///
+pub const CAPACITY_FOR_GUID_STRING: usize = capacity_for_wchars(GUID_STRING_LENGTH);
+
+/// Returns the buffer capacity, in bytes, for `n` [`WCHAR`][wc]s plus a `NUL` terminator.
+///
+/// The result can be used as-is for the capacity of a [`StackBuffer`][sb].  `n` does not include
+/// the `NUL` terminator, the same as most Windows length constants (e.g. [`UNLEN`] and [`DNLEN`]).
+///
+/// # Examples
+///
+/// Sizing a [`StackBuffer`][sb] for a security identifier string...
+///
+/// ```
+/// use grob::{capacity_for_wchars, StackBuffer};
+///
+/// // SECURITY_MAX_SID_STRING_CHARACTERS less the NUL terminator.
+/// const SID_STRING_LENGTH: usize = 186;
+///
+/// let initial_buffer = StackBuffer::<{ capacity_for_wchars(SID_STRING_LENGTH) }>::new();
+/// # let _ = initial_buffer;
+/// ```
+///
+/// Sizing a [`WindowsString`], which is measured in [`WCHAR`][wc]s instead of bytes...
+///
+/// ```
+/// # #[cfg(not(miri))]
+/// # mod miri_skip {
+/// #
+/// use grob::{bytes_to_wchars_usize, WindowsString, CAPACITY_FOR_COMPUTER_NAME};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     const STACK_WCHARS: usize = bytes_to_wchars_usize(CAPACITY_FOR_COMPUTER_NAME);
+///     let new_name = WindowsString::<STACK_WCHARS>::new("TEMPNAME")?;
+///     # let _ = new_name;
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [sb]: crate::StackBuffer
+/// [wc]: https://learn.microsoft.com/en-us/windows/win32/extensible-storage-engine/wchar
+///
+pub const fn capacity_for_wchars(n: usize) -> usize {
+    const_wchars_to_bytes(n + 1)
+}
 
 impl<'gb> Argument<'gb, PWSTR> {
     /// Provides access to the buffer through a writable slice of [`u16`]
@@ -74,6 +311,12 @@ impl<'gb> Argument<'gb, PWSTR> {
     /// Some Windows API calls, like [`GetModuleFileNameW`][1], take a `&mut [u16]`.  This method
     /// provides that argument.
     ///
+    /// The buffer is not initialized unless [`zero_fill`](Argument::zero_fill) was called or the
+    /// [`GrowableBuffer`](crate::GrowableBuffer) fills it with zeros.  A `&mut [u16]` over
+    /// uninitialized memory is undefined behaviour even when the operating system only writes to
+    /// it.  Call [`zero_fill`](Argument::zero_fill) first or, when the Windows API function can be
+    /// given a pointer, use [`as_uninit_slice`](Argument::as_uninit_slice).
+    ///
     /// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/LibraryLoader/fn.GetModuleFileNameW.html
     ///
     pub fn as_mut_slice(&mut self) -> &mut [u16] {
@@ -81,6 +324,67 @@ impl<'gb> Argument<'gb, PWSTR> {
         unsafe { SetLastError(NO_ERROR) };
         rv
     }
+    /// Provides access to the buffer through a writable slice of possibly uninitialized [`u16`].
+    ///
+    /// Unlike [`as_mut_slice`](Argument::as_mut_slice) this is sound whether or not the buffer
+    /// was initialized.  Pass [`as_mut_ptr`][p] and [`len`][l] to the Windows API function.
+    ///
+    /// [p]: slice::as_mut_ptr
+    /// [l]: slice::len
+    ///
+    pub fn as_uninit_slice(&mut self) -> &mut [MaybeUninit<u16>] {
+        let rv = unsafe { from_raw_parts_mut(self.pointer.0.cast(), self.size as usize) };
+        unsafe { SetLastError(NO_ERROR) };
+        rv
+    }
+}
+
+impl<'gb> Argument<'gb, PSTR> {
+    /// Provides access to the buffer through a writable slice of [`u8`]
+    ///
+    /// This is the ANSI (`*A`) version of [`as_mut_slice`](Argument::as_mut_slice) for Windows
+    /// API calls, like [`GetModuleFileNameA`][1], that take a `&mut [u8]`.
+    ///
+    /// The same hazard applies; call [`zero_fill`](Argument::zero_fill) first or use
+    /// [`as_uninit_slice`](Argument::as_uninit_slice).
+    ///
+    /// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/LibraryLoader/fn.GetModuleFileNameA.html
+    ///
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        let rv = unsafe { from_raw_parts_mut(self.pointer.0, self.size as usize) };
+        unsafe { SetLastError(NO_ERROR) };
+        rv
+    }
+    /// Provides access to the buffer through a writable slice of possibly uninitialized [`u8`].
+    ///
+    /// This is the ANSI (`*A`) version of [`as_uninit_slice`](Argument::as_uninit_slice).
+    ///
+    pub fn as_uninit_slice(&mut self) -> &mut [MaybeUninit<u8>] {
+        let rv = unsafe { from_raw_parts_mut(self.pointer.0.cast(), self.size as usize) };
+        unsafe { SetLastError(NO_ERROR) };
+        rv
+    }
+}
+
+impl<'gb, FT> Argument<'gb, *mut FT> {
+    /// Provides access to the buffer through a writable slice of possibly uninitialized `FT`s.
+    ///
+    /// The slice holds as many whole `FT`s as fit in the buffer.  It is empty when `FT` is
+    /// zero-sized or the buffer is not aligned for `FT`.  Use this instead of building a slice
+    /// from [`pointer`](Argument::pointer) for a Windows API function that fills an array of
+    /// `FT`.
+    ///
+    pub fn as_uninit_elements(&mut self) -> &mut [MaybeUninit<FT>] {
+        let aligned = self.pointer.align_offset(std::mem::align_of::<FT>()) == 0;
+        let length = match size_of::<FT>() {
+            0 => 0,
+            _ if !aligned => 0,
+            element_size => self.capacity as usize / element_size,
+        };
+        let rv = unsafe { from_raw_parts_mut(self.pointer.cast(), length) };
+        unsafe { SetLastError(NO_ERROR) };
+        rv
+    }
 }
 
 /// Wrapper for the return value from a Windows API call that returns an error code.
@@ -103,7 +407,7 @@ impl<'gb> Argument<'gb, PWSTR> {
 ///         GET_ADAPTERS_ADDRESSES_FLAGS(0),
 ///         None,
 ///         Some(argument.pointer()),
-///         argument.size(),
+///         argument.size_mut(),
 ///     )
 /// });
 ///
@@ -114,6 +418,22 @@ impl<'gb> Argument<'gb, PWSTR> {
 /// [`GetLogicalProcessorInformationEx`][3] is also a good example for [`RvIsError`].  A complete
 /// example is available on [GitHub][4].
 ///
+/// When the API call returns a [`BOOL`], [`GetLastError`] is called when the [`RvIsError`] is
+/// created.  Anything that runs between the API call and the conversion, like logging or an
+/// allocation, can overwrite the last error.  Create the [`RvIsError`] in the same expression as
+/// the call, use [`checked_bool`], or capture the error with [`capture_last_error`] and use
+/// [`from_bool_with_last_error`](RvIsError::from_bool_with_last_error).
+///
+/// ``` ignore
+/// let rv = checked_bool(|| unsafe {
+///     GetLogicalProcessorInformationEx(
+///         RelationGroup,
+///         Some(argument.pointer()),
+///         argument.size_mut(),
+///     )
+/// });
+/// ```
+///
 /// [gc]: https://crates.io/crates/grob
 /// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/NetworkManagement/IpHelper/fn.GetAdaptersAddresses.html
 /// [2]: https://github.com/Coding-Badly/grob/blob/main/grob/examples/adapters-addresses-full.rs
@@ -121,7 +441,14 @@ impl<'gb> Argument<'gb, PWSTR> {
 /// [4]: https://github.com/Coding-Badly/grob/blob/main/grob/examples/processor-full.rs
 ///
 #[derive(Debug)]
-pub struct RvIsError(WIN32_ERROR);
+pub struct RvIsError(WIN32_ERROR, WIN32_ERROR, bool, Option<ExtraAction>);
+
+// The action for an error code passed to grow_on or no_data_on.
+#[derive(Clone, Copy, Debug)]
+enum ExtraAction {
+    Grow,
+    NoData,
+}
 
 impl RvIsError {
     pub fn new<T>(value: T) -> Self
@@ -130,6 +457,75 @@ impl RvIsError {
     {
         value.into()
     }
+
+    /// Create an [`RvIsError`] from a [`BOOL`] and a last error captured earlier.
+    ///
+    /// `last` should be the value returned from [`capture_last_error`] immediately after the API
+    /// call.  Like the [`BOOL`] conversion, `last` is only used as the error when `rv` is not
+    /// [`TRUE`].
+    ///
+    /// # Examples
+    ///
+    /// ``` ignore
+    /// let rv = unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) };
+    /// let last = capture_last_error();
+    /// log::trace!("GetUserNameW returned {:?}", rv);
+    /// RvIsError::from_bool_with_last_error(rv, last)
+    /// ```
+    ///
+    pub fn from_bool_with_last_error(rv: BOOL, last: WIN32_ERROR) -> Self {
+        if rv == TRUE {
+            Self(NO_ERROR, last, true, None)
+        } else {
+            Self(last, last, true, None)
+        }
+    }
+
+    /// Treat [`ERROR_MORE_DATA`] as an error instead of a request to grow the buffer.
+    ///
+    /// Some functions, like [`NetUserEnum`][1], use [`ERROR_MORE_DATA`] to report that more
+    /// entries are available instead of a buffer that is too small.  Callers that need the error
+    /// returned should use this method.
+    ///
+    /// [1]: https://learn.microsoft.com/en-us/windows/win32/api/lmaccess/nf-lmaccess-netuserenum
+    ///
+    pub fn more_data_is_error(mut self) -> Self {
+        self.2 = false;
+        self
+    }
+
+    /// Treat the error codes in `codes` as a request to grow the buffer.
+    ///
+    /// Some functions report a buffer that is too small with an unusual error code like
+    /// `WSAEFAULT`, `ERROR_NOT_ENOUGH_MEMORY`, or `ERROR_INVALID_USER_BUFFER`.  The built-in codes
+    /// are still recognized.
+    ///
+    /// # Examples
+    ///
+    /// ``` ignore
+    /// let rv = RvIsError::new(rv).grow_on(&[ERROR_NOT_ENOUGH_MEMORY]);
+    /// ```
+    ///
+    pub fn grow_on(self, codes: &[WIN32_ERROR]) -> Self {
+        self.extra(codes, ExtraAction::Grow)
+    }
+
+    /// Treat the error codes in `codes` as no data.
+    ///
+    /// For example, `ERROR_FILE_NOT_FOUND` from a function that reads an optional value.  The
+    /// built-in codes are still recognized.
+    ///
+    pub fn no_data_on(self, codes: &[WIN32_ERROR]) -> Self {
+        self.extra(codes, ExtraAction::NoData)
+    }
+
+    // Success always means commit.
+    fn extra(mut self, codes: &[WIN32_ERROR], action: ExtraAction) -> Self {
+        if self.0 != NO_ERROR && codes.contains(&self.0) {
+            self.3 = Some(action);
+        }
+        self
+    }
 }
 
 impl ToResult for RvIsError {
@@ -149,179 +545,1210 @@ impl ToResult for RvIsError {
     /// | [`NO_ERROR`]                  | Ok([`FillBufferAction::Commit`]) |
     /// | [`ERROR_INSUFFICIENT_BUFFER`] | Ok([`FillBufferAction::Grow`])   |
     /// | [`ERROR_BUFFER_OVERFLOW`]     | Ok([`FillBufferAction::Grow`])   |
+    /// | [`ERROR_MORE_DATA`]           | Ok([`FillBufferAction::Grow`])   |
     /// | [`ERROR_NO_DATA`]             | Ok([`FillBufferAction::NoData`]) |
     /// | all other values              | Err(/\*osecctsie\*/)             |
     ///
     /// Where /\*osecctsie\*/ is the operating system error code converted to a [`std::io::Error`]
     /// by calling [`from_raw_os_error`][1].
     ///
+    /// Functions like [`WNetGetConnectionW`][5] report a buffer that is too small with
+    /// [`ERROR_MORE_DATA`].  [`more_data_is_error`](RvIsError::more_data_is_error) returns it as
+    /// an error instead.  [`grow_on`](RvIsError::grow_on) and
+    /// [`no_data_on`](RvIsError::no_data_on) add codes to the table.
+    ///
+    /// The value from [`GetLastError`] is recorded as a hint, even on success, and is available
+    /// after freezing from [`last_os_error_hint`][4].
+    ///
     /// [1]: std::io::Error::from_raw_os_error
+    /// [4]: crate::FrozenBuffer::last_os_error_hint
+    /// [5]: https://learn.microsoft.com/en-us/windows/win32/api/winnetwk/nf-winnetwk-wnetgetconnectionw
     /// [2]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/NetworkManagement/IpHelper/fn.GetAdaptersAddresses.html
     /// [3]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/SystemInformation/fn.GetLogicalProcessorInformationEx.html
     ///
     fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
-        let rv = match self.0 {
-            NO_ERROR => Ok(FillBufferAction::Commit),
-            ERROR_INSUFFICIENT_BUFFER => Ok(FillBufferAction::Grow),
-            ERROR_BUFFER_OVERFLOW => Ok(FillBufferAction::Grow),
-            ERROR_NO_DATA => Ok(FillBufferAction::NoData),
-            c => Err(std::io::Error::from_raw_os_error(c.0 as i32)),
+        needed_size.set_os_error_hint(self.1 .0);
+        let rv = match self.3 {
+            Some(ExtraAction::Grow) => Ok(FillBufferAction::Grow),
+            Some(ExtraAction::NoData) => Ok(FillBufferAction::NoData),
+            None => match self.0 {
+                NO_ERROR => Ok(FillBufferAction::Commit),
+                ERROR_INSUFFICIENT_BUFFER => Ok(FillBufferAction::Grow),
+                ERROR_BUFFER_OVERFLOW => Ok(FillBufferAction::Grow),
+                ERROR_MORE_DATA if self.2 => Ok(FillBufferAction::Grow),
+                ERROR_NO_DATA => Ok(FillBufferAction::NoData),
+                c => {
+                    needed_size.set_failed();
+                    Err(win32_err(c))
+                }
+            },
         };
-        if rv.is_ok() && needed_size.needed_size() == 0 {
-            Ok(FillBufferAction::NoData)
-        } else {
-            rv
-        }
+        empty_is_no_data(rv, needed_size)
+    }
+}
+
+// A successful call that stored nothing has no data.
+fn empty_is_no_data(rv: FillBufferResult, needed_size: &dyn NeededSize) -> FillBufferResult {
+    if rv.is_ok() && needed_size.needed_size() == 0 {
+        Ok(FillBufferAction::NoData)
+    } else {
+        rv
     }
 }
 
 impl From<BOOL> for RvIsError {
     fn from(value: BOOL) -> Self {
-        if value == TRUE {
-            Self(NO_ERROR)
-        } else {
-            Self(unsafe { GetLastError() })
-        }
+        // Some functions set the last error even when they succeed.  It's kept as a hint.
+        Self::from_bool_with_last_error(value, capture_last_error())
     }
 }
 
+/// Returns the calling thread's last error from [`GetLastError`].
+///
+/// Call this immediately after the Windows API call then pass the value to
+/// [`RvIsError::from_bool_with_last_error`].
+///
+pub fn capture_last_error() -> WIN32_ERROR {
+    unsafe { GetLastError() }
+}
+
+/// Makes a Windows API call that returns a [`BOOL`] and converts the result to an [`RvIsError`].
+///
+/// The last error is captured as soon as `api_call` returns so nothing else can overwrite it.
+///
+/// # Examples
+///
+/// ``` ignore
+/// let rv = checked_bool(|| unsafe { GetUserNameW(argument.pointer(), argument.size_mut()) });
+/// ```
+///
+pub fn checked_bool<F>(api_call: F) -> RvIsError
+where
+    F: FnOnce() -> BOOL,
+{
+    let rv = api_call();
+    RvIsError::from_bool_with_last_error(rv, capture_last_error())
+}
+
 impl From<u32> for RvIsError {
     fn from(value: u32) -> Self {
-        Self(WIN32_ERROR(value))
+        Self(WIN32_ERROR(value), WIN32_ERROR(value), true, None)
     }
 }
 
-/// Wrapper for the return value from a Windows API call that returns the number of elements stored
+/// Wrapper for the `LSTATUS` returned from a registry function like [`RegQueryValueExW`][1].
 ///
-/// The primary purpose of [`RvIsSize`] is to convert the number of elements stored and the value
-/// returned from [`GetLastError`] into a [`FillBufferResult`].  The [`FillBufferResult`] is either
-/// Ok([`FillBufferAction`]) or an operating system error (Err([`std::io::Error`])) that is not
-/// handled by the [grob crate][gc].
+/// The registry functions return the error code instead of setting the last error.  The
+/// [windows crate][ws] declares the return value as a [`WIN32_ERROR`]; the Windows SDK declares
+/// it as an `LSTATUS` (`i32`).  Both convert to an [`RvIsLstatus`] without a cast.
 ///
 /// # Examples
 ///
-/// [`GetModuleFileNameW`][1] is a good example for [`RvIsSize`].  A complete example is
-/// available on [GitHub][2].
-///
 /// ``` ignore
-/// let mut argument = growable_buffer.argument();
-/// let rv = unsafe { GetModuleFileNameW(HMODULE(0), argument.as_mut_slice()) };
-/// let rv: RvIsSize = rv.into();
-/// let result = rv.to_result(&mut argument);
-/// match result? {
-///     FillBufferAction::Commit => {
-///         argument.commit();
-///         break;
-///     }
-///     FillBufferAction::Grow => {
-///         argument.grow();
-///     }
-///     FillBufferAction::NoData => {
-///         argument.commit_no_data();
-///         break;
-///     }
-/// }
+/// let rv = RvIsLstatus::new(unsafe {
+///     RegQueryValueExW(
+///         key,
+///         w!("ProductName"),
+///         None,
+///         None,
+///         Some(argument.pointer()),
+///         Some(argument.size_mut()),
+///     )
+/// });
 /// ```
 ///
-/// [`GetSystemWindowsDirectoryW`][3] is also a good example for [`RvIsError`].  A complete example
-/// is available on [GitHub][4].
-///
-/// [gc]: https://crates.io/crates/grob
-/// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/LibraryLoader/fn.GetModuleFileNameW.html
-/// [2]: https://github.com/Coding-Badly/grob/blob/main/grob/examples/module-filename-full.rs
-/// [3]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/SystemInformation/fn.GetSystemWindowsDirectoryW.html
-/// [4]: https://github.com/Coding-Badly/grob/blob/main/grob/examples/version-info-generic.rs
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regqueryvalueexw
+/// [ws]: https://crates.io/crates/windows
 ///
 #[derive(Debug)]
-pub struct RvIsSize(u32, WIN32_ERROR);
+pub struct RvIsLstatus(WIN32_ERROR, bool);
 
-impl RvIsSize {
+impl RvIsLstatus {
     pub fn new<T>(value: T) -> Self
     where
         T: Into<Self>,
     {
         value.into()
     }
-}
 
-impl ToResult for RvIsSize {
-    /// Determines what should happen based on the value returned from the operating system and the
-    /// [`Argument`] state.
+    /// Treat [`ERROR_FILE_NOT_FOUND`] as an error instead of no data.
     ///
-    /// The return value from the operating system is expected to be the number of elements stored.
+    /// By default a value or key that does not exist is the same as a value with no data.
     ///
-    /// The return value from [`GetLastError`] is captured when [`RvIsSize`] is created.  It's
-    /// important to "clear" the error value by calling `SetLastError(NO_ERROR)` just before calling
-    /// the Windows API function then creating an [`RvIsSize`] right after calling the Windows API
-    /// function.  This crate handles all of that when used as documented.
+    pub fn file_not_found_is_error(mut self) -> Self {
+        self.1 = false;
+        self
+    }
+}
+
+impl ToResult for RvIsLstatus {
+    /// Determines what should happen based on the `LSTATUS` returned from the operating system and
+    /// the [`Argument`] state.
     ///
-    /// The various states are translated as...
+    /// | `LSTATUS`                | [`FillBufferResult`]             |
+    /// | ------------------------ | -------------------------------- |
+    /// | [`NO_ERROR`]             | Ok([`FillBufferAction::Commit`]) |
+    /// | [`ERROR_MORE_DATA`]      | Ok([`FillBufferAction::Grow`])   |
+    /// | [`ERROR_FILE_NOT_FOUND`] | Ok([`FillBufferAction::NoData`]) |
+    /// | all other values         | Err(/\*osecctsie\*/)             |
     ///
-    /// | Return Value       | Capacity | [`GetLastError`]              | [`FillBufferResult`]             |
-    /// | ------------------ | -------- | ----------------------------- | -------------------------------- |
-    /// | zero               | n/a      | [`NO_ERROR`]                  | Ok([`FillBufferAction::NoData`]) |
-    /// | zero               | zero     | n/a                           | Ok([`FillBufferAction::Grow`])   |
-    /// | zero               | not zero | all other values              | Err(/\*osecctsie\*/)             |
-    /// | > 0 && < Capacity  | > 0      | n/a                           | Ok([`FillBufferAction::Commit`]) |
-    /// | > 0 && == Capacity | > 0      | [`ERROR_INSUFFICIENT_BUFFER`] | Ok([`FillBufferAction::Grow`])   |
+    /// Where /\*osecctsie\*/ is the operating system error code converted to a [`std::io::Error`].
+    /// [`file_not_found_is_error`](RvIsLstatus::file_not_found_is_error) returns
+    /// [`ERROR_FILE_NOT_FOUND`] as an error instead.
     ///
-    /// Where /\*osecctsie\*/ is the operating system error code converted to a [`std::io::Error`]
+    /// Like [`RvIsError`], success with a needed size of zero is Ok([`FillBufferAction::NoData`]).
+    ///
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        needed_size.set_os_error_hint(self.0 .0);
+        let rv = match self.0 {
+            NO_ERROR => Ok(FillBufferAction::Commit),
+            ERROR_MORE_DATA => Ok(FillBufferAction::Grow),
+            ERROR_FILE_NOT_FOUND if self.1 => Ok(FillBufferAction::NoData),
+            c => {
+                needed_size.set_failed();
+                Err(win32_err(c))
+            }
+        };
+        empty_is_no_data(rv, needed_size)
+    }
+}
+
+impl From<WIN32_ERROR> for RvIsLstatus {
+    fn from(value: WIN32_ERROR) -> Self {
+        Self(value, true)
+    }
+}
+
+impl From<i32> for RvIsLstatus {
+    fn from(value: i32) -> Self {
+        Self(WIN32_ERROR(value as u32), true)
+    }
+}
+
+/// Wrapper for the return value from a Windows Sockets call like [`WSAEnumProtocolsW`][1].
+///
+/// The Windows Sockets functions return `SOCKET_ERROR` on failure with the reason available from
+/// [`WSAGetLastError`][2] instead of [`GetLastError`].  A buffer that is too small is reported
+/// with `WSAEFAULT` or `WSAENOBUFS` and the size needed is stored in a separate length parameter.
+/// Pass [`size_mut`](Argument::size_mut) as the length parameter.
+///
+/// The value from `WSAGetLastError` is captured when [`RvIsWsa`] is created so create it right
+/// after the call.
+///
+/// # Examples
+///
+/// ``` ignore
+/// let rv = RvIsWsa::new(unsafe {
+///     WSAEnumProtocolsW(None, Some(argument.pointer()), argument.size_mut())
+/// });
+/// ```
+///
+/// A complete example is available on [GitHub][3].
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/winsock2/nf-winsock2-wsaenumprotocolsw
+/// [2]: https://learn.microsoft.com/en-us/windows/win32/api/winsock/nf-winsock-wsagetlasterror
+/// [3]: https://github.com/Coding-Badly/grob/blob/main/grob/examples/wsa-protocols-generic.rs
+///
+#[derive(Debug)]
+pub struct RvIsWsa(i32, WSA_ERROR);
+
+impl RvIsWsa {
+    pub fn new<T>(value: T) -> Self
+    where
+        T: Into<Self>,
+    {
+        value.into()
+    }
+}
+
+impl ToResult for RvIsWsa {
+    /// Determines what should happen based on the value returned from the operating system and the
+    /// [`Argument`] state.
+    ///
+    /// | Return Value       | `WSAGetLastError` | [`FillBufferResult`]             |
+    /// | ------------------ | ----------------- | -------------------------------- |
+    /// | not `SOCKET_ERROR` | n/a               | Ok([`FillBufferAction::Commit`]) |
+    /// | `SOCKET_ERROR`     | `WSAEFAULT`       | Ok([`FillBufferAction::Grow`])   |
+    /// | `SOCKET_ERROR`     | `WSAENOBUFS`      | Ok([`FillBufferAction::Grow`])   |
+    /// | `SOCKET_ERROR`     | all other values  | Err(/\*osecctsie\*/)             |
+    ///
+    /// Where /\*osecctsie\*/ is the Windows Sockets error code converted to a [`std::io::Error`]
+    /// by calling [`from_raw_os_error`][1].
+    ///
+    /// Like [`RvIsError`], success with a needed size of zero is Ok([`FillBufferAction::NoData`]).
+    ///
+    /// [1]: std::io::Error::from_raw_os_error
+    ///
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        needed_size.set_os_error_hint(self.1 .0 as u32);
+        if self.0 != SOCKET_ERROR {
+            empty_is_no_data(Ok(FillBufferAction::Commit), needed_size)
+        } else if self.1 == WSAEFAULT || self.1 == WSAENOBUFS {
+            Ok(FillBufferAction::Grow)
+        } else {
+            needed_size.set_failed();
+            Err(std::io::Error::from_raw_os_error(self.1 .0))
+        }
+    }
+}
+
+impl From<i32> for RvIsWsa {
+    fn from(value: i32) -> Self {
+        let wsa_error = unsafe { WSAGetLastError() };
+        Self(value, wsa_error)
+    }
+}
+
+/// Wrapper for a closure that converts the value returned from a Windows API call.
+///
+/// For functions with one-off semantics, like [`GetSystemFirmwareTable`][1], [`RvWith`] expresses
+/// the commit and grow logic inline without defining a new type.  The closure is given the
+/// [`NeededSize`] and returns the [`FillBufferResult`] exactly like
+/// [`to_result`](ToResult::to_result).  Calling [`set_failed`](NeededSize::set_failed) before
+/// returning an error lets a hand-written loop drop the [`Argument`] without dismissing it.
+///
+/// # Examples
+///
+/// ``` ignore
+/// // GetSystemFirmwareTable returns the size of the table.  Zero is failure.  Nothing is stored
+/// // when the size is larger than the buffer.
+/// let rv = unsafe { GetSystemFirmwareTable(RSMB, 0, Some(argument.as_mut_slice())) };
+/// let gle = unsafe { GetLastError() };
+/// RvWith::new(move |needed_size: &mut dyn NeededSize| {
+///     if rv == 0 {
+///         needed_size.set_failed();
+///         return Err(win32_err(gle));
+///     }
+///     let action = if rv > needed_size.needed_size() {
+///         FillBufferAction::Grow
+///     } else {
+///         FillBufferAction::Commit
+///     };
+///     needed_size.set_needed_size(rv);
+///     Ok(action)
+/// })
+/// ```
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getsystemfirmwaretable
+///
+pub struct RvWith<F>(F);
+
+impl<F> RvWith<F>
+where
+    F: Fn(&mut dyn NeededSize) -> FillBufferResult,
+{
+    pub fn new(f: F) -> Self {
+        Self(f)
+    }
+}
+
+impl<F> std::fmt::Debug for RvWith<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RvWith")
+    }
+}
+
+impl<F> ToResult for RvWith<F>
+where
+    F: Fn(&mut dyn NeededSize) -> FillBufferResult,
+{
+    /// Returns what the closure returns.
+    ///
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        (self.0)(needed_size)
+    }
+}
+
+// The facility of an HRESULT that holds a Win32 error code.
+const FACILITY_WIN32: u32 = 7;
+
+/// Wrapper for the `HRESULT` returned from a shell or WinHTTP function like
+/// [`SHGetFolderPathEx`][1].
+///
+/// A buffer that is too small is usually reported with `E_NOT_SUFFICIENT_BUFFER`; an `HRESULT`
+/// with the Win32 facility and `ERROR_INSUFFICIENT_BUFFER` as the code.  The Win32 code is
+/// extracted and handled exactly like [`RvIsError`] handles it.
+///
+/// # Examples
+///
+/// ``` ignore
+/// let rv = RvIsHresult::new(unsafe {
+///     SHGetFolderPathEx(&FOLDERID_Profile, 0, None, argument.as_mut_slice())
+/// });
+/// ```
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/shlobj_core/nf-shlobj_core-shgetfolderpathex
+///
+#[derive(Debug)]
+pub struct RvIsHresult(HRESULT);
+
+impl RvIsHresult {
+    pub fn new<T>(value: T) -> Self
+    where
+        T: Into<Self>,
+    {
+        value.into()
+    }
+}
+
+impl ToResult for RvIsHresult {
+    /// Determines what should happen based on the `HRESULT` returned from the operating system and
+    /// the [`Argument`] state.
+    ///
+    /// | `HRESULT`                             | [`FillBufferResult`]             |
+    /// | ------------------------------------- | -------------------------------- |
+    /// | success like `S_OK` or `S_FALSE`      | Ok([`FillBufferAction::Commit`]) |
+    /// | failure with the Win32 facility       | the same as [`RvIsError`]        |
+    /// | all other failures                    | Err(/\*hresult\*/)               |
+    ///
+    /// Where /\*hresult\*/ is a [`std::io::Error`] with a kind of
+    /// [`Other`](std::io::ErrorKind::Other) and the `HRESULT` in the message.
+    ///
+    /// Like [`RvIsError`], success with a needed size of zero is Ok([`FillBufferAction::NoData`]).
+    /// That includes `S_FALSE` from functions that use it to report nothing was found.
+    ///
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        let hresult = self.0 .0 as u32;
+        if self.0.is_ok() {
+            empty_is_no_data(Ok(FillBufferAction::Commit), needed_size)
+        } else if (hresult >> 16) & 0x1FFF == FACILITY_WIN32 {
+            RvIsError::new(hresult & 0xFFFF).to_result(needed_size)
+        } else {
+            needed_size.set_os_error_hint(hresult);
+            needed_size.set_failed();
+            Err(std::io::Error::other(format!("HRESULT {:#010X}", hresult)))
+        }
+    }
+}
+
+impl From<HRESULT> for RvIsHresult {
+    fn from(value: HRESULT) -> Self {
+        Self(value)
+    }
+}
+
+impl From<i32> for RvIsHresult {
+    fn from(value: i32) -> Self {
+        Self(HRESULT(value))
+    }
+}
+
+// The bit that marks an HRESULT holding an NTSTATUS (HRESULT_FROM_NT).
+const FACILITY_NT_BIT: i32 = 0x1000_0000;
+
+/// Wrapper for the `SECURITY_STATUS` or `NTSTATUS` returned from a CNG function like
+/// [`BCryptGetProperty`][1] or [`NCryptExportKey`][2].
+///
+/// A buffer that is too small is reported with `STATUS_BUFFER_TOO_SMALL` or
+/// `NTE_BUFFER_TOO_SMALL` and the size needed, in bytes, is stored through the `pcbResult`
+/// parameter.  Pass [`size_mut`](Argument::size_mut) as `pcbResult`.
+///
+/// The [windows crate][ws] converts the status to a `windows::core::Result<()>`; the `NTSTATUS`
+/// from the BCrypt functions is stored in the error as an `HRESULT` with the NT facility bit set.
+/// That, an `HRESULT`, an `NTSTATUS`, and an `i32` `SECURITY_STATUS` all convert to an
+/// [`RvIsSecStatus`].
+///
+/// # Examples
+///
+/// ``` ignore
+/// // The number of bytes needed for a hash object
+/// let rv = RvIsSecStatus::new(unsafe {
+///     BCryptGetProperty(
+///         algorithm,
+///         BCRYPT_OBJECT_LENGTH,
+///         Some(argument.as_mut_slice()),
+///         argument.size_mut(),
+///         0,
+///     )
+/// });
+/// ```
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/bcrypt/nf-bcrypt-bcryptgetproperty
+/// [2]: https://learn.microsoft.com/en-us/windows/win32/api/ncrypt/nf-ncrypt-ncryptexportkey
+/// [ws]: https://crates.io/crates/windows
+///
+#[derive(Debug)]
+pub struct RvIsSecStatus(HRESULT);
+
+impl RvIsSecStatus {
+    pub fn new<T>(value: T) -> Self
+    where
+        T: Into<Self>,
+    {
+        value.into()
+    }
+}
+
+impl ToResult for RvIsSecStatus {
+    /// Determines what should happen based on the status returned from the operating system and
+    /// the [`Argument`] state.
+    ///
+    /// | Status                                | [`FillBufferResult`]             |
+    /// | ------------------------------------- | -------------------------------- |
+    /// | success                               | Ok([`FillBufferAction::Commit`]) |
+    /// | `STATUS_BUFFER_TOO_SMALL`             | Ok([`FillBufferAction::Grow`])   |
+    /// | `NTE_BUFFER_TOO_SMALL`                | Ok([`FillBufferAction::Grow`])   |
+    /// | all other `NTSTATUS` failures         | Err(/\*ntstatus\*/)              |
+    /// | all other `HRESULT` failures          | the same as [`RvIsHresult`]      |
+    ///
+    /// Where /\*ntstatus\*/ is the status converted by [`ntstatus_err`].
+    ///
+    /// Like [`RvIsError`], success with a needed size of zero is Ok([`FillBufferAction::NoData`]).
+    ///
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        let hresult = self.0;
+        if hresult == NTE_BUFFER_TOO_SMALL {
+            Ok(FillBufferAction::Grow)
+        } else if hresult.is_err() && hresult.0 & FACILITY_NT_BIT != 0 {
+            let status = NTSTATUS(hresult.0 & !FACILITY_NT_BIT);
+            if status == STATUS_BUFFER_TOO_SMALL {
+                Ok(FillBufferAction::Grow)
+            } else {
+                needed_size.set_os_error_hint(status.0 as u32);
+                needed_size.set_failed();
+                Err(ntstatus_err(status))
+            }
+        } else {
+            RvIsHresult(hresult).to_result(needed_size)
+        }
+    }
+}
+
+impl From<windows::core::Result<()>> for RvIsSecStatus {
+    fn from(value: windows::core::Result<()>) -> Self {
+        match value {
+            Ok(()) => Self(HRESULT(0)),
+            Err(error) => Self(error.code()),
+        }
+    }
+}
+
+impl From<HRESULT> for RvIsSecStatus {
+    fn from(value: HRESULT) -> Self {
+        Self(value)
+    }
+}
+
+impl From<NTSTATUS> for RvIsSecStatus {
+    fn from(value: NTSTATUS) -> Self {
+        if value.is_ok() {
+            Self(HRESULT(value.0))
+        } else {
+            Self(value.to_hresult())
+        }
+    }
+}
+
+impl From<i32> for RvIsSecStatus {
+    fn from(value: i32) -> Self {
+        Self(HRESULT(value))
+    }
+}
+
+/// Wrapper for the return value from a Windows API call that returns the number of elements stored
+///
+/// The primary purpose of [`RvIsSize`] is to convert the number of elements stored and the value
+/// returned from [`GetLastError`] into a [`FillBufferResult`].  The [`FillBufferResult`] is either
+/// Ok([`FillBufferAction`]) or an operating system error (Err([`std::io::Error`])) that is not
+/// handled by the [grob crate][gc].
+///
+/// [`RvIsSize`] is for functions that return the capacity when the buffer is too small.  Functions
+/// like `GetCurrentDirectoryW` and `GetTempPathW` return the number of elements needed, including
+/// the `NULL` terminator, instead.  Use [`RvIsLength`] for those so the buffer grows to exactly
+/// the size needed.
+///
+/// # Examples
+///
+/// [`GetModuleFileNameW`][1] is a good example for [`RvIsSize`].  A complete example is
+/// available on [GitHub][2].
+///
+/// ``` ignore
+/// let mut argument = growable_buffer.argument();
+/// let rv = unsafe { GetModuleFileNameW(HMODULE(0), argument.as_mut_slice()) };
+/// let rv: RvIsSize = rv.into();
+/// let result = rv.to_result(&mut argument);
+/// match result? {
+///     FillBufferAction::Commit => {
+///         argument.commit();
+///         break;
+///     }
+///     FillBufferAction::Grow => {
+///         argument.grow()?;
+///     }
+///     FillBufferAction::NoData => {
+///         argument.commit_no_data();
+///         break;
+///     }
+/// }
+/// ```
+///
+/// [`GetSystemWindowsDirectoryW`][3] is also a good example for [`RvIsError`].  A complete example
+/// is available on [GitHub][4].
+///
+/// [gc]: https://crates.io/crates/grob
+/// [1]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/LibraryLoader/fn.GetModuleFileNameW.html
+/// [2]: https://github.com/Coding-Badly/grob/blob/main/grob/examples/module-filename-full.rs
+/// [3]: https://microsoft.github.io/windows-docs-rs/doc/windows/Win32/System/SystemInformation/fn.GetSystemWindowsDirectoryW.html
+/// [4]: https://github.com/Coding-Badly/grob/blob/main/grob/examples/version-info-generic.rs
+///
+#[derive(Debug)]
+pub struct RvIsSize(u32, WIN32_ERROR);
+
+impl RvIsSize {
+    pub fn new<T>(value: T) -> Self
+    where
+        T: Into<Self>,
+    {
+        value.into()
+    }
+}
+
+impl ToResult for RvIsSize {
+    /// Determines what should happen based on the value returned from the operating system and the
+    /// [`Argument`] state.
+    ///
+    /// The return value from the operating system is expected to be the number of elements stored.
+    ///
+    /// The return value from [`GetLastError`] is captured when [`RvIsSize`] is created.  It's
+    /// important to "clear" the error value by calling `SetLastError(NO_ERROR)` just before calling
+    /// the Windows API function then creating an [`RvIsSize`] right after calling the Windows API
+    /// function.  This crate handles all of that when used as documented.
+    ///
+    /// The various states are translated as...
+    ///
+    /// | Return Value       | Capacity | [`GetLastError`]              | [`FillBufferResult`]             |
+    /// | ------------------ | -------- | ----------------------------- | -------------------------------- |
+    /// | zero               | n/a      | [`NO_ERROR`]                  | Ok([`FillBufferAction::NoData`]) |
+    /// | zero               | zero     | n/a                           | Ok([`FillBufferAction::Grow`])   |
+    /// | zero               | not zero | all other values              | Err(/\*osecctsie\*/)             |
+    /// | > 0 && < Capacity  | > 0      | n/a                           | Ok([`FillBufferAction::Commit`]) |
+    /// | > 0 && == Capacity | > 0      | [`ERROR_INSUFFICIENT_BUFFER`] | Ok([`FillBufferAction::Grow`])   |
+    /// | > 0 && == Capacity | > 0      | all other values              | Err(/\*undocumented\*/)          |
+    /// | > Capacity         | n/a      | n/a                           | Err(/\*undocumented\*/)          |
+    ///
+    /// Where /\*osecctsie\*/ is the operating system error code converted to a [`std::io::Error`]
+    /// by calling [`from_raw_os_error`][1] and /\*undocumented\*/ is
+    /// [`GrowError::UndocumentedReturnValue`] converted to a [`std::io::Error`] with a kind of
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData).  The latter is returned even without the
+    /// `no_panic` feature.
+    ///
+    /// The value from [`GetLastError`] is recorded as a hint, even on success, and is available
+    /// after freezing from [`last_os_error_hint`][2].
+    ///
+    /// [1]: std::io::Error::from_raw_os_error
+    /// [2]: crate::FrozenBuffer::last_os_error_hint
+    ///
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        needed_size.set_os_error_hint(self.1 .0);
+        let ns = needed_size.needed_size();
+        // Either an error or success with nothing stored
+        if self.0 == 0 {
+            // Success with nothing stored
+            if self.1 == NO_ERROR {
+                Ok(FillBufferAction::NoData)
+            // The buffer has no capacity.  Very likely because the caller does not want to use a
+            // stack buffer.  The expectation is that the GrowStrategy will have a reasonable
+            // minimum capacity so we'll just indicate something more than zero.
+            } else if ns == 0 {
+                needed_size.set_needed_size(1);
+                Ok(FillBufferAction::Grow)
+            // Error
+            } else {
+                needed_size.set_failed();
+                Err(win32_err(self.1))
+            }
+        // Buffer was big enough.  self.1 is presumed to be NO_ERROR.
+        } else if self.0 < ns {
+            needed_size.set_needed_size(self.0);
+            Ok(FillBufferAction::Commit)
+        // Buffer does not have space for the terminator.
+        } else if self.1 == ERROR_INSUFFICIENT_BUFFER {
+            needed_size.set_needed_size(self.0.saturating_mul(2));
+            Ok(FillBufferAction::Grow)
+        // At this point the API function returned precisely the buffer capacity and set the last
+        // error to something other than ERROR_INSUFFICIENT_BUFFER.  Or, the API function returned a
+        // value greater than the capacity.  Those are both undocument behaviours.  API hooking
+        // products have been seen doing the former so an error is returned instead of panicking.
+        } else {
+            needed_size.set_failed();
+            Err(GrowError::UndocumentedReturnValue {
+                returned: self.0,
+                capacity: ns,
+                last_error: self.1 .0,
+            }
+            .into())
+        }
+    }
+}
+
+impl From<u32> for RvIsSize {
+    fn from(value: u32) -> Self {
+        let gle = unsafe { GetLastError() };
+        Self(value, gle)
+    }
+}
+
+/// Wrapper for the return value from a Windows API call that returns the number of elements stored
+/// on success and the number of elements needed, including the `NULL` terminator, when the buffer
+/// is too small.
+///
+/// [`GetEnvironmentVariableW`][1], [`GetCurrentDirectoryW`][2], and [`GetTempPathW`][3] work this
+/// way.  [`RvIsSize`] expects the return value to equal the capacity when the buffer is too small
+/// so it is not a good fit.
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/processenv/nf-processenv-getenvironmentvariablew
+/// [2]: https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-getcurrentdirectory
+/// [3]: https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-gettemppathw
+///
+#[derive(Debug)]
+pub struct RvIsLength(u32, WIN32_ERROR);
+
+impl RvIsLength {
+    pub fn new<T>(value: T) -> Self
+    where
+        T: Into<Self>,
+    {
+        value.into()
+    }
+}
+
+impl ToResult for RvIsLength {
+    /// Determines what should happen based on the value returned from the operating system and the
+    /// [`Argument`] state.
+    ///
+    /// The return value from [`GetLastError`] is captured when [`RvIsLength`] is created.  The
+    /// same care as [`RvIsSize`] is needed.
+    ///
+    /// The various states are translated as...
+    ///
+    /// | Return Value | [`GetLastError`] | [`FillBufferResult`]             |
+    /// | ------------ | ---------------- | -------------------------------- |
+    /// | zero         | [`NO_ERROR`]     | Ok([`FillBufferAction::NoData`]) |
+    /// | zero         | all other values | Err(/\*osecctsie\*/)             |
+    /// | < Capacity   | n/a              | Ok([`FillBufferAction::Commit`]) |
+    /// | >= Capacity  | n/a              | Ok([`FillBufferAction::Grow`])   |
+    ///
+    /// Where /\*osecctsie\*/ is the operating system error code converted to a [`std::io::Error`]
+    /// by calling [`from_raw_os_error`][1].
+    ///
+    /// When the buffer grows the return value is used as the needed size.  The `NULL` terminator
+    /// is not included in the size committed.
+    ///
+    /// [1]: std::io::Error::from_raw_os_error
+    ///
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        needed_size.set_os_error_hint(self.1 .0);
+        let ns = needed_size.needed_size();
+        if self.0 == 0 {
+            if self.1 == NO_ERROR {
+                Ok(FillBufferAction::NoData)
+            } else {
+                needed_size.set_failed();
+                Err(win32_err(self.1))
+            }
+        // The stored length does not include the terminator so it is always less than the capacity.
+        } else if self.0 < ns {
+            needed_size.set_needed_size(self.0);
+            Ok(FillBufferAction::Commit)
+        // The needed size includes the terminator.
+        } else {
+            needed_size.set_needed_size(self.0);
+            Ok(FillBufferAction::Grow)
+        }
+    }
+}
+
+impl From<u32> for RvIsLength {
+    fn from(value: u32) -> Self {
+        let gle = unsafe { GetLastError() };
+        Self(value, gle)
+    }
+}
+
+/// Wrapper for the return value from a Windows API call that returns the number of bytes required
+/// whether or not the buffer is big enough.
+///
+/// [`GetSystemFirmwareTable`][1] and [`EnumSystemFirmwareTables`][2] work this way.  Nothing is
+/// stored when the return value is larger than the buffer.  Zero is returned on failure and the
+/// error is available from [`GetLastError`].
+///
+/// # Examples
+///
+/// ``` ignore
+/// let rv = RvIsRequiredBytes::new(unsafe {
+///     GetSystemFirmwareTable(
+///         RSMB,
+///         FIRMWARE_TABLE_ID(0),
+///         Some(argument.pointer().cast()),
+///         *argument.size_mut(),
+///     )
+/// });
+/// ```
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getsystemfirmwaretable
+/// [2]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-enumsystemfirmwaretables
+///
+#[derive(Debug)]
+pub struct RvIsRequiredBytes(u32, WIN32_ERROR);
+
+impl RvIsRequiredBytes {
+    pub fn new<T>(value: T) -> Self
+    where
+        T: Into<Self>,
+    {
+        value.into()
+    }
+}
+
+impl ToResult for RvIsRequiredBytes {
+    /// Determines what should happen based on the value returned from the operating system and the
+    /// [`Argument`] state.
+    ///
+    /// The return value from [`GetLastError`] is captured when [`RvIsRequiredBytes`] is created.
+    ///
+    /// | Return Value | [`GetLastError`] | [`FillBufferResult`]             |
+    /// | ------------ | ---------------- | -------------------------------- |
+    /// | zero         | [`NO_ERROR`]     | Ok([`FillBufferAction::NoData`]) |
+    /// | zero         | all other values | Err(/\*osecctsie\*/)             |
+    /// | <= Capacity  | n/a              | Ok([`FillBufferAction::Commit`]) |
+    /// | > Capacity   | n/a              | Ok([`FillBufferAction::Grow`])   |
+    ///
+    /// Where /\*osecctsie\*/ is the operating system error code converted to a [`std::io::Error`]
+    /// by calling [`from_raw_os_error`][1].  The return value is used as the size needed when
+    /// growing.
+    ///
+    /// The value from [`GetLastError`] is recorded as a hint, even on success, and is available
+    /// after freezing from [`last_os_error_hint`][2].
+    ///
+    /// [1]: std::io::Error::from_raw_os_error
+    /// [2]: crate::FrozenBuffer::last_os_error_hint
+    ///
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        needed_size.set_os_error_hint(self.1 .0);
+        if self.0 == 0 {
+            if self.1 == NO_ERROR {
+                Ok(FillBufferAction::NoData)
+            } else {
+                needed_size.set_failed();
+                Err(win32_err(self.1))
+            }
+        } else if self.0 <= needed_size.needed_size() {
+            needed_size.set_needed_size(self.0);
+            Ok(FillBufferAction::Commit)
+        } else {
+            needed_size.set_needed_size(self.0);
+            Ok(FillBufferAction::Grow)
+        }
+    }
+}
+
+impl From<u32> for RvIsRequiredBytes {
+    fn from(value: u32) -> Self {
+        let gle = unsafe { GetLastError() };
+        Self(value, gle)
+    }
+}
+
+/// Wrapper for the return value from a Windows API call that fills the buffer without reporting
+/// the size needed.
+///
+/// [`EnumProcesses`][1] stores as many elements as fit and returns the number of bytes stored.
+/// The only sign the buffer was too small is that it is full.  [`RvIsFull`] pairs the return value
+/// with the number of bytes stored.
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/psapi/nf-psapi-enumprocesses
+///
+#[derive(Debug)]
+pub struct RvIsFull(RvIsError, u32);
+
+impl RvIsFull {
+    /// Pairs `rv`, the return value from the operating system call, with `stored`, the number of
+    /// bytes stored.
+    ///
+    /// The value from [`GetLastError`] is captured when `rv` is not [`TRUE`] so `new` has to be
+    /// called right after the operating system call.
+    ///
+    pub fn new(rv: BOOL, stored: u32) -> Self {
+        Self(RvIsError::new(rv), stored)
+    }
+}
+
+impl ToResult for RvIsFull {
+    /// Determines what should happen based on the value returned from the operating system, the
+    /// number of bytes stored, and the [`Argument`] state.
+    ///
+    /// Errors are handled by [`RvIsError`].  After success...
+    ///
+    /// | Bytes Stored | [`FillBufferResult`]             |
+    /// | ------------ | -------------------------------- |
+    /// | zero         | Ok([`FillBufferAction::NoData`]) |
+    /// | < Capacity   | Ok([`FillBufferAction::Commit`]) |
+    /// | >= Capacity  | Ok([`FillBufferAction::Grow`])   |
+    ///
+    /// A full buffer is assumed to be truncated.  The needed size is twice the capacity so the
+    /// [`GrowStrategy`][gs] doubles the buffer.
+    ///
+    /// [gs]: crate::GrowStrategy
+    ///
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        let ns = needed_size.needed_size();
+        if let FillBufferAction::Grow = self.0.to_result(needed_size)? {
+            return Ok(FillBufferAction::Grow);
+        }
+        if self.1 >= ns {
+            needed_size.set_needed_size(ns.max(1).saturating_mul(2));
+            Ok(FillBufferAction::Grow)
+        } else if self.1 == 0 {
+            Ok(FillBufferAction::NoData)
+        } else {
+            needed_size.set_needed_size(self.1);
+            Ok(FillBufferAction::Commit)
+        }
+    }
+}
+
+/// Wrapper for the return value from a Windows API call that returns the number of elements
+/// stored or `(UINT)-1` on failure.
+///
+/// [`GetRawInputDeviceList`][1] works this way.  When the buffer is too small it returns
+/// `(UINT)-1`, sets the last error to `ERROR_INSUFFICIENT_BUFFER`, and stores the number of
+/// elements needed through the size pointer.  Use it with an [`ElementCount`] pointer.
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getrawinputdevicelist
+///
+#[derive(Debug)]
+pub struct RvIsCount(u32, WIN32_ERROR);
+
+impl RvIsCount {
+    pub fn new<T>(value: T) -> Self
+    where
+        T: Into<Self>,
+    {
+        value.into()
+    }
+}
+
+impl ToResult for RvIsCount {
+    /// Determines what should happen based on the value returned from the operating system and the
+    /// [`Argument`] state.
+    ///
+    /// | Return Value | [`GetLastError`]              | [`FillBufferResult`]             |
+    /// | ------------ | ----------------------------- | -------------------------------- |
+    /// | `(UINT)-1`   | [`ERROR_INSUFFICIENT_BUFFER`] | Ok([`FillBufferAction::Grow`])   |
+    /// | `(UINT)-1`   | all other values              | Err(/\*osecctsie\*/)             |
+    /// | zero         | n/a                           | Ok([`FillBufferAction::NoData`]) |
+    /// | all other    | n/a                           | Ok([`FillBufferAction::Commit`]) |
+    ///
+    /// Where /\*osecctsie\*/ is the operating system error code converted to a [`std::io::Error`]
     /// by calling [`from_raw_os_error`][1].
     ///
     /// [1]: std::io::Error::from_raw_os_error
     ///
     fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
-        let ns = needed_size.needed_size();
-        // Either an error or success with nothing stored
-        if self.0 == 0 {
-            // Success with nothing stored
-            if self.1 == NO_ERROR {
-                Ok(FillBufferAction::NoData)
-            // The buffer has no capacity.  Very likely because the caller does not want to use a
-            // stack buffer.  The expectation is that the GrowStrategy will have a reasonable
-            // minimum capacity so we'll just indicate something more than zero.
-            } else if ns == 0 {
-                needed_size.set_needed_size(1);
+        needed_size.set_os_error_hint(self.1 .0);
+        if self.0 == u32::MAX {
+            if self.1 == ERROR_INSUFFICIENT_BUFFER {
                 Ok(FillBufferAction::Grow)
-            // Error
             } else {
-                Err(std::io::Error::from_raw_os_error(self.1 .0 as i32))
+                needed_size.set_failed();
+                Err(win32_err(self.1))
             }
-        // Buffer was big enough.  self.1 is presumed to be NO_ERROR.
-        } else if self.0 < ns {
+        } else if self.0 == 0 {
+            Ok(FillBufferAction::NoData)
+        } else {
             needed_size.set_needed_size(self.0);
             Ok(FillBufferAction::Commit)
-        // Buffer does not have space for the terminator.
-        } else if self.1 == ERROR_INSUFFICIENT_BUFFER {
-            needed_size.set_needed_size(self.0.saturating_mul(2));
-            Ok(FillBufferAction::Grow)
-        // At this point the API function returned precisely the buffer capacity and set the last
-        // error to something other than ERROR_INSUFFICIENT_BUFFER.  Or, the API function returned a
-        // value greater than the capacity.  Those are both undocument behaviours.
-        } else {
-            unreachable!()
         }
     }
 }
 
-impl From<u32> for RvIsSize {
+impl From<u32> for RvIsCount {
     fn from(value: u32) -> Self {
         let gle = unsafe { GetLastError() };
         Self(value, gle)
     }
 }
 
+/// Wrapper for the `NTSTATUS` returned from a native API call like [`NtQuerySystemInformation`][1].
+///
+/// Native API functions do not set the last error.  A buffer that is too small is reported with
+/// `STATUS_INFO_LENGTH_MISMATCH` or `STATUS_BUFFER_TOO_SMALL` and the size needed, in bytes, is
+/// stored in a separate `ReturnLength` parameter.  Either pass
+/// [`size_mut`](Argument::size_mut) as `ReturnLength` or pass a local variable and feed its
+/// value back with [`with_return_length`](RvIsNtstatus::with_return_length).
+///
+/// # Examples
+///
+/// ``` ignore
+/// let mut return_length = 0;
+/// let status = unsafe {
+///     NtQuerySystemInformation(
+///         SystemProcessInformation,
+///         argument.pointer().cast(),
+///         *argument.size_mut(),
+///         &mut return_length,
+///     )
+/// };
+/// RvIsNtstatus::with_return_length(status, return_length)
+/// ```
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/winternl/nf-winternl-ntquerysysteminformation
+///
+#[derive(Debug)]
+pub struct RvIsNtstatus(NTSTATUS, Option<u32>);
+
+impl RvIsNtstatus {
+    pub fn new<T>(value: T) -> Self
+    where
+        T: Into<Self>,
+    {
+        value.into()
+    }
+    /// Pairs `status` with the value stored in the `ReturnLength` parameter.
+    ///
+    /// The return length is set as the needed size before `status` is converted so the buffer
+    /// grows to fit and, on success, only the bytes returned are committed.
+    ///
+    pub fn with_return_length(status: NTSTATUS, return_length: u32) -> Self {
+        Self(status, Some(return_length))
+    }
+}
+
+impl ToResult for RvIsNtstatus {
+    /// Determines what should happen based on the `NTSTATUS` returned from the operating system and
+    /// the [`Argument`] state.
+    ///
+    /// | `NTSTATUS`                    | [`FillBufferResult`]             |
+    /// | ----------------------------- | -------------------------------- |
+    /// | success (zero or positive)    | Ok([`FillBufferAction::Commit`]) |
+    /// | `STATUS_INFO_LENGTH_MISMATCH` | Ok([`FillBufferAction::Grow`])   |
+    /// | `STATUS_BUFFER_TOO_SMALL`     | Ok([`FillBufferAction::Grow`])   |
+    /// | all other values              | Err([`ntstatus_err`])            |
+    ///
+    /// Like [`RvIsError`], success with a needed size of zero is Ok([`FillBufferAction::NoData`]).
+    ///
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        if let Some(return_length) = self.1 {
+            needed_size.set_needed_size(return_length);
+        }
+        let status = self.0;
+        if status.0 >= 0 {
+            if needed_size.needed_size() == 0 {
+                Ok(FillBufferAction::NoData)
+            } else {
+                Ok(FillBufferAction::Commit)
+            }
+        } else if status.0 == STATUS_INFO_LENGTH_MISMATCH.0 || status.0 == STATUS_BUFFER_TOO_SMALL.0
+        {
+            Ok(FillBufferAction::Grow)
+        } else {
+            needed_size.set_failed();
+            Err(ntstatus_err(status))
+        }
+    }
+}
+
+impl From<NTSTATUS> for RvIsNtstatus {
+    fn from(value: NTSTATUS) -> Self {
+        Self(value, None)
+    }
+}
+
+/// Wrapper for the [`CONFIGRET`] returned from a Configuration Manager call like
+/// [`CM_Get_Device_Interface_ListW`][1].
+///
+/// The Configuration Manager functions do not set the last error and do not report the size
+/// needed.  The size comes from a separate `_Size` function like
+/// [`CM_Get_Device_Interface_List_SizeW`][2].  The list can change between the two calls so the
+/// size should be queried on every try and stored with [`size_mut`](Argument::size_mut) before
+/// the list is requested.  [`device_interface_list`](crate::device_interface_list) does that.
+///
+/// # Examples
+///
+/// ``` ignore
+/// let mut length = 0;
+/// let cr = unsafe { CM_Get_Device_ID_List_SizeW(&mut length, PCWSTR::null(), 0) };
+/// if cr != CR_SUCCESS {
+///     return RvIsConfigRet::new(cr);
+/// }
+/// if *argument.size_mut() < length {
+///     *argument.size_mut() = length;
+///     return RvIsConfigRet::new(CR_BUFFER_SMALL);
+/// }
+/// RvIsConfigRet::new(unsafe { CM_Get_Device_ID_ListW(PCWSTR::null(), argument.as_mut_slice(), 0) })
+/// ```
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/cfgmgr32/nf-cfgmgr32-cm_get_device_interface_listw
+/// [2]: https://learn.microsoft.com/en-us/windows/win32/api/cfgmgr32/nf-cfgmgr32-cm_get_device_interface_list_sizew
+///
+#[derive(Debug)]
+pub struct RvIsConfigRet(CONFIGRET);
+
+impl RvIsConfigRet {
+    pub fn new<T>(value: T) -> Self
+    where
+        T: Into<Self>,
+    {
+        value.into()
+    }
+}
+
+impl ToResult for RvIsConfigRet {
+    /// Determines what should happen based on the [`CONFIGRET`] returned from the operating system
+    /// and the [`Argument`] state.
+    ///
+    /// | [`CONFIGRET`]       | [`FillBufferResult`]             |
+    /// | ------------------- | -------------------------------- |
+    /// | [`CR_SUCCESS`]      | Ok([`FillBufferAction::Commit`]) |
+    /// | [`CR_BUFFER_SMALL`] | Ok([`FillBufferAction::Grow`])   |
+    /// | all other values    | Err(/\*cmmcwe\*/)                |
+    ///
+    /// Where /\*cmmcwe\*/ is the [`CONFIGRET`] converted to an operating system error code by
+    /// [`CM_MapCrToWin32Err`][1] then to a [`std::io::Error`].  Codes without an equivalent
+    /// become `ERROR_GEN_FAILURE`.
+    ///
+    /// Like [`RvIsError`], success with a needed size of zero is Ok([`FillBufferAction::NoData`]).
+    ///
+    /// [1]: https://learn.microsoft.com/en-us/windows/win32/api/cfgmgr32/nf-cfgmgr32-cm_mapcrtowin32err
+    ///
+    fn to_result(&self, needed_size: &mut dyn NeededSize) -> FillBufferResult {
+        match self.0 {
+            CR_SUCCESS => {
+                if needed_size.needed_size() == 0 {
+                    Ok(FillBufferAction::NoData)
+                } else {
+                    Ok(FillBufferAction::Commit)
+                }
+            }
+            CR_BUFFER_SMALL => Ok(FillBufferAction::Grow),
+            cr => {
+                needed_size.set_failed();
+                let code = unsafe { CM_MapCrToWin32Err(cr, ERROR_GEN_FAILURE.0) };
+                Err(win32_err(WIN32_ERROR(code)))
+            }
+        }
+    }
+}
+
+impl From<CONFIGRET> for RvIsConfigRet {
+    fn from(value: CONFIGRET) -> Self {
+        Self(value)
+    }
+}
+
 impl RawToInternal for PWSTR {
     fn capacity_to_size(value: u32) -> u32 {
         // The size is specified in WCHARs.
-        value / crate::SIZE_OF_WCHAR
+        bytes_to_wchars(value)
     }
     fn convert_pointer(value: *mut u8) -> PWSTR {
         PWSTR(value as *mut u16)
     }
     fn size_to_capacity(value: u32) -> u32 {
-        // The size is specified in WCHARs.
-        value.saturating_mul(crate::SIZE_OF_WCHAR)
+        // The size is specified in WCHARs.  A size too large to represent saturates.
+        wchars_to_bytes(value).unwrap_or(u32::MAX)
+    }
+}
+
+impl RawToInternal for PSTR {
+    fn capacity_to_size(value: u32) -> u32 {
+        // The size is specified in CHARs (bytes).
+        value
+    }
+    fn convert_pointer(value: *mut u8) -> PSTR {
+        PSTR(value)
+    }
+    fn size_to_capacity(value: u32) -> u32 {
+        value
+    }
+}
+
+/// A typed pointer for operating system calls that measure the buffer in elements instead of bytes.
+///
+/// With a `*mut T` the [`Argument`] size is in bytes.  Functions like
+/// [`GetRawInputDeviceList`][1] take and return a count of `T`s.  With an `ElementCount<T>` the
+/// size is the number of whole `T`s that fit and the [`FrozenBuffer`] [`size`][s] is the number of
+/// `T`s stored.
+///
+/// [1]: https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getrawinputdevicelist
+/// [s]: crate::FrozenBuffer::size
+///
+#[derive(Debug)]
+pub struct ElementCount<T>(pub *mut T);
+
+impl<T> Clone for ElementCount<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ElementCount<T> {}
+
+impl<T> RawToInternal for ElementCount<T> {
+    fn capacity_to_size(value: u32) -> u32 {
+        // Only whole elements.  A zero sized type is counted as one byte.
+        value / size_of::<T>().max(1) as u32
+    }
+    fn convert_pointer(value: *mut u8) -> ElementCount<T> {
+        ElementCount(value as *mut T)
+    }
+    fn size_to_capacity(value: u32) -> u32 {
+        // A size too large to represent saturates.
+        value.saturating_mul(size_of::<T>().max(1) as u32)
+    }
+}
+
+/// What to do with `NULL`s at the end of text when converting a [`FrozenBuffer<u16>`] to a
+/// string.
+///
+/// Most functions store one `NULL` terminator; [`to_os_string`](FrozenBuffer::to_os_string)
+/// removes it.  Some pad the text with more than one `NULL` and others store data with meaningful
+/// `NULL`s in it.  Pass a `NulPolicy` to [`to_os_string_with`](FrozenBuffer::to_os_string_with)
+/// and friends to choose.  For `"a\0b\0\0"` the policies return:
+///
+/// | Policy                  | Returned        |
+/// |-------------------------|-----------------|
+/// | [`StripOne`][so]        | `"a\0b\0"`      |
+/// | [`StripAll`][sa]        | `"a\0b"`        |
+/// | [`TruncateAtFirst`][tf] | `"a"`           |
+/// | [`Keep`][k]             | `"a\0b\0\0"`    |
+///
+/// [so]: NulPolicy::StripOne
+/// [sa]: NulPolicy::StripAll
+/// [tf]: NulPolicy::TruncateAtFirst
+/// [k]: NulPolicy::Keep
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NulPolicy {
+    /// Remove one `NULL` from the end, if present.  This is what
+    /// [`to_os_string`](FrozenBuffer::to_os_string) does.
+    #[default]
+    StripOne,
+    /// Remove every `NULL` from the end.
+    StripAll,
+    /// End the text at the first `NULL`.
+    TruncateAtFirst,
+    /// Return every element stored, `NULL`s included.
+    Keep,
+}
+
+impl NulPolicy {
+    fn apply(self, v: &[u16]) -> &[u16] {
+        match self {
+            NulPolicy::StripOne => match v.split_last() {
+                Some((0, rest)) => rest,
+                _ => v,
+            },
+            NulPolicy::StripAll => {
+                let end = v.iter().rposition(|c| *c != 0).map_or(0, |i| i + 1);
+                &v[..end]
+            }
+            NulPolicy::TruncateAtFirst => {
+                let end = v.iter().position(|c| *c == 0).unwrap_or(v.len());
+                &v[..end]
+            }
+            NulPolicy::Keep => v,
+        }
     }
 }
 
@@ -340,6 +1767,14 @@ impl<'sb> FrozenBuffer<'sb, u16> {
     pub fn to_path_buf(&self) -> Option<PathBuf> {
         self.to_os_string().map(PathBuf::from)
     }
+    /// Convert the data in the buffer to a [`PathBuf`] handling trailing `NULL`s according to
+    /// `policy`.
+    ///
+    /// See [`to_path_buf`](FrozenBuffer::to_path_buf) and [`NulPolicy`].
+    ///
+    pub fn to_path_buf_with(&self, policy: NulPolicy) -> Option<PathBuf> {
+        self.to_os_string_with(policy).map(PathBuf::from)
+    }
     /// Convert the data in the buffer to an [`OsString`].
     ///
     /// If the call to [`read_buffer`](FrozenBuffer::read_buffer) returns a [`null`](std::ptr::null)
@@ -349,21 +1784,175 @@ impl<'sb> FrozenBuffer<'sb, u16> {
     /// A `NULL` terminator, if present, is not included in the returned [`OsString`].
     ///
     pub fn to_os_string(&self) -> Option<OsString> {
-        let (p, s) = self.read_buffer();
-        if s == 0 {
-            return None;
-        }
-        assert!(s > 0);
-        if let Some(p) = p {
+        self.to_os_string_with(NulPolicy::StripOne)
+    }
+    /// Convert the data in the buffer to an [`OsString`] handling trailing `NULL`s according to
+    /// `policy`.
+    ///
+    /// See [`to_os_string`](FrozenBuffer::to_os_string) and [`NulPolicy`].
+    ///
+    pub fn to_os_string_with(&self, policy: NulPolicy) -> Option<OsString> {
+        self.try_to_os_string_with(policy).ok().flatten()
+    }
+    /// Convert the data in the buffer to an [`OsString`] reporting inaccessible data as an error.
+    ///
+    /// `Ok(None)` is returned when zero elements were stored in the buffer.  An error is returned
+    /// when elements were stored but cannot be accessed; see
+    /// [`try_pointer`](FrozenBuffer::try_pointer).  Otherwise this method is identical to
+    /// [`to_os_string`](FrozenBuffer::to_os_string).
+    ///
+    pub fn try_to_os_string(&self) -> Result<Option<OsString>, std::io::Error> {
+        self.try_to_os_string_with(NulPolicy::StripOne)
+    }
+    /// Convert the data in the buffer to an [`OsString`] handling trailing `NULL`s according to
+    /// `policy` and reporting inaccessible data as an error.
+    ///
+    /// See [`try_to_os_string`](FrozenBuffer::try_to_os_string) and [`NulPolicy`].
+    ///
+    pub fn try_to_os_string_with(
+        &self,
+        policy: NulPolicy,
+    ) -> Result<Option<OsString>, std::io::Error> {
+        let s = self.size();
+        Ok(self.try_pointer()?.map(|p| {
             let v = unsafe { from_raw_parts(p, s as usize) };
-            // Protected by the "s == 0" check and assert above.
-            let last: usize = if *v.last().unwrap() == 0 { s - 1 } else { s }
-                .try_into()
-                .unwrap();
-            Some(OsString::from_wide(&v[..last]))
-        } else {
-            None
+            OsString::from_wide(policy.apply(v))
+        }))
+    }
+    /// Convert the data in the buffer to a [`PathBuf`] reporting inaccessible data as an error.
+    ///
+    /// See [`try_to_os_string`](FrozenBuffer::try_to_os_string) for details.
+    ///
+    pub fn try_to_path_buf(&self) -> Result<Option<PathBuf>, std::io::Error> {
+        Ok(self.try_to_os_string()?.map(PathBuf::from))
+    }
+    /// Take the data out of the buffer as a [`Vec<u16>`].
+    ///
+    /// The data, including a `NULL` terminator if one was stored, is copied.  See
+    /// [`FrozenBuffer::<u8>::into_vec`](FrozenBuffer::into_vec) for why the data is never moved.
+    /// An empty [`Vec`] is returned when zero elements were stored or the data cannot be
+    /// accessed.
+    ///
+    pub fn into_wide_vec(self) -> Vec<u16> {
+        self.copy_elements()
+    }
+    /// Split a list of `NULL` separated strings ending with an empty string (two `NULL`s in a row)
+    /// into [`OsString`]s.
+    ///
+    /// This is the `REG_MULTI_SZ` format returned from functions like
+    /// [`GetLogicalDriveStringsW`][gl] and [`QueryDosDeviceW`][qd].  The strings before the first
+    /// empty string are returned.  An empty list is returned when zero elements were stored or the
+    /// buffer only holds the terminator.  A list missing the final `NULL`(s) is accepted; the last
+    /// string ends at the end of the data.
+    ///
+    /// An error is returned when elements were stored but cannot be accessed; see
+    /// [`try_pointer`](FrozenBuffer::try_pointer).
+    ///
+    /// [gl]: https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-getlogicaldrivestringsw
+    /// [qd]: https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-querydosdevicew
+    ///
+    pub fn try_to_multi_sz(&self) -> Result<Vec<OsString>, std::io::Error> {
+        self.try_pointer()?;
+        Ok(self.to_string_list())
+    }
+    /// Split a list of `NULL` separated strings into [`OsString`]s.
+    ///
+    /// The same as [`try_to_multi_sz`](FrozenBuffer::try_to_multi_sz) except inaccessible data is
+    /// treated like zero elements stored; an empty list is returned.  See
+    /// [`strings`](FrozenBuffer::strings) to look at the strings without allocating.
+    ///
+    pub fn to_string_list(&self) -> Vec<OsString> {
+        self.strings().map(OsString::from_wide).collect()
+    }
+    /// Returns an iterator over a list of `NULL` separated strings that borrows the buffer.
+    ///
+    /// Each item is one string without its `NULL` terminator.  The iterator stops at the first
+    /// empty string (two `NULL`s in a row) or at the end of the data when the final `NULL`(s) are
+    /// missing.  Nothing is returned when zero elements were stored or the data cannot be
+    /// accessed.
+    ///
+    pub fn strings(&self) -> impl Iterator<Item = &[u16]> + '_ {
+        self.as_slice()
+            .split(|c| *c == 0)
+            .take_while(|s| !s.is_empty())
+    }
+    /// Try converting the data in the buffer to a [`String`] reporting inaccessible data as an
+    /// error.
+    ///
+    /// See [`to_string`](FrozenBuffer::to_string) for the meaning of the inner [`Result`] and
+    /// [`try_to_os_string`](FrozenBuffer::try_to_os_string) for when the outer error is returned.
+    ///
+    pub fn try_to_string(
+        &self,
+        lossy_ok: bool,
+    ) -> Result<Result<String, OsString>, std::io::Error> {
+        self.try_to_string_with(lossy_ok, NulPolicy::StripOne)
+    }
+    /// Try converting the data in the buffer to a [`String`] handling trailing `NULL`s according
+    /// to `policy` and reporting inaccessible data as an error.
+    ///
+    /// See [`try_to_string`](FrozenBuffer::try_to_string) and [`NulPolicy`].
+    ///
+    pub fn try_to_string_with(
+        &self,
+        lossy_ok: bool,
+        policy: NulPolicy,
+    ) -> Result<Result<String, OsString>, std::io::Error> {
+        Ok(match self.try_to_os_string_with(policy)? {
+            Some(s) => {
+                if lossy_ok {
+                    Ok(s.to_string_lossy().to_string())
+                } else {
+                    s.into_string()
+                }
+            }
+            None => Ok(String::new()),
+        })
+    }
+    /// Convert the data in the buffer to UTF-8 replacing the contents of `buffer`.
+    ///
+    /// `to_string_into` is [`try_to_string`](FrozenBuffer::try_to_string) for a caller-provided
+    /// [`String`].  The capacity of `buffer` is reused so converting many strings with the same
+    /// `buffer` does not allocate once `buffer` is large enough.  The data is converted directly
+    /// from UTF-16; there is no intermediate [`OsString`].
+    ///
+    /// Invalid UTF-16 is handled the same way as [`to_string`](FrozenBuffer::to_string).  If
+    /// `lossy_ok` is [`true`] each unpaired surrogate is replaced with
+    /// [`REPLACEMENT_CHARACTER`](char::REPLACEMENT_CHARACTER) and `Ok(Ok(()))` is returned.  If
+    /// `lossy_ok` is [`false`] `buffer` is left empty and `Ok(Err(raw_os_string))` is returned.
+    ///
+    /// A `NULL` terminator, if present, is not included.  `buffer` is left empty when zero
+    /// elements were stored.  An error is returned when elements were stored but cannot be
+    /// accessed; see [`try_pointer`](FrozenBuffer::try_pointer).
+    ///
+    pub fn to_string_into(
+        &self,
+        buffer: &mut String,
+        lossy_ok: bool,
+    ) -> Result<Result<(), OsString>, std::io::Error> {
+        buffer.clear();
+        let s = self.size();
+        let Some(p) = self.try_pointer()? else {
+            return Ok(Ok(()));
+        };
+        let v = unsafe { from_raw_parts(p, s as usize) };
+        let v = match v.split_last() {
+            Some((0, rest)) => rest,
+            _ => v,
+        };
+        // Every UTF-16 element is at least one UTF-8 byte.
+        buffer.reserve(v.len());
+        for c in char::decode_utf16(v.iter().copied()) {
+            match c {
+                Ok(c) => buffer.push(c),
+                Err(_) if lossy_ok => buffer.push(char::REPLACEMENT_CHARACTER),
+                Err(_) => {
+                    buffer.clear();
+                    return Ok(Err(OsString::from_wide(v)));
+                }
+            }
         }
+        Ok(Ok(()))
     }
     /// Try converting the data in the buffer to a [`String`].
     ///
@@ -384,16 +1973,78 @@ impl<'sb> FrozenBuffer<'sb, u16> {
     /// length / blank string is returned.
     ///
     pub fn to_string(&self, lossy_ok: bool) -> Result<String, OsString> {
-        match self.to_os_string() {
-            Some(s) => {
-                if lossy_ok {
-                    Ok(s.to_string_lossy().to_string())
-                } else {
-                    s.into_string()
-                }
-            }
-            None => Ok(String::new()),
-        }
+        self.to_string_with(lossy_ok, NulPolicy::StripOne)
+    }
+    /// Try converting the data in the buffer to a [`String`] handling trailing `NULL`s according
+    /// to `policy`.
+    ///
+    /// See [`to_string`](FrozenBuffer::to_string) and [`NulPolicy`].
+    ///
+    pub fn to_string_with(&self, lossy_ok: bool, policy: NulPolicy) -> Result<String, OsString> {
+        self.try_to_string_with(lossy_ok, policy)
+            .unwrap_or_else(|_| Ok(String::new()))
+    }
+}
+
+impl<'sb> FrozenBuffer<'sb, u8> {
+    /// Try converting ANSI (`*A` function) text in the buffer to a [`String`] reporting
+    /// inaccessible data as an error.
+    ///
+    /// The text is checked to be UTF-8.  That is always true for ASCII and for any text when the
+    /// active code page is UTF-8 (e.g. the application manifest sets `activeCodePage` to
+    /// `UTF-8`).  Text in another code page has to be converted by the caller, for example with
+    /// [`MultiByteToWideChar`][mb].
+    ///
+    /// If `lossy_ok` is [`true`] then invalid UTF-8 is replaced according to the
+    /// [`from_utf8_lossy`](String::from_utf8_lossy) documentation and `Ok(Ok(string))` is returned.
+    /// If `lossy_ok` is [`false`] and the text is not valid UTF-8 then `Ok(Err(raw_bytes))` is
+    /// returned.
+    ///
+    /// A `NULL` terminator, if present, is not included.  A blank string is returned when zero
+    /// bytes were stored.  An error is returned when bytes were stored but cannot be accessed; see
+    /// [`try_pointer`](FrozenBuffer::try_pointer).
+    ///
+    /// [mb]: https://learn.microsoft.com/en-us/windows/win32/api/stringapiset/nf-stringapiset-multibytetowidechar
+    ///
+    pub fn try_to_string_a(
+        &self,
+        lossy_ok: bool,
+    ) -> Result<Result<String, Vec<u8>>, std::io::Error> {
+        let s = self.size();
+        let Some(p) = self.try_pointer()? else {
+            return Ok(Ok(String::new()));
+        };
+        let v = unsafe { from_raw_parts(p, s as usize) };
+        let v = match v.split_last() {
+            Some((0, rest)) => rest,
+            _ => v,
+        };
+        Ok(if lossy_ok {
+            Ok(String::from_utf8_lossy(v).into_owned())
+        } else {
+            String::from_utf8(v.to_vec()).map_err(|e| e.into_bytes())
+        })
+    }
+    /// Take the data out of the buffer as a [`Vec<u8>`].
+    ///
+    /// The returned [`Vec`] does not borrow the initial buffer so it can be stored or sent to
+    /// another thread.  The data is always copied.  A heap buffer is aligned to
+    /// [`ALIGNMENT`](crate::ALIGNMENT) and may come from any
+    /// [`BufferAllocator`](crate::BufferAllocator) so it cannot be handed to a [`Vec`].  Use
+    /// [`VecBuffer`](crate::VecBuffer) to have the data written to a [`Vec`] in the first place.
+    ///
+    /// An empty [`Vec`] is returned when zero bytes were stored or the data cannot be accessed;
+    /// see [`try_pointer`](FrozenBuffer::try_pointer).
+    ///
+    pub fn into_vec(self) -> Vec<u8> {
+        self.copy_elements()
+    }
+    /// Take the data out of the buffer as a boxed slice.
+    ///
+    /// See [`into_vec`](FrozenBuffer::into_vec).
+    ///
+    pub fn into_boxed_slice(self) -> Box<[u8]> {
+        self.into_vec().into_boxed_slice()
     }
 }
 
@@ -460,9 +2111,10 @@ impl WindowsPathString {
     /// # Arguments
     ///
     /// * `s` - The [`OsStr`] to convert to a Windows API UTF-16 NUL terminated string.  Anything
-    /// that can be converted to an [`OsStr`] reference, including plain ole Rust strings, can be
-    /// passed.
+    ///   that can be converted to an [`OsStr`] reference, including plain ole Rust strings, can be
+    ///   passed.
     ///
+    #[allow(clippy::new_ret_no_self)]
     pub fn new<S>(s: S) -> std::io::Result<WindowsString<BETTER_MAX_PATH>>
     where
         S: AsRef<OsStr>,
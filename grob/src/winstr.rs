@@ -55,7 +55,16 @@ use std::os::windows::ffi::OsStrExt;
 /// # }
 /// ```
 ///
+/// # Secrets
+///
+/// With the `zeroize` feature a [`WindowsString`] overwrites its copy of the string with zeros
+/// when dropped.  Both the stack array and the heap buffer are overwritten.  Use
+/// [`zeroize`](WindowsString::zeroize) to overwrite the copy before the [`WindowsString`] is
+/// dropped.  This is meant for passwords and connection strings passed to functions like
+/// [`LogonUserW`][luw].  The string the [`WindowsString`] was created from is not touched.
+///
 /// [gc]: https://crates.io/crates/grob
+/// [luw]: https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-logonuserw
 /// [scn]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-setcomputernamew
 /// [rf]: https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-replacefilew
 /// [wps]: crate::WindowsPathString
@@ -75,8 +84,8 @@ impl<const STACK_BUFFER_SIZE: usize> WindowsString<STACK_BUFFER_SIZE> {
     /// # Arguments
     ///
     /// * `s` - The [`OsStr`] to convert to a Windows API UTF-16 NUL terminated string.  Anything
-    /// that can be converted to an [`OsStr`] reference, including plain ole Rust strings, can be
-    /// passed.
+    ///   that can be converted to an [`OsStr`] reference, including plain ole Rust strings, can be
+    ///   passed.
     ///
     pub fn new<S>(s: S) -> std::io::Result<Self>
     where
@@ -104,6 +113,19 @@ impl<const STACK_BUFFER_SIZE: usize> WindowsString<STACK_BUFFER_SIZE> {
         }
     }
 
+    /// Overwrite the converted string with zeros.
+    ///
+    /// The [`WindowsString`] is left holding an empty string.  Dropping the [`WindowsString`] does
+    /// the same so `zeroize` is only needed to scrub the copy sooner.
+    ///
+    #[cfg(feature = "zeroize")]
+    pub fn zeroize(&mut self) {
+        if let Some(heap) = &mut self.heap {
+            wipe(heap.as_mut_ptr(), heap.capacity());
+        }
+        wipe(self.stack.as_mut_ptr() as *mut u16, STACK_BUFFER_SIZE);
+    }
+
     fn convert_and_store(&mut self, s: &OsStr) -> std::io::Result<()> {
         if s.len() + 1 > STACK_BUFFER_SIZE {
             return self.use_heap(s);
@@ -117,7 +139,7 @@ impl<const STACK_BUFFER_SIZE: usize> WindowsString<STACK_BUFFER_SIZE> {
             let mut buffer = Vec::with_capacity(capacity);
             capacity = buffer.capacity(); // rmv?
             let mut encoder = s.encode_wide();
-            let mut p = buffer.as_mut_ptr() as *mut u16;
+            let mut p = buffer.as_mut_ptr();
             let base = p as *const u16;
             let mut finished = false;
             for _ in 0..capacity {
@@ -125,6 +147,9 @@ impl<const STACK_BUFFER_SIZE: usize> WindowsString<STACK_BUFFER_SIZE> {
                     #[cfg(not(feature = "skip_null_check"))]
                     {
                         if c == 0 {
+                            // Do not leave part of the string in the freed buffer.
+                            #[cfg(feature = "zeroize")]
+                            wipe(buffer.as_mut_ptr(), capacity);
                             return Err(Self::no_nuls());
                         }
                     }
@@ -135,14 +160,16 @@ impl<const STACK_BUFFER_SIZE: usize> WindowsString<STACK_BUFFER_SIZE> {
                     finished = true;
                     let stored = unsafe { p.offset_from(base) } + 1;
                     unsafe { buffer.set_len(stored as usize) };
-                    self.heap = Some(buffer);
                     break;
                 }
             }
             if finished {
+                self.heap = Some(buffer);
                 break;
             }
             // Note: This point was never reached during testing.
+            #[cfg(feature = "zeroize")]
+            wipe(buffer.as_mut_ptr(), capacity);
             capacity *= 2;
         }
         Ok(())
@@ -184,3 +211,20 @@ impl<const STACK_BUFFER_SIZE: usize> WindowsString<STACK_BUFFER_SIZE> {
         )
     }
 }
+
+#[cfg(feature = "zeroize")]
+impl<const STACK_BUFFER_SIZE: usize> Drop for WindowsString<STACK_BUFFER_SIZE> {
+    fn drop(&mut self) {
+        self.zeroize();
+        #[cfg(feature = "testing")]
+        if let Some(heap) = &self.heap {
+            crate::testing::observe_windows_string_drop(heap);
+        }
+    }
+}
+
+// Overwrite `count` elements starting at `p` with zeros in a way the optimizer cannot remove.
+#[cfg(feature = "zeroize")]
+fn wipe(p: *mut u16, count: usize) {
+    crate::buffer::wipe(p as *mut u8, count * std::mem::size_of::<u16>());
+}
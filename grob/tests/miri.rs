@@ -18,7 +18,7 @@ mod large_binary {
             ERROR_ADDRESS_NOT_ASSOCIATED, ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS,
         };
 
-        use grob::{winapi_large_binary, RvIsError};
+        use grob::{winapi_large_binary, IoErrorExt, RvIsError};
 
         fn write_zero_bytes(_data: Option<*mut u8>, size: *mut u32) -> u32 {
             unsafe { *size = 0 };
@@ -29,7 +29,10 @@ mod large_binary {
         fn nothing_stored() {
             winapi_large_binary(
                 |argument| {
-                    RvIsError::new(write_zero_bytes(Some(argument.pointer()), argument.size()))
+                    RvIsError::new(write_zero_bytes(
+                        Some(argument.pointer()),
+                        argument.size_mut(),
+                    ))
                 },
                 |frozen_buffer| {
                     assert!(frozen_buffer.size() == 0);
@@ -54,12 +57,15 @@ mod large_binary {
         fn one_byte_stored() {
             winapi_large_binary(
                 |argument| {
-                    RvIsError::new(write_one_byte(Some(argument.pointer()), argument.size()))
+                    RvIsError::new(write_one_byte(
+                        Some(argument.pointer()),
+                        argument.size_mut(),
+                    ))
                 },
                 |frozen_buffer| {
                     assert!(frozen_buffer.size() == 1);
                     let p = frozen_buffer.pointer().unwrap();
-                    assert!(p != std::ptr::null());
+                    assert!(!p.is_null());
                     assert!(unsafe { *p } == 42);
                     Ok(())
                 },
@@ -75,7 +81,7 @@ mod large_binary {
                 ERROR_BUFFER_OVERFLOW.0
             } else {
                 let p = data.unwrap();
-                assert!(p != std::ptr::null_mut());
+                assert!(!p.is_null());
                 unsafe { std::ptr::write_bytes(p, 42, (*size).try_into().unwrap()) };
                 ERROR_SUCCESS.0
             }
@@ -88,17 +94,14 @@ mod large_binary {
                     RvIsError::new(grow_then_fill(
                         argument.tries(),
                         Some(argument.pointer()),
-                        argument.size(),
+                        argument.size_mut(),
                     ))
                 },
                 |frozen_buffer| {
                     assert!(frozen_buffer.size() > 0);
-                    let p = frozen_buffer.pointer().unwrap();
-                    assert!(p != std::ptr::null());
-                    let s = unsafe {
-                        std::slice::from_raw_parts(p, frozen_buffer.size().try_into().unwrap())
-                    };
-                    for v in s.into_iter() {
+                    let s = frozen_buffer.as_slice();
+                    assert!(s.len() == frozen_buffer.size() as usize);
+                    for v in s.iter() {
                         assert!(*v == 42);
                     }
                     Ok(())
@@ -118,31 +121,76 @@ mod large_binary {
                     RvIsError::new(return_error(
                         argument.tries(),
                         Some(argument.pointer()),
-                        argument.size(),
+                        argument.size_mut(),
                     ))
                 },
-                |_frozen_buffer| {
-                    assert!(false);
-                    Ok(())
-                },
+                |_frozen_buffer| unreachable!(),
             ) {
-                Ok(()) => assert!(false),
-                Err(_e) => {
-                    if let Some(raw) = _e.raw_os_error() {
-                        if raw == ERROR_ADDRESS_NOT_ASSOCIATED.0 as i32 {
-                            // all good
-                        } else {
-                            assert!(false);
-                        }
-                    } else {
-                        assert!(false);
-                    }
-                }
+                Ok(()) => unreachable!(),
+                Err(e) => assert!(e.is_win32(ERROR_ADDRESS_NOT_ASSOCIATED)),
             }
         }
     }
 }
 
+mod large_binary_small_stack {
+    use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
+
+    use grob::{winapi_large_binary, winapi_large_binary_heap, RvIsError};
+
+    // Room for the test harness plus a 16 KiB stack buffer but nowhere near enough for 64 KiB.
+    const SMALL_STACK: usize = 48 * 1024;
+
+    fn needs_100_kibi(data: *mut u8, size: *mut u32) -> u32 {
+        let needed = 100 * 1024;
+        let available = unsafe { *size };
+        unsafe { *size = needed };
+        if available >= needed {
+            unsafe { std::ptr::write_bytes(data, 42, needed as usize) };
+            ERROR_SUCCESS.0
+        } else {
+            ERROR_BUFFER_OVERFLOW.0
+        }
+    }
+
+    fn run_on_small_stack<F>(f: F)
+    where
+        F: FnOnce() -> Result<u32, std::io::Error> + Send + 'static,
+    {
+        let size = std::thread::Builder::new()
+            .stack_size(SMALL_STACK)
+            .spawn(f)
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+        assert!(size == 100 * 1024);
+    }
+
+    #[test]
+    fn default_fits() {
+        run_on_small_stack(|| {
+            winapi_large_binary(
+                |argument| RvIsError::new(needs_100_kibi(argument.pointer(), argument.size_mut())),
+                |frozen_buffer| Ok(frozen_buffer.size()),
+            )
+        });
+    }
+
+    #[test]
+    fn heap_fits() {
+        run_on_small_stack(|| {
+            winapi_large_binary_heap(
+                |argument| {
+                    assert!(argument.tries() > 1 || *argument.size_mut() == 0);
+                    RvIsError::new(needs_100_kibi(argument.pointer(), argument.size_mut()))
+                },
+                |frozen_buffer| Ok(frozen_buffer.size()),
+            )
+        });
+    }
+}
+
 mod small_binary {
     mod rv_is_size {
         use std::mem::size_of;
@@ -151,7 +199,7 @@ mod small_binary {
             SetLastError, ERROR_ADDRESS_NOT_ASSOCIATED, ERROR_INSUFFICIENT_BUFFER, ERROR_SUCCESS,
         };
 
-        use grob::{winapi_small_binary, RvIsSize};
+        use grob::{winapi_small_binary, IoErrorExt, RvIsSize};
 
         const LARGE_INTEGER: u128 = 12345678901234567890123456789012345678_u128;
         const SIZE_OF_U128: u32 = size_of::<u128>() as u32;
@@ -165,9 +213,10 @@ mod small_binary {
         fn nothing_stored() {
             winapi_small_binary(
                 |argument| {
-                    RvIsSize::new(write_zero_bytes(Some(argument.pointer()), unsafe {
-                        *argument.size()
-                    }))
+                    RvIsSize::new(write_zero_bytes(
+                        Some(argument.pointer()),
+                        *argument.size_mut(),
+                    ))
                 },
                 |frozen_buffer| {
                     assert!(frozen_buffer.size() == 0);
@@ -188,12 +237,15 @@ mod small_binary {
         fn one_thing_stored() {
             winapi_small_binary(
                 |argument| {
-                    RvIsSize::new(write_one_thing(Some(argument.pointer()), argument.size()))
+                    RvIsSize::new(write_one_thing(
+                        Some(argument.pointer()),
+                        argument.size_mut(),
+                    ))
                 },
                 |frozen_buffer| {
                     assert!(frozen_buffer.size() == 16);
                     let p = frozen_buffer.pointer().unwrap();
-                    assert!(p != std::ptr::null());
+                    assert!(!p.is_null());
                     assert!(unsafe { *p } == LARGE_INTEGER);
                     Ok(())
                 },
@@ -224,13 +276,13 @@ mod small_binary {
                     RvIsSize::new(grow_then_fill(
                         argument.tries(),
                         Some(argument.pointer()),
-                        unsafe { *argument.size() },
+                        *argument.size_mut(),
                     ))
                 },
                 |frozen_buffer| {
                     assert!(frozen_buffer.size() > 0);
                     let p = frozen_buffer.pointer().unwrap();
-                    assert!(p != std::ptr::null());
+                    assert!(!p.is_null());
                     assert!(unsafe { *p } == LARGE_INTEGER);
                     let last = ((frozen_buffer.size() as usize / size_of::<u128>()) - 1) as isize;
                     assert!(unsafe { *(p.offset(last)) } == LARGE_INTEGER);
@@ -252,26 +304,13 @@ mod small_binary {
                     RvIsSize::new(return_error(
                         argument.tries(),
                         Some(argument.pointer()),
-                        unsafe { *argument.size() },
+                        *argument.size_mut(),
                     ))
                 },
-                |_frozen_buffer| {
-                    assert!(false);
-                    Ok(())
-                },
+                |_frozen_buffer| unreachable!(),
             ) {
-                Ok(()) => assert!(false),
-                Err(_e) => {
-                    if let Some(raw) = _e.raw_os_error() {
-                        if raw == ERROR_ADDRESS_NOT_ASSOCIATED.0 as i32 {
-                            // all good
-                        } else {
-                            assert!(false);
-                        }
-                    } else {
-                        assert!(false);
-                    }
-                }
+                Ok(()) => unreachable!(),
+                Err(e) => assert!(e.is_win32(ERROR_ADDRESS_NOT_ASSOCIATED)),
             }
         }
     }
@@ -279,14 +318,19 @@ mod small_binary {
 
 mod string {
     mod rv_is_error {
+        use std::ffi::OsString;
         use std::os::windows::ffi::OsStrExt;
 
         use windows::core::PWSTR;
         use windows::Win32::Foundation::{
-            SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, FALSE, TRUE,
+            SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, ERROR_INVALID_DATA, FALSE, NO_ERROR,
+            TRUE, WIN32_ERROR,
         };
 
-        use grob::{winapi_string, RvIsError};
+        use grob::{
+            winapi_osstring, winapi_string, GrowForStaticText, GrowableBuffer, RvIsError,
+            StackBuffer, ToResult, CAPACITY_FOR_NAMES,
+        };
 
         fn write_zero_bytes(_data: PWSTR, size: *mut u32) -> BOOL {
             unsafe { *size = 0 };
@@ -296,11 +340,11 @@ mod string {
         #[test]
         fn nothing_stored() {
             let s = winapi_string(false, |argument| {
-                RvIsError::new(write_zero_bytes(argument.pointer(), argument.size()))
+                RvIsError::new(write_zero_bytes(argument.pointer(), argument.size_mut()))
             })
             .unwrap()
             .unwrap();
-            assert!(s == "");
+            assert!(s.is_empty());
         }
 
         fn write_terminator(data: PWSTR, size: *mut u32) -> BOOL {
@@ -318,11 +362,11 @@ mod string {
         #[test]
         fn terminator_stored() {
             let s = winapi_string(false, |argument| {
-                RvIsError::new(write_terminator(argument.pointer(), argument.size()))
+                RvIsError::new(write_terminator(argument.pointer(), argument.size_mut()))
             })
             .unwrap()
             .unwrap();
-            assert!(s == "");
+            assert!(s.is_empty());
         }
 
         const ZATHRAS: [u16; 8] = [
@@ -344,7 +388,7 @@ mod string {
         #[test]
         fn try_zathras() {
             let s = winapi_string(false, |argument| {
-                RvIsError::new(write_zathras(argument.pointer(), argument.size()))
+                RvIsError::new(write_zathras(argument.pointer(), argument.size_mut()))
             })
             .unwrap()
             .unwrap();
@@ -368,7 +412,10 @@ mod string {
         #[test]
         fn invalid_unicode_dropped() {
             let s = winapi_string(true, |argument| {
-                RvIsError::new(write_invalid_unicode(argument.pointer(), argument.size()))
+                RvIsError::new(write_invalid_unicode(
+                    argument.pointer(),
+                    argument.size_mut(),
+                ))
             })
             .unwrap()
             .unwrap();
@@ -380,24 +427,207 @@ mod string {
         #[test]
         fn invalid_unicode_fails() {
             let rv = winapi_string(false, |argument| {
-                RvIsError::new(write_invalid_unicode(argument.pointer(), argument.size()))
+                RvIsError::new(write_invalid_unicode(
+                    argument.pointer(),
+                    argument.size_mut(),
+                ))
             })
             .unwrap();
             match rv {
-                Ok(_) => assert!(false),
+                Ok(_) => unreachable!(),
                 Err(s) => {
                     // Convert the string
                     let r: Vec<u16> = s.encode_wide().collect();
                     // Compare the two.  r should be one byte shorter (no terminator) so just the
                     // actual characters will end up being compared.
-                    let e = r
-                        .into_iter()
-                        .zip(INVALID_UNICODE)
-                        .fold(true, |a, v| a && (v.0 == v.1));
+                    let e = r.into_iter().zip(INVALID_UNICODE).all(|v| v.0 == v.1);
                     assert!(e);
                 }
             }
         }
+
+        #[test]
+        fn osstring_nothing_stored() {
+            let s = winapi_osstring(|argument| {
+                RvIsError::new(write_zero_bytes(argument.pointer(), argument.size_mut()))
+            })
+            .unwrap();
+            assert!(s.is_empty());
+        }
+
+        #[test]
+        fn osstring_terminator_stored() {
+            let s = winapi_osstring(|argument| {
+                RvIsError::new(write_terminator(argument.pointer(), argument.size_mut()))
+            })
+            .unwrap();
+            assert!(s.is_empty());
+        }
+
+        #[test]
+        fn osstring_zathras() {
+            let s = winapi_osstring(|argument| {
+                RvIsError::new(write_zathras(argument.pointer(), argument.size_mut()))
+            })
+            .unwrap();
+            assert!(s == "Zathras");
+        }
+
+        #[test]
+        fn osstring_invalid_unicode_kept() {
+            let s = winapi_osstring(|argument| {
+                RvIsError::new(write_invalid_unicode(
+                    argument.pointer(),
+                    argument.size_mut(),
+                ))
+            })
+            .unwrap();
+            let r: Vec<u16> = s.encode_wide().collect();
+            assert!(r == INVALID_UNICODE[..3]);
+        }
+
+        fn write_invalid_unicode_with_last_error(data: PWSTR, size: *mut u32) -> BOOL {
+            let rv = write_invalid_unicode(data, size);
+            if rv == TRUE {
+                // The call "succeeded" but left something behind.
+                unsafe { SetLastError(ERROR_INVALID_DATA) };
+            }
+            rv
+        }
+
+        fn freeze_and_check_hint<F>(f: F, hint: Option<WIN32_ERROR>) -> Result<String, OsString>
+        where
+            F: Fn(PWSTR, *mut u32) -> BOOL,
+        {
+            let mut initial_buffer = StackBuffer::<CAPACITY_FOR_NAMES>::new();
+            let grow_strategy = GrowForStaticText::new();
+            let mut growable_buffer =
+                GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+            loop {
+                let mut argument = growable_buffer.argument();
+                let rv = RvIsError::new(f(argument.pointer(), argument.size_mut()));
+                let result = rv.to_result(&mut argument).unwrap();
+                if argument.try_apply(result).unwrap() {
+                    break;
+                }
+            }
+            let frozen_buffer = growable_buffer.freeze();
+            assert!(frozen_buffer.last_os_error_hint() == hint);
+            frozen_buffer.to_string(false)
+        }
+
+        #[test]
+        fn invalid_unicode_keeps_hint() {
+            let rv = freeze_and_check_hint(
+                write_invalid_unicode_with_last_error,
+                Some(ERROR_INVALID_DATA),
+            );
+            assert!(rv.is_err());
+        }
+
+        #[test]
+        fn clean_success_has_no_hint() {
+            unsafe { SetLastError(NO_ERROR) };
+            let rv = freeze_and_check_hint(write_zathras, None);
+            assert!(rv.unwrap() == "Zathras");
+        }
+    }
+
+    mod ansi {
+        use windows::core::PSTR;
+        use windows::Win32::Foundation::{
+            SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, FALSE, TRUE,
+        };
+
+        use grob::{winapi_string_a, RvIsError, RvIsSize};
+
+        fn write_narrow(narrow: &[u8], data: PSTR, size: *mut u32) -> BOOL {
+            let rv = if unsafe { *size >= narrow.len() as u32 } {
+                unsafe { std::ptr::copy(narrow.as_ptr(), data.0, narrow.len()) };
+                TRUE
+            } else {
+                unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+                FALSE
+            };
+            unsafe { *size = narrow.len() as u32 };
+            rv
+        }
+
+        fn call(lossy_ok: bool, narrow: &[u8]) -> Result<String, Vec<u8>> {
+            winapi_string_a(lossy_ok, |argument| {
+                RvIsError::new(write_narrow(
+                    narrow,
+                    argument.pointer(),
+                    argument.size_mut(),
+                ))
+            })
+            .unwrap()
+        }
+
+        #[test]
+        fn nothing_stored() {
+            assert!(call(false, b"").unwrap() == "");
+        }
+
+        #[test]
+        fn terminator_stored() {
+            assert!(call(false, b"\0").unwrap() == "");
+        }
+
+        #[test]
+        fn try_zathras() {
+            assert!(call(false, b"Zathras\0").unwrap() == "Zathras");
+        }
+
+        #[test]
+        fn no_terminator() {
+            assert!(call(false, b"Zathras").unwrap() == "Zathras");
+        }
+
+        #[test]
+        fn larger_than_initial_buffer() {
+            let mut narrow = vec![b'z'; 5000];
+            narrow.push(0);
+            let s = call(false, &narrow).unwrap();
+            assert!(s.len() == 5000);
+            assert!(s.bytes().all(|b| b == b'z'));
+        }
+
+        // 0xE9 is é in Windows-1252 but is not valid UTF-8 on its own.
+        const INVALID_UTF8: [u8; 4] = [b'a', 0xE9, b'z', 0];
+
+        #[test]
+        fn invalid_utf8_dropped() {
+            let s = call(true, &INVALID_UTF8).unwrap();
+            assert!(s == "a\u{FFFD}z");
+        }
+
+        #[test]
+        fn invalid_utf8_fails() {
+            match call(false, &INVALID_UTF8) {
+                Ok(_) => unreachable!(),
+                Err(v) => assert!(v == INVALID_UTF8[..3]),
+            }
+        }
+
+        #[test]
+        fn as_mut_slice() {
+            // Mimics GetModuleFileNameA.  A truncated result returns the buffer size.
+            let narrow = [b'C', b':', b'\\', b'x'];
+            let s = winapi_string_a(false, |argument| {
+                let buffer = argument.as_mut_slice();
+                if buffer.len() <= narrow.len() {
+                    unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+                    return RvIsSize::new(buffer.len() as u32);
+                }
+                buffer[..narrow.len()].copy_from_slice(&narrow);
+                buffer[narrow.len()] = 0;
+                RvIsSize::new(narrow.len() as u32)
+            })
+            .unwrap()
+            .unwrap();
+            assert!(s == "C:\\x");
+        }
     }
 }
 
@@ -460,53 +690,143 @@ mod path_buf {
             assert!(s.len() == 23);
         }
     }
+
+    mod ex {
+        use std::ffi::OsStr;
+        use std::os::windows::ffi::OsStrExt;
+
+        use windows::Win32::Foundation::{SetLastError, ERROR_INSUFFICIENT_BUFFER, ERROR_SUCCESS};
+
+        use grob::{winapi_path_buf_ex, PathBufOptions, RvIsSize};
+
+        // Mimics GetModuleFileNameW.  A truncated path returns the buffer size.
+        fn write_path(buffer: &mut [u16], path: &str) -> u32 {
+            let wide: Vec<u16> = OsStr::new(path).encode_wide().collect();
+            if buffer.len() <= wide.len() {
+                let last = buffer.len() - 1;
+                buffer[..last].copy_from_slice(&wide[..last]);
+                buffer[last] = 0;
+                unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+                return buffer.len() as u32;
+            }
+            buffer[..wide.len()].copy_from_slice(&wide);
+            buffer[wide.len()] = 0;
+            unsafe { SetLastError(ERROR_SUCCESS) };
+            wide.len() as u32
+        }
+
+        fn long_path() -> String {
+            let mut path = String::from("C:");
+            while path.len() < 600 {
+                path.push_str("\\directory");
+            }
+            path.push_str("\\file.txt");
+            path
+        }
+
+        fn call(options: PathBufOptions, path: &str) -> (std::path::PathBuf, usize) {
+            let mut calls = 0;
+            let rv = winapi_path_buf_ex(options, |argument| {
+                calls += 1;
+                RvIsSize::new(write_path(argument.as_mut_slice(), path))
+            })
+            .unwrap();
+            (rv, calls)
+        }
+
+        #[test]
+        fn longer_than_max_path() {
+            let path = long_path();
+            let (rv, calls) = call(PathBufOptions::new(), &path);
+            assert!(rv.as_os_str() == path.as_str());
+            assert!(calls > 1);
+        }
+
+        #[test]
+        fn floor_avoids_growing() {
+            let path = long_path();
+            let (rv, calls) = call(PathBufOptions::new().floor(32767), &path);
+            assert!(rv.as_os_str() == path.as_str());
+            assert!(calls == 1);
+        }
+
+        #[test]
+        fn verbatim_prefix_kept() {
+            let (rv, _) = call(PathBufOptions::new(), r"\\?\C:\Windows\notepad.exe");
+            assert!(rv.as_os_str() == r"\\?\C:\Windows\notepad.exe");
+        }
+
+        #[test]
+        fn verbatim_prefix_stripped() {
+            let options = PathBufOptions::new().strip_verbatim(true);
+            let (rv, _) = call(options, r"\\?\C:\Windows\notepad.exe");
+            assert!(rv.as_os_str() == r"C:\Windows\notepad.exe");
+            let (rv, _) = call(options, r"\\?\unc\server\share\file.txt");
+            assert!(rv.as_os_str() == r"\\server\share\file.txt");
+            let volume = r"\\?\Volume{01234567-89ab-cdef-0123-456789abcdef}\file.txt";
+            let (rv, _) = call(options, volume);
+            assert!(rv.as_os_str() == volume);
+            let (rv, _) = call(options, r"C:\Windows");
+            assert!(rv.as_os_str() == r"C:\Windows");
+        }
+
+        #[test]
+        fn verbatim_long_path_stripped() {
+            let path = long_path();
+            let verbatim = format!(r"\\?\{}", path);
+            let (rv, _) = call(PathBufOptions::new().strip_verbatim(true), &verbatim);
+            assert!(rv.as_os_str() == path.as_str());
+        }
+
+        #[test]
+        fn empty() {
+            let (rv, _) = call(PathBufOptions::new(), "");
+            assert!(rv.as_os_str() == "");
+            let rv = winapi_path_buf_ex(PathBufOptions::new().empty_is_error(true), |argument| {
+                RvIsSize::new(write_path(argument.as_mut_slice(), ""))
+            });
+            assert!(rv.unwrap_err().kind() == std::io::ErrorKind::NotFound);
+        }
+    }
 }
 
 mod straight_to_heap {
     mod rv_is_error {
         use std::mem::size_of;
 
-        use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
-
-        use grob::{GrowForSmallBinary, GrowableBuffer, RvIsError, StackBuffer, ToResult};
+        use grob::testing::MockApi;
+        use grob::{GrowForSmallBinary, GrowableBuffer, StackBuffer, ToResult, ALIGNMENT};
 
         const SIZE_OF_U128: u32 = size_of::<u128>() as u32;
         const LARGE_INTEGER: u128 = 12345678901234567890123456789012345678_u128;
 
-        pub unsafe fn mimic_os(buffer: Option<*mut u128>, size: *mut u32) -> u32 {
-            let available = *size;
-            *size = SIZE_OF_U128;
-            if available >= SIZE_OF_U128 {
-                if let Some(buffer) = buffer {
-                    *buffer = LARGE_INTEGER;
-                    ERROR_SUCCESS.0
-                } else {
-                    ERROR_BUFFER_OVERFLOW.0
-                }
-            } else {
-                ERROR_BUFFER_OVERFLOW.0
-            }
-        }
-
         #[test]
         fn zero_sized_stack_buffer() {
+            let mut mock_api = MockApi::new()
+                .needs(SIZE_OF_U128)
+                .writes(LARGE_INTEGER.to_ne_bytes());
             let mut initial_buffer = StackBuffer::<0>::new();
             let grow_strategy = GrowForSmallBinary::new();
             let mut growable_buffer =
                 GrowableBuffer::<u128, *mut u128>::new(&mut initial_buffer, &grow_strategy);
             loop {
                 let mut argument = growable_buffer.argument();
-                let rv =
-                    RvIsError::new(unsafe { mimic_os(Some(argument.pointer()), argument.size()) });
+                let rv = mock_api.call_with_error_rv(&mut argument);
                 let result = rv.to_result(&mut argument).unwrap();
-                if argument.apply(result) {
+                if argument.try_apply(result).unwrap() {
                     break;
                 }
             }
+            assert!(mock_api.is_finished());
+            let observations = mock_api.observations();
+            assert!(observations.len() == 2);
+            assert!(observations[0].capacity == 0);
+            assert!(observations[1].capacity >= SIZE_OF_U128);
+            assert!((observations[1].pointer as usize).is_multiple_of(ALIGNMENT));
             let frozen_buffer = growable_buffer.freeze();
             assert!(frozen_buffer.size() == SIZE_OF_U128);
             let p = frozen_buffer.pointer().unwrap();
-            assert!(p != std::ptr::null());
+            assert!(!p.is_null());
             assert!(unsafe { *p } == LARGE_INTEGER);
         }
     }
@@ -515,23 +835,14 @@ mod straight_to_heap {
 
         use windows::core::PWSTR;
 
+        use grob::testing::MockApi;
         use grob::{
-            GrowForStoredIsReturned, GrowableBuffer, RvIsSize, StackBuffer, ToResult,
-            CAPACITY_FOR_PATHS,
+            GrowForStoredIsReturned, GrowableBuffer, StackBuffer, ToResult, CAPACITY_FOR_PATHS,
         };
 
-        pub unsafe fn mimic_os(lpfilename: &mut [u16]) -> u32 {
-            if lpfilename.len() >= 2 {
-                lpfilename[0] = '?' as u16;
-                lpfilename[1] = 0;
-                2
-            } else {
-                0
-            }
-        }
-
         #[test]
         fn zero_sized_stack_buffer() {
+            let mut mock_api = MockApi::new().writes_wide(&['?' as u16, 0]);
             let mut initial_buffer = StackBuffer::<0>::new();
             const CFP: u64 = CAPACITY_FOR_PATHS as u64;
             let grow_strategy = GrowForStoredIsReturned::<CFP>::new();
@@ -539,16 +850,89 @@ mod straight_to_heap {
                 GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
             loop {
                 let mut argument = growable_buffer.argument();
-                let rv = RvIsSize::new(unsafe { mimic_os(argument.as_mut_slice()) });
+                let rv = mock_api.call_with_size_rv(&mut argument);
                 let result = rv.to_result(&mut argument).unwrap();
-                if argument.apply(result) {
+                if argument.try_apply(result).unwrap() {
                     break;
                 }
             }
+            assert!(mock_api.is_finished());
+            // The floor is allocated before the first call so one call is enough.
+            assert!(mock_api.observations().len() == 1);
+            assert!(mock_api.observations()[0].capacity >= CAPACITY_FOR_PATHS as u32);
+            let frozen_buffer = growable_buffer.freeze();
+            assert!(frozen_buffer.to_string(false).unwrap() == "?");
         }
     }
 }
 
+mod mock_api {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_MORE_DATA};
+
+    use grob::testing::MockApi;
+    use grob::{winapi_path_buf, winapi_small_binary, winapi_string, FrozenBuffer, IoErrorExt};
+
+    const ZATHRAS: [u16; 8] = [
+        'Z' as u16, 'a' as u16, 't' as u16, 'h' as u16, 'r' as u16, 'a' as u16, 's' as u16, 0,
+    ];
+
+    #[test]
+    fn grows_until_the_payload_fits() {
+        let mut mock_api = MockApi::new().needs(100).needs(1000).writes([42; 1000]);
+        let size = winapi_small_binary(
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 1000);
+        let capacities: Vec<u32> = mock_api.observations().iter().map(|o| o.capacity).collect();
+        assert!(capacities.len() == 3);
+        assert!(capacities[1] >= 100);
+        assert!(capacities[2] >= 1000);
+    }
+
+    #[test]
+    fn error_is_returned() {
+        let mut mock_api = MockApi::new().needs(100).fails(ERROR_ACCESS_DENIED);
+        let rv = winapi_string(false, |argument| mock_api.call_with_error_rv(argument));
+        assert!(rv.unwrap_err().is_win32(ERROR_ACCESS_DENIED));
+        assert!(mock_api.is_finished());
+    }
+
+    #[test]
+    fn last_error_is_kept() {
+        let mut mock_api = MockApi::new()
+            .writes_wide(&ZATHRAS)
+            .with_last_error(ERROR_MORE_DATA);
+        let mut initial_buffer = grob::StackBuffer::<64>::new();
+        let grow_strategy = grob::GrowForStaticText::new();
+        let growable_buffer =
+            grob::GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+        let hint = grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer| {
+                assert!(frozen_buffer.to_string(false).unwrap() == "Zathras");
+                Ok(frozen_buffer.last_os_error_hint())
+            },
+        )
+        .unwrap();
+        assert!(hint == Some(ERROR_MORE_DATA));
+    }
+
+    #[test]
+    fn size_rv_doubles() {
+        let mut mock_api = MockApi::new().needs(0).needs(0).writes_wide(&ZATHRAS);
+        let path = winapi_path_buf(|argument| mock_api.call_with_size_rv(argument)).unwrap();
+        assert!(path.to_str() == Some("Zathras"));
+        let o = mock_api.observations();
+        assert!(o.len() == 3);
+        assert!(o[1].capacity > o[0].capacity);
+        assert!(o[2].capacity > o[1].capacity);
+    }
+}
+
 mod windows_string {
     mod storing_just_null {
         use grob::WindowsString;
@@ -645,6 +1029,8024 @@ mod windows_string {
     }
 }
 
+mod interleaving {
+    mod rv_is_error {
+        use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
+
+        use grob::{GrowForSmallBinary, GrowableBuffer, RvIsError, StackBuffer, ToResult};
+
+        const PAYLOAD: u8 = 42;
+
+        // Takes the size pointer before the data pointer and writes through both in the reverse
+        // order they were obtained.
+        unsafe fn size_first(size: *mut u32, data: *mut u8, needed: u32) -> u32 {
+            let available = *size;
+            *size = needed;
+            if available >= needed {
+                std::ptr::write_bytes(data, PAYLOAD, needed as usize);
+                ERROR_SUCCESS.0
+            } else {
+                ERROR_BUFFER_OVERFLOW.0
+            }
+        }
+
+        fn check_frozen(growable_buffer: GrowableBuffer<u8, *mut u8>, needed: u32) {
+            let frozen_buffer = growable_buffer.freeze();
+            // Hold the first pointer across the other accessors then read through it.
+            let first = frozen_buffer.pointer().unwrap();
+            let (second, size) = frozen_buffer.read_buffer();
+            assert!(size == needed);
+            assert!(frozen_buffer.size() == needed);
+            let second = second.unwrap();
+            let s = unsafe { std::slice::from_raw_parts(first, needed as usize) };
+            assert!(s.iter().all(|v| *v == PAYLOAD));
+            let s = unsafe { std::slice::from_raw_parts(second, needed as usize) };
+            assert!(s.iter().all(|v| *v == PAYLOAD));
+        }
+
+        fn run(growable_buffer: &mut GrowableBuffer<u8, *mut u8>, needed: u32) {
+            loop {
+                let mut argument = growable_buffer.argument();
+                let size: *mut u32 = argument.size_mut();
+                let early = argument.pointer();
+                // Ask again; both pointers must remain usable.
+                let late = argument.pointer();
+                assert!(early == late);
+                let _ = argument.tries();
+                let rv = RvIsError::new(unsafe { size_first(size, early, needed) });
+                let result = rv.to_result(&mut argument).unwrap();
+                if argument.try_apply(result).unwrap() {
+                    break;
+                }
+            }
+        }
+
+        #[test]
+        fn stack_phase() {
+            let mut initial_buffer = StackBuffer::<256>::new();
+            let grow_strategy = GrowForSmallBinary::new();
+            let mut growable_buffer =
+                GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+            run(&mut growable_buffer, 100);
+            check_frozen(growable_buffer, 100);
+        }
+
+        #[test]
+        fn heap_phase() {
+            let mut initial_buffer = StackBuffer::<0>::new();
+            let grow_strategy = GrowForSmallBinary::new();
+            let mut growable_buffer =
+                GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+            run(&mut growable_buffer, 1000);
+            check_frozen(growable_buffer, 1000);
+        }
+
+        #[test]
+        fn abandoned_arguments_then_grow() {
+            let mut initial_buffer = StackBuffer::<64>::new();
+            let grow_strategy = GrowForSmallBinary::new();
+            let mut growable_buffer =
+                GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+            // Arguments that are created then dropped without being used must not disturb the
+            // pointers handed out later.
+            for _ in 0..3 {
+                let mut argument = growable_buffer.argument();
+                let _ = argument.size_mut();
+                let _ = argument.pointer();
+                argument.dismiss();
+            }
+            let mut argument = growable_buffer.argument();
+            let size = argument.size_mut();
+            *size = 512;
+            argument.grow().unwrap();
+            run(&mut growable_buffer, 300);
+            check_frozen(growable_buffer, 300);
+        }
+    }
+}
+
+mod argument_lifecycle {
+    use grob::{FillBufferAction, GrowForSmallBinary, GrowableBuffer, StackBuffer};
+
+    const PAYLOAD: u8 = 42;
+
+    fn fill(argument: &mut grob::Argument<*mut u8>, count: u32) {
+        unsafe { std::ptr::write_bytes(argument.pointer(), PAYLOAD, count as usize) };
+        *argument.size_mut() = count;
+    }
+
+    fn assert_payload(growable_buffer: GrowableBuffer<u8, *mut u8>, count: u32) {
+        let frozen_buffer = growable_buffer.freeze();
+        assert!(frozen_buffer.size() == count);
+        let s = frozen_buffer.as_slice();
+        assert!(s.len() == count as usize);
+        assert!(s.iter().all(|v| *v == PAYLOAD));
+    }
+
+    #[test]
+    fn commit_then_stray_argument() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        fill(&mut argument, 10);
+        argument.commit();
+        // An Argument that is never resolved must not discard the committed data.
+        growable_buffer.argument().dismiss();
+        assert_payload(growable_buffer, 10);
+    }
+
+    #[cfg(all(debug_assertions, not(feature = "no_panic")))]
+    #[test]
+    #[should_panic(expected = "dropped without calling try_apply")]
+    fn forgotten_argument_panics() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        fill(&mut argument, 10);
+        drop(argument);
+    }
+
+    #[test]
+    fn failed_call_does_not_need_dismiss() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        let rv = grob::RvIsError::new(windows::Win32::Foundation::ERROR_ACCESS_DENIED.0);
+        assert!(grob::ToResult::to_result(&rv, &mut argument).is_err());
+        drop(argument);
+        assert_payload(growable_buffer, 0);
+    }
+
+    // A ToResult from outside the crate that does not know about set_failed.
+    struct UserError(bool);
+
+    impl grob::ToResult for UserError {
+        fn to_result(&self, _needed_size: &mut dyn grob::NeededSize) -> grob::FillBufferResult {
+            if self.0 {
+                Err(std::io::Error::other("user error"))
+            } else {
+                Ok(FillBufferAction::NoData)
+            }
+        }
+    }
+
+    fn is_user_error(error: &std::io::Error) -> bool {
+        error.to_string().contains("user error")
+    }
+
+    #[test]
+    fn user_error_from_the_generic_loop() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let rv = grob::winapi_generic(growable_buffer, |_| UserError(true), |_| Ok(()));
+        assert!(is_user_error(&rv.unwrap_err()));
+    }
+
+    #[test]
+    fn user_error_retried_by_the_generic_loop() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let retry_policy = grob::RetryPolicy::new(is_user_error).sleeper(|_| {});
+        let mut calls = 0;
+        let rv = grob::winapi_generic_with_retry(
+            retry_policy,
+            growable_buffer,
+            |_| {
+                calls += 1;
+                UserError(calls < 3)
+            },
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        );
+        assert!(rv.unwrap() == 0);
+        assert!(calls == 3);
+    }
+
+    #[test]
+    fn user_error_from_an_attempt() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut attempts = growable_buffer.attempts();
+        let attempt = attempts.next_attempt().unwrap();
+        assert!(is_user_error(&attempt.finish(UserError(true)).unwrap_err()));
+        assert!(!attempts.is_done());
+    }
+
+    #[test]
+    fn user_error_from_grob_call() {
+        fn apply_style() -> Result<u32, std::io::Error> {
+            let mut initial_buffer = StackBuffer::<64>::new();
+            let grow_strategy = GrowForSmallBinary::new();
+            let frozen_buffer = grob::grob_call!(
+                GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy),
+                UserError,
+                |argument| argument.tries() > 0
+            );
+            Ok(frozen_buffer.size())
+        }
+        fn match_style() -> Result<u32, std::io::Error> {
+            let mut initial_buffer = StackBuffer::<64>::new();
+            let grow_strategy = GrowForSmallBinary::new();
+            let frozen_buffer = grob::grob_call!(
+                GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy),
+                UserError,
+                |argument| argument.tries() > 0,
+                |action| argument.try_apply(action)?
+            );
+            Ok(frozen_buffer.size())
+        }
+        assert!(is_user_error(&apply_style().unwrap_err()));
+        assert!(is_user_error(&match_style().unwrap_err()));
+    }
+
+    #[test]
+    fn commit_then_stray_argument_then_no_data() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        fill(&mut argument, 10);
+        argument.commit();
+        growable_buffer.argument().dismiss();
+        // A resolved Argument replaces what came before.
+        let argument = growable_buffer.argument();
+        assert!(argument.try_apply(FillBufferAction::NoData).unwrap());
+        assert_payload(growable_buffer, 0);
+    }
+
+    #[test]
+    fn grow_then_freeze_without_retry() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        fill(&mut argument, 10);
+        argument.commit();
+        let mut argument = growable_buffer.argument();
+        *argument.size_mut() = 1000;
+        assert!(!argument.try_apply(FillBufferAction::Grow).unwrap());
+        // The data from the first call lived in the buffer that was just replaced.
+        assert_payload(growable_buffer, 0);
+    }
+}
+
+mod small_stack_buffer {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, FALSE, TRUE};
+
+    use grob::{
+        FillBufferAction, GrowForStaticText, GrowableBuffer, ReadBuffer, RvIsError, StackBuffer,
+        ToResult, WriteBuffer, ALIGNMENT,
+    };
+
+    const ZATHRAS: [u16; 8] = [
+        'Z' as u16, 'a' as u16, 't' as u16, 'h' as u16, 'r' as u16, 'a' as u16, 's' as u16, 0,
+    ];
+
+    fn write_zathras(data: PWSTR, size: *mut u32) -> BOOL {
+        let rv = if unsafe { *size >= ZATHRAS.len() as u32 } {
+            unsafe { std::ptr::copy(ZATHRAS.as_ptr(), data.0, ZATHRAS.len()) };
+            TRUE
+        } else {
+            unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+            FALSE
+        };
+        unsafe { *size = ZATHRAS.len() as u32 };
+        rv
+    }
+
+    fn try_zathras<const N: usize>() {
+        let mut initial_buffer = StackBuffer::<N>::new();
+        // Every byte is usable no matter how small the buffer.
+        assert!(initial_buffer.capacity() == N as u32);
+        let (pointer, capacity) = initial_buffer.write_buffer();
+        assert!((pointer as usize).is_multiple_of(ALIGNMENT));
+        assert!(capacity == N as u32);
+        initial_buffer.set_final_size(3);
+        assert!(initial_buffer.read_buffer() == (Some(pointer as *const u8), 3));
+        initial_buffer.set_final_size(0);
+        let grow_strategy = GrowForStaticText::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = RvIsError::new(write_zathras(argument.pointer(), argument.size_mut()));
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+        let frozen_buffer = growable_buffer.freeze();
+        let s = frozen_buffer.try_to_string(false).unwrap().unwrap();
+        assert!(s == "Zathras");
+    }
+
+    macro_rules! smaller_than_alignment {
+        ($($name:ident: $n:literal,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    try_zathras::<$n>();
+                }
+            )*
+        };
+    }
+
+    smaller_than_alignment! {
+        capacity_1: 1,
+        capacity_2: 2,
+        capacity_3: 3,
+        capacity_4: 4,
+        capacity_5: 5,
+        capacity_6: 6,
+        capacity_7: 7,
+        capacity_8: 8,
+        capacity_9: 9,
+        capacity_10: 10,
+        capacity_11: 11,
+        capacity_12: 12,
+        capacity_13: 13,
+        capacity_14: 14,
+        capacity_15: 15,
+        capacity_16: 16,
+    }
+
+    #[test]
+    fn only_zero_capacity_is_unreadable() {
+        let mut initial_buffer = StackBuffer::<0>::new();
+        assert!(initial_buffer.capacity() == 0);
+        initial_buffer.set_final_size(3);
+        assert!(initial_buffer.read_buffer() == (None, 3));
+    }
+
+    // A buffer that accepts writes but cannot provide read access afterwards.
+    struct Unreadable {
+        inner: StackBuffer<64>,
+        final_size: u32,
+    }
+
+    impl ReadBuffer for Unreadable {
+        fn read_buffer(&self) -> (Option<*const u8>, u32) {
+            (None, self.final_size)
+        }
+    }
+
+    impl WriteBuffer for Unreadable {
+        fn as_read_buffer(&self) -> &dyn ReadBuffer {
+            self
+        }
+        fn capacity(&self) -> u32 {
+            self.inner.capacity()
+        }
+        fn set_final_size(&mut self, final_size: u32) {
+            self.final_size = final_size;
+        }
+        fn write_buffer(&mut self) -> (*mut u8, u32) {
+            self.inner.write_buffer()
+        }
+    }
+
+    #[test]
+    fn stored_but_unreadable_is_an_error() {
+        let mut initial_buffer = Unreadable {
+            inner: StackBuffer::<64>::new(),
+            final_size: 0,
+        };
+        let grow_strategy = GrowForStaticText::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        let rv = RvIsError::new(write_zathras(argument.pointer(), argument.size_mut()));
+        let result = rv.to_result(&mut argument).unwrap();
+        assert!(matches!(result, FillBufferAction::Commit));
+        argument.commit();
+        let frozen_buffer = growable_buffer.freeze();
+        assert!(frozen_buffer.size() == ZATHRAS.len() as u32);
+        assert!(frozen_buffer.try_pointer().is_err());
+        assert!(frozen_buffer.try_to_os_string().is_err());
+        assert!(frozen_buffer.try_to_path_buf().is_err());
+        assert!(frozen_buffer.try_to_string(true).is_err());
+        // The infallible conversions keep their original behaviour.
+        assert!(frozen_buffer.to_os_string().is_none());
+    }
+}
+
+mod broken_strategy {
+    use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
+
+    use grob::{winapi_binary, GrowError, GrowStrategy, RvIsError, StackBuffer};
+
+    // Always asks for half of what is needed.
+    struct GrowsBackwards {}
+
+    impl GrowStrategy for GrowsBackwards {
+        fn next_capacity(&self, _tries: usize, desired_capacity: u32) -> u32 {
+            desired_capacity / 2
+        }
+    }
+
+    fn needs_200_bytes(data: *mut u8, size: *mut u32) -> u32 {
+        let available = unsafe { *size };
+        unsafe { *size = 200 };
+        if available >= 200 {
+            unsafe { std::ptr::write_bytes(data, 42, 200) };
+            ERROR_SUCCESS.0
+        } else {
+            ERROR_BUFFER_OVERFLOW.0
+        }
+    }
+
+    fn run(initial_buffer: &mut dyn grob::WriteBuffer) -> std::io::Error {
+        let grow_strategy = GrowsBackwards {};
+        let rv = winapi_binary(
+            initial_buffer,
+            &grow_strategy,
+            |argument| RvIsError::new(needs_200_bytes(argument.pointer(), argument.size_mut())),
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        );
+        rv.unwrap_err()
+    }
+
+    fn assert_names_strategy(e: std::io::Error) {
+        assert!(e.kind() == std::io::ErrorKind::Other);
+        assert!(e.to_string().contains("GrowsBackwards"));
+        let inner = e.into_inner().unwrap();
+        let grow_error = inner.downcast_ref::<GrowError>().unwrap();
+        assert!(matches!(
+            grow_error,
+            GrowError::StrategyDidNotGrow {
+                next_capacity: 100,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn smaller_than_stack_buffer() {
+        let mut initial_buffer = StackBuffer::<128>::new();
+        assert_names_strategy(run(&mut initial_buffer));
+    }
+
+    #[test]
+    fn smaller_than_heap_buffer() {
+        let mut initial_buffer = StackBuffer::<0>::new();
+        // The first grow (0 -> 100) works.  The second grow (100 -> 100) does not.
+        assert_names_strategy(run(&mut initial_buffer));
+    }
+}
+
+mod formatting {
+    use grob::testing::MockApi;
+    use grob::{
+        FillBufferAction, GrowByDoubleWithNull, GrowForSmallBinary, GrowForStaticText,
+        GrowToNearestQuarterKibi, GrowableBuffer, StackBuffer, ToResult,
+    };
+
+    #[test]
+    fn strategies() {
+        let s = format!("{:?}", GrowForSmallBinary::new());
+        assert!(s.contains("GrowToNearestNibble"));
+        let s = format!("{:?}", GrowForStaticText::new());
+        assert!(s.contains("GrowToNearestNibbleWithNull"));
+        assert!(s.contains("extra: 2"));
+        let s = format!("{:?}", GrowByDoubleWithNull::<520>::new());
+        assert!(s.contains("floor: 520"));
+        assert!(s.contains("scale: 2"));
+        let s = format!("{:?}", GrowToNearestQuarterKibi::<4096>::with_const_floor());
+        assert!(s.contains("floor: 4096"));
+    }
+
+    #[test]
+    fn fill_buffer_action() {
+        assert!(FillBufferAction::Commit.to_string() == "commit");
+        assert!(FillBufferAction::Grow.to_string() == "grow");
+        assert!(FillBufferAction::NoData.to_string() == "no data");
+    }
+
+    #[test]
+    fn buffers() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let s = format!("{:?}", initial_buffer);
+        assert!(s.contains("CAPACITY: 64"));
+        assert!(s.contains("final_size: 0"));
+        let mut mock_api = MockApi::new().needs(100).writes([42; 100]);
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let s = format!("{:?}", growable_buffer);
+        assert!(s.contains("Initial"));
+        assert!(s.contains("tries: 0"));
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            assert!(format!("{:?}", argument).contains("resolved: false"));
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+        let s = format!("{:?}", growable_buffer);
+        assert!(s.contains("Heap"));
+        assert!(s.contains("capacity: 112"));
+        assert!(s.contains("tries: 1"));
+        assert!(s.contains("final_size: 100"));
+        let frozen_buffer = growable_buffer.freeze();
+        let s = format!("{:?}", frozen_buffer);
+        assert!(s.contains("Heap"));
+        assert!(s.contains("size: 100"));
+    }
+}
+
+mod builder {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{
+        SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, FALSE, NO_ERROR, TRUE,
+    };
+
+    use grob::testing::MockApi;
+    use grob::{Argument, Grob, GrowForStaticText, GrowToNearestQuarterKibi, RvIsError};
+
+    #[repr(C)]
+    struct Record {
+        id: u32,
+        count: u32,
+    }
+
+    fn mimic_records(pointer: *mut Record, size: *mut u32) -> BOOL {
+        let size = unsafe { &mut *size };
+        let needed = std::mem::size_of::<Record>() as u32 * 2;
+        if *size < needed {
+            *size = needed;
+            unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+            return FALSE;
+        }
+        unsafe {
+            pointer.write(Record { id: 1, count: 10 });
+            pointer.add(1).write(Record { id: 2, count: 20 });
+            SetLastError(NO_ERROR);
+        }
+        *size = needed;
+        TRUE
+    }
+
+    #[test]
+    fn binary_type_is_inferred() {
+        let total = Grob::builder()
+            .heap_only()
+            .run(
+                |argument| RvIsError::new(mimic_records(argument.pointer(), argument.size_mut())),
+                |frozen_buffer| {
+                    let records =
+                        unsafe { std::slice::from_raw_parts(frozen_buffer.pointer().unwrap(), 2) };
+                    Ok(records.iter().map(|r| r.id * r.count).sum::<u32>())
+                },
+            )
+            .unwrap();
+        assert!(total == 50);
+    }
+
+    #[test]
+    fn wide_type_is_inferred() {
+        let text = Grob::builder()
+            .stack::<4>()
+            .strategy(GrowForStaticText::new())
+            .run_wide(
+                |argument: &mut Argument<PWSTR>| {
+                    let wide: Vec<u16> = "Zathras\0".encode_utf16().collect();
+                    let needed = wide.len() as u32;
+                    let (pointer, size) = argument.pointer_and_size_mut();
+                    if *size < needed {
+                        *size = needed;
+                        unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+                        return RvIsError::new(FALSE);
+                    }
+                    unsafe {
+                        std::ptr::copy(wide.as_ptr(), pointer.0, wide.len());
+                        SetLastError(NO_ERROR);
+                    }
+                    *size = needed - 1;
+                    RvIsError::new(TRUE)
+                },
+                |frozen_buffer| Ok(frozen_buffer.to_string(false).unwrap()),
+            )
+            .unwrap();
+        assert!(text == "Zathras");
+    }
+
+    #[test]
+    fn max_tries_gives_up() {
+        let mut mock_api = MockApi::new();
+        for bytes in 1..=8 {
+            mock_api = mock_api.needs(bytes * 1024);
+        }
+        let rv = Grob::builder()
+            .strategy(GrowToNearestQuarterKibi::new())
+            .max_tries(3)
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: grob::FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            );
+        let error = rv.unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::Other);
+        assert!(error.to_string().contains("3 tries"));
+        assert!(mock_api.observations().len() == 3);
+    }
+
+    #[test]
+    fn zeroed_buffer_is_zero_on_every_call() {
+        let mut calls = 0;
+        let size = Grob::builder()
+            .stack::<64>()
+            .zeroed()
+            .run(
+                |argument| {
+                    calls += 1;
+                    let (bytes, size) = argument.pointer_and_size_mut();
+                    let capacity = *size as usize;
+                    let buffer = unsafe { std::slice::from_raw_parts_mut(bytes, capacity) };
+                    assert!(buffer.iter().all(|b| *b == 0));
+                    buffer.fill(0xFF);
+                    if calls < 3 {
+                        *size = (capacity as u32 + 1) * 2;
+                        unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+                        RvIsError::new(FALSE)
+                    } else {
+                        unsafe { SetLastError(NO_ERROR) };
+                        RvIsError::new(TRUE)
+                    }
+                },
+                |frozen_buffer: grob::FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(calls == 3);
+        assert!(size > 64);
+    }
+}
+
+mod grob_call {
+    use grob::testing::MockApi;
+    use grob::{grob_call, FillBufferAction, FrozenBuffer, GrowForSmallBinary};
+    use grob::{GrowableBuffer, IoErrorExt, RvIsError, StackBuffer};
+
+    fn sized(frozen_buffer: FrozenBuffer<u8>) -> u32 {
+        frozen_buffer.size()
+    }
+
+    #[test]
+    fn apply_style_evaluates_each_piece_once() -> Result<(), std::io::Error> {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut mock_api = MockApi::new().needs(100).needs(300).writes([7; 300]);
+        let mut buffers = 0;
+        let mut calls = 0;
+        let frozen_buffer = grob_call!(
+            {
+                buffers += 1;
+                GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+            },
+            RvIsError::new,
+            |argument| {
+                calls += 1;
+                mock_api.call_with_error_rv(&mut argument)
+            }
+        );
+        assert!(sized(frozen_buffer) == 300);
+        assert!(buffers == 1);
+        assert!(calls == 3);
+        assert!(mock_api.observations().len() == 3);
+        Ok(())
+    }
+
+    #[test]
+    fn match_style_sees_every_action() -> Result<(), std::io::Error> {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut mock_api = MockApi::new().needs(100).writes([7; 100]);
+        let mut actions = Vec::new();
+        let frozen_buffer = grob_call!(
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy),
+            RvIsError::new,
+            |argument| mock_api.call_with_error_rv(&mut argument),
+            |action| {
+                actions.push(action.to_string());
+                match action {
+                    FillBufferAction::Commit => {
+                        argument.commit();
+                        true
+                    }
+                    FillBufferAction::Grow => {
+                        argument.grow()?;
+                        false
+                    }
+                    FillBufferAction::NoData => {
+                        argument.commit_no_data();
+                        true
+                    }
+                }
+            }
+        );
+        assert!(sized(frozen_buffer) == 100);
+        assert!(actions == ["grow", "commit"]);
+        Ok(())
+    }
+
+    #[test]
+    fn error_is_propagated() {
+        fn run(mock_api: &mut MockApi) -> Result<u32, std::io::Error> {
+            let mut initial_buffer = StackBuffer::<64>::new();
+            let grow_strategy = GrowForSmallBinary::new();
+            let frozen_buffer = grob_call!(
+                GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy),
+                RvIsError::new,
+                |argument| mock_api.call_with_error_rv(&mut argument),
+            );
+            Ok(frozen_buffer.size())
+        }
+        let code = windows::Win32::Foundation::ERROR_ACCESS_DENIED;
+        let mut mock_api = MockApi::new().fails(code);
+        let error = run(&mut mock_api).unwrap_err();
+        assert!(error.is_win32(code));
+    }
+}
+
+mod simulator {
+    use grob::{markdown_table, simulate, simulate_with, SimHandler, SimStep};
+    use grob::{GrowForMultiSz, GrowToNearestQuarterKibi, CAPACITY_FOR_PATHS};
+    use grob::{GrowForSmallBinary, GrowForStaticText, GrowForStoredIsReturned, GrowStrategy};
+
+    // The tables in the strategy documentation are generated by the simulator.  If one of these
+    // fails, update the table in src/tables with the output of markdown_table.
+    #[test]
+    fn nibble_table_matches() {
+        let steps = simulate(&GrowForSmallBinary::new(), &[16, 100, 1008, 1008]);
+        let expected = include_str!("../src/tables/grow-to-nearest-nibble.md");
+        assert!(markdown_table(&steps) == expected);
+    }
+
+    #[test]
+    fn nibble_with_null_table_matches() {
+        let steps = simulate(&GrowForStaticText::new(), &[16, 100, 1008, 1008]);
+        let expected = include_str!("../src/tables/grow-to-nearest-nibble-with-null.md");
+        assert!(markdown_table(&steps) == expected);
+    }
+
+    #[test]
+    fn nibble_with_double_null_table_matches() {
+        let steps = simulate(&GrowForMultiSz::new(), &[14, 110, 1006, 1006]);
+        let expected = include_str!("../src/tables/grow-to-nearest-nibble-with-double-null.md");
+        assert!(markdown_table(&steps) == expected);
+    }
+
+    #[test]
+    fn double_with_null_table_matches() {
+        let handler = SimHandler::StoredIsReturned { element_size: 2 };
+        let steps = simulate_with(&GrowForStoredIsReturned::<0>::new(), handler, 0, &[600; 4]);
+        let expected = include_str!("../src/tables/grow-by-double-with-null.md");
+        assert!(markdown_table(&steps) == expected);
+    }
+
+    #[test]
+    fn quarter_kibi_table_matches() {
+        let steps = simulate(&GrowToNearestQuarterKibi::new(), &[1000, 1300, 1300]);
+        let expected = include_str!("../src/tables/grow-to-nearest-quarter-kibi.md");
+        assert!(markdown_table(&steps) == expected);
+    }
+
+    #[test]
+    fn big_enough_initial_buffer_never_grows() {
+        let handler = SimHandler::StoredIsReturned { element_size: 2 };
+        const CFP: u64 = CAPACITY_FOR_PATHS as u64;
+        let strategy = GrowForStoredIsReturned::<CFP>::new();
+        let steps = simulate_with(&strategy, handler, CAPACITY_FOR_PATHS as u32, &[500]);
+        assert!(steps.is_empty());
+    }
+
+    #[test]
+    fn stops_when_demands_are_used_up() {
+        let steps = simulate(&GrowForSmallBinary::new(), &[100, 200]);
+        assert!(steps.len() == 2);
+        assert!(steps[1].try_n == 2);
+    }
+
+    struct Stuck {}
+
+    impl GrowStrategy for Stuck {
+        fn next_capacity(&self, _tries: usize, _desired_capacity: u32) -> u32 {
+            64
+        }
+    }
+
+    #[test]
+    fn stops_when_the_strategy_does_not_grow() {
+        let steps = simulate(&Stuck {}, &[100, 100, 100, 100]);
+        assert!(
+            steps
+                == [
+                    SimStep {
+                        try_n: 1,
+                        desired: 100,
+                        chosen: 64
+                    },
+                    SimStep {
+                        try_n: 2,
+                        desired: 100,
+                        chosen: 64
+                    }
+                ]
+        );
+    }
+}
+
+mod no_panic {
+    use windows::Win32::Foundation::{SetLastError, NO_ERROR, TRUE};
+
+    use grob::{winapi_small_binary, FrozenBuffer, Grob, GrowStrategy, RvIsError};
+
+    fn claims_too_much(size: *mut u32) -> RvIsError {
+        unsafe {
+            *size += 100;
+            SetLastError(NO_ERROR);
+        }
+        RvIsError::new(TRUE)
+    }
+
+    #[cfg(not(feature = "no_panic"))]
+    #[test]
+    #[should_panic(expected = "bytes in a")]
+    fn size_exceeds_capacity_panics() {
+        let _ = winapi_small_binary(
+            |argument| claims_too_much(argument.size_mut()),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        );
+    }
+
+    #[cfg(feature = "no_panic")]
+    #[test]
+    fn size_exceeds_capacity_is_an_error() {
+        let rv = winapi_small_binary(
+            |argument| claims_too_much(argument.size_mut()),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        );
+        let error = rv.unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("bytes in a"));
+    }
+
+    #[test]
+    fn manual_try_commit_of_too_much_is_an_error() {
+        let mut initial_buffer = grob::StackBuffer::<64>::new();
+        let grow_strategy = grob::GrowForSmallBinary::new();
+        let mut growable_buffer =
+            grob::GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        let _ = claims_too_much(argument.size_mut());
+        let error = argument.try_commit().unwrap_err();
+        assert!(matches!(error, grob::GrowError::SizeExceedsCapacity { .. }));
+        let frozen_buffer = growable_buffer.freeze();
+        assert!(frozen_buffer.size() == 0);
+        assert!(frozen_buffer.pointer().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "bytes in a")]
+    fn manual_commit_of_too_much_panics() {
+        let mut initial_buffer = grob::StackBuffer::<64>::new();
+        let grow_strategy = grob::GrowForSmallBinary::new();
+        let mut growable_buffer =
+            grob::GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        let _ = claims_too_much(argument.size_mut());
+        argument.commit();
+    }
+
+    #[test]
+    fn undocumented_return_value_is_an_error() {
+        let rv = winapi_small_binary(
+            |argument| {
+                // Precisely the capacity without ERROR_INSUFFICIENT_BUFFER is not documented.
+                let stored = *argument.size_mut();
+                unsafe { SetLastError(NO_ERROR) };
+                grob::RvIsSize::new(stored)
+            },
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        );
+        let error = rv.unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("undocumented"));
+    }
+
+    #[test]
+    fn more_than_capacity_is_an_error() {
+        let rv = winapi_small_binary(
+            |argument| {
+                let stored = *argument.size_mut() + 1;
+                unsafe { SetLastError(NO_ERROR) };
+                grob::RvIsSize::new(stored)
+            },
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        );
+        let error = rv.unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("undocumented"));
+    }
+
+    struct Shrinks {}
+
+    impl GrowStrategy for Shrinks {
+        fn next_capacity(&self, _tries: usize, _desired_capacity: u32) -> u32 {
+            1
+        }
+    }
+
+    #[test]
+    fn strategy_violation_is_an_error() {
+        let rv = Grob::builder().strategy(Shrinks {}).run(
+            |argument| {
+                let mut mock_api = grob::testing::MockApi::new().needs(4096);
+                mock_api.call_with_error_rv(argument)
+            },
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        );
+        assert!(rv.unwrap_err().to_string().contains("Shrinks"));
+    }
+}
+
+mod unwinding {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use grob::testing::MockApi;
+    use grob::{winapi_generic, winapi_small_binary, FrozenBuffer, GrowForSmallBinary};
+    use grob::{GrowStrategy, GrowableBuffer, RvIsError, StackBuffer};
+
+    fn finish(growable_buffer: GrowableBuffer<u8, *mut u8>) {
+        let mut mock_api = MockApi::new().needs(200).writes([42; 200]);
+        let size = winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 200);
+    }
+
+    #[test]
+    fn panic_in_stack_phase_leaves_buffer_reusable() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let rv = catch_unwind(AssertUnwindSafe(|| {
+            let mut argument = growable_buffer.argument();
+            *argument.size_mut() = 10;
+            panic!("unrelated failure");
+        }));
+        assert!(rv.is_err());
+        finish(growable_buffer);
+    }
+
+    #[test]
+    fn panic_in_heap_phase_leaves_buffer_reusable() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        *argument.size_mut() = 100;
+        argument.grow().unwrap();
+        let rv = catch_unwind(AssertUnwindSafe(|| {
+            let mut argument = growable_buffer.argument();
+            let mut mock_api = MockApi::new().writes([7; 50]);
+            let _ = mock_api.call_with_error_rv(&mut argument);
+            panic!("unrelated failure");
+        }));
+        assert!(rv.is_err());
+        finish(growable_buffer);
+    }
+
+    #[test]
+    fn panic_in_api_wrapper_passes_through() {
+        let mut mock_api = MockApi::new().needs(100);
+        let mut calls = 0;
+        let rv = catch_unwind(AssertUnwindSafe(|| {
+            winapi_small_binary(
+                |argument| {
+                    calls += 1;
+                    if calls == 2 {
+                        panic!("unrelated failure");
+                    }
+                    mock_api.call_with_error_rv(argument)
+                },
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+        }));
+        let payload = rv.unwrap_err();
+        assert!(payload.downcast_ref::<&str>() == Some(&"unrelated failure"));
+        assert!(calls == 2);
+    }
+
+    #[test]
+    fn panic_in_finalize_passes_through() {
+        let mut mock_api = MockApi::new().needs(100).writes([7; 100]);
+        let rv = catch_unwind(AssertUnwindSafe(|| {
+            winapi_small_binary(
+                |argument| mock_api.call_with_error_rv(argument),
+                |_frozen_buffer: FrozenBuffer<u8>| -> Result<(), std::io::Error> {
+                    panic!("unrelated failure")
+                },
+            )
+        }));
+        assert!(rv.is_err());
+    }
+
+    struct Explodes {}
+
+    impl GrowStrategy for Explodes {
+        fn next_capacity(&self, _tries: usize, _desired_capacity: u32) -> u32 {
+            panic!("strategy failure")
+        }
+    }
+
+    #[test]
+    fn panic_in_strategy_leaves_buffer_droppable() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = Explodes {};
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let rv = catch_unwind(AssertUnwindSafe(|| {
+            let mut argument = growable_buffer.argument();
+            let rv = RvIsError::new(windows::Win32::Foundation::ERROR_INSUFFICIENT_BUFFER.0);
+            *argument.size_mut() = 100;
+            let fill_buffer_action = grob::ToResult::to_result(&rv, &mut argument).unwrap();
+            argument.try_apply(fill_buffer_action)
+        }));
+        assert!(rv.is_err());
+        // The stack buffer is still in place.
+        let mut argument = growable_buffer.argument();
+        *argument.size_mut() = 10;
+        argument.commit();
+        assert!(growable_buffer.freeze().size() == 10);
+    }
+}
+
+mod deadline {
+    use std::time::{Duration, Instant};
+
+    use windows::Win32::Foundation::ERROR_INSUFFICIENT_BUFFER;
+
+    use grob::testing::MockApi;
+    use grob::{winapi_generic_with_deadline, FrozenBuffer, Grob, GrobTimeout};
+    use grob::{GrowForSmallBinary, GrowableBuffer, RvIsError, StackBuffer};
+
+    fn always_grow(size: *mut u32) -> RvIsError {
+        unsafe { *size += 16 };
+        RvIsError::new(ERROR_INSUFFICIENT_BUFFER.0)
+    }
+
+    fn timeout(error: &std::io::Error) -> GrobTimeout {
+        *error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<GrobTimeout>())
+            .unwrap()
+    }
+
+    #[test]
+    fn always_growing_times_out() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut calls = 0;
+        let rv = winapi_generic_with_deadline(
+            Instant::now() + Duration::from_millis(5),
+            growable_buffer,
+            |argument| {
+                calls += 1;
+                always_grow(argument.size_mut())
+            },
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        );
+        let error = rv.unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::TimedOut);
+        let timeout = timeout(&error);
+        assert!(timeout.tries >= 1);
+        assert!(timeout.tries == calls);
+    }
+
+    #[test]
+    fn passed_deadline_makes_no_calls() {
+        let mut calls = 0;
+        let rv = Grob::builder().deadline(Instant::now()).run(
+            |argument| {
+                calls += 1;
+                always_grow(argument.size_mut())
+            },
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        );
+        let error = rv.unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::TimedOut);
+        assert!(timeout(&error).tries == 0);
+        assert!(calls == 0);
+    }
+
+    #[test]
+    fn max_tries_still_applies() {
+        let rv = Grob::builder()
+            .deadline(Instant::now() + Duration::from_secs(3600))
+            .max_tries(3)
+            .run(
+                |argument| always_grow(argument.size_mut()),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            );
+        assert!(rv.unwrap_err().kind() == std::io::ErrorKind::Other);
+    }
+
+    #[test]
+    fn success_before_the_deadline() {
+        let mut mock_api = MockApi::new().needs(300).writes([42; 300]);
+        let size = Grob::builder()
+            .deadline(Instant::now() + Duration::from_secs(3600))
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(size == 300);
+    }
+}
+
+mod retry {
+    use std::cell::RefCell;
+    use std::time::{Duration, Instant};
+
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_BUSY};
+
+    use grob::testing::MockApi;
+    use grob::{winapi_generic_with_retry, Backoff, ExponentialBackoff, FixedBackoff, Jittered};
+    use grob::{FrozenBuffer, Grob, GrobTimeout, GrowForSmallBinary, GrowableBuffer, IoErrorExt};
+    use grob::{RetryPolicy, RetryWithBackoff, RvIsError, StackBuffer, DEFAULT_MAX_RETRIES};
+
+    thread_local! {
+        static DELAYS: RefCell<Vec<Duration>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(delay: Duration) {
+        DELAYS.with(|d| d.borrow_mut().push(delay));
+    }
+
+    fn take_delays() -> Vec<Duration> {
+        DELAYS.with(|d| std::mem::take(&mut *d.borrow_mut()))
+    }
+
+    fn is_busy(error: &std::io::Error) -> bool {
+        error.is_win32(ERROR_BUSY)
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn fixed_backoff_sequence() {
+        let backoff = FixedBackoff::new(ms(7));
+        for attempt in 1..10 {
+            assert!(backoff.delay(attempt) == Some(ms(7)));
+        }
+    }
+
+    #[test]
+    fn exponential_backoff_sequence() {
+        let backoff = ExponentialBackoff::new(ms(10), ms(100));
+        let delays: Vec<_> = (1..=6).map(|a| backoff.delay(a).unwrap()).collect();
+        assert!(delays == [ms(10), ms(20), ms(40), ms(80), ms(100), ms(100)]);
+        // Huge attempt numbers stay at the cap.
+        assert!(backoff.delay(usize::MAX) == Some(ms(100)));
+    }
+
+    #[test]
+    fn jitter_stays_in_range() {
+        let backoff = Jittered::new(ExponentialBackoff::new(ms(10), ms(100)));
+        for attempt in 1..=6 {
+            let full = ExponentialBackoff::new(ms(10), ms(100))
+                .delay(attempt)
+                .unwrap();
+            let delay = backoff.delay(attempt).unwrap();
+            assert!(delay <= full);
+            assert!(delay >= full / 2);
+        }
+    }
+
+    #[test]
+    fn transient_errors_are_retried() {
+        take_delays();
+        let mut mock_api = MockApi::new()
+            .fails(ERROR_BUSY)
+            .fails(ERROR_BUSY)
+            .fails(ERROR_BUSY)
+            .writes([42; 100]);
+        let retry_policy = RetryPolicy::new(is_busy)
+            .backoff(ExponentialBackoff::new(ms(10), ms(25)))
+            .sleeper(record);
+        let size = Grob::builder()
+            .retry(retry_policy)
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(size == 100);
+        assert!(mock_api.is_finished());
+        assert!(mock_api.observations().len() == 4);
+        assert!(take_delays() == [ms(10), ms(20), ms(25)]);
+    }
+
+    #[test]
+    fn max_retries_returns_the_error() {
+        take_delays();
+        let mut calls = 0;
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let retry_policy = RetryPolicy::new(is_busy)
+            .backoff(FixedBackoff::new(ms(5)))
+            .max_retries(2)
+            .sleeper(record);
+        let rv = winapi_generic_with_retry(
+            retry_policy,
+            growable_buffer,
+            |_| {
+                calls += 1;
+                RvIsError::new(ERROR_BUSY.0)
+            },
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        );
+        assert!(rv.unwrap_err().is_win32(ERROR_BUSY));
+        assert!(calls == 3);
+        assert!(take_delays() == [ms(5), ms(5)]);
+    }
+
+    #[test]
+    fn retries_are_capped_by_default() {
+        take_delays();
+        let mut calls = 0;
+        let rv = Grob::builder()
+            .retry(RetryPolicy::new(is_busy).sleeper(record))
+            .run(
+                |_| {
+                    calls += 1;
+                    RvIsError::new(ERROR_BUSY.0)
+                },
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            );
+        assert!(rv.unwrap_err().is_win32(ERROR_BUSY));
+        assert!(calls == DEFAULT_MAX_RETRIES + 1);
+        assert!(take_delays() == vec![ms(10); DEFAULT_MAX_RETRIES]);
+    }
+
+    #[test]
+    fn other_errors_are_not_retried() {
+        take_delays();
+        let mut calls = 0;
+        let rv = Grob::builder()
+            .retry(RetryPolicy::new(is_busy).sleeper(record))
+            .run(
+                |_| {
+                    calls += 1;
+                    RvIsError::new(ERROR_ACCESS_DENIED.0)
+                },
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            );
+        assert!(rv.unwrap_err().is_win32(ERROR_ACCESS_DENIED));
+        assert!(calls == 1);
+        assert!(take_delays().is_empty());
+    }
+
+    #[test]
+    fn backoff_returning_none_stops() {
+        struct Twice;
+        impl Backoff for Twice {
+            fn delay(&self, attempt: usize) -> Option<Duration> {
+                (attempt <= 2).then(|| Duration::from_millis(attempt as u64))
+            }
+        }
+        take_delays();
+        let mut calls = 0;
+        let rv = Grob::builder()
+            .retry(RetryPolicy::new(is_busy).backoff(Twice).sleeper(record))
+            .run(
+                |_| {
+                    calls += 1;
+                    RvIsError::new(ERROR_BUSY.0)
+                },
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            );
+        assert!(rv.unwrap_err().is_win32(ERROR_BUSY));
+        assert!(calls == 3);
+        assert!(take_delays() == [ms(1), ms(2)]);
+    }
+
+    #[test]
+    fn deadline_is_checked_before_sleeping() {
+        take_delays();
+        let mut calls = 0;
+        let rv = Grob::builder()
+            .deadline(Instant::now() + Duration::from_secs(60))
+            .retry(
+                RetryPolicy::new(is_busy)
+                    .backoff(FixedBackoff::new(Duration::from_secs(3600)))
+                    .sleeper(record),
+            )
+            .run(
+                |_| {
+                    calls += 1;
+                    RvIsError::new(ERROR_BUSY.0)
+                },
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            );
+        let error = rv.unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::TimedOut);
+        let timeout = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<GrobTimeout>())
+            .unwrap();
+        assert!(timeout.tries == 1);
+        assert!(calls == 1);
+        assert!(take_delays().is_empty());
+    }
+
+    #[test]
+    fn no_wait_after_the_size_probe() {
+        take_delays();
+        let mut mock_api = MockApi::new().needs(2000).writes([42; 2000]);
+        let size = Grob::builder()
+            .grow_backoff(RetryWithBackoff::new(FixedBackoff::new(ms(5))).sleeper(record))
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(size == 2000);
+        assert!(take_delays().is_empty());
+    }
+
+    #[test]
+    fn waits_when_the_size_keeps_changing() {
+        take_delays();
+        let mut mock_api = MockApi::new()
+            .needs(2000)
+            .needs(3000)
+            .needs(4000)
+            .writes([42; 4000]);
+        let grow_backoff = RetryWithBackoff::new(ExponentialBackoff::new(ms(10), ms(100)));
+        let size = Grob::builder()
+            .grow_backoff(grow_backoff.sleeper(record))
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(size == 4000);
+        assert!(mock_api.observations().len() == 4);
+        assert!(take_delays() == [ms(10), ms(20)]);
+    }
+
+    #[test]
+    fn grow_backoff_checks_the_deadline() {
+        take_delays();
+        let mut mock_api = MockApi::new().needs(2000).needs(3000).writes([42; 3000]);
+        let rv = Grob::builder()
+            .deadline(Instant::now() + Duration::from_secs(60))
+            .grow_backoff(
+                RetryWithBackoff::new(FixedBackoff::new(Duration::from_secs(3600))).sleeper(record),
+            )
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            );
+        let error = rv.unwrap_err();
+        let timeout = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<GrobTimeout>())
+            .unwrap();
+        assert!(timeout.tries == 2);
+        assert!(take_delays().is_empty());
+    }
+}
+
+mod headroom {
+    use grob::{
+        GrowForSmallBinary, GrowStrategy, GrowToNearestQuarterKibi, GrowWith, GrowWithCap,
+        GrowWithHeadroom,
+    };
+
+    #[test]
+    fn first_grow_is_unchanged() {
+        let grow_strategy = GrowWithHeadroom::new(GrowForSmallBinary::new(), 110);
+        assert!(grow_strategy.next_capacity(1, 1000) == 1008);
+        assert!(grow_strategy.next_capacity(1, 16) == 16);
+    }
+
+    #[test]
+    fn later_grows_add_headroom() {
+        let grow_strategy = GrowWithHeadroom::new(GrowForSmallBinary::new(), 110);
+        assert!(grow_strategy.next_capacity(2, 1000) == 1109);
+        assert!(grow_strategy.next_capacity(3, 1000) == 1109);
+        assert!(grow_strategy.next_capacity(2, 100) == 124);
+        let grow_strategy = GrowWithHeadroom::new(GrowToNearestQuarterKibi::new(), 150);
+        assert!(grow_strategy.next_capacity(2, 1000) == 1536);
+    }
+
+    #[test]
+    fn never_less_than_the_inner_strategy() {
+        let grow_strategy = GrowWithHeadroom::new(GrowForSmallBinary::new(), 50);
+        assert!(grow_strategy.next_capacity(2, 1000) == 1008);
+        let grow_strategy = GrowWithHeadroom::new(GrowForSmallBinary::new(), 0);
+        assert!(grow_strategy.next_capacity(2, 1000) == 1008);
+    }
+
+    #[test]
+    fn no_overflow() {
+        let grow_strategy = GrowWithHeadroom::new(GrowForSmallBinary::new(), 110);
+        assert!(grow_strategy.next_capacity(2, u32::MAX) == u32::MAX);
+        assert!(grow_strategy.next_capacity(2, u32::MAX - 100) == u32::MAX);
+        let grow_strategy = GrowWithHeadroom::new(GrowWith::new(|_, d| d), u32::MAX);
+        assert!(grow_strategy.next_capacity(2, u32::MAX) == u32::MAX);
+        assert!(grow_strategy.next_capacity(2, 1) == 42_949_673);
+        assert!(grow_strategy.next_capacity(2, 0) == 0);
+    }
+
+    #[test]
+    fn respects_the_inner_limit() {
+        let capped = GrowWithCap::new(GrowForSmallBinary::new(), 1100);
+        let grow_strategy = GrowWithHeadroom::new(capped, 110);
+        assert!(grow_strategy.max_capacity() == Some(1100));
+        assert!(grow_strategy.next_capacity(2, 1000) == 1100);
+    }
+}
+
+mod strategy_factory {
+    use std::cell::{Cell, RefCell};
+    use std::sync::Mutex;
+
+    use grob::testing::MockApi;
+    use grob::{reset_default_strategy_factory, set_default_strategy_factory};
+    use grob::{winapi_large_binary, winapi_small_binary, winapi_string};
+    use grob::{FrozenBuffer, GrowStrategy, StrategyKind};
+
+    const CAP: u32 = 65536;
+
+    // The factory is process-wide.  Only threads that opt in see the capped strategy so the other
+    // tests, running in parallel, are not affected.
+    thread_local! {
+        static ACTIVE: Cell<bool> = const { Cell::new(false) };
+        static INVOCATIONS: RefCell<Vec<StrategyKind>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Serializes the tests that set or reset the factory.
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    struct Capped(Box<dyn GrowStrategy + Send + Sync>);
+
+    impl GrowStrategy for Capped {
+        fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+            self.0.next_capacity(tries, desired_capacity).min(CAP)
+        }
+    }
+
+    fn capping_factory(kind: StrategyKind) -> Box<dyn GrowStrategy + Send + Sync> {
+        if !ACTIVE.with(|a| a.get()) {
+            return kind.built_in();
+        }
+        INVOCATIONS.with(|i| i.borrow_mut().push(kind));
+        Box::new(Capped(kind.built_in()))
+    }
+
+    fn take_invocations() -> Vec<StrategyKind> {
+        INVOCATIONS.with(|i| std::mem::take(&mut *i.borrow_mut()))
+    }
+
+    fn large_binary(mock_api: &mut MockApi) -> Result<u32, std::io::Error> {
+        winapi_large_binary(
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+    }
+
+    #[test]
+    fn wrappers_honor_the_factory() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        set_default_strategy_factory(capping_factory);
+        ACTIVE.with(|a| a.set(true));
+        take_invocations();
+
+        // Fits under the cap so the capped strategy changes nothing.
+        let mut mock_api = MockApi::new().needs(20000).writes(vec![42; 20000]);
+        assert!(large_binary(&mut mock_api).unwrap() == 20000);
+
+        // Larger than the cap; the loop ends when the strategy cannot grow any further.
+        let mut mock_api = MockApi::new().needs(100000).writes(vec![42; 100000]);
+        let error = large_binary(&mut mock_api).unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::Other);
+        assert!(mock_api.observations().iter().all(|o| o.capacity <= CAP));
+
+        let size = winapi_small_binary(
+            |argument| MockApi::new().writes([1; 8]).call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 8);
+        let _ = winapi_string(true, |argument| {
+            MockApi::new()
+                .writes_wide(&[0x41, 0x42, 0])
+                .call_with_error_rv(argument)
+        });
+
+        ACTIVE.with(|a| a.set(false));
+        reset_default_strategy_factory();
+        assert!(
+            take_invocations()
+                == [
+                    StrategyKind::LargeBinary,
+                    StrategyKind::LargeBinary,
+                    StrategyKind::SmallBinary,
+                    StrategyKind::String
+                ]
+        );
+    }
+
+    #[test]
+    fn reset_restores_the_built_in_strategies() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        set_default_strategy_factory(capping_factory);
+        reset_default_strategy_factory();
+        ACTIVE.with(|a| a.set(true));
+        take_invocations();
+
+        let mut mock_api = MockApi::new().needs(100000).writes(vec![42; 100000]);
+        assert!(large_binary(&mut mock_api).unwrap() == 100000);
+
+        ACTIVE.with(|a| a.set(false));
+        assert!(take_invocations().is_empty());
+    }
+}
+
+mod call_context {
+    use windows::Win32::Foundation::ERROR_ACCESS_DENIED;
+
+    use grob::testing::MockApi;
+    use grob::{as_win32_err, win32_err, FrozenBuffer, Grob, IoErrorExt};
+
+    fn grow_then_fail(mock_api: &mut MockApi) -> std::io::Error {
+        Grob::builder()
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap_err()
+    }
+
+    #[test]
+    fn code_survives() {
+        let mut mock_api = MockApi::new().needs(2000).fails(ERROR_ACCESS_DENIED);
+        let error = grow_then_fail(&mut mock_api);
+        assert!(error.is_win32(ERROR_ACCESS_DENIED));
+        assert!(as_win32_err(&error) == Some(ERROR_ACCESS_DENIED));
+        assert!(error.kind() == win32_err(ERROR_ACCESS_DENIED).kind());
+    }
+
+    #[cfg(not(feature = "raw_os_errors"))]
+    mod wrapped {
+        use std::error::Error;
+
+        use windows::Win32::Foundation::ERROR_ACCESS_DENIED;
+
+        use grob::testing::MockApi;
+        use grob::{win32_err, GrobCallError};
+
+        use super::grow_then_fail;
+
+        fn call_error(error: &std::io::Error) -> &GrobCallError {
+            error
+                .get_ref()
+                .and_then(|e| e.downcast_ref::<GrobCallError>())
+                .unwrap()
+        }
+
+        #[test]
+        fn grow_then_fail_context() {
+            let mut mock_api = MockApi::new().needs(2000).fails(ERROR_ACCESS_DENIED);
+            let error = grow_then_fail(&mut mock_api);
+            let call_error = call_error(&error);
+            assert!(call_error.tries == 2);
+            assert!(call_error.grew);
+            assert!(call_error.capacity_at_failure >= 2000);
+            assert!(call_error.capacity_at_failure == mock_api.observations()[1].capacity);
+            assert!(call_error.raw_os_error() == Some(ERROR_ACCESS_DENIED.0 as i32));
+            assert!(error.raw_os_error().is_none());
+            assert!(error.to_string().contains("after growing"));
+        }
+
+        #[test]
+        fn fail_first_context() {
+            let mut mock_api = MockApi::new().fails(ERROR_ACCESS_DENIED);
+            let error = grow_then_fail(&mut mock_api);
+            let call_error = call_error(&error);
+            assert!(call_error.tries == 1);
+            assert!(!call_error.grew);
+            assert!(call_error.capacity_at_failure == mock_api.observations()[0].capacity);
+            assert!(call_error.last_requested == 0);
+            assert!(!error.to_string().contains("after growing"));
+        }
+
+        #[test]
+        fn three_tries_context() {
+            let mut mock_api = MockApi::new()
+                .needs(2000)
+                .needs(5000)
+                .fails(ERROR_ACCESS_DENIED);
+            let error = grow_then_fail(&mut mock_api);
+            let call_error = GrobCallError::from_io_error(&error).unwrap();
+            assert!(call_error.tries == 3);
+            assert!(call_error.grew);
+            assert!(call_error.last_requested == 5000);
+            assert!(call_error.capacity_at_failure >= 5000);
+            assert!(call_error.capacity_at_failure == mock_api.observations()[2].capacity);
+        }
+
+        #[test]
+        fn source_is_recovered() {
+            let mut mock_api = MockApi::new().needs(2000).fails(ERROR_ACCESS_DENIED);
+            let error = grow_then_fail(&mut mock_api);
+            let call_error = error
+                .into_inner()
+                .unwrap()
+                .downcast::<GrobCallError>()
+                .unwrap();
+            let source = call_error.into_source();
+            assert!(source.raw_os_error() == Some(ERROR_ACCESS_DENIED.0 as i32));
+        }
+
+        #[test]
+        fn not_a_call_error() {
+            let error = std::io::Error::other("not from the call loop");
+            assert!(GrobCallError::from_io_error(&error).is_none());
+            assert!(GrobCallError::from_io_error(&win32_err(ERROR_ACCESS_DENIED)).is_none());
+        }
+
+        #[test]
+        fn source_is_the_os_error() {
+            let mut mock_api = MockApi::new().fails(ERROR_ACCESS_DENIED);
+            let error = grow_then_fail(&mut mock_api);
+            let source = call_error(&error).source().unwrap();
+            let source = source.downcast_ref::<std::io::Error>().unwrap();
+            assert!(source.raw_os_error() == Some(ERROR_ACCESS_DENIED.0 as i32));
+        }
+    }
+
+    #[cfg(feature = "raw_os_errors")]
+    #[test]
+    fn raw_error_is_returned() {
+        let mut mock_api = MockApi::new().needs(2000).fails(ERROR_ACCESS_DENIED);
+        let error = grow_then_fail(&mut mock_api);
+        assert!(error.raw_os_error() == Some(ERROR_ACCESS_DENIED.0 as i32));
+        assert!(error.get_ref().is_none());
+    }
+}
+
+mod preserve_on_grow {
+    use windows::Win32::Foundation::{ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+
+    use grob::{winapi_generic, Argument, FrozenBuffer, GrowForSmallBinary, GrowableBuffer};
+    use grob::{RvIsError, StackBuffer};
+
+    const PAYLOAD_SIZE: usize = 100;
+
+    fn payload() -> Vec<u8> {
+        (0..PAYLOAD_SIZE as u8).collect()
+    }
+
+    fn to_vec(frozen_buffer: &FrozenBuffer<u8>) -> Vec<u8> {
+        frozen_buffer.as_slice().to_vec()
+    }
+
+    // Mimics a resumable API.  Each call writes what fits after the data already fetched then asks
+    // for `step` more bytes.
+    fn resumable(
+        argument: &mut Argument<*mut u8>,
+        step: usize,
+        resumes: &mut Vec<u32>,
+    ) -> RvIsError {
+        let payload = payload();
+        let start = argument.resumed_from() as usize;
+        resumes.push(argument.resumed_from());
+        let capacity = *argument.size_mut() as usize;
+        let end = capacity.min(PAYLOAD_SIZE);
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                payload[start..end].as_ptr(),
+                argument.pointer().add(start),
+                end - start,
+            )
+        };
+        if end < PAYLOAD_SIZE {
+            *argument.size_mut() = (capacity + step).min(PAYLOAD_SIZE) as u32;
+            RvIsError::new(ERROR_INSUFFICIENT_BUFFER.0)
+        } else {
+            *argument.size_mut() = PAYLOAD_SIZE as u32;
+            RvIsError::new(NO_ERROR.0)
+        }
+    }
+
+    #[test]
+    fn stack_to_heap_keeps_the_first_half() {
+        let mut initial_buffer = StackBuffer::<48>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                .preserve_on_grow();
+        let mut resumes = Vec::new();
+        let data = winapi_generic(
+            growable_buffer,
+            |argument| resumable(argument, PAYLOAD_SIZE, &mut resumes),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(to_vec(&frozen_buffer)),
+        )
+        .unwrap();
+        assert!(data == payload());
+        assert!(resumes == [0, 48]);
+    }
+
+    #[test]
+    fn heap_to_heap_reallocates() {
+        let mut initial_buffer = StackBuffer::<32>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                .preserve_on_grow();
+        let mut resumes = Vec::new();
+        let data = winapi_generic(
+            growable_buffer,
+            |argument| resumable(argument, 32, &mut resumes),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(to_vec(&frozen_buffer)),
+        )
+        .unwrap();
+        assert!(data == payload());
+        assert!(resumes.len() > 2);
+        assert!(resumes[0] == 0);
+        assert!(resumes.windows(2).all(|w| w[1] > w[0]));
+    }
+
+    #[test]
+    fn default_does_not_resume() {
+        let mut initial_buffer = StackBuffer::<48>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        *argument.size_mut() = PAYLOAD_SIZE as u32;
+        argument.grow().unwrap();
+        let argument = growable_buffer.argument();
+        assert!(argument.resumed_from() == 0);
+        argument.dismiss();
+    }
+}
+
+mod alloc_source {
+    use grob::testing::MockApi;
+    use grob::{AllocSource, FrozenBuffer, GrowForSmallBinary, GrowableBuffer, StackBuffer};
+
+    const PAYLOAD: [u8; 300] = [42; 300];
+
+    fn fill(alloc_source: AllocSource, mut f: impl FnMut(FrozenBuffer<u8>)) {
+        let mut initial_buffer = StackBuffer::<0>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                .alloc_source(alloc_source)
+                .preserve_on_grow();
+        let mut mock_api = MockApi::new().needs(200).writes(PAYLOAD);
+        grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| {
+                f(frozen_buffer);
+                Ok(())
+            },
+        )
+        .unwrap();
+    }
+
+    fn check(frozen_buffer: &FrozenBuffer<u8>) {
+        assert!(frozen_buffer.as_slice() == PAYLOAD);
+    }
+
+    #[test]
+    fn rust_global_round_trip() {
+        fill(AllocSource::RustGlobal, |frozen_buffer| {
+            check(&frozen_buffer)
+        });
+    }
+
+    #[test]
+    fn rust_global_cannot_be_released() {
+        fill(AllocSource::RustGlobal, |frozen_buffer| {
+            let frozen_buffer = frozen_buffer.release_to_os().unwrap_err();
+            // Still usable after the rejection.
+            check(&frozen_buffer);
+        });
+    }
+
+    #[test]
+    fn initial_buffer_cannot_be_released() {
+        let mut initial_buffer = StackBuffer::<512>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                .alloc_source(AllocSource::ProcessHeap);
+        let mut mock_api = MockApi::new().writes(PAYLOAD);
+        let released = grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.release_to_os().is_ok()),
+        )
+        .unwrap();
+        assert!(!released);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn process_heap_round_trip() {
+        fill(AllocSource::ProcessHeap, |frozen_buffer| {
+            check(&frozen_buffer)
+        });
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn process_heap_release() {
+        use windows::Win32::System::Memory::{GetProcessHeap, HeapFree, HEAP_FLAGS};
+
+        fill(AllocSource::ProcessHeap, |frozen_buffer| {
+            let (pointer, size) = frozen_buffer.release_to_os().unwrap();
+            assert!(size == PAYLOAD.len() as u32);
+            let data = unsafe { std::slice::from_raw_parts(pointer, size as usize) };
+            assert!(data == PAYLOAD);
+            let heap = unsafe { GetProcessHeap() }.unwrap();
+            assert!(unsafe { HeapFree(heap, HEAP_FLAGS(0), Some(pointer as *const _)) }.as_bool());
+        });
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn co_task_mem_round_trip() {
+        fill(AllocSource::CoTaskMem, |frozen_buffer| {
+            check(&frozen_buffer)
+        });
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn co_task_mem_release() {
+        use windows::Win32::System::Com::CoTaskMemFree;
+
+        fill(AllocSource::CoTaskMem, |frozen_buffer| {
+            let (pointer, size) = frozen_buffer.release_to_os().unwrap();
+            assert!(size == PAYLOAD.len() as u32);
+            let data = unsafe { std::slice::from_raw_parts(pointer, size as usize) };
+            assert!(data == PAYLOAD);
+            unsafe { CoTaskMemFree(Some(pointer as *const _)) };
+        });
+    }
+}
+
+mod thaw {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    use windows::Win32::Foundation::{ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+
+    use grob::{winapi_generic, Argument, FrozenBuffer, GrowForSmallBinary, GrowableBuffer};
+    use grob::{RvIsError, StackBuffer};
+
+    // Counts allocations made by the current thread so the other tests, running in parallel, do
+    // not interfere.
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+            System.alloc(layout)
+        }
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+
+    pub(crate) fn allocations() -> usize {
+        ALLOCATIONS.with(|a| a.get())
+    }
+
+    const PAYLOAD_SIZE: u32 = 300;
+
+    // Mimics an API that always returns PAYLOAD_SIZE bytes of `fill`.
+    fn mimic(argument: &mut Argument<*mut u8>, fill: u8) -> RvIsError {
+        unsafe {
+            if *argument.size_mut() < PAYLOAD_SIZE {
+                *argument.size_mut() = PAYLOAD_SIZE;
+                return RvIsError::new(ERROR_INSUFFICIENT_BUFFER.0);
+            }
+            std::ptr::write_bytes(argument.pointer(), fill, PAYLOAD_SIZE as usize);
+            *argument.size_mut() = PAYLOAD_SIZE;
+        }
+        RvIsError::new(NO_ERROR.0)
+    }
+
+    fn sum(frozen_buffer: &FrozenBuffer<u8>) -> u32 {
+        frozen_buffer.as_slice().iter().map(|b| *b as u32).sum()
+    }
+
+    fn call<'gs>(
+        growable_buffer: GrowableBuffer<'gs, '_, u8, *mut u8>,
+        fill: u8,
+        grow_strategy: &'gs GrowForSmallBinary,
+    ) -> GrowableBuffer<'gs, 'static, u8, *mut u8> {
+        winapi_generic(
+            growable_buffer,
+            |argument| mimic(argument, fill),
+            |frozen_buffer| {
+                assert!(sum(&frozen_buffer) == fill as u32 * PAYLOAD_SIZE);
+                frozen_buffer.thaw(grow_strategy).map_err(|e| e.into())
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn steady_state_does_not_allocate() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut initial_buffer = StackBuffer::<0>::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let before = allocations();
+        let growable_buffer = call(growable_buffer, 1, &grow_strategy);
+        let first = allocations() - before;
+        assert!(first == 1);
+        let before = allocations();
+        let growable_buffer = call(growable_buffer, 2, &grow_strategy);
+        let growable_buffer = call(growable_buffer, 3, &grow_strategy);
+        assert!(allocations() == before);
+        drop(growable_buffer);
+    }
+
+    #[test]
+    fn initial_buffer_is_copied() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer = {
+            let mut initial_buffer = StackBuffer::<512>::new();
+            let growable_buffer =
+                GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+            let before = allocations();
+            let growable_buffer = call(growable_buffer, 4, &grow_strategy);
+            // One allocation for the copy of the initial buffer.
+            assert!(allocations() - before == 1);
+            growable_buffer
+        };
+        // The initial buffer is gone.  The thawed buffer keeps working without growing.
+        let before = allocations();
+        let growable_buffer = call(growable_buffer, 5, &grow_strategy);
+        assert!(allocations() == before);
+        drop(growable_buffer);
+    }
+
+    #[test]
+    fn failed_call_thaws_empty() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut initial_buffer = StackBuffer::<512>::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut growable_buffer = growable_buffer.freeze().thaw(&grow_strategy).unwrap();
+        let mut argument = growable_buffer.argument();
+        assert!(*argument.size_mut() == 0);
+        argument.dismiss();
+        let growable_buffer = call(growable_buffer, 6, &grow_strategy);
+        drop(growable_buffer);
+    }
+}
+
+mod wchar_units {
+    use windows::core::PWSTR;
+
+    use grob::{bytes_to_wchars, bytes_to_wchars_usize, wchars_to_bytes, wchars_to_bytes_usize};
+    use grob::{RawToInternal, CAPACITY_FOR_NAMES, CAPACITY_FOR_PATHS, SIZE_OF_WCHAR};
+
+    // Small values plus the values around the edges.
+    fn samples() -> impl Iterator<Item = u32> {
+        let edges = [
+            u32::MAX / 2 - 1,
+            u32::MAX / 2,
+            u32::MAX / 2 + 1,
+            u32::MAX - 1,
+            u32::MAX,
+        ];
+        (0..300).chain(edges)
+    }
+
+    #[test]
+    fn round_trip() {
+        for wchars in samples() {
+            match wchars_to_bytes(wchars) {
+                Some(bytes) => {
+                    assert!(bytes == wchars * SIZE_OF_WCHAR);
+                    assert!(bytes_to_wchars(bytes) == wchars);
+                }
+                None => assert!(wchars > u32::MAX / SIZE_OF_WCHAR),
+            }
+        }
+    }
+
+    #[test]
+    fn odd_bytes_round_down() {
+        for bytes in samples() {
+            let wchars = bytes_to_wchars(bytes);
+            let whole = wchars_to_bytes(wchars).unwrap();
+            assert!(whole <= bytes);
+            assert!(bytes - whole == bytes % SIZE_OF_WCHAR);
+        }
+        assert!(bytes_to_wchars(1) == 0);
+        assert!(bytes_to_wchars(3) == 1);
+        assert!(bytes_to_wchars(u32::MAX) == u32::MAX / 2);
+    }
+
+    #[test]
+    fn near_u32_max() {
+        assert!(wchars_to_bytes(u32::MAX / 2) == Some(u32::MAX - 1));
+        assert!(wchars_to_bytes(u32::MAX / 2 + 1).is_none());
+        assert!(wchars_to_bytes(u32::MAX).is_none());
+    }
+
+    #[test]
+    fn usize_matches_u32() {
+        for value in samples() {
+            let wide = value as usize;
+            assert!(bytes_to_wchars_usize(wide) == bytes_to_wchars(value) as usize);
+            if let Some(bytes) = wchars_to_bytes(value) {
+                assert!(wchars_to_bytes_usize(wide) == Some(bytes as usize));
+            }
+        }
+        assert!(wchars_to_bytes_usize(usize::MAX).is_none());
+        assert!(wchars_to_bytes_usize(usize::MAX / 2) == Some(usize::MAX - 1));
+    }
+
+    #[test]
+    fn pwstr_conversions() {
+        for value in samples() {
+            assert!(PWSTR::capacity_to_size(value) == bytes_to_wchars(value));
+            let capacity = PWSTR::size_to_capacity(value);
+            assert!(capacity == wchars_to_bytes(value).unwrap_or(u32::MAX));
+        }
+    }
+
+    #[test]
+    fn capacity_constants() {
+        use grob::{capacity_for_wchars, CAPACITY_FOR_COMPUTER_NAME};
+        use grob::{CAPACITY_FOR_DOMAIN_NAME, CAPACITY_FOR_GUID_STRING};
+
+        assert!(capacity_for_wchars(0) == 2);
+        const { assert!(CAPACITY_FOR_NAMES == 514) };
+        const { assert!(CAPACITY_FOR_PATHS == 520) };
+        const { assert!(CAPACITY_FOR_COMPUTER_NAME == 32) };
+        const { assert!(CAPACITY_FOR_DOMAIN_NAME == 32) };
+        const { assert!(CAPACITY_FOR_GUID_STRING == 78) };
+    }
+
+    #[test]
+    fn capacities_hold_whole_wchars() {
+        assert!(CAPACITY_FOR_NAMES > wchars_to_bytes_usize(256).unwrap());
+        assert!(CAPACITY_FOR_PATHS >= wchars_to_bytes_usize(260).unwrap());
+    }
+}
+
+mod grob_call_trait {
+    use windows::Win32::Foundation::ERROR_ACCESS_DENIED;
+
+    use grob::testing::MockApi;
+    use grob::{run_call, Argument, FrozenBuffer, GrobCall, GrowForSmallBinary, GrowableBuffer};
+    use grob::{IoErrorExt, RvIsError, StackBuffer};
+
+    // The state is shared by the call and finish steps without any RefCell.
+    struct Counted {
+        mock_api: MockApi,
+        calls: usize,
+        finished: bool,
+    }
+
+    impl GrobCall for &mut Counted {
+        type Ft = u8;
+        type It = *mut u8;
+        type Rv = RvIsError;
+        type Output = Vec<u8>;
+
+        fn call(&mut self, argument: &mut Argument<*mut u8>) -> RvIsError {
+            self.calls += 1;
+            self.mock_api.call_with_error_rv(argument)
+        }
+        fn finish(self, frozen_buffer: FrozenBuffer<u8>) -> Result<Vec<u8>, std::io::Error> {
+            self.finished = true;
+            Ok(frozen_buffer.as_slice().to_vec())
+        }
+    }
+
+    #[test]
+    fn state_is_carried_through_the_loop() {
+        let mut counted = Counted {
+            mock_api: MockApi::new().writes([7; 200]),
+            calls: 0,
+            finished: false,
+        };
+        let mut initial_buffer = StackBuffer::<16>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer = GrowableBuffer::new(&mut initial_buffer, &grow_strategy);
+        let data = run_call(growable_buffer, &mut counted).unwrap();
+        assert!(data == [7; 200]);
+        assert!(counted.calls == 2);
+        assert!(counted.finished);
+        assert!(counted.mock_api.is_finished());
+    }
+
+    #[test]
+    fn errors_skip_finish() {
+        let mut counted = Counted {
+            mock_api: MockApi::new().fails(ERROR_ACCESS_DENIED),
+            calls: 0,
+            finished: false,
+        };
+        let mut initial_buffer = StackBuffer::<16>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer = GrowableBuffer::new(&mut initial_buffer, &grow_strategy);
+        let error = run_call(growable_buffer, &mut counted).unwrap_err();
+        assert!(error.is_win32(ERROR_ACCESS_DENIED));
+        assert!(counted.calls == 1);
+        assert!(!counted.finished);
+    }
+}
+
+mod attempts {
+    mod rv_is_error {
+        use windows::Win32::Foundation::{
+            ERROR_ADDRESS_NOT_ASSOCIATED, ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS,
+        };
+
+        use grob::{
+            GrowForSmallBinary, GrowableBuffer, IoErrorExt, RvIsError, StackBuffer, WriteBuffer,
+        };
+
+        fn grow_then_fill(tries: usize, data: Option<*mut u8>, size: *mut u32) -> u32 {
+            if tries == 1 {
+                unsafe {
+                    *size += 1;
+                }
+                ERROR_BUFFER_OVERFLOW.0
+            } else {
+                let p = data.unwrap();
+                assert!(!p.is_null());
+                unsafe { std::ptr::write_bytes(p, 42, (*size).try_into().unwrap()) };
+                ERROR_SUCCESS.0
+            }
+        }
+
+        #[test]
+        fn full_stack_buffer() {
+            let mut initial_buffer = StackBuffer::<64>::new();
+            // Alignment can take a few bytes from the stack buffer.
+            let stack_capacity = initial_buffer.capacity();
+            let grow_strategy = GrowForSmallBinary::new();
+            let mut growable_buffer =
+                GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+            let mut attempts = growable_buffer.attempts();
+            let mut finished = Vec::new();
+            while let Some(mut attempt) = attempts.next_attempt() {
+                let rv = RvIsError::new(grow_then_fill(
+                    attempt.tries(),
+                    Some(attempt.pointer()),
+                    attempt.size_mut(),
+                ));
+                finished.push(attempt.finish(rv).unwrap());
+            }
+            assert!(attempts.is_done());
+            assert!(attempts.next_attempt().is_none());
+            assert!(finished == [None, Some(())]);
+            let frozen_buffer = growable_buffer.freeze();
+            assert!(frozen_buffer.size() > stack_capacity);
+            let s = frozen_buffer.as_slice();
+            assert!(s.len() == frozen_buffer.size() as usize);
+            for v in s.iter() {
+                assert!(*v == 42);
+            }
+        }
+
+        fn return_error(_tries: usize, _data: Option<*mut u8>, _size: *mut u32) -> u32 {
+            ERROR_ADDRESS_NOT_ASSOCIATED.0
+        }
+
+        #[test]
+        fn error_leaves_attempts_open() {
+            let mut initial_buffer = StackBuffer::<64>::new();
+            let grow_strategy = GrowForSmallBinary::new();
+            let mut growable_buffer =
+                GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+            let mut attempts = growable_buffer.attempts();
+            let mut attempt = attempts.next_attempt().unwrap();
+            let rv = RvIsError::new(return_error(
+                attempt.tries(),
+                Some(attempt.pointer()),
+                attempt.size_mut(),
+            ));
+            match attempt.finish(rv) {
+                Ok(_) => unreachable!(),
+                Err(e) => assert!(e.is_win32(ERROR_ADDRESS_NOT_ASSOCIATED)),
+            }
+            // The call can be retried.
+            assert!(!attempts.is_done());
+            let attempt = attempts.next_attempt().unwrap();
+            assert!(attempt.tries() == 1);
+            attempt.dismiss();
+        }
+    }
+
+    mod rv_is_size {
+        use std::mem::size_of;
+
+        use windows::Win32::Foundation::{
+            SetLastError, ERROR_ADDRESS_NOT_ASSOCIATED, ERROR_INSUFFICIENT_BUFFER, ERROR_SUCCESS,
+        };
+
+        use grob::{GrowForSmallBinary, GrowableBuffer, IoErrorExt, RvIsSize, StackBuffer};
+
+        const LARGE_INTEGER: u128 = 0x0123456789ABCDEF0123456789ABCDEF;
+
+        fn grow_then_fill(tries: usize, data: Option<*mut u128>, size: u32) -> u32 {
+            if tries == 1 {
+                unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+                size
+            } else {
+                let c = size as usize / size_of::<u128>();
+                let p = data.unwrap();
+                let s = std::ptr::slice_from_raw_parts_mut(p, c);
+                for e in unsafe { (*s).iter_mut() } {
+                    *e = LARGE_INTEGER;
+                }
+                unsafe { SetLastError(ERROR_SUCCESS) };
+                size - 1
+            }
+        }
+
+        #[test]
+        fn full_stack_buffer() {
+            let mut initial_buffer = StackBuffer::<64>::new();
+            let grow_strategy = GrowForSmallBinary::new();
+            let mut growable_buffer =
+                GrowableBuffer::<u128, *mut u128>::new(&mut initial_buffer, &grow_strategy);
+            let mut attempts = growable_buffer.attempts();
+            while let Some(mut attempt) = attempts.next_attempt() {
+                let rv = RvIsSize::new(grow_then_fill(
+                    attempt.tries(),
+                    Some(attempt.pointer()),
+                    *attempt.size_mut(),
+                ));
+                attempt.finish(rv).unwrap();
+            }
+            let frozen_buffer = growable_buffer.freeze();
+            assert!(frozen_buffer.size() > 0);
+            let p = frozen_buffer.pointer().unwrap();
+            assert!(!p.is_null());
+            assert!(unsafe { *p } == LARGE_INTEGER);
+            let last = ((frozen_buffer.size() as usize / size_of::<u128>()) - 1) as isize;
+            assert!(unsafe { *(p.offset(last)) } == LARGE_INTEGER);
+        }
+
+        fn return_error(_tries: usize, _data: Option<*mut u128>, _size: u32) -> u32 {
+            unsafe { SetLastError(ERROR_ADDRESS_NOT_ASSOCIATED) };
+            0
+        }
+
+        #[test]
+        fn no_freeze_when_error() {
+            let mut initial_buffer = StackBuffer::<64>::new();
+            let grow_strategy = GrowForSmallBinary::new();
+            let mut growable_buffer =
+                GrowableBuffer::<u128, *mut u128>::new(&mut initial_buffer, &grow_strategy);
+            let mut attempts = growable_buffer.attempts();
+            let result = (|| {
+                while let Some(mut attempt) = attempts.next_attempt() {
+                    let rv = RvIsSize::new(return_error(
+                        attempt.tries(),
+                        Some(attempt.pointer()),
+                        *attempt.size_mut(),
+                    ));
+                    attempt.finish(rv)?;
+                }
+                Ok::<(), std::io::Error>(())
+            })();
+            match result {
+                Ok(()) => unreachable!(),
+                Err(e) => assert!(e.is_win32(ERROR_ADDRESS_NOT_ASSOCIATED)),
+            }
+        }
+    }
+}
+
+mod argument_with_min {
+    use grob::testing::MockApi;
+    use grob::{FrozenBuffer, GrowForSmallBinary, GrowStrategy, GrowableBuffer, StackBuffer};
+    use grob::{ToResult, WriteBuffer};
+
+    // Call until success passing `min_bytes` for the first try only.  Returns the tries used and
+    // the size of the data.
+    fn run(
+        initial_buffer: &mut dyn WriteBuffer,
+        grow_strategy: &dyn GrowStrategy,
+        mock_api: &mut MockApi,
+        min_bytes: u32,
+    ) -> (usize, u32) {
+        let mut growable_buffer = GrowableBuffer::<u8, *mut u8>::new(initial_buffer, grow_strategy);
+        let mut argument = growable_buffer.argument_with_min(min_bytes);
+        let tries = loop {
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let fill_buffer_action = rv.to_result(&mut argument).unwrap();
+            let tries = argument.tries();
+            if argument.try_apply(fill_buffer_action).unwrap() {
+                break tries;
+            }
+            argument = growable_buffer.argument();
+        };
+        let frozen_buffer: FrozenBuffer<u8> = growable_buffer.freeze();
+        (tries, frozen_buffer.size())
+    }
+
+    #[test]
+    fn sufficient_hint_means_one_call() {
+        let mut mock_api = MockApi::new().writes([1; 300]);
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let (tries, size) = run(&mut initial_buffer, &grow_strategy, &mut mock_api, 300);
+        assert!(size == 300);
+        // The pre-grow counts as a try.
+        assert!(tries == 2);
+        assert!(mock_api.observations().len() == 1);
+        assert!(mock_api.observations()[0].capacity >= 300);
+        assert!(mock_api.is_finished());
+    }
+
+    #[test]
+    fn insufficient_hint_grows_normally() {
+        let mut mock_api = MockApi::new().writes([1; 300]);
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let (tries, size) = run(&mut initial_buffer, &grow_strategy, &mut mock_api, 100);
+        assert!(size == 300);
+        assert!(tries == 3);
+        let observations = mock_api.observations();
+        assert!(observations.len() == 2);
+        assert!(observations[0].capacity >= 100 && observations[0].capacity < 300);
+        assert!(observations[1].capacity >= 300);
+        assert!(mock_api.is_finished());
+    }
+
+    #[test]
+    fn hint_within_capacity_is_not_a_try() {
+        let mut mock_api = MockApi::new().writes([1; 50]);
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let initial_capacity = initial_buffer.capacity();
+        let grow_strategy = GrowForSmallBinary::new();
+        let (tries, size) = run(&mut initial_buffer, &grow_strategy, &mut mock_api, 32);
+        assert!(size == 50);
+        assert!(tries == 1);
+        assert!(mock_api.observations()[0].capacity == initial_capacity);
+    }
+
+    struct Stuck;
+
+    impl GrowStrategy for Stuck {
+        fn next_capacity(&self, _tries: usize, _desired_capacity: u32) -> u32 {
+            0
+        }
+    }
+
+    #[test]
+    fn failed_hint_keeps_the_initial_buffer() {
+        let mut mock_api = MockApi::new().writes([1; 50]);
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let initial_capacity = initial_buffer.capacity();
+        let (tries, size) = run(&mut initial_buffer, &Stuck, &mut mock_api, 1000);
+        assert!(size == 50);
+        assert!(tries == 1);
+        assert!(mock_api.observations()[0].capacity == initial_capacity);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+mod zeroize {
+    use std::alloc::Layout;
+    use std::cell::RefCell;
+    use std::mem::ManuallyDrop;
+    use std::sync::Mutex;
+
+    use grob::testing::{set_windows_string_drop_observer, MockApi};
+    use grob::WindowsString;
+    use grob::{
+        BufferAllocator, FrozenBuffer, GrowForSmallBinary, GrowableBuffer, HeapBuffer, StackBuffer,
+        StdAlloc, WriteBuffer,
+    };
+
+    thread_local! {
+        static DROPPED: RefCell<Vec<Vec<u16>>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(heap: &[u16]) {
+        DROPPED.with(|d| d.borrow_mut().push(heap.to_vec()));
+    }
+
+    fn stack_contents<const N: usize>(ws: &WindowsString<N>) -> Vec<u16> {
+        unsafe { std::slice::from_raw_parts(ws.as_wide(), N) }.to_vec()
+    }
+
+    #[test]
+    fn explicit_zeroize_on_the_stack() {
+        let mut ws = WindowsString::<16>::new("hunter2").unwrap();
+        assert!(stack_contents(&ws)[..7] == *"hunter2".encode_utf16().collect::<Vec<_>>());
+        ws.zeroize();
+        assert!(stack_contents(&ws).iter().all(|c| *c == 0));
+    }
+
+    #[test]
+    fn drop_wipes_the_stack() {
+        let mut ws = ManuallyDrop::new(WindowsString::<16>::new("hunter2").unwrap());
+        let p = ws.as_wide();
+        unsafe { ManuallyDrop::drop(&mut ws) };
+        // ManuallyDrop keeps the storage so it is still safe to read.
+        let after = unsafe { std::slice::from_raw_parts(p, 16) };
+        assert!(after.iter().all(|c| *c == 0));
+    }
+
+    #[test]
+    fn drop_wipes_the_heap() {
+        DROPPED.with(|d| d.borrow_mut().clear());
+        set_windows_string_drop_observer(Some(record));
+        let ws = WindowsString::<4>::new("correct horse battery staple").unwrap();
+        drop(ws);
+        set_windows_string_drop_observer(None);
+        let dropped = DROPPED.with(|d| std::mem::take(&mut *d.borrow_mut()));
+        assert!(dropped.len() == 1);
+        assert!(dropped[0].len() == 29);
+        assert!(dropped[0].iter().all(|c| *c == 0));
+    }
+
+    #[test]
+    fn explicit_zeroize_on_the_heap() {
+        let mut ws = WindowsString::<0>::new("secret").unwrap();
+        ws.zeroize();
+        // What remains is an empty string.
+        assert!(unsafe { *ws.as_wide() } == 0);
+        let after = unsafe { std::slice::from_raw_parts(ws.as_wide(), 7) };
+        assert!(after.iter().all(|c| *c == 0));
+    }
+
+    // Reads back every block before passing it to StdAlloc to be freed.  Each test has its own so
+    // the tests, running in parallel, do not interfere.
+    #[derive(Debug)]
+    struct Inspecting {
+        freed: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl Inspecting {
+        const fn new() -> Self {
+            Self {
+                freed: Mutex::new(Vec::new()),
+            }
+        }
+        fn freed(&self) -> Vec<Vec<u8>> {
+            self.freed.lock().unwrap().clone()
+        }
+    }
+
+    unsafe impl BufferAllocator for Inspecting {
+        fn alloc(&self, capacity: usize, align: usize) -> *mut u8 {
+            StdAlloc.alloc(capacity, align)
+        }
+        unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+            let contents = std::slice::from_raw_parts(pointer, layout.size()).to_vec();
+            self.freed.lock().unwrap().push(contents);
+            StdAlloc.dealloc(pointer, layout)
+        }
+    }
+
+    fn all_zero(freed: &[Vec<u8>]) -> bool {
+        freed.iter().all(|block| block.iter().all(|b| *b == 0))
+    }
+
+    fn fill(allocator: &'static dyn BufferAllocator, preserve: bool) -> Vec<u8> {
+        let mut initial_buffer = StackBuffer::<0>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                .allocator(allocator);
+        if preserve {
+            growable_buffer = growable_buffer.preserve_on_grow();
+        }
+        let mut mock_api = MockApi::new().needs(200).needs(300).writes([42; 300]);
+        grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.as_slice().to_vec()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn growing_wipes_the_old_heap_buffer() {
+        static INSPECTING: Inspecting = Inspecting::new();
+        assert!(fill(&INSPECTING, false) == [42; 300]);
+        let freed = INSPECTING.freed();
+        // The block replaced by the second grow and the final block.
+        assert!(freed.len() == 2);
+        assert!(all_zero(&freed));
+    }
+
+    #[test]
+    fn preserved_contents_survive_the_wipe() {
+        static INSPECTING: Inspecting = Inspecting::new();
+        let mut heap_buffer = HeapBuffer::with_capacity_in(4, &INSPECTING).unwrap();
+        let (pointer, _) = heap_buffer.write_buffer();
+        unsafe { std::ptr::copy_nonoverlapping(b"abcd".as_ptr(), pointer, 4) };
+        heap_buffer.grow_to(64).unwrap();
+        let (pointer, _) = heap_buffer.write_buffer();
+        assert!(unsafe { std::slice::from_raw_parts(pointer, 4) } == b"abcd");
+        assert!(INSPECTING.freed().len() == 1);
+        drop(heap_buffer);
+        let freed = INSPECTING.freed();
+        assert!(freed.len() == 2);
+        assert!(all_zero(&freed));
+    }
+
+    #[test]
+    fn frozen_buffer_wipes_on_drop() {
+        static INSPECTING: Inspecting = Inspecting::new();
+        let mut initial_buffer = StackBuffer::<0>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                .allocator(&INSPECTING);
+        let mut mock_api = MockApi::new().needs(64).writes([42; 64]);
+        grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| {
+                // Still alive so nothing has been freed.
+                assert!(INSPECTING.freed().is_empty());
+                Ok(frozen_buffer.size())
+            },
+        )
+        .unwrap();
+        let freed = INSPECTING.freed();
+        assert!(freed.len() == 1);
+        assert!(freed[0].len() == 64);
+        assert!(all_zero(&freed));
+    }
+
+    #[test]
+    fn stack_buffer_wipes_on_drop() {
+        let mut stack_buffer = ManuallyDrop::new(StackBuffer::<32>::new());
+        let (pointer, capacity) = stack_buffer.write_buffer();
+        unsafe { std::ptr::write_bytes(pointer, 0x5A, capacity as usize) };
+        unsafe { ManuallyDrop::drop(&mut stack_buffer) };
+        // ManuallyDrop keeps the storage so it is still safe to read.
+        let after = unsafe { std::slice::from_raw_parts(pointer, capacity as usize) };
+        assert!(after.iter().all(|b| *b == 0));
+    }
+}
+
+mod size_accessors {
+    use std::ffi::c_void;
+
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{
+        SetLastError, BOOL, ERROR_BUFFER_OVERFLOW, ERROR_INSUFFICIENT_BUFFER, ERROR_SUCCESS, FALSE,
+        NO_ERROR, TRUE,
+    };
+
+    use grob::{winapi_path_buf, winapi_small_binary, winapi_string, GrowForSmallBinary};
+    use grob::{GrowableBuffer, RvIsError, StackBuffer};
+
+    const NAME: &str = "Zathras";
+
+    // Same shape as GetUserNameW.  The size is in characters and includes the terminator.
+    unsafe fn get_user_name(buffer: PWSTR, size: *mut u32) -> BOOL {
+        let wide: Vec<u16> = NAME.encode_utf16().chain(Some(0)).collect();
+        let needed = wide.len() as u32;
+        if *size < needed {
+            *size = needed;
+            SetLastError(ERROR_INSUFFICIENT_BUFFER);
+            return FALSE;
+        }
+        std::ptr::copy(wide.as_ptr(), buffer.0, wide.len());
+        *size = needed;
+        SetLastError(NO_ERROR);
+        TRUE
+    }
+
+    // Same shape as GetAdaptersAddresses.
+    unsafe fn get_adapters_addresses(
+        _family: u32,
+        _flags: u32,
+        _reserved: Option<*const c_void>,
+        addresses: Option<*mut u8>,
+        size: *mut u32,
+    ) -> u32 {
+        if *size < 100 {
+            *size = 100;
+            return ERROR_BUFFER_OVERFLOW.0;
+        }
+        std::ptr::write_bytes(addresses.unwrap(), 42, 100);
+        *size = 100;
+        ERROR_SUCCESS.0
+    }
+
+    // The size comes before the buffer.
+    unsafe fn size_first(size: *mut u32, buffer: *mut u8) -> u32 {
+        get_adapters_addresses(0, 0, None, Some(buffer), size)
+    }
+
+    #[test]
+    fn get_user_name_pattern() {
+        let name = winapi_string(false, |argument| {
+            RvIsError::new(unsafe { get_user_name(argument.pointer(), argument.size_mut()) })
+        })
+        .unwrap()
+        .unwrap();
+        assert!(name == NAME);
+    }
+
+    #[test]
+    fn get_adapters_addresses_pattern() {
+        let size = winapi_small_binary(
+            |argument| {
+                RvIsError::new(unsafe {
+                    get_adapters_addresses(
+                        0,
+                        0,
+                        None,
+                        Some(argument.pointer()),
+                        argument.size_mut(),
+                    )
+                })
+            },
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 100);
+    }
+
+    #[test]
+    fn size_first_pattern() {
+        let size = winapi_small_binary(
+            |argument| {
+                let (pointer, size) = argument.pointer_and_size_mut();
+                RvIsError::new(unsafe { size_first(size, pointer) })
+            },
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 100);
+    }
+
+    #[test]
+    fn size_without_unsafe() {
+        let path = winapi_path_buf(|argument| {
+            let wide: Vec<u16> = NAME.encode_utf16().collect();
+            let capacity = *argument.size_mut() as usize;
+            if capacity < wide.len() + 1 {
+                *argument.size_mut() = wide.len() as u32 + 1;
+                return RvIsError::new(ERROR_INSUFFICIENT_BUFFER.0);
+            }
+            unsafe { std::ptr::copy(wide.as_ptr(), argument.pointer().0, wide.len()) };
+            *argument.size_mut() = wide.len() as u32;
+            RvIsError::new(NO_ERROR.0)
+        })
+        .unwrap();
+        assert!(path.as_os_str() == NAME);
+    }
+
+    #[test]
+    fn size_opt_mut_without_a_buffer() {
+        let mut initial_buffer = StackBuffer::<0>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        assert!(argument.size_opt_mut().is_none());
+        *argument.size_mut() = 64;
+        argument.grow().unwrap();
+        let mut argument = growable_buffer.argument();
+        let size = argument.size_opt_mut().unwrap();
+        assert!(*size >= 64);
+        argument.dismiss();
+    }
+}
+
+mod string_into {
+    use std::os::windows::ffi::OsStrExt;
+
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{
+        SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, FALSE, NO_ERROR, TRUE,
+    };
+
+    use grob::{winapi_string_into, RvIsError};
+
+    use crate::thaw::allocations;
+
+    const INVALID_UNICODE: [u16; 4] = ['a' as u16, 0xD800, 'z' as u16, 0];
+
+    fn write_wide(wide: &[u16], data: PWSTR, size: *mut u32) -> BOOL {
+        let rv = if unsafe { *size >= wide.len() as u32 } {
+            unsafe { std::ptr::copy(wide.as_ptr(), data.0, wide.len()) };
+            unsafe { SetLastError(NO_ERROR) };
+            TRUE
+        } else {
+            unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+            FALSE
+        };
+        unsafe { *size = wide.len() as u32 };
+        rv
+    }
+
+    fn call(buffer: &mut String, lossy_ok: bool, wide: &[u16]) -> Result<(), std::ffi::OsString> {
+        winapi_string_into(buffer, lossy_ok, |argument| {
+            RvIsError::new(write_wide(wide, argument.pointer(), argument.size_mut()))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn replaces_contents() {
+        let zathras: Vec<u16> = "Zathras\0".encode_utf16().collect();
+        let mut buffer = String::from("left over from before");
+        call(&mut buffer, false, &zathras).unwrap();
+        assert!(buffer == "Zathras");
+    }
+
+    #[test]
+    fn invalid_unicode_replaced() {
+        let mut buffer = String::new();
+        call(&mut buffer, true, &INVALID_UNICODE).unwrap();
+        // Same as to_string_lossy; each unpaired surrogate becomes U+FFFD.
+        assert!(buffer == "a\u{FFFD}z");
+    }
+
+    #[test]
+    fn invalid_unicode_fails() {
+        let mut buffer = String::from("left over from before");
+        match call(&mut buffer, false, &INVALID_UNICODE) {
+            Ok(_) => unreachable!(),
+            Err(s) => {
+                let r: Vec<u16> = s.encode_wide().collect();
+                assert!(r == INVALID_UNICODE[..3]);
+            }
+        }
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn no_terminator_and_empty() {
+        let mut buffer = String::new();
+        call(&mut buffer, false, &['h' as u16, 'i' as u16]).unwrap();
+        assert!(buffer == "hi");
+        call(&mut buffer, false, &[0]).unwrap();
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn repeated_calls_reuse_the_buffer() {
+        let wide: Vec<u16> = "Zathras\0".encode_utf16().collect();
+        let mut buffer = String::new();
+        call(&mut buffer, true, &wide).unwrap();
+        let capacity = buffer.capacity();
+        let before = allocations();
+        for _ in 0..100 {
+            call(&mut buffer, true, &wide).unwrap();
+            assert!(buffer == "Zathras");
+        }
+        assert!(allocations() == before);
+        assert!(buffer.capacity() == capacity);
+    }
+
+    #[test]
+    fn winapi_string_allocates_every_call() {
+        let wide: Vec<u16> = "Zathras\0".encode_utf16().collect();
+        let before = allocations();
+        for _ in 0..100 {
+            let s = grob::winapi_string(true, |argument| {
+                RvIsError::new(write_wide(&wide, argument.pointer(), argument.size_mut()))
+            })
+            .unwrap()
+            .unwrap();
+            assert!(s == "Zathras");
+        }
+        assert!(allocations() - before >= 100);
+    }
+}
+
+mod multi_sz {
+    use std::ffi::OsString;
+
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{SetLastError, ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+
+    use grob::{
+        winapi_multi_sz, GrowForMultiSz, GrowForStaticText, GrowStrategy, RvIsSize, StrategyKind,
+    };
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().collect()
+    }
+
+    // Returns the number of characters stored or, when the block does not fit, fills the buffer
+    // and returns its size with ERROR_INSUFFICIENT_BUFFER.
+    fn mimic(block: &[u16], data: PWSTR, size: u32) -> u32 {
+        if size <= block.len() as u32 {
+            unsafe { std::ptr::copy(block.as_ptr(), data.0, size as usize) };
+            unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+            return size;
+        }
+        unsafe { std::ptr::copy(block.as_ptr(), data.0, block.len()) };
+        unsafe { SetLastError(NO_ERROR) };
+        block.len() as u32
+    }
+
+    fn run(block: &[u16]) -> Vec<OsString> {
+        winapi_multi_sz(|argument| {
+            RvIsSize::new(mimic(block, argument.pointer(), *argument.size_mut()))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn two_strings() {
+        let list = run(&wide("A:\\\0C:\\\0\0"));
+        assert!(list == [OsString::from("A:\\"), OsString::from("C:\\")]);
+    }
+
+    #[test]
+    fn grows_for_a_long_list() {
+        let mut block = Vec::new();
+        for i in 0..200 {
+            block.extend(wide(&format!("\\Device\\HarddiskVolume{}", i)));
+            block.push(0);
+        }
+        block.push(0);
+        let list = run(&block);
+        assert!(list.len() == 200);
+        assert!(list[199] == "\\Device\\HarddiskVolume199");
+    }
+
+    #[test]
+    fn nothing_stored() {
+        assert!(run(&[]).is_empty());
+    }
+
+    #[test]
+    fn only_the_terminator() {
+        assert!(run(&[0, 0]).is_empty());
+        assert!(run(&[0]).is_empty());
+    }
+
+    #[test]
+    fn missing_final_nul() {
+        let list = run(&wide("one\0two"));
+        assert!(list == [OsString::from("one"), OsString::from("two")]);
+        let list = run(&wide("one\0two\0"));
+        assert!(list == [OsString::from("one"), OsString::from("two")]);
+    }
+
+    #[test]
+    fn stops_at_the_empty_string() {
+        let list = run(&wide("one\0\0ignored\0\0"));
+        assert!(list == [OsString::from("one")]);
+    }
+
+    #[test]
+    fn strategy_reserves_two_terminators() {
+        let grow_strategy = GrowForMultiSz::new();
+        // Room for two more WCHARs, rounded up to a multiple of 16.
+        for desired in [0, 1, 12, 13, 14, 16, 100, 1006, 1020] {
+            let chosen = grow_strategy.next_capacity(1, desired);
+            assert!(chosen >= desired + 4);
+            assert!(chosen % 16 == 0);
+            assert!(chosen < desired + 4 + 16);
+        }
+        assert!(grow_strategy.next_capacity(1, 12) == 16);
+        assert!(grow_strategy.next_capacity(1, 14) == 32);
+        assert!(grow_strategy.next_capacity(1, 28) == 32);
+        assert!(grow_strategy.next_capacity(1, 29) == 48);
+        assert!(grow_strategy.next_capacity(1, u32::MAX) == u32::MAX);
+        // One more WCHAR of slack than static text.
+        assert!(GrowForStaticText::new().next_capacity(1, 14) == 16);
+        let s = format!("{:?}", grow_strategy);
+        assert!(s.contains("GrowToNearestNibbleWithDoubleNull"));
+        assert!(s.contains("extra: 4"));
+    }
+
+    #[test]
+    fn built_in_for_the_multi_sz_kind() {
+        let grow_strategy = StrategyKind::MultiSz.built_in();
+        assert!(grow_strategy.next_capacity(1, 14) == 32);
+    }
+}
+
+mod vec {
+    use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
+
+    use grob::{winapi_vec, Argument, RvIsError};
+
+    // Stores `bytes` of the pattern 0x01, 0x02, ...
+    fn mimic<T>(argument: &mut Argument<*mut T>, bytes: u32) -> RvIsError {
+        if *argument.size_mut() < bytes {
+            *argument.size_mut() = bytes;
+            return RvIsError::new(ERROR_BUFFER_OVERFLOW.0);
+        }
+        let p = argument.pointer() as *mut u8;
+        for i in 0..bytes {
+            unsafe { *p.add(i as usize) = (i + 1) as u8 };
+        }
+        *argument.size_mut() = bytes;
+        RvIsError::new(ERROR_SUCCESS.0)
+    }
+
+    #[test]
+    fn zero_elements() {
+        let v = winapi_vec::<u32, _, _>(|argument| mimic(argument, 0)).unwrap();
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn exact_multiple() {
+        let v = winapi_vec::<u32, _, _>(|argument| mimic(argument, 8)).unwrap();
+        assert!(
+            v == [
+                u32::from_ne_bytes([1, 2, 3, 4]),
+                u32::from_ne_bytes([5, 6, 7, 8])
+            ]
+        );
+    }
+
+    #[test]
+    fn heap_buffer_outlives_the_call() {
+        // Larger than the stack buffer so the data comes from the heap.
+        let v = winapi_vec::<[u8; 4], _, _>(|argument| mimic(argument, 100_000)).unwrap();
+        assert!(v.len() == 25_000);
+        assert!(v[0] == [1, 2, 3, 4]);
+        assert!(v[24_999] == [0x9D, 0x9E, 0x9F, 0xA0]);
+    }
+
+    #[test]
+    fn not_a_multiple() {
+        let e = winapi_vec::<u32, _, _>(|argument| mimic(argument, 6)).unwrap_err();
+        assert!(e.kind() == std::io::ErrorKind::InvalidData);
+    }
+}
+
+mod single_struct {
+    use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
+
+    use grob::{winapi_struct, Argument, Pod, RvIsError};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[repr(C)]
+    struct Info {
+        size: u32,
+        flags: u16,
+        value: u64,
+    }
+
+    unsafe impl Pod for Info {}
+
+    const INFO: Info = Info {
+        size: 24,
+        flags: 0xBEEF,
+        value: 0x0123456789ABCDEF,
+    };
+
+    // Stores `bytes` bytes starting with INFO.
+    fn mimic(argument: &mut Argument<*mut Info>, bytes: u32) -> RvIsError {
+        if *argument.size_mut() < bytes {
+            *argument.size_mut() = bytes;
+            return RvIsError::new(ERROR_BUFFER_OVERFLOW.0);
+        }
+        let source = [INFO, INFO];
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                source.as_ptr() as *const u8,
+                argument.pointer() as *mut u8,
+                bytes as usize,
+            )
+        };
+        *argument.size_mut() = bytes;
+        RvIsError::new(ERROR_SUCCESS.0)
+    }
+
+    #[test]
+    fn one_struct() {
+        let size = std::mem::size_of::<Info>() as u32;
+        let info = winapi_struct(|argument| mimic(argument, size)).unwrap();
+        assert!(info == Some(INFO));
+    }
+
+    #[test]
+    fn extra_data_ignored() {
+        let size = std::mem::size_of::<Info>() as u32 * 2;
+        let info = winapi_struct(|argument| mimic(argument, size)).unwrap();
+        assert!(info == Some(INFO));
+    }
+
+    #[test]
+    fn nothing_stored() {
+        let info = winapi_struct(|argument| mimic(argument, 0)).unwrap();
+        assert!(info.is_none());
+    }
+
+    #[test]
+    fn too_small() {
+        let e = winapi_struct(|argument| mimic(argument, 6)).unwrap_err();
+        assert!(e.kind() == std::io::ErrorKind::InvalidData);
+        assert!(e.to_string().contains("6 bytes"));
+    }
+}
+
+mod stack_capacity {
+    use grob::testing::MockApi;
+    use grob::{winapi_large_binary_with, winapi_small_binary_with, FrozenBuffer};
+
+    #[test]
+    fn zero_small_binary_goes_to_the_heap() {
+        let mut mock_api = MockApi::new().writes([7; 100]);
+        let size = winapi_small_binary_with::<0, _, _, _, _, _>(
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 100);
+        let observations = mock_api.observations();
+        assert!(observations.len() == 2);
+        assert!(observations[0].capacity == 0);
+        assert!(observations[1].capacity >= 100);
+    }
+
+    #[test]
+    fn zero_large_binary_goes_to_the_heap() {
+        let mut mock_api = MockApi::new().writes([7; 100]);
+        let size = winapi_large_binary_with::<0, _, _, _, _, _>(
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 100);
+        let observations = mock_api.observations();
+        assert!(observations.len() == 2);
+        assert!(observations[0].capacity == 0);
+        assert!(observations[1].capacity >= 65536);
+    }
+
+    #[test]
+    fn larger_stack_means_one_call() {
+        let mut mock_api = MockApi::new().writes([7; 3000]);
+        let size = winapi_small_binary_with::<4096, _, _, _, _, _>(
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 3000);
+        assert!(mock_api.observations().len() == 1);
+    }
+}
+
+mod reuse_in {
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+
+    use grob::{winapi_large_binary_in, Argument, FrozenBuffer, GrowForSmallBinary};
+    use grob::{GrowableBuffer, RvIsError, StackBuffer, WriteBuffer};
+
+    use crate::thaw::allocations;
+
+    // Mimics an API that returns `needed` bytes of `fill`.
+    fn mimic(argument: &mut Argument<*mut u8>, needed: u32, fill: u8) -> RvIsError {
+        if *argument.size_mut() < needed {
+            *argument.size_mut() = needed;
+            return RvIsError::new(ERROR_INSUFFICIENT_BUFFER.0);
+        }
+        unsafe { std::ptr::write_bytes(argument.pointer(), fill, needed as usize) };
+        *argument.size_mut() = needed;
+        RvIsError::new(NO_ERROR.0)
+    }
+
+    fn sum(frozen_buffer: &FrozenBuffer<u8>) -> u32 {
+        frozen_buffer.as_slice().iter().map(|b| *b as u32).sum()
+    }
+
+    fn call(
+        growable_buffer: &mut GrowableBuffer<u8, *mut u8>,
+        needed: u32,
+        fill: u8,
+    ) -> Result<u32, std::io::Error> {
+        winapi_large_binary_in(
+            growable_buffer,
+            |argument| mimic(argument, needed, fill),
+            |frozen_buffer| Ok(sum(&frozen_buffer)),
+        )
+    }
+
+    #[test]
+    fn second_call_does_not_allocate() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut initial_buffer = StackBuffer::<0>::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let before = allocations();
+        assert!(call(&mut growable_buffer, 300, 1).unwrap() == 300);
+        assert!(allocations() - before == 1);
+        let before = allocations();
+        assert!(call(&mut growable_buffer, 300, 2).unwrap() == 600);
+        assert!(call(&mut growable_buffer, 200, 3).unwrap() == 600);
+        assert!(allocations() == before);
+    }
+
+    #[test]
+    fn larger_need_grows() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut initial_buffer = StackBuffer::<0>::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        assert!(call(&mut growable_buffer, 300, 1).unwrap() == 300);
+        let before = allocations();
+        assert!(call(&mut growable_buffer, 5000, 1).unwrap() == 5000);
+        assert!(allocations() - before == 1);
+    }
+
+    #[test]
+    fn initial_buffer_is_reused() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut initial_buffer = StackBuffer::<512>::new();
+        let capacity = initial_buffer.capacity();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let before = allocations();
+        assert!(call(&mut growable_buffer, capacity, 1).unwrap() == capacity);
+        assert!(call(&mut growable_buffer, 10, 2).unwrap() == 20);
+        assert!(allocations() == before);
+    }
+
+    #[test]
+    fn usable_after_error() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut initial_buffer = StackBuffer::<0>::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        assert!(call(&mut growable_buffer, 300, 1).unwrap() == 300);
+        let rv = winapi_large_binary_in(
+            &mut growable_buffer,
+            |_| RvIsError::new(ERROR_ACCESS_DENIED.0),
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        );
+        assert!(rv.is_err());
+        // The error is not carried over to the next call.
+        let before = allocations();
+        assert!(call(&mut growable_buffer, 300, 4).unwrap() == 1200);
+        assert!(allocations() == before);
+    }
+}
+
+mod with_strategy {
+    use std::cell::RefCell;
+
+    use grob::testing::MockApi;
+    use grob::{winapi_large_binary_with_strategy, winapi_small_binary_with_strategy};
+    use grob::{FrozenBuffer, GrowStrategy};
+
+    // Rounds up to a 4 KiB page and records every call.
+    #[derive(Default)]
+    struct RecordingStrategy {
+        calls: RefCell<Vec<(usize, u32)>>,
+    }
+
+    impl GrowStrategy for RecordingStrategy {
+        fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+            self.calls.borrow_mut().push((tries, desired_capacity));
+            (desired_capacity + 64).next_multiple_of(4096)
+        }
+    }
+
+    #[test]
+    fn large_binary_consults_the_strategy() {
+        let grow_strategy = RecordingStrategy::default();
+        let mut mock_api = MockApi::new().needs(20000).writes([7; 20000]);
+        let size = winapi_large_binary_with_strategy(
+            &grow_strategy,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 20000);
+        assert!(*grow_strategy.calls.borrow() == [(1, 20000)]);
+        // The default 16 KiB stack buffer was used first and there is no 64 KiB floor.
+        let observations = mock_api.observations();
+        assert!(observations.len() == 2);
+        assert!(observations[0].capacity > 8192 && observations[0].capacity <= 16384);
+        assert!(observations[1].capacity == 20480);
+    }
+
+    #[test]
+    fn small_binary_consults_the_strategy() {
+        let grow_strategy = RecordingStrategy::default();
+        let mut mock_api = MockApi::new().needs(2000).needs(5000).writes([7; 5000]);
+        let size = winapi_small_binary_with_strategy(
+            &grow_strategy,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 5000);
+        assert!(*grow_strategy.calls.borrow() == [(1, 2000), (2, 5000)]);
+        let capacities: Vec<u32> = mock_api.observations().iter().map(|o| o.capacity).collect();
+        assert!(capacities[0] <= 1024);
+        assert!(capacities[1..] == [4096, 8192]);
+    }
+
+    #[test]
+    fn strategy_not_needed() {
+        let grow_strategy = RecordingStrategy::default();
+        let mut mock_api = MockApi::new().writes([7; 100]);
+        let size = winapi_small_binary_with_strategy(
+            &grow_strategy,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 100);
+        assert!(grow_strategy.calls.borrow().is_empty());
+    }
+}
+
+mod registry_value {
+    use std::ffi::OsString;
+
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_MORE_DATA, NO_ERROR, WIN32_ERROR};
+    use windows::Win32::System::Registry::{
+        REG_BINARY, REG_DWORD, REG_DWORD_BIG_ENDIAN, REG_EXPAND_SZ, REG_MULTI_SZ, REG_QWORD,
+        REG_SZ, REG_VALUE_TYPE,
+    };
+
+    use grob::{winapi_registry_value, Argument, IoErrorExt, RegistryValue};
+
+    // Mimics RegQueryValueExW.
+    fn mimic(
+        argument: &mut Argument<*mut u8>,
+        value_type: &mut REG_VALUE_TYPE,
+        data_type: REG_VALUE_TYPE,
+        data: &[u8],
+    ) -> WIN32_ERROR {
+        *value_type = data_type;
+        let size = argument.size_mut();
+        if (*size as usize) < data.len() {
+            *size = data.len() as u32;
+            return ERROR_MORE_DATA;
+        }
+        *size = data.len() as u32;
+        unsafe { std::ptr::copy(data.as_ptr(), argument.pointer(), data.len()) };
+        NO_ERROR
+    }
+
+    fn read(data_type: REG_VALUE_TYPE, data: &[u8]) -> Result<RegistryValue, std::io::Error> {
+        winapi_registry_value(|argument, value_type| mimic(argument, value_type, data_type, data))
+    }
+
+    fn wide(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(|c| c.to_ne_bytes()).collect()
+    }
+
+    #[test]
+    fn dword() {
+        let rv = read(REG_DWORD, &42u32.to_le_bytes()).unwrap();
+        assert!(rv == RegistryValue::Dword(42));
+        let rv = read(REG_DWORD_BIG_ENDIAN, &42u32.to_be_bytes()).unwrap();
+        assert!(rv == RegistryValue::Dword(42));
+    }
+
+    #[test]
+    fn qword() {
+        let rv = read(REG_QWORD, &0x1234_5678_9abc_def0u64.to_le_bytes()).unwrap();
+        assert!(rv == RegistryValue::Qword(0x1234_5678_9abc_def0));
+    }
+
+    #[test]
+    fn wrong_size() {
+        let rv = read(REG_DWORD, &[1, 2]);
+        assert!(rv.unwrap_err().kind() == std::io::ErrorKind::InvalidData);
+        let rv = read(REG_QWORD, &42u32.to_le_bytes());
+        assert!(rv.unwrap_err().kind() == std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn string() {
+        let rv = read(REG_SZ, &wide("Zathras\0")).unwrap();
+        assert!(rv == RegistryValue::String(OsString::from("Zathras")));
+        let rv = read(REG_EXPAND_SZ, &wide("%SystemRoot%\\System32\0")).unwrap();
+        assert!(rv == RegistryValue::String(OsString::from("%SystemRoot%\\System32")));
+    }
+
+    #[test]
+    fn string_without_terminator() {
+        let rv = read(REG_SZ, &wide("Zathras")).unwrap();
+        assert!(rv == RegistryValue::String(OsString::from("Zathras")));
+    }
+
+    #[test]
+    fn string_with_extra_terminators() {
+        let rv = read(REG_SZ, &wide("Zathras\0\0\0")).unwrap();
+        assert!(rv == RegistryValue::String(OsString::from("Zathras")));
+    }
+
+    #[test]
+    fn empty_string() {
+        let rv = read(REG_SZ, &[]).unwrap();
+        assert!(rv == RegistryValue::String(OsString::new()));
+    }
+
+    #[test]
+    fn multi_string() {
+        let expected =
+            RegistryValue::MultiString(vec![OsString::from("one"), OsString::from("two")]);
+        let rv = read(REG_MULTI_SZ, &wide("one\0two\0\0")).unwrap();
+        assert!(rv == expected);
+        // Missing the final terminators.
+        let rv = read(REG_MULTI_SZ, &wide("one\0two")).unwrap();
+        assert!(rv == expected);
+    }
+
+    #[test]
+    fn binary_grows() {
+        let data: Vec<u8> = (0..5000u32).map(|i| i as u8).collect();
+        let mut calls = 0;
+        let rv = winapi_registry_value(|argument, value_type| {
+            calls += 1;
+            mimic(argument, value_type, REG_BINARY, &data)
+        })
+        .unwrap();
+        assert!(rv == RegistryValue::Binary(data.clone()));
+        assert!(calls == 2);
+    }
+
+    #[test]
+    fn long_string_grows() {
+        let s = "z".repeat(3000);
+        let rv = read(REG_SZ, &wide(&s)).unwrap();
+        assert!(rv == RegistryValue::String(OsString::from(s)));
+    }
+
+    #[test]
+    fn error_is_returned() {
+        let rv = winapi_registry_value(|_, _| ERROR_ACCESS_DENIED);
+        assert!(rv.unwrap_err().is_win32(ERROR_ACCESS_DENIED));
+    }
+}
+
+mod token_info {
+    use windows::Win32::Foundation::{SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, PSID};
+    use windows::Win32::Security::{SID_AND_ATTRIBUTES, TOKEN_ELEVATION, TOKEN_USER};
+
+    use grob::{winapi_frozen, Argument, RvIsError};
+
+    // Mimics GetTokenInformation.  `fill` writes the data and returns the number of bytes needed.
+    fn mimic<T>(argument: &mut Argument<*mut T>, needed: u32, fill: impl Fn(*mut u8)) -> RvIsError {
+        let (pointer, size) = argument.pointer_and_size_mut();
+        let length = *size;
+        *size = needed;
+        if length < needed {
+            unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+            return RvIsError::new(BOOL::from(false));
+        }
+        fill(pointer.cast());
+        RvIsError::new(BOOL::from(true))
+    }
+
+    #[test]
+    fn fixed_size() {
+        let needed = std::mem::size_of::<TOKEN_ELEVATION>() as u32;
+        let mut calls = 0;
+        let frozen_buffer = winapi_frozen(|argument: &mut Argument<*mut TOKEN_ELEVATION>| {
+            calls += 1;
+            mimic(argument, needed, |p| unsafe {
+                *p.cast::<TOKEN_ELEVATION>() = TOKEN_ELEVATION { TokenIsElevated: 1 }
+            })
+        })
+        .unwrap();
+        // The buffer starts big enough for a TOKEN_ELEVATION.
+        assert!(calls == 1);
+        let p = frozen_buffer.try_pointer().unwrap().unwrap();
+        assert!(unsafe { (*p).TokenIsElevated } == 1);
+    }
+
+    #[test]
+    fn variable_size() {
+        // A TOKEN_USER followed by the SID it points to.
+        const SID: [u8; 12] = [1, 1, 0, 0, 0, 0, 0, 5, 18, 0, 0, 0];
+        let header = std::mem::size_of::<TOKEN_USER>();
+        let needed = (header + SID.len()) as u32;
+        let mut calls = 0;
+        let frozen_buffer = winapi_frozen(|argument: &mut Argument<*mut TOKEN_USER>| {
+            calls += 1;
+            mimic(argument, needed, |p| unsafe {
+                let sid = p.add(header);
+                std::ptr::copy(SID.as_ptr(), sid, SID.len());
+                *p.cast::<TOKEN_USER>() = TOKEN_USER {
+                    User: SID_AND_ATTRIBUTES {
+                        Sid: PSID(sid.cast()),
+                        Attributes: 0,
+                    },
+                };
+            })
+        })
+        .unwrap();
+        assert!(calls == 2);
+        assert!(frozen_buffer.size() == needed);
+        // The interior pointer is still valid.
+        let p = frozen_buffer.try_pointer().unwrap().unwrap();
+        let sid = unsafe { std::slice::from_raw_parts((*p).User.Sid.0.cast::<u8>(), SID.len()) };
+        assert!(sid == SID);
+    }
+
+    #[test]
+    fn short_is_rejected() {
+        // Only four of the bytes of a TOKEN_USER are stored.
+        let rv = winapi_frozen(|argument: &mut Argument<*mut TOKEN_USER>| {
+            mimic(argument, 4, |p| unsafe { std::ptr::write_bytes(p, 0, 4) })
+        });
+        let e = rv.unwrap_err();
+        assert!(e.kind() == std::io::ErrorKind::InvalidData);
+        assert!(e.to_string().contains("TOKEN_USER"));
+    }
+}
+
+mod security_descriptor {
+    use windows::Win32::Foundation::{SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER};
+    use windows::Win32::Security::SECURITY_DESCRIPTOR;
+
+    use grob::{winapi_security_descriptor_with, Argument, RvIsError, ALIGNMENT};
+
+    // Mimics GetFileSecurityW.  The descriptor is `needed` bytes of a repeating pattern.
+    fn mimic(
+        argument: &mut Argument<*mut SECURITY_DESCRIPTOR>,
+        needed: u32,
+        capacities: &mut Vec<u32>,
+    ) -> RvIsError {
+        let (pointer, size) = argument.pointer_and_size_mut();
+        let length = *size;
+        capacities.push(length);
+        *size = needed;
+        if length < needed {
+            unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+            return RvIsError::new(BOOL::from(false));
+        }
+        for i in 0..needed as usize {
+            unsafe { *pointer.cast::<u8>().add(i) = i as u8 };
+        }
+        RvIsError::new(BOOL::from(true))
+    }
+
+    #[test]
+    fn grow_is_aligned() {
+        let mut capacities = Vec::new();
+        let frozen_buffer =
+            winapi_security_descriptor_with(|argument| mimic(argument, 203, &mut capacities))
+                .unwrap();
+        assert!(capacities.len() == 2);
+        assert!(capacities[0] < 203);
+        assert!(capacities[1] >= 203);
+        assert!(capacities
+            .iter()
+            .all(|c| (*c as usize).is_multiple_of(ALIGNMENT)));
+        assert!(frozen_buffer.size() == 203);
+        let p = frozen_buffer.try_pointer().unwrap().unwrap();
+        assert!((p as usize).is_multiple_of(ALIGNMENT));
+        let bytes = unsafe { std::slice::from_raw_parts(p.cast::<u8>(), 203) };
+        assert!(bytes.iter().enumerate().all(|(i, b)| *b == i as u8));
+    }
+
+    #[test]
+    fn empty_self_relative_is_accepted() {
+        // A self-relative descriptor with nothing requested is smaller than a SECURITY_DESCRIPTOR.
+        let mut capacities = Vec::new();
+        let frozen_buffer =
+            winapi_security_descriptor_with(|argument| mimic(argument, 20, &mut capacities))
+                .unwrap();
+        assert!(capacities.len() == 1);
+        assert!(frozen_buffer.size() == 20);
+    }
+
+    #[test]
+    fn short_is_rejected() {
+        let mut capacities = Vec::new();
+        let rv = winapi_security_descriptor_with(|argument| mimic(argument, 8, &mut capacities));
+        let e = rv.unwrap_err();
+        assert!(e.kind() == std::io::ErrorKind::InvalidData);
+        assert!(e.to_string().contains("SECURITY_DESCRIPTOR"));
+    }
+}
+
+mod max_tries {
+    use windows::Win32::Foundation::{SetLastError, ERROR_INSUFFICIENT_BUFFER, FALSE};
+
+    use grob::{
+        winapi_generic_limited, winapi_small_binary, Argument, GrowForSmallBinary, GrowableBuffer,
+        RvIsError, StackBuffer, DEFAULT_MAX_TRIES,
+    };
+
+    // Mimics a misbehaving function that always needs one byte more than it was given.
+    fn always_grow(argument: &mut Argument<*mut u8>, calls: &mut usize) -> RvIsError {
+        *calls += 1;
+        let size = argument.size_mut();
+        *size += 1;
+        unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+        RvIsError::new(FALSE)
+    }
+
+    #[test]
+    fn default_limit_ends_the_loop() {
+        let mut calls = 0;
+        let rv = winapi_small_binary(
+            |argument| always_grow(argument, &mut calls),
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        );
+        let error = rv.unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::Other);
+        assert!(error
+            .to_string()
+            .contains(&format!("{} tries", DEFAULT_MAX_TRIES)));
+        assert!(calls == DEFAULT_MAX_TRIES);
+    }
+
+    #[test]
+    fn limited() {
+        let mut calls = 0;
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let rv = winapi_generic_limited(
+            5,
+            growable_buffer,
+            |argument| always_grow(argument, &mut calls),
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        );
+        let error = rv.unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::Other);
+        assert!(error.to_string().contains("5 tries"));
+        // The size the last call needed is in the message.
+        assert!(error.to_string().contains("needed a size of"));
+        assert!(calls == 5);
+    }
+}
+
+mod env_var {
+    use std::ffi::OsString;
+
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{
+        SetLastError, ERROR_ACCESS_DENIED, ERROR_ENVVAR_NOT_FOUND, NO_ERROR,
+    };
+
+    use grob::{winapi_env_var_with, Argument, RvIsLength};
+
+    // Mimics GetEnvironmentVariableW.  On success the length without the NUL is returned.  When
+    // the buffer is too small the size with the NUL is returned.
+    fn mimic(
+        argument: &mut Argument<PWSTR>,
+        value: Option<&str>,
+        calls: &mut Vec<u32>,
+    ) -> RvIsLength {
+        let buffer = argument.as_mut_slice();
+        calls.push(buffer.len() as u32);
+        let Some(value) = value else {
+            unsafe { SetLastError(ERROR_ENVVAR_NOT_FOUND) };
+            return RvIsLength::new(0);
+        };
+        let wide: Vec<u16> = value.encode_utf16().collect();
+        if buffer.len() <= wide.len() {
+            return RvIsLength::new(wide.len() as u32 + 1);
+        }
+        buffer[..wide.len()].copy_from_slice(&wide);
+        buffer[wide.len()] = 0;
+        unsafe { SetLastError(NO_ERROR) };
+        RvIsLength::new(wide.len() as u32)
+    }
+
+    fn read(value: Option<&str>, calls: &mut Vec<u32>) -> Option<OsString> {
+        winapi_env_var_with(|argument| mimic(argument, value, calls)).unwrap()
+    }
+
+    #[test]
+    fn found() {
+        let mut calls = Vec::new();
+        let value = read(Some("C:\\Windows"), &mut calls);
+        assert!(value == Some(OsString::from("C:\\Windows")));
+        assert!(calls.len() == 1);
+    }
+
+    #[test]
+    fn empty() {
+        let mut calls = Vec::new();
+        assert!(read(Some(""), &mut calls) == Some(OsString::new()));
+    }
+
+    #[test]
+    fn not_found() {
+        let mut calls = Vec::new();
+        assert!(read(None, &mut calls).is_none());
+        assert!(calls.len() == 1);
+    }
+
+    #[test]
+    fn grow_then_succeed() {
+        let long: String = "abcdefghij".repeat(100);
+        let mut calls = Vec::new();
+        let value = read(Some(&long), &mut calls);
+        assert!(value == Some(OsString::from(&long)));
+        assert!(calls.len() == 2);
+        // The second call has room for the value and the NUL.
+        assert!(calls[1] as usize > long.len());
+    }
+
+    #[test]
+    fn other_errors_are_returned() {
+        let rv = winapi_env_var_with(|argument| {
+            let _ = argument.as_mut_slice();
+            unsafe { SetLastError(ERROR_ACCESS_DENIED) };
+            RvIsLength::new(0)
+        });
+        assert!(rv.is_err());
+    }
+}
+
+mod expand_environment_strings {
+    use std::ffi::OsString;
+
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{SetLastError, ERROR_ACCESS_DENIED};
+
+    use grob::{expand_environment_strings_with, Argument};
+
+    // How the mimic reports sizes.
+    #[derive(Clone, Copy)]
+    enum Quirk {
+        // The documented behaviour; the count includes the NULL.
+        None,
+        // A buffer that is too small gets a count of its capacity plus one.
+        CapacityPlusOne,
+        // Both counts are one short; the NULL is not included.
+        OneShort,
+    }
+
+    // Mimics ExpandEnvironmentStringsW for an input that expands to `output`.
+    fn mimic(argument: &mut Argument<PWSTR>, output: &str, quirk: Quirk, calls: &mut usize) -> u32 {
+        *calls += 1;
+        let buffer = argument.as_mut_slice();
+        let capacity = buffer.len();
+        let wide: Vec<u16> = output.encode_utf16().collect();
+        let needed = wide.len() + 1;
+        if capacity < needed {
+            return match quirk {
+                Quirk::None => needed as u32,
+                Quirk::CapacityPlusOne => capacity as u32 + 1,
+                Quirk::OneShort => wide.len() as u32,
+            };
+        }
+        buffer[..wide.len()].copy_from_slice(&wide);
+        buffer[wide.len()] = 0;
+        match quirk {
+            Quirk::OneShort => wide.len() as u32,
+            _ => needed as u32,
+        }
+    }
+
+    fn expand(output: &str, quirk: Quirk) -> (OsString, usize) {
+        let mut calls = 0;
+        let rv =
+            expand_environment_strings_with(|argument| mimic(argument, output, quirk, &mut calls))
+                .unwrap();
+        (rv, calls)
+    }
+
+    fn long() -> String {
+        // Longer than the stack buffer.  The last character is different so losing it is obvious.
+        let mut rv: String = "C:\\Windows\\".repeat(40);
+        rv.push('Z');
+        rv
+    }
+
+    #[test]
+    fn fits_on_the_stack() {
+        let (rv, calls) = expand("C:\\Windows\\System32", Quirk::None);
+        assert!(rv == "C:\\Windows\\System32");
+        assert!(calls == 1);
+    }
+
+    #[test]
+    fn grows() {
+        let long = long();
+        let (rv, calls) = expand(&long, Quirk::None);
+        assert!(rv == OsString::from(&long));
+        assert!(calls == 2);
+    }
+
+    #[test]
+    fn capacity_plus_one_is_not_truncated() {
+        let long = long();
+        let (rv, calls) = expand(&long, Quirk::CapacityPlusOne);
+        assert!(rv == OsString::from(&long));
+        // The guess is not trusted; the buffer doubles.
+        assert!(calls == 2);
+    }
+
+    #[test]
+    fn one_short_is_not_truncated() {
+        // The spare WCHAR makes room for the NULL so one grow is enough.
+        let long = long();
+        let (rv, calls) = expand(&long, Quirk::OneShort);
+        assert!(rv == OsString::from(&long));
+        assert!(calls == 2);
+        let (rv, _) = expand("Z", Quirk::OneShort);
+        assert!(rv == "Z");
+    }
+
+    #[test]
+    fn failure() {
+        let rv = expand_environment_strings_with(|argument| {
+            let _ = argument.as_mut_slice();
+            unsafe { SetLastError(ERROR_ACCESS_DENIED) };
+            0
+        });
+        assert!(rv.is_err());
+    }
+}
+
+mod two_strings {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{
+        SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, ERROR_NONE_MAPPED, FALSE, NO_ERROR, TRUE,
+    };
+
+    use grob::{winapi_two_strings, Argument, RvIsError};
+
+    // Copies `value` when it fits.  Otherwise the size needed, including the NULL, is stored.
+    fn fill(argument: &mut Argument<PWSTR>, value: &str) -> bool {
+        let wide: Vec<u16> = value.encode_utf16().collect();
+        let size = argument.size_mut();
+        if (*size as usize) < wide.len() + 1 {
+            *size = wide.len() as u32 + 1;
+            return false;
+        }
+        *size = wide.len() as u32;
+        let p = argument.pointer().0;
+        unsafe {
+            std::ptr::copy(wide.as_ptr(), p, wide.len());
+            *p.add(wide.len()) = 0;
+        }
+        true
+    }
+
+    // Mimics LookupAccountSidW.  The sizes are recorded for each call.
+    fn mimic(
+        name: &mut Argument<PWSTR>,
+        domain: &mut Argument<PWSTR>,
+        values: (&str, &str),
+        calls: &mut Vec<(u32, u32)>,
+    ) -> RvIsError {
+        calls.push((*name.size_mut(), *domain.size_mut()));
+        // Both are checked so both sizes are updated.
+        let name_fits = fill(name, values.0);
+        let domain_fits = fill(domain, values.1);
+        let rv: BOOL = if name_fits && domain_fits {
+            unsafe { SetLastError(NO_ERROR) };
+            TRUE
+        } else {
+            unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+            FALSE
+        };
+        RvIsError::new(rv)
+    }
+
+    #[test]
+    fn both_fit() {
+        let mut calls = Vec::new();
+        let (name, domain) =
+            winapi_two_strings(|a, b| mimic(a, b, ("Zathras", "BABYLON5"), &mut calls)).unwrap();
+        assert!(name == "Zathras");
+        assert!(domain == "BABYLON5");
+        assert!(calls.len() == 1);
+    }
+
+    #[test]
+    fn only_the_second_grows() {
+        let long: String = "DOMAIN".repeat(100);
+        let mut calls = Vec::new();
+        let (name, domain) =
+            winapi_two_strings(|a, b| mimic(a, b, ("Zathras", &long), &mut calls)).unwrap();
+        assert!(name == "Zathras");
+        assert!(domain == long.as_str());
+        assert!(calls.len() == 2);
+        // The first buffer was big enough so it kept its capacity.
+        assert!(calls[0].0 == calls[1].0);
+        assert!(calls[1].1 as usize > long.len());
+    }
+
+    #[test]
+    fn both_grow() {
+        let first: String = "NAME".repeat(100);
+        let second: String = "DOMAIN".repeat(100);
+        let mut calls = Vec::new();
+        let (name, domain) =
+            winapi_two_strings(|a, b| mimic(a, b, (&first, &second), &mut calls)).unwrap();
+        assert!(name == first.as_str());
+        assert!(domain == second.as_str());
+        assert!(calls.len() == 2);
+    }
+
+    // A ToResult from outside the crate that does not call set_failed.
+    struct UserError;
+
+    impl grob::ToResult for UserError {
+        fn to_result(&self, _needed_size: &mut dyn grob::NeededSize) -> grob::FillBufferResult {
+            Err(std::io::Error::other("user error"))
+        }
+    }
+
+    #[test]
+    fn user_error() {
+        let error = winapi_two_strings(|_, _| UserError).unwrap_err();
+        assert!(error.to_string().contains("user error"));
+    }
+
+    #[cfg(feature = "no_panic")]
+    #[test]
+    fn first_commit_fails() {
+        // Reporting more than the buffer holds cannot be committed.  The second Argument is still
+        // resolved so the GrowError is returned.
+        let error = winapi_two_strings(|a, _| {
+            *a.size_mut() += 1;
+            unsafe { SetLastError(NO_ERROR) };
+            RvIsError::new(TRUE)
+        })
+        .unwrap_err();
+        assert!(error.get_ref().is_some_and(|e| e.is::<grob::GrowError>()));
+    }
+
+    #[test]
+    fn error() {
+        let rv = winapi_two_strings(|_, _| {
+            unsafe { SetLastError(ERROR_NONE_MAPPED) };
+            RvIsError::new(FALSE)
+        });
+        assert!(rv.is_err());
+    }
+}
+
+mod ntstatus {
+    use windows::Win32::Foundation::{
+        NTSTATUS, STATUS_ACCESS_DENIED, STATUS_INFO_LENGTH_MISMATCH, STATUS_SUCCESS,
+    };
+
+    use grob::{as_ntstatus, winapi_small_binary, Argument, IoErrorExt, RvIsNtstatus};
+
+    const NEEDED: u32 = 4000;
+
+    // Mimics NtQuerySystemInformation.  The needed size goes to a separate ReturnLength.
+    fn mimic(buffer: *mut u8, length: u32, return_length: &mut u32) -> NTSTATUS {
+        *return_length = NEEDED;
+        if length < NEEDED {
+            return STATUS_INFO_LENGTH_MISMATCH;
+        }
+        unsafe { std::ptr::write_bytes(buffer, 42, NEEDED as usize) };
+        STATUS_SUCCESS
+    }
+
+    #[test]
+    fn mismatch_then_success() {
+        let mut calls = 0;
+        let size = winapi_small_binary(
+            |argument: &mut Argument<*mut u8>| {
+                calls += 1;
+                let mut return_length = 0;
+                let status = mimic(argument.pointer(), *argument.size_mut(), &mut return_length);
+                RvIsNtstatus::with_return_length(status, return_length)
+            },
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == NEEDED);
+        assert!(calls == 2);
+    }
+
+    #[test]
+    fn size_as_return_length() {
+        let size = winapi_small_binary(
+            |argument: &mut Argument<*mut u8>| {
+                let (pointer, size) = argument.pointer_and_size_mut();
+                let length = *size;
+                RvIsNtstatus::new(mimic(pointer, length, size))
+            },
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == NEEDED);
+    }
+
+    #[test]
+    fn access_denied() {
+        let rv = winapi_small_binary(
+            |_: &mut Argument<*mut u8>| RvIsNtstatus::new(STATUS_ACCESS_DENIED),
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        );
+        let error = rv.unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::PermissionDenied);
+        assert!(error.is_ntstatus(STATUS_ACCESS_DENIED));
+        assert!(as_ntstatus(&error).unwrap().0 == STATUS_ACCESS_DENIED.0);
+        assert!(error.to_string().contains("0xC0000022"));
+    }
+}
+
+mod device_io {
+    use windows::Win32::Foundation::{
+        SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, ERROR_INVALID_FUNCTION, ERROR_MORE_DATA,
+        FALSE, TRUE, WIN32_ERROR,
+    };
+
+    use grob::{winapi_device_io_with, Argument};
+
+    const INPUT: [u8; 4] = [1, 2, 3, 4];
+
+    // Mimics DeviceIoControl returning `output`.  `too_small` is the error for a small buffer.
+    fn mimic(
+        input: &[u8],
+        argument: &mut Argument<*mut u8>,
+        bytes_returned: &mut u32,
+        output: &[u8],
+        too_small: WIN32_ERROR,
+        inputs: &mut Vec<Vec<u8>>,
+    ) -> BOOL {
+        inputs.push(input.to_vec());
+        if (*argument.size_mut() as usize) < output.len() {
+            *bytes_returned = 0;
+            unsafe { SetLastError(too_small) };
+            return FALSE;
+        }
+        unsafe { std::ptr::copy(output.as_ptr(), argument.pointer(), output.len()) };
+        *bytes_returned = output.len() as u32;
+        TRUE
+    }
+
+    fn run(output: &[u8], too_small: WIN32_ERROR) -> (Vec<u8>, Vec<Vec<u8>>) {
+        let mut inputs = Vec::new();
+        let data = winapi_device_io_with(
+            &INPUT,
+            |input, argument, bytes_returned| {
+                mimic(
+                    input,
+                    argument,
+                    bytes_returned,
+                    output,
+                    too_small,
+                    &mut inputs,
+                )
+            },
+            |frozen_buffer| Ok(frozen_buffer.as_slice().to_vec()),
+        )
+        .unwrap();
+        (data, inputs)
+    }
+
+    #[test]
+    fn bytes_returned_is_the_size() {
+        let (data, inputs) = run(&[7; 10], ERROR_INSUFFICIENT_BUFFER);
+        assert!(data == [7; 10]);
+        assert!(inputs.len() == 1);
+    }
+
+    #[test]
+    fn grows_on_insufficient_buffer() {
+        let output = vec![9; 3000];
+        let (data, inputs) = run(&output, ERROR_INSUFFICIENT_BUFFER);
+        assert!(data == output);
+        assert!(inputs.len() > 1);
+        // Every call got the same input.
+        assert!(inputs.iter().all(|i| i == &INPUT));
+    }
+
+    #[test]
+    fn grows_on_more_data() {
+        let output = vec![5; 3000];
+        let (data, inputs) = run(&output, ERROR_MORE_DATA);
+        assert!(data == output);
+        assert!(inputs.len() > 1);
+        assert!(inputs.iter().all(|i| i == &INPUT));
+    }
+
+    #[test]
+    fn other_errors_are_returned() {
+        let mut inputs = Vec::new();
+        let rv = winapi_device_io_with(
+            &INPUT,
+            |input, argument: &mut Argument<*mut u8>, bytes_returned| {
+                mimic(
+                    input,
+                    argument,
+                    bytes_returned,
+                    &[1; 3000],
+                    ERROR_INVALID_FUNCTION,
+                    &mut inputs,
+                )
+            },
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        );
+        assert!(rv.is_err());
+        assert!(inputs.len() == 1);
+    }
+}
+
+mod filled_until_slack {
+    use windows::Win32::Foundation::{SetLastError, ERROR_ACCESS_DENIED, FALSE, TRUE};
+
+    use grob::{winapi_filled_until_slack, Argument, RvIsFull};
+
+    // Mimics EnumProcesses.  Stores as many of `ids` as fit and returns the bytes stored.
+    fn mimic(
+        argument: &mut Argument<*mut u32>,
+        ids: &[u32],
+        capacities: &mut Vec<u32>,
+    ) -> RvIsFull {
+        let capacity = *argument.size_mut();
+        capacities.push(capacity);
+        let count = ids
+            .len()
+            .min(capacity as usize / std::mem::size_of::<u32>());
+        unsafe { std::ptr::copy(ids.as_ptr(), argument.pointer(), count) };
+        RvIsFull::new(TRUE, (count * std::mem::size_of::<u32>()) as u32)
+    }
+
+    #[test]
+    fn three_doublings() {
+        let mut capacities = Vec::new();
+        // Enough that the first buffer is filled three times.
+        let first = 1024 / std::mem::size_of::<u32>();
+        let ids: Vec<u32> = (0..(first * 7) as u32).collect();
+        let rv =
+            winapi_filled_until_slack(|argument| mimic(argument, &ids, &mut capacities)).unwrap();
+        assert!(rv == ids);
+        assert!(capacities.len() == 4);
+        for pair in capacities.windows(2) {
+            assert!(pair[1] >= pair[0] * 2);
+        }
+    }
+
+    #[test]
+    fn slack_on_the_first_call() {
+        let mut capacities = Vec::new();
+        let ids = [4, 8, 15, 16, 23, 42];
+        let rv =
+            winapi_filled_until_slack(|argument| mimic(argument, &ids, &mut capacities)).unwrap();
+        assert!(rv == ids);
+        assert!(capacities.len() == 1);
+    }
+
+    #[test]
+    fn nothing_stored() {
+        let mut capacities = Vec::new();
+        let rv =
+            winapi_filled_until_slack(|argument| mimic(argument, &[], &mut capacities)).unwrap();
+        assert!(rv.is_empty());
+    }
+
+    #[test]
+    fn error() {
+        let rv = winapi_filled_until_slack(|_: &mut Argument<*mut u32>| {
+            unsafe { SetLastError(ERROR_ACCESS_DENIED) };
+            RvIsFull::new(FALSE, 0)
+        });
+        assert!(rv.is_err());
+    }
+}
+
+mod element_count {
+    use windows::Win32::Foundation::{
+        SetLastError, ERROR_INSUFFICIENT_BUFFER, ERROR_INVALID_PARAMETER,
+    };
+
+    use grob::{winapi_element_count, Argument, ElementCount, RawToInternal, RvIsCount};
+
+    #[derive(Clone, Copy, PartialEq)]
+    #[repr(C)]
+    struct Twelve {
+        a: u32,
+        b: u32,
+        c: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    struct Seven([u8; 7]);
+
+    #[test]
+    fn conversions() {
+        assert!(<ElementCount<Twelve>>::capacity_to_size(120) == 10);
+        // Only whole elements count.
+        assert!(<ElementCount<Twelve>>::capacity_to_size(131) == 10);
+        assert!(<ElementCount<Twelve>>::capacity_to_size(11) == 0);
+        assert!(<ElementCount<Twelve>>::size_to_capacity(10) == 120);
+        assert!(<ElementCount<Seven>>::capacity_to_size(1024) == 146);
+        assert!(<ElementCount<Seven>>::size_to_capacity(146) == 1022);
+        assert!(<ElementCount<Seven>>::size_to_capacity(u32::MAX) == u32::MAX);
+    }
+
+    // Mimics GetRawInputDeviceList returning `devices`.
+    fn mimic(argument: &mut Argument<ElementCount<Twelve>>, devices: &[Twelve]) -> RvIsCount {
+        let (pointer, size) = argument.pointer_and_size_mut();
+        if (*size as usize) < devices.len() {
+            *size = devices.len() as u32;
+            unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+            return RvIsCount::new(u32::MAX);
+        }
+        unsafe { std::ptr::copy(devices.as_ptr(), pointer.0, devices.len()) };
+        RvIsCount::new(devices.len() as u32)
+    }
+
+    fn devices(count: u32) -> Vec<Twelve> {
+        (0..count)
+            .map(|i| Twelve {
+                a: i,
+                b: i * 2,
+                c: i * 3,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn size_is_the_element_count() {
+        let expected = devices(5);
+        let mut calls = 0;
+        let (size, copy) = winapi_element_count(
+            |argument| {
+                calls += 1;
+                mimic(argument, &expected)
+            },
+            |frozen_buffer| {
+                let size = frozen_buffer.size();
+                let p = frozen_buffer.pointer().unwrap();
+                Ok((
+                    size,
+                    unsafe { std::slice::from_raw_parts(p, size as usize) }.to_vec(),
+                ))
+            },
+        )
+        .unwrap();
+        assert!(size == 5);
+        assert!(copy == expected);
+        assert!(calls == 1);
+    }
+
+    #[test]
+    fn grows_by_count() {
+        // More than fit in the stack buffer.
+        let expected = devices(500);
+        let mut calls = 0;
+        let copy = winapi_element_count(
+            |argument| {
+                calls += 1;
+                mimic(argument, &expected)
+            },
+            |frozen_buffer| {
+                let p = frozen_buffer.pointer().unwrap();
+                Ok(
+                    unsafe { std::slice::from_raw_parts(p, frozen_buffer.size() as usize) }
+                        .to_vec(),
+                )
+            },
+        )
+        .unwrap();
+        assert!(copy == expected);
+        assert!(calls == 2);
+    }
+
+    #[test]
+    fn minus_one_is_an_error() {
+        let rv = winapi_element_count(
+            |_: &mut Argument<ElementCount<Twelve>>| {
+                unsafe { SetLastError(ERROR_INVALID_PARAMETER) };
+                RvIsCount::new(u32::MAX)
+            },
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        );
+        assert!(rv.is_err());
+    }
+}
+
+mod more_data {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{ERROR_MORE_DATA, NO_ERROR};
+
+    use grob::{winapi_osstring, Argument, IoErrorExt, RvIsError};
+
+    // Mimics WNetGetConnectionW.  The length needed, in WCHARs, is stored through the size pointer
+    // and ERROR_MORE_DATA returned when the buffer is too small.
+    fn mimic(argument: &mut Argument<PWSTR>, remote_name: &[u16], calls: &mut usize) -> u32 {
+        *calls += 1;
+        let needed = remote_name.len() as u32 + 1;
+        if *argument.size_mut() < needed {
+            *argument.size_mut() = needed;
+            return ERROR_MORE_DATA.0;
+        }
+        let slice = argument.as_mut_slice();
+        slice[..remote_name.len()].copy_from_slice(remote_name);
+        slice[remote_name.len()] = 0;
+        NO_ERROR.0
+    }
+
+    #[test]
+    fn converges_in_two_tries() {
+        let expected = "\\\\server\\share".repeat(100);
+        let remote_name: Vec<u16> = expected.encode_utf16().collect();
+        let mut calls = 0;
+        let rv =
+            winapi_osstring(|argument| RvIsError::new(mimic(argument, &remote_name, &mut calls)))
+                .unwrap();
+        assert!(calls == 2);
+        // The length is not updated on success so the name ends at the NULL terminator.
+        let rv = rv.to_string_lossy();
+        assert!(rv.split('\0').next() == Some(expected.as_str()));
+    }
+
+    #[test]
+    fn opt_out_returns_the_error() {
+        let remote_name: Vec<u16> = "\\\\server\\share".repeat(100).encode_utf16().collect();
+        let mut calls = 0;
+        let rv = winapi_osstring(|argument| {
+            RvIsError::new(mimic(argument, &remote_name, &mut calls)).more_data_is_error()
+        });
+        assert!(calls == 1);
+        assert!(rv.unwrap_err().is_win32(ERROR_MORE_DATA));
+    }
+}
+
+mod config_ret {
+    use windows::Win32::Devices::DeviceAndDriverInstallation::{
+        CONFIGRET, CR_BUFFER_SMALL, CR_NO_SUCH_VALUE, CR_SUCCESS,
+    };
+
+    use grob::device_interface_list_with;
+
+    // A REG_MULTI_SZ style list of `count` interface names.
+    fn list(count: usize) -> Vec<u16> {
+        let mut rv = Vec::new();
+        for i in 0..count {
+            rv.extend(format!("\\\\?\\USB#VID_1234&PID_{:04}#{{guid}}", i).encode_utf16());
+            rv.push(0);
+        }
+        rv.push(0);
+        rv
+    }
+
+    // Mimics CM_Get_Device_Interface_ListW.
+    fn fill(buffer: &mut [u16], current: &[u16]) -> CONFIGRET {
+        if buffer.len() < current.len() {
+            return CR_BUFFER_SMALL;
+        }
+        buffer[..current.len()].copy_from_slice(current);
+        CR_SUCCESS
+    }
+
+    #[test]
+    fn fits() {
+        let current = list(2);
+        let rv = device_interface_list_with(
+            |length| {
+                *length = current.len() as u32;
+                CR_SUCCESS
+            },
+            |buffer| fill(buffer, &current),
+        )
+        .unwrap();
+        assert!(rv.len() == 2);
+        assert!(rv[1] == "\\\\?\\USB#VID_1234&PID_0001#{guid}");
+    }
+
+    #[test]
+    fn devices_arrive_between_calls() {
+        // One device when the size is first queried.  Fifty by the time the list is requested.
+        let before = list(1);
+        let after = list(50);
+        let mut size_calls = 0;
+        let mut list_calls = 0;
+        let rv = device_interface_list_with(
+            |length| {
+                size_calls += 1;
+                let current = if size_calls == 1 { &before } else { &after };
+                *length = current.len() as u32;
+                CR_SUCCESS
+            },
+            |buffer| {
+                list_calls += 1;
+                fill(buffer, &after)
+            },
+        )
+        .unwrap();
+        assert!(size_calls == 3);
+        assert!(list_calls == 2);
+        assert!(rv.len() == 50);
+        assert!(rv[49] == "\\\\?\\USB#VID_1234&PID_0049#{guid}");
+    }
+
+    #[test]
+    fn empty_list() {
+        let current = list(0);
+        let rv = device_interface_list_with(
+            |length| {
+                *length = current.len() as u32;
+                CR_SUCCESS
+            },
+            |buffer| fill(buffer, &current),
+        )
+        .unwrap();
+        assert!(rv.is_empty());
+    }
+
+    #[test]
+    fn size_error_is_returned() {
+        let mut list_calls = 0;
+        let rv = device_interface_list_with(
+            |_| CR_NO_SUCH_VALUE,
+            |_| {
+                list_calls += 1;
+                CR_SUCCESS
+            },
+        );
+        assert!(rv.is_err());
+        assert!(list_calls == 0);
+    }
+}
+
+mod tcp_table {
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+    use windows::Win32::Foundation::{ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+    use windows::Win32::NetworkManagement::IpHelper::{MIB_TCP_STATE_ESTAB, MIB_TCP_STATE_LISTEN};
+    use windows::Win32::Networking::WinSock::{AF_INET, AF_INET6, AF_UNSPEC};
+
+    use grob::{extended_tcp_table_with, Argument, RvIsError, TcpRow};
+
+    // Mimics GetExtendedTcpTable.  The table is copied when it fits; otherwise the size needed is
+    // stored.
+    fn mimic(argument: &mut Argument<*mut u8>, table: &[u8], calls: &mut usize) -> RvIsError {
+        *calls += 1;
+        let needed = table.len() as u32;
+        if *argument.size_mut() < needed {
+            *argument.size_mut() = needed;
+            return RvIsError::new(ERROR_INSUFFICIENT_BUFFER.0);
+        }
+        unsafe { std::ptr::copy(table.as_ptr(), argument.pointer(), table.len()) };
+        *argument.size_mut() = needed;
+        RvIsError::new(NO_ERROR.0)
+    }
+
+    fn dword(table: &mut Vec<u8>, value: u32) {
+        table.extend(value.to_ne_bytes());
+    }
+
+    // A port is stored in network byte order in the first two bytes of a DWORD.
+    fn port(table: &mut Vec<u8>, value: u16) {
+        table.extend(value.to_be_bytes());
+        table.extend([0, 0]);
+    }
+
+    fn v4_table(count: u32) -> Vec<u8> {
+        let mut table = Vec::new();
+        dword(&mut table, count);
+        // Listening on 0.0.0.0:135
+        dword(&mut table, MIB_TCP_STATE_LISTEN.0 as u32);
+        table.extend([0, 0, 0, 0]);
+        port(&mut table, 135);
+        table.extend([0, 0, 0, 0]);
+        port(&mut table, 0);
+        dword(&mut table, 1234);
+        // Connected from 192.168.1.10:50000 to 93.184.216.34:443
+        dword(&mut table, MIB_TCP_STATE_ESTAB.0 as u32);
+        table.extend([192, 168, 1, 10]);
+        port(&mut table, 50000);
+        table.extend([93, 184, 216, 34]);
+        port(&mut table, 443);
+        dword(&mut table, 5678);
+        table
+    }
+
+    #[test]
+    fn v4_rows_are_decoded() {
+        let table = v4_table(2);
+        let mut calls = 0;
+        let rows = extended_tcp_table_with(AF_INET, |argument| mimic(argument, &table, &mut calls))
+            .unwrap();
+        assert!(calls == 1);
+        assert!(rows.len() == 2);
+        assert!(
+            rows[0]
+                == TcpRow {
+                    local: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 135)),
+                    remote: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)),
+                    state: MIB_TCP_STATE_LISTEN,
+                    owning_pid: 1234,
+                }
+        );
+        assert!(rows[1].local == "192.168.1.10:50000".parse().unwrap());
+        assert!(rows[1].remote == "93.184.216.34:443".parse().unwrap());
+        assert!(rows[1].state == MIB_TCP_STATE_ESTAB);
+        assert!(rows[1].owning_pid == 5678);
+    }
+
+    #[test]
+    fn v6_rows_are_decoded_after_growing() {
+        let mut table = Vec::new();
+        // More than fits in the stack buffer.
+        let count = 400;
+        dword(&mut table, count);
+        for i in 0..count {
+            table.extend(Ipv6Addr::LOCALHOST.octets());
+            dword(&mut table, 7);
+            port(&mut table, 8080);
+            table.extend("fe80::1".parse::<Ipv6Addr>().unwrap().octets());
+            dword(&mut table, 9);
+            port(&mut table, 40000 + i as u16);
+            dword(&mut table, MIB_TCP_STATE_ESTAB.0 as u32);
+            dword(&mut table, i);
+        }
+        let mut calls = 0;
+        let rows =
+            extended_tcp_table_with(AF_INET6, |argument| mimic(argument, &table, &mut calls))
+                .unwrap();
+        assert!(calls == 2);
+        assert!(rows.len() == count as usize);
+        let expected = TcpRow {
+            local: SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::LOCALHOST, 8080, 0, 7)),
+            remote: SocketAddr::V6(SocketAddrV6::new("fe80::1".parse().unwrap(), 40399, 0, 9)),
+            state: MIB_TCP_STATE_ESTAB,
+            owning_pid: 399,
+        };
+        assert!(rows[399] == expected);
+    }
+
+    #[test]
+    fn short_table_is_rejected() {
+        // Five entries claimed but only two stored.
+        let table = v4_table(5);
+        let mut calls = 0;
+        let rv = extended_tcp_table_with(AF_INET, |argument| mimic(argument, &table, &mut calls));
+        assert!(rv.unwrap_err().kind() == std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn huge_count_is_rejected() {
+        let table = v4_table(u32::MAX);
+        let mut calls = 0;
+        let rv = extended_tcp_table_with(AF_INET, |argument| mimic(argument, &table, &mut calls));
+        assert!(rv.unwrap_err().kind() == std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn unsupported_family() {
+        let mut calls = 0;
+        let rv = extended_tcp_table_with(AF_UNSPEC, |argument| mimic(argument, &[], &mut calls));
+        assert!(calls == 0);
+        assert!(rv.unwrap_err().kind() == std::io::ErrorKind::InvalidInput);
+    }
+}
+
+mod computer_name_ex {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{SetLastError, BOOL, ERROR_MORE_DATA};
+
+    use grob::{computer_name_ex_with, Argument};
+
+    // Mimics GetComputerNameExW.  The size reported never includes the NULL terminator.
+    fn mimic(argument: &mut Argument<PWSTR>, name: &[u16], calls: &mut usize) -> BOOL {
+        *calls += 1;
+        let length = name.len() as u32;
+        if *argument.size_mut() <= length {
+            *argument.size_mut() = length;
+            unsafe { SetLastError(ERROR_MORE_DATA) };
+            return BOOL::from(false);
+        }
+        let slice = argument.as_mut_slice();
+        slice[..name.len()].copy_from_slice(name);
+        slice[name.len()] = 0;
+        *argument.size_mut() = length;
+        BOOL::from(true)
+    }
+
+    #[test]
+    fn empty_is_not_an_error() {
+        // ComputerNameDnsDomain on a workgroup computer.
+        let mut calls = 0;
+        let rv = computer_name_ex_with(|argument| mimic(argument, &[], &mut calls)).unwrap();
+        assert!(calls == 1);
+        assert!(rv.is_empty());
+    }
+
+    #[test]
+    fn fits() {
+        let name: Vec<u16> = "zathras.example.com".encode_utf16().collect();
+        let mut calls = 0;
+        let rv = computer_name_ex_with(|argument| mimic(argument, &name, &mut calls)).unwrap();
+        assert!(calls == 1);
+        assert!(rv == "zathras.example.com");
+    }
+
+    #[test]
+    fn grows() {
+        let expected = format!("{}.example.com", "z".repeat(400));
+        let name: Vec<u16> = expected.encode_utf16().collect();
+        let mut calls = 0;
+        let rv = computer_name_ex_with(|argument| mimic(argument, &name, &mut calls)).unwrap();
+        assert!(calls == 2);
+        assert!(rv == expected.as_str());
+    }
+}
+
+mod lstatus {
+    use windows::Win32::Foundation::{
+        ERROR_ACCESS_DENIED, ERROR_FILE_NOT_FOUND, ERROR_MORE_DATA, NO_ERROR,
+    };
+
+    use grob::{
+        FillBufferAction, FillBufferResult, GrowForSmallBinary, GrowableBuffer, IoErrorExt,
+        RvIsLstatus, StackBuffer, ToResult,
+    };
+
+    // Converts `rv` with `needed` as the size stored by the registry function.
+    fn to_result(rv: RvIsLstatus, needed: u32) -> FillBufferResult {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        *argument.size_mut() = needed;
+        let result = rv.to_result(&mut argument);
+        argument.dismiss();
+        result
+    }
+
+    #[test]
+    fn success_is_commit() {
+        let result = to_result(RvIsLstatus::new(NO_ERROR), 4);
+        assert!(matches!(result, Ok(FillBufferAction::Commit)));
+        // The LSTATUS from the Windows SDK is an i32.
+        let result = to_result(RvIsLstatus::new(0i32), 4);
+        assert!(matches!(result, Ok(FillBufferAction::Commit)));
+    }
+
+    #[test]
+    fn success_with_nothing_stored_is_no_data() {
+        let result = to_result(RvIsLstatus::new(NO_ERROR), 0);
+        assert!(matches!(result, Ok(FillBufferAction::NoData)));
+    }
+
+    #[test]
+    fn more_data_is_grow() {
+        let result = to_result(RvIsLstatus::new(ERROR_MORE_DATA), 100);
+        assert!(matches!(result, Ok(FillBufferAction::Grow)));
+        let result = to_result(RvIsLstatus::new(ERROR_MORE_DATA.0 as i32), 100);
+        assert!(matches!(result, Ok(FillBufferAction::Grow)));
+    }
+
+    #[test]
+    fn file_not_found_is_no_data() {
+        let result = to_result(RvIsLstatus::new(ERROR_FILE_NOT_FOUND), 64);
+        assert!(matches!(result, Ok(FillBufferAction::NoData)));
+    }
+
+    #[test]
+    fn file_not_found_can_be_an_error() {
+        let rv = RvIsLstatus::new(ERROR_FILE_NOT_FOUND).file_not_found_is_error();
+        let error = to_result(rv, 64).unwrap_err();
+        assert!(error.is_win32(ERROR_FILE_NOT_FOUND));
+    }
+
+    #[test]
+    fn other_values_are_errors() {
+        let error = to_result(RvIsLstatus::new(ERROR_ACCESS_DENIED), 64).unwrap_err();
+        assert!(error.is_win32(ERROR_ACCESS_DENIED));
+    }
+}
+
+mod hresult {
+    use windows::core::HRESULT;
+    use windows::Win32::Foundation::{
+        ERROR_ACCESS_DENIED, ERROR_INSUFFICIENT_BUFFER, E_ACCESSDENIED, E_FAIL, S_FALSE, S_OK,
+    };
+
+    use grob::{
+        FillBufferAction, FillBufferResult, GrowForSmallBinary, GrowableBuffer, IoErrorExt,
+        RvIsHresult, StackBuffer, ToResult,
+    };
+
+    const E_NOT_SUFFICIENT_BUFFER: HRESULT = ERROR_INSUFFICIENT_BUFFER.to_hresult();
+
+    // Converts `rv` with `needed` as the size stored by the function.
+    fn to_result(rv: RvIsHresult, needed: u32) -> FillBufferResult {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        *argument.size_mut() = needed;
+        let result = rv.to_result(&mut argument);
+        argument.dismiss();
+        result
+    }
+
+    #[test]
+    fn s_ok_is_commit() {
+        let result = to_result(RvIsHresult::new(S_OK), 16);
+        assert!(matches!(result, Ok(FillBufferAction::Commit)));
+    }
+
+    #[test]
+    fn s_false_with_nothing_stored_is_no_data() {
+        let result = to_result(RvIsHresult::new(S_FALSE), 0);
+        assert!(matches!(result, Ok(FillBufferAction::NoData)));
+    }
+
+    #[test]
+    fn not_sufficient_buffer_is_grow() {
+        let result = to_result(RvIsHresult::new(E_NOT_SUFFICIENT_BUFFER), 100);
+        assert!(matches!(result, Ok(FillBufferAction::Grow)));
+        let result = to_result(RvIsHresult::new(E_NOT_SUFFICIENT_BUFFER.0), 100);
+        assert!(matches!(result, Ok(FillBufferAction::Grow)));
+    }
+
+    #[test]
+    fn access_denied_is_a_win32_error() {
+        let error = to_result(RvIsHresult::new(E_ACCESSDENIED), 16).unwrap_err();
+        assert!(error.is_win32(ERROR_ACCESS_DENIED));
+    }
+
+    #[test]
+    fn other_facilities_are_generic_errors() {
+        let error = to_result(RvIsHresult::new(E_FAIL), 16).unwrap_err();
+        assert!(error.raw_os_error().is_none());
+        assert!(error.kind() == std::io::ErrorKind::Other);
+        assert!(error.to_string().contains("0x80004005"));
+    }
+}
+
+mod extra_codes {
+    use windows::Win32::Foundation::{
+        ERROR_ACCESS_DENIED, ERROR_FILE_NOT_FOUND, ERROR_INSUFFICIENT_BUFFER,
+        ERROR_NOT_ENOUGH_MEMORY, ERROR_NO_DATA, NO_ERROR, WIN32_ERROR,
+    };
+    use windows::Win32::Networking::WinSock::WSAEFAULT;
+
+    use grob::{
+        winapi_small_binary, Argument, FillBufferAction, FillBufferResult, GrowForSmallBinary,
+        GrowableBuffer, IoErrorExt, RvIsError, StackBuffer, ToResult,
+    };
+
+    const WSAEFAULT_ERROR: WIN32_ERROR = WIN32_ERROR(WSAEFAULT.0 as u32);
+
+    // Converts `rv` with `needed` as the size stored by the function.
+    fn to_result(rv: RvIsError, needed: u32) -> FillBufferResult {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        *argument.size_mut() = needed;
+        let result = rv.to_result(&mut argument);
+        argument.dismiss();
+        result
+    }
+
+    #[test]
+    fn custom_grow_code() {
+        let rv = RvIsError::new(WSAEFAULT_ERROR.0).grow_on(&[WSAEFAULT_ERROR]);
+        assert!(matches!(to_result(rv, 100), Ok(FillBufferAction::Grow)));
+        // Without grow_on it is an error.
+        let rv = RvIsError::new(WSAEFAULT_ERROR.0);
+        assert!(to_result(rv, 100).unwrap_err().is_win32(WSAEFAULT_ERROR));
+    }
+
+    #[test]
+    fn custom_no_data_code() {
+        let rv = RvIsError::new(ERROR_FILE_NOT_FOUND.0).no_data_on(&[ERROR_FILE_NOT_FOUND]);
+        assert!(matches!(to_result(rv, 100), Ok(FillBufferAction::NoData)));
+    }
+
+    #[test]
+    fn built_in_codes_still_work() {
+        let extra = [ERROR_NOT_ENOUGH_MEMORY, WSAEFAULT_ERROR];
+        let rv = RvIsError::new(NO_ERROR.0).grow_on(&extra);
+        assert!(matches!(to_result(rv, 100), Ok(FillBufferAction::Commit)));
+        let rv = RvIsError::new(ERROR_INSUFFICIENT_BUFFER.0).grow_on(&extra);
+        assert!(matches!(to_result(rv, 100), Ok(FillBufferAction::Grow)));
+        let rv = RvIsError::new(ERROR_NO_DATA.0)
+            .grow_on(&extra)
+            .no_data_on(&[ERROR_FILE_NOT_FOUND]);
+        assert!(matches!(to_result(rv, 100), Ok(FillBufferAction::NoData)));
+        let rv = RvIsError::new(ERROR_ACCESS_DENIED.0).grow_on(&extra);
+        assert!(to_result(rv, 100)
+            .unwrap_err()
+            .is_win32(ERROR_ACCESS_DENIED));
+    }
+
+    #[test]
+    fn loop_grows_on_custom_code() {
+        // Mimics a NetApi wrapper that reports ERROR_NOT_ENOUGH_MEMORY for a small buffer.
+        let mut calls = 0;
+        let size = winapi_small_binary(
+            |argument: &mut Argument<*mut u8>| {
+                calls += 1;
+                let rv = if *argument.size_mut() < 10000 {
+                    *argument.size_mut() = 10000;
+                    ERROR_NOT_ENOUGH_MEMORY
+                } else {
+                    *argument.size_mut() = 10000;
+                    NO_ERROR
+                };
+                RvIsError::new(rv.0).grow_on(&[ERROR_NOT_ENOUGH_MEMORY])
+            },
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(calls == 2);
+        assert!(size == 10000);
+    }
+}
+
+mod current_directory {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{SetLastError, NO_ERROR};
+
+    use grob::{winapi_osstring, Argument, RvIsLength};
+
+    // Mimics GetCurrentDirectoryW.  The length needed includes the NULL terminator; the length
+    // stored does not.
+    fn mimic(argument: &mut Argument<PWSTR>, directory: &[u16], capacities: &mut Vec<u32>) -> u32 {
+        let capacity = *argument.size_mut();
+        capacities.push(capacity);
+        unsafe { SetLastError(NO_ERROR) };
+        let needed = directory.len() as u32 + 1;
+        if capacity < needed {
+            return needed;
+        }
+        let slice = argument.as_mut_slice();
+        slice[..directory.len()].copy_from_slice(directory);
+        slice[directory.len()] = 0;
+        directory.len() as u32
+    }
+
+    #[test]
+    fn converges_in_two_calls() {
+        let expected = format!("C:\\{}", "deeply\\nested\\".repeat(50));
+        let directory: Vec<u16> = expected.encode_utf16().collect();
+        let mut capacities = Vec::new();
+        let rv = winapi_osstring(|argument| {
+            RvIsLength::new(mimic(argument, &directory, &mut capacities))
+        })
+        .unwrap();
+        assert!(capacities.len() == 2);
+        // The needed size is trusted instead of doubling the capacity.
+        let needed = directory.len() as u32 + 1;
+        assert!(capacities[1] >= needed);
+        assert!(capacities[1] < needed * 2);
+        assert!(rv == expected.as_str());
+    }
+
+    #[test]
+    fn exactly_full_grows() {
+        // The capacity is exactly the length so there is no room for the terminator.
+        let mut calls = 0;
+        let mut capacity = 0;
+        let rv = winapi_osstring(|argument| {
+            calls += 1;
+            if calls == 1 {
+                capacity = *argument.size_mut();
+            }
+            let directory = vec![u16::from(b'x'); capacity as usize];
+            let mut ignored = Vec::new();
+            RvIsLength::new(mimic(argument, &directory, &mut ignored))
+        })
+        .unwrap();
+        assert!(calls == 2);
+        assert!(rv.len() == capacity as usize);
+    }
+}
+
+mod wsa {
+    use windows::Win32::Foundation::WIN32_ERROR;
+    use windows::Win32::Networking::WinSock::{
+        WSASetLastError, SOCKET_ERROR, WSAEFAULT, WSAENOBUFS, WSAEPROTONOSUPPORT, WSA_ERROR,
+    };
+
+    use grob::{winapi_small_binary, Argument, IoErrorExt, RvIsWsa};
+
+    const NEEDED: u32 = 4000;
+
+    // Mimics WSAEnumProtocolsW.  `too_small` is the error reported for a small buffer.
+    fn mimic(argument: &mut Argument<*mut u8>, too_small: WSA_ERROR, calls: &mut usize) -> i32 {
+        *calls += 1;
+        if *argument.size_mut() < NEEDED {
+            *argument.size_mut() = NEEDED;
+            unsafe { WSASetLastError(too_small.0) };
+            return SOCKET_ERROR;
+        }
+        unsafe { std::ptr::write_bytes(argument.pointer(), 42, NEEDED as usize) };
+        *argument.size_mut() = NEEDED;
+        // The number of protocols.
+        10
+    }
+
+    fn run(too_small: WSA_ERROR) -> (std::io::Result<u32>, usize) {
+        let mut calls = 0;
+        let rv = winapi_small_binary(
+            |argument| RvIsWsa::new(mimic(argument, too_small, &mut calls)),
+            |frozen_buffer| Ok(frozen_buffer.size()),
+        );
+        (rv, calls)
+    }
+
+    #[test]
+    fn fault_grows() {
+        let (rv, calls) = run(WSAEFAULT);
+        assert!(rv.unwrap() == NEEDED);
+        assert!(calls == 2);
+    }
+
+    #[test]
+    fn no_buffers_grows() {
+        let (rv, calls) = run(WSAENOBUFS);
+        assert!(rv.unwrap() == NEEDED);
+        assert!(calls == 2);
+    }
+
+    #[test]
+    fn other_errors_are_returned() {
+        let (rv, calls) = run(WSAEPROTONOSUPPORT);
+        let code = WIN32_ERROR(WSAEPROTONOSUPPORT.0 as u32);
+        assert!(rv.unwrap_err().is_win32(code));
+        assert!(calls == 1);
+    }
+}
+
+mod rv_with {
+    use windows::Win32::Foundation::{ERROR_INVALID_PARAMETER, WIN32_ERROR};
+
+    use grob::{
+        win32_err, winapi_generic, winapi_small_binary, Argument, FillBufferAction,
+        FillBufferResult, FrozenBuffer, GrowForSmallBinary, GrowableBuffer, IoErrorExt, NeededSize,
+        RvWith, StackBuffer,
+    };
+
+    const TABLE_SIZE: u32 = 3000;
+
+    // Mimics GetSystemFirmwareTable.  The size of the table is returned.  Nothing is stored when
+    // the buffer is too small.  Zero means failure.
+    fn mimic(argument: &mut Argument<*mut u8>, table_size: u32) -> u32 {
+        if table_size == 0 {
+            return 0;
+        }
+        if *argument.size_mut() >= table_size {
+            unsafe { std::ptr::write_bytes(argument.pointer(), 7, table_size as usize) };
+        }
+        table_size
+    }
+
+    // The firmware table semantics expressed with a closure.
+    fn firmware(
+        rv: u32,
+        gle: WIN32_ERROR,
+    ) -> RvWith<impl Fn(&mut dyn NeededSize) -> FillBufferResult> {
+        RvWith::new(move |needed_size: &mut dyn NeededSize| {
+            if rv == 0 {
+                needed_size.set_failed();
+                return Err(win32_err(gle));
+            }
+            let action = if rv > needed_size.needed_size() {
+                FillBufferAction::Grow
+            } else {
+                FillBufferAction::Commit
+            };
+            needed_size.set_needed_size(rv);
+            Ok(action)
+        })
+    }
+
+    #[test]
+    fn firmware_table() {
+        let mut calls = 0;
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let table = winapi_generic(
+            growable_buffer,
+            |argument| {
+                calls += 1;
+                firmware(mimic(argument, TABLE_SIZE), ERROR_INVALID_PARAMETER)
+            },
+            |frozen_buffer: FrozenBuffer<u8>| {
+                frozen_buffer.try_pointer()?;
+                Ok(frozen_buffer.as_slice().to_vec())
+            },
+        )
+        .unwrap();
+        assert!(calls == 2);
+        assert!(table.len() == TABLE_SIZE as usize);
+        assert!(table.iter().all(|b| *b == 7));
+    }
+
+    #[test]
+    fn firmware_table_failure() {
+        let rv = winapi_small_binary(
+            |argument: &mut Argument<*mut u8>| {
+                firmware(mimic(argument, 0), ERROR_INVALID_PARAMETER)
+            },
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        );
+        assert!(rv.unwrap_err().is_win32(ERROR_INVALID_PARAMETER));
+    }
+}
+
+mod last_error {
+    use windows::Win32::Foundation::{
+        SetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, FALSE, NO_ERROR, TRUE, WIN32_ERROR,
+    };
+
+    use grob::{
+        capture_last_error, checked_bool, FillBufferAction, FillBufferResult, GrowForSmallBinary,
+        GrowableBuffer, RvIsError, StackBuffer, ToResult,
+    };
+
+    // A call that fails because the buffer is too small.
+    fn too_small() -> BOOL {
+        unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+        FALSE
+    }
+
+    // Something like logging that resets the last error.
+    fn clobber() {
+        unsafe { SetLastError(NO_ERROR) };
+    }
+
+    fn to_result(rv: RvIsError) -> FillBufferResult {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        *argument.size_mut() = 64;
+        let rv = rv.to_result(&mut argument);
+        argument.dismiss();
+        rv
+    }
+
+    #[test]
+    fn late_conversion_is_wrong() {
+        let rv = too_small();
+        clobber();
+        // The failure looks like success.
+        assert!(matches!(
+            to_result(RvIsError::new(rv)),
+            Ok(FillBufferAction::Commit)
+        ));
+    }
+
+    #[test]
+    fn captured_last_error_is_right() {
+        let rv = too_small();
+        let last = capture_last_error();
+        clobber();
+        assert!(last == ERROR_INSUFFICIENT_BUFFER);
+        assert!(matches!(
+            to_result(RvIsError::from_bool_with_last_error(rv, last)),
+            Ok(FillBufferAction::Grow)
+        ));
+    }
+
+    #[test]
+    fn checked_bool_is_right() {
+        let rv = checked_bool(too_small);
+        clobber();
+        assert!(matches!(to_result(rv), Ok(FillBufferAction::Grow)));
+    }
+
+    #[test]
+    fn success_ignores_last_error() {
+        let rv = RvIsError::from_bool_with_last_error(TRUE, WIN32_ERROR(5));
+        assert!(matches!(to_result(rv), Ok(FillBufferAction::Commit)));
+        let rv = checked_bool(|| {
+            unsafe { SetLastError(WIN32_ERROR(5)) };
+            TRUE
+        });
+        assert!(matches!(to_result(rv), Ok(FillBufferAction::Commit)));
+    }
+}
+
+mod required_bytes {
+    use windows::Win32::Foundation::{ERROR_NOT_FOUND, NO_ERROR};
+
+    use grob::testing::MockApi;
+    use grob::{system_firmware_table_with, IoErrorExt};
+
+    #[test]
+    fn grows_to_the_required_size() {
+        let table = (0..9000).map(|i| i as u8).collect::<Vec<u8>>();
+        let mut mock_api = MockApi::new().needs(9000).writes(table.clone());
+        let rv =
+            system_firmware_table_with(|argument| mock_api.call_with_required_bytes_rv(argument))
+                .unwrap();
+        assert!(rv == table);
+        let observations = mock_api.observations();
+        assert!(observations.len() == 2);
+        assert!(observations[0].capacity < 9000);
+        assert!(observations[1].capacity >= 9000);
+        assert!(mock_api.is_finished());
+    }
+
+    #[test]
+    fn fits_the_first_time() {
+        let mut mock_api = MockApi::new().writes([42; 100]);
+        let rv =
+            system_firmware_table_with(|argument| mock_api.call_with_required_bytes_rv(argument))
+                .unwrap();
+        assert!(rv == [42; 100]);
+        assert!(mock_api.observations().len() == 1);
+    }
+
+    #[test]
+    fn zero_is_an_error() {
+        let mut mock_api = MockApi::new().fails(ERROR_NOT_FOUND);
+        let rv =
+            system_firmware_table_with(|argument| mock_api.call_with_required_bytes_rv(argument));
+        assert!(rv.unwrap_err().is_win32(ERROR_NOT_FOUND));
+    }
+
+    #[test]
+    fn zero_without_an_error_is_empty() {
+        let mut mock_api = MockApi::new().fails(NO_ERROR);
+        let rv =
+            system_firmware_table_with(|argument| mock_api.call_with_required_bytes_rv(argument))
+                .unwrap();
+        assert!(rv.is_empty());
+    }
+}
+
+mod sec_status {
+    use windows::core::HRESULT;
+    use windows::Win32::Foundation::{
+        ERROR_ACCESS_DENIED, ERROR_INSUFFICIENT_BUFFER, NTE_BAD_KEY, NTE_BUFFER_TOO_SMALL,
+        STATUS_BUFFER_TOO_SMALL, STATUS_INVALID_PARAMETER, STATUS_SUCCESS,
+    };
+
+    use grob::{
+        as_ntstatus, winapi_small_binary, Argument, FillBufferAction, FillBufferResult,
+        FrozenBuffer, GrowForSmallBinary, GrowableBuffer, IoErrorExt, RvIsSecStatus, StackBuffer,
+        ToResult,
+    };
+
+    fn to_result(rv: RvIsSecStatus, needed: u32) -> FillBufferResult {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        *argument.size_mut() = needed;
+        let rv = rv.to_result(&mut argument);
+        argument.dismiss();
+        rv
+    }
+
+    #[test]
+    fn mapping() {
+        assert!(matches!(
+            to_result(RvIsSecStatus::new(STATUS_SUCCESS), 8),
+            Ok(FillBufferAction::Commit)
+        ));
+        assert!(matches!(
+            to_result(RvIsSecStatus::new(STATUS_SUCCESS.ok()), 0),
+            Ok(FillBufferAction::NoData)
+        ));
+        assert!(matches!(
+            to_result(RvIsSecStatus::new(STATUS_BUFFER_TOO_SMALL), 100),
+            Ok(FillBufferAction::Grow)
+        ));
+        assert!(matches!(
+            to_result(RvIsSecStatus::new(STATUS_BUFFER_TOO_SMALL.ok()), 100),
+            Ok(FillBufferAction::Grow)
+        ));
+        assert!(matches!(
+            to_result(RvIsSecStatus::new(NTE_BUFFER_TOO_SMALL), 100),
+            Ok(FillBufferAction::Grow)
+        ));
+        assert!(matches!(
+            to_result(RvIsSecStatus::new(NTE_BUFFER_TOO_SMALL.0), 100),
+            Ok(FillBufferAction::Grow)
+        ));
+        assert!(matches!(
+            to_result(
+                RvIsSecStatus::new(HRESULT::from(ERROR_INSUFFICIENT_BUFFER)),
+                100
+            ),
+            Ok(FillBufferAction::Grow)
+        ));
+    }
+
+    #[test]
+    fn errors() {
+        let error = to_result(RvIsSecStatus::new(STATUS_INVALID_PARAMETER.ok()), 8).unwrap_err();
+        assert!(as_ntstatus(&error) == Some(STATUS_INVALID_PARAMETER));
+        let error = to_result(RvIsSecStatus::new(NTE_BAD_KEY), 8).unwrap_err();
+        assert!(error.to_string().contains("0x80090003"));
+        let hresult = HRESULT::from(ERROR_ACCESS_DENIED);
+        let error = to_result(RvIsSecStatus::new(hresult), 8).unwrap_err();
+        assert!(error.is_win32(ERROR_ACCESS_DENIED));
+    }
+
+    // Mimics BCryptGetProperty.  The size needed is stored through pcbResult.
+    fn mimic(argument: &mut Argument<*mut u8>, object_length: u32) -> windows::core::Result<()> {
+        if *argument.size_mut() < object_length {
+            *argument.size_mut() = object_length;
+            return STATUS_BUFFER_TOO_SMALL.ok();
+        }
+        unsafe { std::ptr::write_bytes(argument.pointer(), 9, object_length as usize) };
+        *argument.size_mut() = object_length;
+        STATUS_SUCCESS.ok()
+    }
+
+    #[test]
+    fn grows_to_the_reported_size() {
+        let mut calls = 0;
+        let size = winapi_small_binary(
+            |argument| {
+                calls += 1;
+                RvIsSecStatus::new(mimic(argument, 5000))
+            },
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 5000);
+        assert!(calls == 2);
+    }
+}
+
+mod grow_with_cap {
+    use grob::testing::MockApi;
+    use grob::{
+        FrozenBuffer, Grob, GrowError, GrowStrategy, GrowToNearestQuarterKibi, GrowWithCap,
+    };
+
+    const MIB: u32 = 1024 * 1024;
+    const GIB: u32 = 1024 * MIB;
+
+    fn run(mock_api: &mut MockApi) -> Result<u32, std::io::Error> {
+        Grob::builder()
+            .strategy(GrowWithCap::new(GrowToNearestQuarterKibi::new(), MIB))
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+    }
+
+    #[test]
+    fn one_gib_is_rejected() {
+        let mut mock_api = MockApi::new().needs(GIB).writes(vec![0; 16]);
+        let error = run(&mut mock_api).unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::OutOfMemory);
+        let grow_error = error.get_ref().unwrap().downcast_ref::<GrowError>();
+        assert!(matches!(
+            grow_error,
+            Some(GrowError::CapacityLimitExceeded {
+                desired_capacity: GIB,
+                max_capacity: MIB,
+                ..
+            })
+        ));
+        assert!(error.to_string().contains("GrowToNearestQuarterKibi"));
+        // The buffer never grew so there was only one call.
+        assert!(mock_api.observations().len() == 1);
+    }
+
+    #[test]
+    fn under_the_cap_grows() {
+        let mut mock_api = MockApi::new()
+            .needs(500 * 1024)
+            .writes(vec![42; 500 * 1024]);
+        assert!(run(&mut mock_api).unwrap() == 500 * 1024);
+        assert!(mock_api.observations().len() == 2);
+    }
+
+    #[test]
+    fn exactly_the_cap_grows() {
+        let mut mock_api = MockApi::new().needs(MIB).writes(vec![42; MIB as usize]);
+        assert!(run(&mut mock_api).unwrap() == MIB);
+        assert!(mock_api.observations()[1].capacity == MIB);
+    }
+
+    #[test]
+    fn next_capacity_is_clamped() {
+        let grow_strategy = GrowWithCap::new(GrowToNearestQuarterKibi::new(), MIB);
+        assert!(grow_strategy.next_capacity(1, MIB) == MIB);
+        assert!(grow_strategy.next_capacity(1, 1000) == 1024);
+    }
+
+    #[test]
+    fn tighter_limit_wins() {
+        let inner = GrowWithCap::new(GrowToNearestQuarterKibi::new(), 4096);
+        assert!(GrowWithCap::new(inner, MIB).max_capacity() == Some(4096));
+        let inner = GrowWithCap::new(GrowToNearestQuarterKibi::new(), MIB);
+        assert!(GrowWithCap::new(inner, 4096).max_capacity() == Some(4096));
+        assert!(GrowToNearestQuarterKibi::new().max_capacity().is_none());
+    }
+}
+
+mod max_tries_strategy {
+    use grob::testing::MockApi;
+    use grob::{
+        winapi_large_binary_with_strategy, FrozenBuffer, Grob, GrowError, GrowToNearestQuarterKibi,
+        MaxTries,
+    };
+
+    // An API that always needs more than it has, starting with more than the 1 KiB stack buffer.
+    fn insatiable() -> MockApi {
+        let mut mock_api = MockApi::new();
+        for kibis in 2..=21 {
+            mock_api = mock_api.needs(kibis * 1024);
+        }
+        mock_api
+    }
+
+    fn run(n: usize, mock_api: &mut MockApi) -> Result<u32, std::io::Error> {
+        Grob::builder()
+            .strategy(MaxTries::new(n, GrowToNearestQuarterKibi::new()))
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+    }
+
+    fn gave_up_after(error: &std::io::Error) -> Option<usize> {
+        match error.get_ref()?.downcast_ref::<GrowError>()? {
+            GrowError::StrategyGaveUp { tries, .. } => Some(*tries),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn stops_after_exactly_n_attempts() {
+        for n in 1..=5 {
+            let mut mock_api = insatiable();
+            let error = run(n, &mut mock_api).unwrap_err();
+            assert!(mock_api.observations().len() == n);
+            assert!(gave_up_after(&error) == Some(n));
+            assert!(error.kind() == std::io::ErrorKind::Other);
+        }
+    }
+
+    #[test]
+    fn error_names_the_strategy() {
+        let mut mock_api = insatiable();
+        let error = run(3, &mut mock_api).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("GrowToNearestQuarterKibi"));
+        assert!(message.contains("gave up after 3 tries"));
+    }
+
+    #[test]
+    fn success_within_the_limit() {
+        let mut mock_api = MockApi::new()
+            .needs(1024)
+            .needs(2048)
+            .writes(vec![42; 2048]);
+        assert!(run(3, &mut mock_api).unwrap() == 2048);
+        assert!(mock_api.observations().len() == 3);
+    }
+
+    #[test]
+    fn works_without_the_builder() {
+        let mut mock_api = MockApi::new();
+        for kibis in 17..=30 {
+            mock_api = mock_api.needs(kibis * 1024);
+        }
+        let grow_strategy = MaxTries::new(2, GrowToNearestQuarterKibi::new());
+        let error = winapi_large_binary_with_strategy(
+            &grow_strategy,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap_err();
+        assert!(gave_up_after(&error) == Some(2));
+        assert!(mock_api.observations().len() == 2);
+    }
+}
+
+mod step_and_page {
+    use grob::{GrowByFixedStep, GrowStrategy, GrowToNearestPage};
+
+    #[test]
+    fn fixed_step() {
+        let grow_strategy = GrowByFixedStep::<256>::new();
+        assert!(grow_strategy.next_capacity(1, 0) == 256);
+        assert!(grow_strategy.next_capacity(1, 1) == 257);
+        assert!(grow_strategy.next_capacity(2, 256) == 512);
+        assert!(grow_strategy.next_capacity(3, u32::MAX - 256) == u32::MAX);
+        assert!(grow_strategy.next_capacity(3, u32::MAX - 255) == u32::MAX);
+        assert!(grow_strategy.next_capacity(3, u32::MAX) == u32::MAX);
+        assert!(format!("{:?}", grow_strategy) == "GrowByFixedStep { step: 256 }");
+    }
+
+    #[test]
+    fn page_boundaries() {
+        let page_size = GrowToNearestPage::page_size();
+        assert!(page_size.is_power_of_two());
+        let grow_strategy = GrowToNearestPage::new();
+        assert!(grow_strategy.next_capacity(1, 0) == page_size);
+        assert!(grow_strategy.next_capacity(1, 1) == page_size);
+        assert!(grow_strategy.next_capacity(1, page_size - 1) == page_size);
+        assert!(grow_strategy.next_capacity(1, page_size) == page_size);
+        assert!(grow_strategy.next_capacity(1, page_size + 1) == 2 * page_size);
+        assert!(grow_strategy.next_capacity(1, 2 * page_size) == 2 * page_size);
+    }
+
+    #[test]
+    fn page_overflow() {
+        let page_size = GrowToNearestPage::page_size();
+        let largest = u32::MAX / page_size * page_size;
+        let grow_strategy = GrowToNearestPage::new();
+        assert!(grow_strategy.next_capacity(1, largest) == largest);
+        assert!(grow_strategy.next_capacity(1, largest - 1) == largest);
+        assert!(grow_strategy.next_capacity(1, u32::MAX) == largest);
+    }
+}
+
+mod grow_with {
+    use std::cell::RefCell;
+
+    use grob::testing::MockApi;
+    use grob::{winapi_large_binary_with_strategy, FrozenBuffer, GrowError, GrowWith};
+
+    #[test]
+    fn closure_is_used() {
+        let calls = RefCell::new(Vec::new());
+        let grow_strategy = GrowWith::new(|tries, want: u32| {
+            calls.borrow_mut().push((tries, want));
+            want.max(1 << (10 + tries))
+        });
+        let mut mock_api = MockApi::new().needs(20000).writes(vec![42; 20000]);
+        let size = winapi_large_binary_with_strategy(
+            &grow_strategy,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 20000);
+        assert!(*calls.borrow() == [(1, 20000)]);
+        assert!(mock_api.observations()[1].capacity == 20000);
+    }
+
+    #[test]
+    fn contract_violation_names_grow_with() {
+        let grow_strategy = GrowWith::new(|_, _| 0);
+        let mut mock_api = MockApi::new().needs(20000);
+        let error = winapi_large_binary_with_strategy(
+            &grow_strategy,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap_err();
+        let grow_error = error.get_ref().unwrap().downcast_ref::<GrowError>();
+        assert!(matches!(
+            grow_error,
+            Some(GrowError::StrategyDidNotGrow {
+                strategy: "GrowWith",
+                ..
+            })
+        ));
+    }
+}
+
+mod instrumented {
+    use std::cell::RefCell;
+
+    use grob::testing::MockApi;
+    use grob::{FrozenBuffer, Grob, GrowEvent, GrowToNearestQuarterKibi, InstrumentedGrowStrategy};
+
+    #[test]
+    fn two_grows_are_recorded() {
+        let events = RefCell::new(Vec::new());
+        let grow_strategy =
+            InstrumentedGrowStrategy::new(GrowToNearestQuarterKibi::new(), |event| {
+                events.borrow_mut().push(event)
+            });
+        let mut mock_api = MockApi::new()
+            .needs(2000)
+            .needs(5000)
+            .writes(vec![42; 5000]);
+        let size = Grob::builder()
+            .stack::<1024>()
+            .strategy(grow_strategy)
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(size == 5000);
+        let observations = mock_api.observations();
+        let expected = [
+            GrowEvent {
+                tries: 1,
+                desired: 2000,
+                chosen: observations[1].capacity,
+            },
+            GrowEvent {
+                tries: 2,
+                desired: 5000,
+                chosen: observations[2].capacity,
+            },
+        ];
+        assert!(*events.borrow() == expected);
+        assert!(expected[0].chosen >= 2000 && expected[0].chosen.is_multiple_of(256));
+        assert!(expected[1].chosen >= 5000 && expected[1].chosen.is_multiple_of(256));
+    }
+
+    #[test]
+    fn nothing_is_recorded_without_growing() {
+        let events = RefCell::new(Vec::new());
+        let grow_strategy =
+            InstrumentedGrowStrategy::new(GrowToNearestQuarterKibi::new(), |event| {
+                events.borrow_mut().push(event)
+            });
+        let mut mock_api = MockApi::new().writes(vec![42; 10]);
+        let _ = Grob::builder()
+            .stack::<1024>()
+            .strategy(grow_strategy)
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(events.borrow().is_empty());
+    }
+
+    #[cfg(feature = "tracing")]
+    mod traced {
+        use std::sync::{Arc, Mutex};
+
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Level, Metadata, Subscriber};
+
+        use grob::{GrowStrategy, GrowToNearestQuarterKibi, InstrumentedGrowStrategy};
+
+        // Records the target, level, and fields of every event.
+        #[derive(Clone, Default)]
+        struct Collector(Arc<Mutex<Vec<String>>>);
+
+        struct Fields(String);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0 += &format!(" {}={:?}", field.name(), value);
+            }
+        }
+
+        impl Subscriber for Collector {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _span: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _span: &Id, _values: &Record<'_>) {}
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let metadata = event.metadata();
+                let mut fields = Fields(format!("{} {}", metadata.target(), metadata.level()));
+                event.record(&mut fields);
+                self.0.lock().unwrap().push(fields.0);
+            }
+            fn enter(&self, _span: &Id) {}
+            fn exit(&self, _span: &Id) {}
+        }
+
+        #[test]
+        fn events_are_emitted() {
+            let collector = Collector::default();
+            let grow_strategy = InstrumentedGrowStrategy::traced(GrowToNearestQuarterKibi::new());
+            let chosen = tracing::subscriber::with_default(collector.clone(), || {
+                [
+                    grow_strategy.next_capacity(1, 2000),
+                    grow_strategy.next_capacity(2, 5000),
+                ]
+            });
+            let events = collector.0.lock().unwrap();
+            assert!(events.len() == 2);
+            assert!(events[0].starts_with(&format!("grob {}", Level::DEBUG)));
+            assert!(events[0].ends_with(&format!("tries=1 desired=2000 chosen={}", chosen[0])));
+            assert!(events[1].ends_with(&format!("tries=2 desired=5000 chosen={}", chosen[1])));
+        }
+    }
+}
+
+mod initial_capacity {
+    use grob::testing::MockApi;
+    use grob::{
+        simulate_with, FrozenBuffer, Grob, GrowForStoredIsReturned, GrowStrategy,
+        GrowToNearestQuarterKibi, GrowWithCap, GrowableBuffer, SimHandler, StackBuffer, ToResult,
+    };
+
+    struct Primed {}
+
+    impl GrowStrategy for Primed {
+        fn next_capacity(&self, _tries: usize, desired_capacity: u32) -> u32 {
+            desired_capacity
+        }
+        fn initial_capacity(&self) -> Option<u32> {
+            Some(4096)
+        }
+    }
+
+    #[test]
+    fn one_try_when_the_hook_supplies_enough() {
+        let mut mock_api = MockApi::new().writes(vec![42; 3000]);
+        let mut tries = 0;
+        let size = Grob::builder()
+            .heap_only()
+            .strategy(Primed {})
+            .run(
+                |argument| {
+                    tries = argument.tries();
+                    mock_api.call_with_error_rv(argument)
+                },
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(size == 3000);
+        assert!(tries == 1);
+        assert!(mock_api.observations().len() == 1);
+        assert!(mock_api.observations()[0].capacity == 4096);
+    }
+
+    #[test]
+    fn still_grows_when_the_hook_is_too_small() {
+        let mut mock_api = MockApi::new().needs(6000).writes(vec![42; 6000]);
+        let size = Grob::builder()
+            .heap_only()
+            .strategy(Primed {})
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(size == 6000);
+        let observations = mock_api.observations();
+        assert!(observations.len() == 2);
+        assert!(observations[0].capacity == 4096);
+        assert!(observations[1].capacity == 6000);
+    }
+
+    #[test]
+    fn a_stack_buffer_is_kept() {
+        let mut mock_api = MockApi::new().writes(vec![42; 10]);
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = Primed {};
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        let rv = mock_api.call_with_error_rv(&mut argument);
+        let result = rv.to_result(&mut argument).unwrap();
+        assert!(argument.try_apply(result).unwrap());
+        assert!(mock_api.observations()[0].capacity < 4096);
+    }
+
+    #[test]
+    fn stored_is_returned_floor() {
+        assert!(GrowForStoredIsReturned::<0>::new()
+            .initial_capacity()
+            .is_none());
+        assert!(GrowForStoredIsReturned::<520>::new().initial_capacity() == Some(520));
+        assert!(GrowToNearestQuarterKibi::new().initial_capacity().is_none());
+        let capped = GrowWithCap::new(GrowForStoredIsReturned::<520>::new(), 256);
+        assert!(capped.initial_capacity() == Some(256));
+    }
+
+    #[test]
+    fn simulation_is_primed() {
+        let handler = SimHandler::StoredIsReturned { element_size: 2 };
+        let steps = simulate_with(&GrowForStoredIsReturned::<520>::new(), handler, 0, &[500]);
+        assert!(steps.is_empty());
+    }
+
+    #[test]
+    fn wide_zero_stack_buffer() {
+        let mut mock_api = MockApi::new().writes_wide(&['?' as u16, 0]);
+        let text = Grob::builder()
+            .heap_only()
+            .strategy(GrowForStoredIsReturned::<64>::new())
+            .run_wide(
+                |argument| mock_api.call_with_size_rv(argument),
+                |frozen_buffer: FrozenBuffer<u16>| Ok(frozen_buffer.to_string(false).unwrap()),
+            )
+            .unwrap();
+        assert!(text == "?");
+        assert!(mock_api.observations().len() == 1);
+    }
+}
+
+mod recording_strategy {
+    use windows::core::PWSTR;
+
+    use grob::testing::{MockApi, RecordingGrowStrategy};
+    use grob::{
+        winapi_small_binary_with_strategy, FrozenBuffer, GrowEvent, GrowForStaticText,
+        GrowStrategy, GrowToNearestQuarterKibi, GrowableBuffer, StackBuffer, ToResult,
+    };
+
+    #[test]
+    fn static_text_sequence() {
+        let mut mock_api = MockApi::new()
+            .needs(16)
+            .needs(100)
+            .needs(1008)
+            .writes_wide(&[42; 504]);
+        let mut initial_buffer = StackBuffer::<0>::new();
+        let grow_strategy = RecordingGrowStrategy::new(GrowForStaticText::new());
+        let mut growable_buffer =
+            GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+        assert!(mock_api.is_finished());
+        let expected = [
+            GrowEvent {
+                tries: 1,
+                desired: 16,
+                chosen: 32,
+            },
+            GrowEvent {
+                tries: 2,
+                desired: 100,
+                chosen: 112,
+            },
+            GrowEvent {
+                tries: 3,
+                desired: 1008,
+                chosen: 1024,
+            },
+        ];
+        assert!(grow_strategy.events() == expected);
+        let capacities: Vec<u32> = mock_api.observations().iter().map(|o| o.capacity).collect();
+        assert!(capacities == [0, 32, 112, 1024]);
+    }
+
+    #[test]
+    fn quarter_kibi_sequence() {
+        let mut mock_api = MockApi::new()
+            .needs(1100)
+            .needs(1300)
+            .needs(5000)
+            .writes([42; 5000]);
+        let grow_strategy = RecordingGrowStrategy::new(GrowToNearestQuarterKibi::new());
+        let size = winapi_small_binary_with_strategy(
+            &grow_strategy,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 5000);
+        assert!(grow_strategy.capacities() == [1280, 1536, 5120]);
+        let tries: Vec<usize> = grow_strategy.events().iter().map(|e| e.tries).collect();
+        assert!(tries == [1, 2, 3]);
+        let desired: Vec<u32> = grow_strategy.events().iter().map(|e| e.desired).collect();
+        assert!(desired == [1100, 1300, 5000]);
+    }
+
+    #[test]
+    fn nothing_recorded_without_growth() {
+        let mut mock_api = MockApi::new().writes([42; 10]);
+        let grow_strategy = RecordingGrowStrategy::new(GrowToNearestQuarterKibi::new());
+        let _ = winapi_small_binary_with_strategy(
+            &grow_strategy,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(grow_strategy.events().is_empty());
+    }
+
+    #[test]
+    fn forwards_and_clears() {
+        let grow_strategy = RecordingGrowStrategy::new(GrowForStaticText::new());
+        assert!(grow_strategy.name() == GrowForStaticText::new().name());
+        let _ = grow_strategy.next_capacity(1, 16);
+        assert!(grow_strategy.events().len() == 1);
+        grow_strategy.clear();
+        assert!(grow_strategy.events().is_empty());
+        let s = format!("{:?}", grow_strategy);
+        assert!(s.contains("RecordingGrowStrategy"));
+    }
+}
+
+mod owned_strategy {
+    use grob::testing::{MockApi, RecordingGrowStrategy};
+    use grob::{
+        FrozenBuffer, GrowForSmallBinary, GrowStrategy, GrowableBuffer, StackBuffer, StrategyKind,
+        ToResult,
+    };
+
+    // The strategy lives as long as the GrowableBuffer so only the initial buffer is borrowed.
+    struct Poller<'sb> {
+        buffer: GrowableBuffer<'static, 'sb, u8, *mut u8>,
+    }
+
+    impl<'sb> Poller<'sb> {
+        fn new(initial: &'sb mut StackBuffer<64>, grow_strategy: Box<dyn GrowStrategy>) -> Self {
+            Self {
+                buffer: GrowableBuffer::with_owned_strategy(initial, grow_strategy),
+            }
+        }
+        fn poll(mut self, mock_api: &mut MockApi) -> FrozenBuffer<'sb, u8> {
+            loop {
+                let mut argument = self.buffer.argument();
+                let rv = mock_api.call_with_error_rv(&mut argument);
+                let result = rv.to_result(&mut argument).unwrap();
+                if argument.try_apply(result).unwrap() {
+                    break;
+                }
+            }
+            self.buffer.freeze()
+        }
+    }
+
+    #[test]
+    fn stored_in_a_struct() {
+        let mut mock_api = MockApi::new().needs(100).writes([42; 100]);
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let poller = Poller::new(&mut initial_buffer, Box::new(GrowForSmallBinary::new()));
+        let frozen_buffer = poller.poll(&mut mock_api);
+        assert!(frozen_buffer.size() == 100);
+        let observations = mock_api.observations();
+        assert!(observations.len() == 2);
+        assert!(observations[0].capacity <= 64);
+        assert!(observations[1].capacity == 112);
+    }
+
+    #[test]
+    fn owned_strategy_is_used() {
+        // A stand-in that lets the test see the owned strategy without keeping a reference to it.
+        struct Shared(std::rc::Rc<RecordingGrowStrategy<GrowForSmallBinary>>);
+        impl GrowStrategy for Shared {
+            fn next_capacity(&self, tries: usize, desired_capacity: u32) -> u32 {
+                self.0.next_capacity(tries, desired_capacity)
+            }
+        }
+        let recorder = std::rc::Rc::new(RecordingGrowStrategy::new(GrowForSmallBinary::new()));
+        let mut mock_api = MockApi::new().needs(200).writes([42; 200]);
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let poller = Poller::new(&mut initial_buffer, Box::new(Shared(recorder.clone())));
+        let _ = poller.poll(&mut mock_api);
+        assert!(recorder.capacities() == [208]);
+    }
+
+    #[test]
+    fn built_in_strategy() {
+        let mut mock_api = MockApi::new().needs(2000).writes([42; 2000]);
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let poller = Poller::new(&mut initial_buffer, StrategyKind::SmallBinary.built_in());
+        assert!(poller.poll(&mut mock_api).size() == 2000);
+    }
+}
+
+mod heap_buffer {
+    use grob::testing::MockApi;
+    use grob::{
+        FrozenBuffer, GrowForSmallBinary, GrowableBuffer, HeapBuffer, ReadBuffer, ToResult,
+        WriteBuffer, ALIGNMENT,
+    };
+
+    #[test]
+    fn capacity_and_alignment() {
+        for capacity in [0, 1, 7, 8, 15, 16, 100, 4096, 65537] {
+            let mut heap_buffer = HeapBuffer::with_capacity(capacity).unwrap();
+            assert!(heap_buffer.capacity() == capacity);
+            let (pointer, size) = heap_buffer.write_buffer();
+            assert!(!pointer.is_null());
+            assert!((pointer as usize).is_multiple_of(ALIGNMENT));
+            assert!(size == capacity);
+            // The whole capacity is writable.
+            unsafe { std::ptr::write_bytes(pointer, 42, capacity as usize) };
+        }
+    }
+
+    #[test]
+    fn zeroed() {
+        let mut heap_buffer = HeapBuffer::zeroed(1000).unwrap();
+        let (pointer, size) = heap_buffer.write_buffer();
+        let s = unsafe { std::slice::from_raw_parts(pointer, size as usize) };
+        assert!(s.iter().all(|b| *b == 0));
+        assert!(HeapBuffer::zeroed(0).unwrap().capacity() == 0);
+    }
+
+    #[test]
+    fn empty_is_not_a_panic() {
+        let heap_buffer = HeapBuffer::with_capacity(64).unwrap();
+        let (pointer, size) = heap_buffer.read_buffer();
+        assert!(pointer.is_some());
+        assert!(size == 0);
+    }
+
+    #[test]
+    fn used_as_the_initial_buffer() {
+        let mut mock_api = MockApi::new().writes([42; 3000]);
+        let mut initial_buffer = HeapBuffer::with_capacity(4096).unwrap();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+        // The first call had the whole heap buffer.
+        assert!(mock_api.observations().len() == 1);
+        assert!(mock_api.observations()[0].capacity == 4096);
+        let frozen_buffer: FrozenBuffer<u8> = growable_buffer.freeze();
+        assert!(frozen_buffer.size() == 3000);
+        let s = frozen_buffer.as_slice();
+        assert!(s.len() == 3000);
+        assert!(s.iter().all(|b| *b == 42));
+    }
+
+    #[test]
+    fn grows_past_the_initial_heap_buffer() {
+        let mut mock_api = MockApi::new().needs(5000).writes([42; 5000]);
+        let mut initial_buffer = HeapBuffer::with_capacity(64).unwrap();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+        let frozen_buffer: FrozenBuffer<u8> = growable_buffer.freeze();
+        assert!(frozen_buffer.size() == 5000);
+        let capacities: Vec<u32> = mock_api.observations().iter().map(|o| o.capacity).collect();
+        assert!(capacities == [64, 5008]);
+    }
+
+    #[test]
+    fn grow_to_three_times() {
+        let mut heap_buffer = HeapBuffer::with_capacity(16).unwrap();
+        let (pointer, _) = heap_buffer.write_buffer();
+        unsafe { std::ptr::write_bytes(pointer, 7, 16) };
+        heap_buffer.set_final_size(16);
+        for capacity in [100, 1000, 5000] {
+            heap_buffer.grow_to(capacity).unwrap();
+            assert!(heap_buffer.capacity() == capacity);
+            let (pointer, size) = heap_buffer.write_buffer();
+            assert!(size == capacity);
+            assert!((pointer as usize).is_multiple_of(ALIGNMENT));
+            // The contents are kept and the whole capacity is writable.
+            let s = unsafe { std::slice::from_raw_parts(pointer, 16) };
+            assert!(s.iter().all(|b| *b == 7));
+            unsafe { std::ptr::write_bytes(pointer.add(16), 42, capacity as usize - 16) };
+        }
+        let (pointer, size) = heap_buffer.read_buffer();
+        assert!(pointer.is_some());
+        assert!(size == 16);
+        // Never shrinks.
+        heap_buffer.grow_to(10).unwrap();
+        assert!(heap_buffer.capacity() == 5000);
+    }
+
+    #[test]
+    fn loop_grows_the_heap_buffer_in_place() {
+        let mut mock_api = MockApi::new()
+            .needs(100)
+            .needs(1000)
+            .needs(5000)
+            .writes([42; 5000]);
+        let size = grob::Grob::builder()
+            .heap_only()
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(size == 5000);
+        let capacities: Vec<u32> = mock_api.observations().iter().map(|o| o.capacity).collect();
+        assert!(capacities == [0, 112, 1008, 5008]);
+    }
+
+    #[test]
+    fn nothing_stored_in_a_heap_buffer() {
+        let mut mock_api = MockApi::new().needs(5000).writes([]);
+        let size = grob::Grob::builder()
+            .heap_only()
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(size == 0);
+    }
+}
+
+mod buffer_allocator {
+    use std::alloc::Layout;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use grob::testing::MockApi;
+    use grob::{
+        AllocSource, BufferAllocator, FrozenBuffer, GrowForSmallBinary, GrowableBuffer, HeapBuffer,
+        StackBuffer, StdAlloc, WriteBuffer, ALIGNMENT,
+    };
+
+    // Passes everything to StdAlloc and counts the calls.  Each test has its own so the tests,
+    // running in parallel, do not interfere.
+    #[derive(Debug)]
+    struct Counting {
+        allocs: AtomicUsize,
+        reallocs: AtomicUsize,
+        deallocs: AtomicUsize,
+        releasable: bool,
+    }
+
+    impl Counting {
+        const fn new(releasable: bool) -> Self {
+            Self {
+                allocs: AtomicUsize::new(0),
+                reallocs: AtomicUsize::new(0),
+                deallocs: AtomicUsize::new(0),
+                releasable,
+            }
+        }
+        fn counts(&self) -> (usize, usize, usize) {
+            (
+                self.allocs.load(Ordering::SeqCst),
+                self.reallocs.load(Ordering::SeqCst),
+                self.deallocs.load(Ordering::SeqCst),
+            )
+        }
+    }
+
+    unsafe impl BufferAllocator for Counting {
+        fn alloc(&self, capacity: usize, align: usize) -> *mut u8 {
+            self.allocs.fetch_add(1, Ordering::SeqCst);
+            StdAlloc.alloc(capacity, align)
+        }
+        unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+            self.deallocs.fetch_add(1, Ordering::SeqCst);
+            StdAlloc.dealloc(pointer, layout)
+        }
+        unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, capacity: usize) -> *mut u8 {
+            self.reallocs.fetch_add(1, Ordering::SeqCst);
+            StdAlloc.realloc(pointer, layout, capacity)
+        }
+        fn can_release(&self) -> bool {
+            self.releasable
+        }
+    }
+
+    fn fill(allocator: &'static dyn BufferAllocator, preserve: bool) -> u32 {
+        let mut initial_buffer = StackBuffer::<0>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                .allocator(allocator);
+        if preserve {
+            growable_buffer = growable_buffer.preserve_on_grow();
+        }
+        let mut mock_api = MockApi::new().needs(200).needs(300).writes([42; 300]);
+        grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap()
+    }
+
+    // With zeroize the second grow moves to a new allocation so the old one can be wiped.
+    const GROWN: (usize, usize, usize) = if cfg!(feature = "zeroize") {
+        (2, 0, 2)
+    } else {
+        (1, 1, 1)
+    };
+
+    #[test]
+    fn every_allocation_is_freed_by_the_allocator() {
+        static COUNTING: Counting = Counting::new(false);
+        assert!(fill(&COUNTING, false) == 300);
+        // The second grow reallocates the heap buffer.
+        assert!(COUNTING.counts() == GROWN);
+    }
+
+    #[test]
+    fn preserved_buffers_are_resized_by_the_allocator() {
+        static COUNTING: Counting = Counting::new(false);
+        assert!(fill(&COUNTING, true) == 300);
+        assert!(COUNTING.counts() == GROWN);
+    }
+
+    #[test]
+    fn heap_buffer_with_capacity_in() {
+        static COUNTING: Counting = Counting::new(false);
+        {
+            let mut heap_buffer = HeapBuffer::with_capacity_in(100, &COUNTING).unwrap();
+            assert!(heap_buffer.capacity() == 100);
+            assert!((heap_buffer.write_buffer().0 as usize).is_multiple_of(ALIGNMENT));
+            assert!(COUNTING.counts() == (1, 0, 0));
+        }
+        assert!(COUNTING.counts() == (1, 0, 1));
+        let s = format!("{:?}", HeapBuffer::with_capacity_in(8, &COUNTING).unwrap());
+        assert!(s.contains("Counting"));
+    }
+
+    #[test]
+    fn release_depends_on_the_allocator() {
+        static KEEPS: Counting = Counting::new(false);
+        static RELEASES: Counting = Counting::new(true);
+        let release = |allocator: &'static dyn BufferAllocator| {
+            let mut initial_buffer = StackBuffer::<0>::new();
+            let grow_strategy = GrowForSmallBinary::new();
+            let growable_buffer =
+                GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                    .allocator(allocator);
+            let mut mock_api = MockApi::new().needs(64).writes([42; 64]);
+            grob::winapi_generic(
+                growable_buffer,
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| {
+                    Ok(match frozen_buffer.release_to_os() {
+                        Ok((pointer, size)) => Some((pointer, size)),
+                        Err(_) => None,
+                    })
+                },
+            )
+            .unwrap()
+        };
+        assert!(release(&KEEPS).is_none());
+        assert!(KEEPS.counts() == (1, 0, 1));
+        let (pointer, size) = release(&RELEASES).unwrap();
+        // Nothing was freed.  Ownership now belongs to the test.
+        assert!(RELEASES.counts() == (1, 0, 0));
+        let layout = Layout::from_size_align(64, ALIGNMENT).unwrap();
+        assert!(size == 64);
+        unsafe { RELEASES.dealloc(pointer, layout) };
+        assert!(RELEASES.counts() == (1, 0, 1));
+    }
+
+    #[test]
+    fn alloc_source_maps_to_an_allocator() {
+        assert!(format!("{:?}", AllocSource::RustGlobal.allocator()) == "StdAlloc");
+        assert!(format!("{:?}", AllocSource::ProcessHeap.allocator()) == "ProcessHeapAlloc");
+        assert!(format!("{:?}", AllocSource::CoTaskMem.allocator()) == "CoTaskMemAlloc");
+        assert!(!AllocSource::RustGlobal.allocator().can_release());
+        assert!(AllocSource::ProcessHeap.allocator().can_release());
+        assert!(AllocSource::CoTaskMem.allocator().can_release());
+    }
+
+    #[test]
+    fn std_alloc_rejects_zero_bytes() {
+        assert!(StdAlloc.alloc(0, ALIGNMENT).is_null());
+    }
+}
+
+mod vec_buffer {
+    use std::alloc::Layout;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use windows::core::PWSTR;
+
+    use grob::testing::MockApi;
+    use grob::{
+        BufferAllocator, FrozenBuffer, GrowForSmallBinary, GrowForStaticText, GrowableBuffer,
+        ReadBuffer, StackBuffer, StdAlloc, VecBuffer, WriteBuffer, ALIGNMENT,
+    };
+
+    // Counts heap buffers created by the GrowableBuffer.  Each test has its own so the tests,
+    // running in parallel, do not interfere.
+    #[derive(Debug)]
+    struct HeapBuffers(AtomicUsize);
+
+    impl HeapBuffers {
+        const fn new() -> Self {
+            Self(AtomicUsize::new(0))
+        }
+        fn count(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    unsafe impl BufferAllocator for HeapBuffers {
+        fn alloc(&self, capacity: usize, align: usize) -> *mut u8 {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            StdAlloc.alloc(capacity, align)
+        }
+        unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+            StdAlloc.dealloc(pointer, layout)
+        }
+    }
+
+    fn fill(
+        data: &mut Vec<u8>,
+        allocator: &'static dyn BufferAllocator,
+        preserve: bool,
+        mut mock_api: MockApi,
+    ) -> u32 {
+        let mut initial_buffer = VecBuffer::new(data);
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                .allocator(allocator);
+        if preserve {
+            growable_buffer = growable_buffer.preserve_on_grow();
+        }
+        grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn growth_stays_in_the_vec() {
+        static HEAP_BUFFERS: HeapBuffers = HeapBuffers::new();
+        let mut data = Vec::new();
+        let mock_api = MockApi::new().needs(200).needs(300).writes([42; 300]);
+        assert!(fill(&mut data, &HEAP_BUFFERS, false, mock_api) == 300);
+        assert!(data == [42; 300]);
+        assert!(HEAP_BUFFERS.count() == 0);
+    }
+
+    #[test]
+    fn preserved_growth_stays_in_the_vec() {
+        static HEAP_BUFFERS: HeapBuffers = HeapBuffers::new();
+        let mut data = Vec::new();
+        let mock_api = MockApi::new().needs(200).needs(300).writes([42; 300]);
+        assert!(fill(&mut data, &HEAP_BUFFERS, true, mock_api) == 300);
+        assert!(data == [42; 300]);
+        assert!(HEAP_BUFFERS.count() == 0);
+    }
+
+    #[test]
+    fn reused_vec_holds_only_the_new_data() {
+        static HEAP_BUFFERS: HeapBuffers = HeapBuffers::new();
+        let mut data = vec![7; 512];
+        let capacity = data.capacity();
+        let mock_api = MockApi::new().writes([42; 100]);
+        assert!(fill(&mut data, &HEAP_BUFFERS, false, mock_api) == 100);
+        assert!(data == [42; 100]);
+        assert!(data.capacity() == capacity);
+        assert!(HEAP_BUFFERS.count() == 0);
+    }
+
+    #[test]
+    fn nothing_stored_leaves_the_vec_empty() {
+        static HEAP_BUFFERS: HeapBuffers = HeapBuffers::new();
+        let mut data = vec![7; 64];
+        let mock_api = MockApi::new().writes([]);
+        assert!(fill(&mut data, &HEAP_BUFFERS, false, mock_api) == 0);
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn wide_data_fills_the_vec() {
+        let text = "Zathras".encode_utf16().chain([0]).collect::<Vec<u16>>();
+        for needs in [false, true] {
+            let mut mock_api = MockApi::new();
+            if needs {
+                mock_api = mock_api.needs(200);
+            }
+            mock_api = mock_api.writes_wide(&text);
+            let mut data = Vec::with_capacity(64);
+            let size = {
+                let mut initial_buffer = VecBuffer::new(&mut data);
+                let grow_strategy = GrowForStaticText::new();
+                let growable_buffer =
+                    GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+                grob::winapi_generic(
+                    growable_buffer,
+                    |argument| mock_api.call_with_error_rv(argument),
+                    |frozen_buffer: FrozenBuffer<u16>| {
+                        assert!(frozen_buffer.as_slice() == text);
+                        Ok(frozen_buffer.size())
+                    },
+                )
+                .unwrap()
+            };
+            // The size is in WCHARs but the Vec holds every byte.
+            assert!(size as usize == text.len());
+            assert!(data.len() == text.len() * 2);
+            let wide = data
+                .chunks_exact(2)
+                .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+                .collect::<Vec<u16>>();
+            assert!(wide == text);
+        }
+    }
+
+    #[test]
+    fn try_grow_keeps_the_contents() {
+        let mut data = Vec::new();
+        let mut vec_buffer = VecBuffer::new(&mut data);
+        assert!(vec_buffer.capacity() == 0);
+        assert!(vec_buffer.read_buffer().0.is_none());
+        assert!(vec_buffer.try_grow(4));
+        let (pointer, capacity) = vec_buffer.write_buffer();
+        assert!((pointer as usize).is_multiple_of(ALIGNMENT));
+        assert!(capacity >= 4);
+        unsafe { std::ptr::copy_nonoverlapping(b"abcd".as_ptr(), pointer, 4) };
+        assert!(vec_buffer.try_grow(64));
+        assert!(vec_buffer.capacity() >= 64);
+        vec_buffer.set_final_size(4);
+        assert!(format!("{:?}", vec_buffer).starts_with("VecBuffer { len: 4,"));
+        assert!(data == b"abcd");
+    }
+
+    #[test]
+    fn other_buffers_do_not_grow_themselves() {
+        let mut initial_buffer = StackBuffer::<16>::new();
+        assert!(!initial_buffer.try_grow(64));
+        assert!(initial_buffer.capacity() == 16);
+    }
+}
+
+mod borrowed_buffer {
+    use std::alloc::Layout;
+    use std::mem::MaybeUninit;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use grob::testing::MockApi;
+    use grob::{
+        BorrowedBuffer, BufferAllocator, FrozenBuffer, GrowError, GrowForSmallBinary,
+        GrowableBuffer, ReadBuffer, StdAlloc, WriteBuffer, ALIGNMENT,
+    };
+
+    // Counts heap buffers created by the GrowableBuffer.  Each test has its own so the tests,
+    // running in parallel, do not interfere.
+    #[derive(Debug)]
+    struct HeapBuffers(AtomicUsize);
+
+    impl HeapBuffers {
+        const fn new() -> Self {
+            Self(AtomicUsize::new(0))
+        }
+        fn count(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    unsafe impl BufferAllocator for HeapBuffers {
+        fn alloc(&self, capacity: usize, align: usize) -> *mut u8 {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            StdAlloc.alloc(capacity, align)
+        }
+        unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+            StdAlloc.dealloc(pointer, layout)
+        }
+    }
+
+    fn fill(
+        arena: &mut [MaybeUninit<u8>],
+        allocator: &'static dyn BufferAllocator,
+        no_heap: bool,
+        mut mock_api: MockApi,
+    ) -> Result<Vec<u8>, std::io::Error> {
+        let mut initial_buffer = BorrowedBuffer::new(arena);
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                .allocator(allocator);
+        if no_heap {
+            growable_buffer = growable_buffer.no_heap();
+        }
+        grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.as_slice().to_vec()),
+        )
+    }
+
+    #[test]
+    fn data_is_written_to_the_slice() {
+        static HEAP_BUFFERS: HeapBuffers = HeapBuffers::new();
+        let mut arena = [MaybeUninit::<u8>::uninit(); 256];
+        let mock_api = MockApi::new().writes([42; 200]);
+        let data = fill(&mut arena, &HEAP_BUFFERS, true, mock_api).unwrap();
+        assert!(data == [42; 200]);
+        assert!(HEAP_BUFFERS.count() == 0);
+    }
+
+    #[test]
+    fn no_heap_ends_with_a_typed_error() {
+        static HEAP_BUFFERS: HeapBuffers = HeapBuffers::new();
+        let mut arena = [MaybeUninit::<u8>::uninit(); 256];
+        let mock_api = MockApi::new().needs(1000).writes([42; 1000]);
+        let error = fill(&mut arena, &HEAP_BUFFERS, true, mock_api).unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::OutOfMemory);
+        let grow_error = error.get_ref().unwrap().downcast_ref::<GrowError>();
+        assert!(
+            matches!(grow_error, Some(GrowError::HeapNotAllowed { capacity }) if *capacity >= 1000)
+        );
+        assert!(HEAP_BUFFERS.count() == 0);
+    }
+
+    #[test]
+    fn heap_is_used_when_allowed() {
+        static HEAP_BUFFERS: HeapBuffers = HeapBuffers::new();
+        let mut arena = [MaybeUninit::<u8>::uninit(); 256];
+        let mock_api = MockApi::new().needs(1000).writes([42; 1000]);
+        let data = fill(&mut arena, &HEAP_BUFFERS, false, mock_api).unwrap();
+        assert!(data == [42; 1000]);
+        assert!(HEAP_BUFFERS.count() == 1);
+    }
+
+    #[test]
+    fn the_start_is_skipped_for_alignment() {
+        let mut arena = [MaybeUninit::<u8>::uninit(); 64];
+        let start = arena.as_ptr() as usize;
+        // Start one byte in so the slice is never aligned.
+        let mut initial_buffer = BorrowedBuffer::new(&mut arena[1..]);
+        let (pointer, capacity) = initial_buffer.write_buffer();
+        let skipped = pointer as usize - start - 1;
+        assert!((pointer as usize).is_multiple_of(ALIGNMENT));
+        assert!(skipped < ALIGNMENT);
+        assert!(capacity as usize == 63 - skipped);
+        initial_buffer.set_final_size(5);
+        assert!(initial_buffer.read_buffer() == (Some(pointer as *const u8), 5));
+    }
+
+    #[test]
+    fn too_small_for_alignment_is_unreadable() {
+        let mut arena = [MaybeUninit::<u8>::uninit(); 64];
+        let offset = arena.as_ptr().align_offset(ALIGNMENT);
+        // Only the bytes before the first aligned address.
+        let mut initial_buffer = BorrowedBuffer::new(&mut arena[offset + 1..offset + ALIGNMENT]);
+        assert!(initial_buffer.capacity() == 0);
+        initial_buffer.set_final_size(3);
+        assert!(initial_buffer.read_buffer() == (None, 3));
+        assert!(format!("{:?}", initial_buffer).starts_with("BorrowedBuffer { len: "));
+    }
+}
+
+mod pooled {
+    use windows::Win32::Foundation::ERROR_ACCESS_DENIED;
+
+    use grob::testing::MockApi;
+    use grob::{pooled_bytes, set_pool_cap, winapi_large_binary_pooled, FrozenBuffer};
+
+    // The pool is per-thread and every test runs on its own thread so the tests start with an
+    // empty pool and do not interfere.
+    fn call(mock_api: &mut MockApi) -> Result<u32, std::io::Error> {
+        winapi_large_binary_pooled(
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+    }
+
+    #[test]
+    fn second_call_reuses_the_buffer() {
+        assert!(pooled_bytes() == 0);
+        let mut first = MockApi::new().writes([42; 100]);
+        assert!(call(&mut first).unwrap() == 100);
+        assert!(pooled_bytes() > 0);
+        let mut second = MockApi::new().writes([43; 100]);
+        assert!(call(&mut second).unwrap() == 100);
+        assert!(first.observations()[0].pointer == second.observations()[0].pointer);
+    }
+
+    #[test]
+    fn grown_buffer_is_kept() {
+        let mut first = MockApi::new().needs(100_000).writes([42; 100_000]);
+        assert!(call(&mut first).unwrap() == 100_000);
+        assert!(first.observations().len() == 2);
+        assert!(pooled_bytes() >= 100_000);
+        // The buffer is already large enough so one call does it.
+        let mut second = MockApi::new().writes([43; 100_000]);
+        assert!(call(&mut second).unwrap() == 100_000);
+        assert!(second.observations().len() == 1);
+        assert!(first.observations()[1].pointer == second.observations()[0].pointer);
+    }
+
+    #[test]
+    fn buffer_is_returned_after_an_error() {
+        let mut mock_api = MockApi::new().fails(ERROR_ACCESS_DENIED);
+        assert!(call(&mut mock_api).is_err());
+        assert!(pooled_bytes() > 0);
+    }
+
+    #[test]
+    fn cap_evicts_oversized_buffers() {
+        set_pool_cap(32 * 1024);
+        let mut mock_api = MockApi::new().writes([42; 100]);
+        call(&mut mock_api).unwrap();
+        let kept = pooled_bytes();
+        assert!(kept > 0 && kept <= 32 * 1024);
+        let mut mock_api = MockApi::new().needs(100_000).writes([42; 100_000]);
+        assert!(call(&mut mock_api).unwrap() == 100_000);
+        assert!(pooled_bytes() == 0);
+    }
+
+    #[test]
+    fn lowering_the_cap_frees_buffers() {
+        let mut mock_api = MockApi::new().writes([42; 100]);
+        call(&mut mock_api).unwrap();
+        assert!(pooled_bytes() > 0);
+        set_pool_cap(0);
+        assert!(pooled_bytes() == 0);
+        // With a cap of zero nothing is kept.
+        let mut mock_api = MockApi::new().writes([42; 100]);
+        assert!(call(&mut mock_api).unwrap() == 100);
+        assert!(pooled_bytes() == 0);
+    }
+}
+
+mod into_vec {
+    use windows::core::PWSTR;
+
+    use grob::testing::MockApi;
+    use grob::{FrozenBuffer, GrowForSmallBinary, GrowForStaticText, GrowableBuffer, StackBuffer};
+
+    fn bytes<const N: usize>(mut mock_api: MockApi) -> (Vec<u8>, &'static str) {
+        let mut initial_buffer = StackBuffer::<N>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| {
+                let backing = if format!("{:?}", frozen_buffer).contains("Heap") {
+                    "Heap"
+                } else {
+                    "Initial"
+                };
+                Ok((frozen_buffer.into_vec(), backing))
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn initial_buffer_is_copied() {
+        let (data, backing) = bytes::<64>(MockApi::new().writes([42; 10]));
+        assert!(backing == "Initial");
+        assert!(data == [42; 10]);
+    }
+
+    #[test]
+    fn heap_buffer_is_copied() {
+        let (data, backing) = bytes::<0>(MockApi::new().needs(300).writes([42; 300]));
+        assert!(backing == "Heap");
+        assert!(data == [42; 300]);
+        // The Vec outlives the FrozenBuffer and can go to another thread.
+        let sum = std::thread::spawn(move || data.iter().map(|b| *b as u32).sum::<u32>());
+        assert!(sum.join().unwrap() == 42 * 300);
+    }
+
+    #[test]
+    fn nothing_stored_is_empty() {
+        let (data, _) = bytes::<64>(MockApi::new().writes([]));
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn boxed_slice() {
+        let mut mock_api = MockApi::new().needs(100).writes([7; 100]);
+        let boxed = grob::winapi_small_binary(
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.into_boxed_slice()),
+        )
+        .unwrap();
+        assert!(*boxed == [7; 100]);
+    }
+
+    #[test]
+    fn wide_vec() {
+        let text = "Zathras".encode_utf16().chain([0]).collect::<Vec<u16>>();
+        for needs in [false, true] {
+            let mut mock_api = MockApi::new();
+            if needs {
+                mock_api = mock_api.needs(1000);
+            }
+            mock_api = mock_api.writes_wide(&text);
+            let mut initial_buffer = StackBuffer::<32>::new();
+            let grow_strategy = GrowForStaticText::new();
+            let growable_buffer =
+                GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+            let wide = grob::winapi_generic(
+                growable_buffer,
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u16>| Ok(frozen_buffer.into_wide_vec()),
+            )
+            .unwrap();
+            assert!(wide == text);
+        }
+    }
+}
+
+mod into_owned {
+    use grob::testing::MockApi;
+    use grob::{FrozenBuffer, GrowForSmallBinary, GrowableBuffer, HeapBuffer, StackBuffer};
+    use grob::{ToResult, WriteBuffer};
+
+    // The StackBuffer is local so only an owned FrozenBuffer can be returned.
+    fn owned<const N: usize>(mock_api: &mut MockApi) -> FrozenBuffer<'static, u8> {
+        let mut initial_buffer = StackBuffer::<N>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+        growable_buffer.freeze().into_owned().unwrap()
+    }
+
+    fn contents(frozen_buffer: &FrozenBuffer<u8>) -> Vec<u8> {
+        frozen_buffer.as_slice().to_vec()
+    }
+
+    #[test]
+    fn stack_data_is_copied() {
+        let mut mock_api = MockApi::new().writes([42; 10]);
+        let frozen_buffer = owned::<64>(&mut mock_api);
+        assert!(format!("{:?}", frozen_buffer).contains("Heap"));
+        assert!(frozen_buffer.size() == 10);
+        assert!(contents(&frozen_buffer) == [42; 10]);
+        assert!(frozen_buffer.pointer() != Some(mock_api.observations()[0].pointer));
+    }
+
+    #[test]
+    fn sent_to_another_thread() {
+        let mut mock_api = MockApi::new().writes([42; 10]);
+        let frozen_buffer = owned::<64>(&mut mock_api);
+        let handle = std::thread::spawn(move || contents(&frozen_buffer));
+        assert!(handle.join().unwrap() == [42; 10]);
+    }
+
+    #[test]
+    fn borrowed_shared_with_scoped_threads() {
+        let mut mock_api = MockApi::new().writes([42; 10]);
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        let rv = mock_api.call_with_error_rv(&mut argument);
+        let result = rv.to_result(&mut argument).unwrap();
+        assert!(argument.try_apply(result).unwrap());
+        let frozen_buffer = growable_buffer.freeze();
+        std::thread::scope(|scope| {
+            let first = scope.spawn(|| contents(&frozen_buffer));
+            let second = scope.spawn(|| contents(&frozen_buffer));
+            assert!(first.join().unwrap() == [42; 10]);
+            assert!(second.join().unwrap() == [42; 10]);
+        });
+    }
+
+    #[test]
+    fn borrowed_sent_to_a_scoped_thread() {
+        let mut mock_api = MockApi::new().writes([42; 10]);
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        let rv = mock_api.call_with_error_rv(&mut argument);
+        let result = rv.to_result(&mut argument).unwrap();
+        assert!(argument.try_apply(result).unwrap());
+        let frozen_buffer = growable_buffer.freeze();
+        assert!(format!("{:?}", frozen_buffer).contains("Initial"));
+        let data = std::thread::scope(|scope| {
+            // The FrozenBuffer is moved to the other thread and dropped there.
+            scope
+                .spawn(move || contents(&frozen_buffer))
+                .join()
+                .unwrap()
+        });
+        assert!(data == [42; 10]);
+        // The initial buffer is usable again once the FrozenBuffer is gone.
+        assert!(initial_buffer.capacity() == 64);
+    }
+
+    #[test]
+    fn heap_data_is_kept() {
+        let mut mock_api = MockApi::new().needs(300).writes([42; 300]);
+        let frozen_buffer = owned::<64>(&mut mock_api);
+        assert!(contents(&frozen_buffer) == [42; 300]);
+        // The heap buffer the data was written to is the one returned.
+        assert!(frozen_buffer.pointer() == Some(mock_api.observations()[1].pointer));
+    }
+
+    #[test]
+    fn nothing_stored_is_empty() {
+        let mut mock_api = MockApi::new().writes([]);
+        let frozen_buffer = owned::<64>(&mut mock_api);
+        assert!(frozen_buffer.size() == 0);
+        assert!(frozen_buffer.pointer().is_none());
+    }
+
+    #[test]
+    fn owned_buffers_can_be_stored() {
+        struct Cache {
+            entries: Vec<FrozenBuffer<'static, u8>>,
+        }
+        let mut cache = Cache {
+            entries: Vec::new(),
+        };
+        for n in 1..=3u8 {
+            let mut mock_api = MockApi::new().writes(vec![n; n as usize]);
+            cache.entries.push(owned::<16>(&mut mock_api));
+        }
+        for (n, entry) in (1..=3u8).zip(cache.entries.iter()) {
+            assert!(contents(entry) == vec![n; n as usize]);
+        }
+    }
+
+    #[test]
+    fn heap_buffers_can_be_sent() {
+        let heap_buffer = HeapBuffer::with_capacity(64).unwrap();
+        let capacity = std::thread::spawn(move || heap_buffer.capacity());
+        assert!(capacity.join().unwrap() == 64);
+    }
+}
+
+mod reset {
+    use grob::testing::MockApi;
+    use grob::{GrowForSmallBinary, GrowableBuffer, StackBuffer, ToResult};
+
+    fn run(growable_buffer: &mut GrowableBuffer<u8, *mut u8>, mock_api: &mut MockApi) -> Vec<u8> {
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+        let frozen_buffer = growable_buffer.freeze_in_place();
+        frozen_buffer.as_slice().to_vec()
+    }
+
+    #[test]
+    fn second_loop_starts_at_the_learned_capacity() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut first = MockApi::new().needs(300).needs(2000).writes([42; 2000]);
+        assert!(run(&mut growable_buffer, &mut first) == [42; 2000]);
+        let reached = first.observations().last().unwrap().capacity;
+        assert!(reached >= 2000);
+
+        growable_buffer.reset();
+        {
+            let mut argument = growable_buffer.argument();
+            assert!(*argument.size_mut() == reached);
+            // The first try again.
+            assert!(argument.tries() == 1);
+            argument.dismiss();
+        }
+        let mut second = MockApi::new().writes([43; 1500]);
+        assert!(run(&mut growable_buffer, &mut second) == [43; 1500]);
+        assert!(second.observations().len() == 1);
+        assert!(second.observations()[0].capacity == reached);
+        assert!(second.observations()[0].pointer == first.observations()[2].pointer);
+    }
+
+    #[test]
+    fn reset_clears_the_stored_size() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut mock_api = MockApi::new().writes([42; 10]);
+        assert!(run(&mut growable_buffer, &mut mock_api) == [42; 10]);
+        growable_buffer.reset();
+        assert!(growable_buffer.freeze_in_place().size() == 0);
+        assert!(growable_buffer.freeze().size() == 0);
+    }
+}
+
+mod failed_grow {
+    use grob::StackBuffer;
+    use grob::{FillBufferAction, GrowError, GrowForSmallBinary, GrowWithCap, GrowableBuffer};
+
+    // A grow that fails, then a call that fits in the buffer that was there before.
+    fn grow_fails_then_fits(growable_buffer: &mut GrowableBuffer<u8, *mut u8>) -> GrowError {
+        let mut argument = growable_buffer.argument();
+        *argument.size_mut() = 1000;
+        let grow_error = argument.try_apply(FillBufferAction::Grow).unwrap_err();
+        let mut argument = growable_buffer.argument();
+        assert!(*argument.size_mut() == 64);
+        unsafe { std::ptr::write_bytes(argument.pointer(), 42, 10) };
+        *argument.size_mut() = 10;
+        assert!(argument.try_apply(FillBufferAction::Commit).unwrap());
+        grow_error
+    }
+
+    #[test]
+    fn heap_not_allowed_keeps_the_initial_buffer() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy).no_heap();
+        let grow_error = grow_fails_then_fits(&mut growable_buffer);
+        assert!(matches!(grow_error, GrowError::HeapNotAllowed { .. }));
+        let frozen_buffer = growable_buffer.freeze();
+        assert!(frozen_buffer.as_slice() == [42; 10]);
+    }
+
+    #[test]
+    fn capacity_limit_keeps_the_initial_buffer() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowWithCap::new(GrowForSmallBinary::new(), 128);
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let grow_error = grow_fails_then_fits(&mut growable_buffer);
+        assert!(matches!(
+            grow_error,
+            GrowError::CapacityLimitExceeded { .. }
+        ));
+        let frozen_buffer = growable_buffer.freeze();
+        assert!(frozen_buffer.as_slice() == [42; 10]);
+    }
+}
+
+mod heap_capacity {
+    use grob::testing::MockApi;
+    use grob::{FrozenBuffer, Grob, GrowForSmallBinary, GrowableBuffer};
+
+    #[test]
+    fn first_argument_has_the_requested_capacity() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::with_heap_capacity(100_000, &grow_strategy).unwrap();
+        let mut argument = growable_buffer.argument();
+        assert!(*argument.size_mut() == 100_000);
+        argument.dismiss();
+    }
+
+    #[test]
+    fn data_that_fits_needs_one_call() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::with_heap_capacity(100_000, &grow_strategy).unwrap();
+        let mut mock_api = MockApi::new().writes([42; 90_000]);
+        let frozen_buffer = grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.into_owned().unwrap()),
+        )
+        .unwrap();
+        assert!(frozen_buffer.size() == 90_000);
+        assert!(mock_api.observations().len() == 1);
+        assert!(mock_api.observations()[0].capacity == 100_000);
+    }
+
+    #[test]
+    fn builder_initial_capacity() {
+        let mut mock_api = MockApi::new().writes([42; 5000]);
+        let size = Grob::builder()
+            .initial_capacity(8192)
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(size == 5000);
+        assert!(mock_api.observations().len() == 1);
+        assert!(mock_api.observations()[0].capacity == 8192);
+    }
+
+    #[test]
+    fn still_grows_when_needed() {
+        let mut mock_api = MockApi::new().needs(10_000).writes([42; 10_000]);
+        let size = Grob::builder()
+            .initial_capacity(1000)
+            .run(
+                |argument| mock_api.call_with_error_rv(argument),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+        assert!(size == 10_000);
+        assert!(mock_api.observations()[0].capacity == 1000);
+        assert!(mock_api.observations()[1].capacity >= 10_000);
+    }
+}
+
+mod grow_stats {
+    use grob::testing::MockApi;
+    use grob::{
+        FrozenBuffer, GrowForSmallBinary, GrowStats, GrowableBuffer, StackBuffer, ToResult,
+    };
+
+    #[test]
+    fn fits_on_stack() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut mock_api = MockApi::new().writes([42; 40]);
+        let (size, stats) = grob::winapi_generic_with_stats(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 40);
+        let expected = GrowStats {
+            tries: 1,
+            switched_to_heap: false,
+            peak_capacity: 64,
+            final_size: 40,
+        };
+        assert!(stats == expected);
+    }
+
+    #[test]
+    fn grows_twice() {
+        let mut mock_api = MockApi::new().needs(2000).needs(5000).writes([42; 5000]);
+        let (size, stats) = grob::winapi_small_binary_with_stats(
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 5000);
+        assert!(stats.tries == 3);
+        assert!(stats.switched_to_heap);
+        assert!(stats.peak_capacity == mock_api.observations()[2].capacity);
+        assert!(stats.peak_capacity >= 5000);
+        assert!(stats.final_size == 5000);
+    }
+
+    #[test]
+    fn finalize_sees_the_stats() {
+        let mut mock_api = MockApi::new().needs(100_000).writes([42; 100_000]);
+        let stats = grob::winapi_large_binary(
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.stats()),
+        )
+        .unwrap();
+        assert!(stats.tries == 2);
+        assert!(stats.switched_to_heap);
+        assert!(stats.final_size == 100_000);
+    }
+
+    #[test]
+    fn final_size_is_in_bytes() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u16, *mut u16>::new(&mut initial_buffer, &grow_strategy);
+        let mut mock_api = MockApi::new().writes_wide(&[65; 10]);
+        let mut argument = growable_buffer.argument();
+        let rv = mock_api.call_with_error_rv(&mut argument);
+        let result = rv.to_result(&mut argument).unwrap();
+        assert!(argument.try_apply(result).unwrap());
+        assert!(growable_buffer.stats().final_size == 20);
+    }
+
+    #[test]
+    fn reset_clears_the_stats() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut mock_api = MockApi::new().needs(1000).writes([42; 1000]);
+        let size = grob::winapi_generic_in(
+            &mut growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 1000);
+        assert!(growable_buffer.stats().switched_to_heap);
+        growable_buffer.reset();
+        assert!(growable_buffer.stats() == GrowStats::default());
+    }
+}
+
+mod freeze_shrunk {
+    use grob::testing::MockApi;
+    use grob::{
+        FrozenBuffer, GrowForSmallBinary, GrowableBuffer, StackBuffer, ToResult, ALIGNMENT,
+    };
+
+    fn fill(growable_buffer: &mut GrowableBuffer<u8, *mut u8>, mock_api: &mut MockApi) {
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+    }
+
+    fn contents(frozen_buffer: &FrozenBuffer<u8>) -> Vec<u8> {
+        assert!((frozen_buffer.pointer().unwrap() as usize).is_multiple_of(ALIGNMENT));
+        frozen_buffer.as_slice().to_vec()
+    }
+
+    #[test]
+    fn over_grown_heap_buffer_is_shrunk() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::with_heap_capacity(512 * 1024, &grow_strategy).unwrap();
+        let payload = (0..900).map(|i| i as u8).collect::<Vec<u8>>();
+        fill(
+            &mut growable_buffer,
+            &mut MockApi::new().writes(payload.clone()),
+        );
+        let frozen_buffer = growable_buffer.freeze_shrunk();
+        assert!(frozen_buffer.capacity() == 900_u32.next_multiple_of(ALIGNMENT as u32));
+        assert!(contents(&frozen_buffer) == payload);
+    }
+
+    #[test]
+    fn mostly_full_heap_buffer_is_kept() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::with_heap_capacity(4096, &grow_strategy).unwrap();
+        fill(&mut growable_buffer, &mut MockApi::new().writes([42; 3000]));
+        let frozen_buffer = growable_buffer.freeze_shrunk();
+        assert!(frozen_buffer.capacity() == 4096);
+        assert!(contents(&frozen_buffer) == [42; 3000]);
+    }
+
+    #[test]
+    fn initial_buffer_is_kept() {
+        let mut initial_buffer = StackBuffer::<1024>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        fill(&mut growable_buffer, &mut MockApi::new().writes([42; 10]));
+        let frozen_buffer = growable_buffer.freeze_shrunk();
+        assert!(frozen_buffer.capacity() == 1024);
+        assert!(contents(&frozen_buffer) == [42; 10]);
+    }
+
+    #[test]
+    fn shrunk_buffer_outlives_the_call() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut mock_api = MockApi::new().needs(100_000).writes([42; 100]);
+        fill(&mut growable_buffer, &mut mock_api);
+        let owned = growable_buffer.freeze_shrunk().into_owned().unwrap();
+        assert!(owned.capacity() == 100_u32.next_multiple_of(ALIGNMENT as u32));
+        assert!(contents(&owned) == [42; 100]);
+    }
+
+    #[test]
+    fn empty_buffer_has_no_capacity() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::with_heap_capacity(4096, &grow_strategy).unwrap();
+        assert!(growable_buffer.freeze_shrunk().capacity() == 0);
+    }
+}
+
+mod uninit_slices {
+    use std::mem::MaybeUninit;
+
+    use windows::Win32::Foundation::{
+        SetLastError, ERROR_BUFFER_OVERFLOW, ERROR_INSUFFICIENT_BUFFER, ERROR_SUCCESS,
+    };
+
+    use grob::{
+        winapi_path_buf, winapi_string_a, winapi_vec, FrozenBuffer, GrowForSmallBinary,
+        GrowableBuffer, RvIsError, RvIsSize, StackBuffer,
+    };
+
+    // Mimics GetModuleFileNameW writing through a pointer and length.
+    fn write_path(buffer: &mut [MaybeUninit<u16>], path: &str) -> u32 {
+        let wide = path.encode_utf16().chain([0]).collect::<Vec<u16>>();
+        if buffer.len() < wide.len() {
+            unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+            return buffer.len() as u32;
+        }
+        for (d, s) in buffer.iter_mut().zip(wide.iter()) {
+            d.write(*s);
+        }
+        unsafe { SetLastError(ERROR_SUCCESS) };
+        wide.len() as u32 - 1
+    }
+
+    #[test]
+    fn wide() {
+        let path = winapi_path_buf(|argument| {
+            RvIsSize::new(write_path(argument.as_uninit_slice(), "C:\\W"))
+        })
+        .unwrap();
+        assert!(path.as_os_str() == "C:\\W");
+    }
+
+    #[test]
+    fn wide_grows() {
+        let long = "x".repeat(1000);
+        let path = winapi_path_buf(|argument| {
+            RvIsSize::new(write_path(argument.as_uninit_slice(), &long))
+        })
+        .unwrap();
+        assert!(path.as_os_str() == long.as_str());
+    }
+
+    #[test]
+    fn narrow() {
+        let narrow = b"C:\\x\0";
+        let s = winapi_string_a(false, |argument| {
+            let buffer = argument.as_uninit_slice();
+            if buffer.len() < narrow.len() {
+                unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+                return RvIsSize::new(buffer.len() as u32);
+            }
+            for (d, s) in buffer.iter_mut().zip(narrow.iter()) {
+                d.write(*s);
+            }
+            RvIsSize::new(narrow.len() as u32 - 1)
+        })
+        .unwrap()
+        .unwrap();
+        assert!(s == "C:\\x");
+    }
+
+    #[test]
+    fn elements() {
+        let v = winapi_vec::<u32, _, _>(|argument| {
+            let elements = argument.as_uninit_elements();
+            if elements.len() < 3000 {
+                *argument.size_mut() = 3000 * 4;
+                return RvIsError::new(ERROR_BUFFER_OVERFLOW.0);
+            }
+            for (i, e) in elements[..3000].iter_mut().enumerate() {
+                e.write(i as u32);
+            }
+            *argument.size_mut() = 3000 * 4;
+            RvIsError::new(ERROR_SUCCESS.0)
+        })
+        .unwrap();
+        assert!(v.len() == 3000);
+        assert!(v.iter().enumerate().all(|(i, e)| *e == i as u32));
+    }
+
+    #[test]
+    fn elements_are_whole() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<[u8; 24], *mut [u8; 24]>::new(&mut initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        assert!(argument.as_uninit_elements().len() == 2);
+        argument.dismiss();
+    }
+
+    #[test]
+    fn zero_fill() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let size = grob::winapi_generic_in(
+            &mut growable_buffer,
+            |argument| {
+                for e in argument.as_uninit_elements().iter_mut() {
+                    e.write(0xFF);
+                }
+                RvIsError::new(ERROR_SUCCESS.0)
+            },
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 64);
+        let sum = grob::winapi_generic_in(
+            &mut growable_buffer,
+            |argument| {
+                argument.zero_fill();
+                RvIsError::new(ERROR_SUCCESS.0)
+            },
+            |frozen_buffer: FrozenBuffer<u8>| {
+                Ok(frozen_buffer
+                    .as_slice()
+                    .iter()
+                    .map(|b| *b as u32)
+                    .sum::<u32>())
+            },
+        )
+        .unwrap();
+        assert!(sum == 0);
+    }
+}
+
+mod zeroed_growable_buffer {
+    use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
+
+    use grob::{FrozenBuffer, GrowForSmallBinary, GrowableBuffer, RvIsError, StackBuffer};
+
+    // Checks the buffer is zero, dirties it, then asks for `needs` bytes until it fits.
+    fn mimic(argument: &mut grob::Argument<*mut u8>, needs: u32, calls: &mut usize) -> RvIsError {
+        *calls += 1;
+        let (pointer, size) = argument.pointer_and_size_mut();
+        let capacity = *size;
+        let buffer = unsafe { std::slice::from_raw_parts_mut(pointer, capacity as usize) };
+        assert!(buffer.iter().all(|b| *b == 0));
+        buffer.fill(0xFF);
+        *size = needs;
+        if capacity < needs {
+            RvIsError::new(ERROR_BUFFER_OVERFLOW.0)
+        } else {
+            RvIsError::new(ERROR_SUCCESS.0)
+        }
+    }
+
+    #[test]
+    fn zero_across_grows() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy).zeroed();
+        let mut calls = 0;
+        let mut needs = [100, 3000].into_iter();
+        let mut current = 0;
+        let size = grob::winapi_generic(
+            growable_buffer,
+            |argument| {
+                current = needs.next().unwrap_or(current);
+                mimic(argument, current, &mut calls)
+            },
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 3000);
+        assert!(calls == 3);
+    }
+
+    #[test]
+    fn zero_after_reset() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::with_heap_capacity(1000, &grow_strategy)
+                .unwrap()
+                .zeroed();
+        for _ in 0..2 {
+            let mut calls = 0;
+            let size = grob::winapi_generic_in(
+                &mut growable_buffer,
+                |argument| mimic(argument, 1000, &mut calls),
+                |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+            )
+            .unwrap();
+            assert!(size == 1000);
+            assert!(calls == 1);
+        }
+    }
+
+    #[test]
+    fn carried_over_data_is_kept() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                .preserve_on_grow()
+                .zeroed();
+        {
+            let mut argument = growable_buffer.argument();
+            argument.as_uninit_elements().iter_mut().for_each(|b| {
+                b.write(0xAB);
+            });
+            *argument.size_mut() = 200;
+            argument.grow().unwrap();
+        }
+        let mut argument = growable_buffer.argument();
+        let kept = argument.resumed_from() as usize;
+        let (pointer, size) = argument.pointer_and_size_mut();
+        let buffer = unsafe { std::slice::from_raw_parts(pointer, *size as usize) };
+        assert!(kept == 64);
+        assert!(buffer[..kept].iter().all(|b| *b == 0xAB));
+        assert!(buffer[kept..].iter().all(|b| *b == 0));
+        argument.dismiss();
+    }
+}
+
+mod over_aligned {
+    use grob::testing::MockApi;
+    use grob::{
+        FrozenBuffer, GrowForSmallBinary, GrowableBuffer, HeapBuffer, StackBuffer, WriteBuffer,
+    };
+
+    #[repr(C, align(64))]
+    #[derive(Clone, Copy)]
+    struct XsaveArea([u8; 64]);
+
+    fn is_aligned(pointer: *const u8, align: usize) -> bool {
+        (pointer as usize).is_multiple_of(align)
+    }
+
+    #[test]
+    fn stack_buffer() {
+        let mut stack_buffer = StackBuffer::<100, 64>::new();
+        assert!(stack_buffer.capacity() == 100);
+        assert!(stack_buffer.alignment() == 64);
+        assert!(is_aligned(stack_buffer.write_buffer().0, 64));
+        assert!(std::mem::align_of::<StackBuffer<100, 64>>() == 64);
+    }
+
+    #[test]
+    fn default_is_alignment() {
+        let stack_buffer = StackBuffer::<100>::new();
+        assert!(stack_buffer.alignment() == grob::ALIGNMENT);
+    }
+
+    #[test]
+    fn survives_stack_to_heap() {
+        let mut initial_buffer = StackBuffer::<64, 64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let mut mock_api = MockApi::new().needs(1000).needs(5000).writes([42; 5000]);
+        let pointer = grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.pointer().unwrap()),
+        )
+        .unwrap();
+        let observations = mock_api.observations();
+        assert!(observations.len() == 3);
+        assert!(observations.iter().all(|o| is_aligned(o.pointer, 64)));
+        assert!(is_aligned(pointer, 64));
+    }
+
+    #[test]
+    fn survives_preserve_on_grow() {
+        let mut initial_buffer = StackBuffer::<64, 128>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy)
+                .preserve_on_grow();
+        let mut mock_api = MockApi::new().needs(1000).writes([42; 1000]);
+        let size = grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<u8>| Ok(frozen_buffer.size()),
+        )
+        .unwrap();
+        assert!(size == 1000);
+        assert!(mock_api
+            .observations()
+            .iter()
+            .all(|o| is_aligned(o.pointer, 128)));
+    }
+
+    #[test]
+    fn element_alignment_is_used_for_the_heap() {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut mock_api = MockApi::new().needs(4096).writes([42; 4096]);
+        let growable_buffer =
+            GrowableBuffer::<XsaveArea, *mut XsaveArea>::with_heap_capacity(100, &grow_strategy)
+                .unwrap();
+        let pointer = grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<XsaveArea>| Ok(frozen_buffer.pointer().unwrap()),
+        )
+        .unwrap();
+        assert!(mock_api
+            .observations()
+            .iter()
+            .all(|o| is_aligned(o.pointer, 64)));
+        assert!(is_aligned(pointer as *const u8, 64));
+    }
+
+    #[test]
+    fn under_aligned_initial_buffer_is_not_used() {
+        let mut initial_buffer = StackBuffer::<1024>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<XsaveArea, *mut XsaveArea>::new(&mut initial_buffer, &grow_strategy);
+        let mut mock_api = MockApi::new().writes([42; 640]);
+        let size = grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<XsaveArea>| {
+                assert!(is_aligned(
+                    frozen_buffer.pointer().unwrap() as *const u8,
+                    64
+                ));
+                Ok(frozen_buffer.size())
+            },
+        )
+        .unwrap();
+        assert!(size == 640);
+        let observations = mock_api.observations();
+        assert!(observations[0].capacity == 0);
+        assert!(observations[1..].iter().all(|o| is_aligned(o.pointer, 64)));
+    }
+
+    #[test]
+    fn copies_keep_element_alignment() {
+        let mut initial_buffer = StackBuffer::<1024, 64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<XsaveArea, *mut XsaveArea>::new(&mut initial_buffer, &grow_strategy);
+        let mut mock_api = MockApi::new().writes([42; 128]);
+        let owned = grob::winapi_generic(
+            growable_buffer,
+            |argument| mock_api.call_with_error_rv(argument),
+            |frozen_buffer: FrozenBuffer<XsaveArea>| Ok(frozen_buffer.into_owned().unwrap()),
+        )
+        .unwrap();
+        assert!(is_aligned(owned.pointer().unwrap() as *const u8, 64));
+        let mut thawed = owned.thaw::<*mut XsaveArea>(&grow_strategy).unwrap();
+        let argument = thawed.argument();
+        assert!(is_aligned(argument.pointer() as *const u8, 64));
+        argument.dismiss();
+    }
+
+    #[test]
+    fn heap_buffer_with_align() {
+        let mut heap_buffer = HeapBuffer::with_align(100, 256).unwrap();
+        assert!(heap_buffer.alignment() == 256);
+        assert!(is_aligned(heap_buffer.write_buffer().0, 256));
+        heap_buffer.grow_to(100_000).unwrap();
+        assert!(is_aligned(heap_buffer.write_buffer().0, 256));
+        assert!(HeapBuffer::with_align(100, 1).unwrap().alignment() == grob::ALIGNMENT);
+        assert!(HeapBuffer::with_align(100, 24).is_err());
+    }
+}
+
+mod owned_growable_buffer {
+    use grob::testing::MockApi;
+    use grob::{FrozenBuffer, GrowForSmallBinary, OwnedGrowableBuffer, PathGrowableBuffer};
+    use grob::{GrowStrategy, RawToInternal, ToResult, CAPACITY_FOR_PATHS};
+
+    type SmallBuffer = OwnedGrowableBuffer<u8, *mut u8, 64, GrowForSmallBinary>;
+
+    // Built in one function and used in another; nothing is borrowed from this frame.
+    fn small_buffer() -> SmallBuffer {
+        SmallBuffer::new(GrowForSmallBinary::new())
+    }
+
+    fn run<FT, IT, const N: usize, S>(
+        growable_buffer: &mut OwnedGrowableBuffer<FT, IT, N, S>,
+        mock_api: &mut MockApi,
+    ) where
+        IT: RawToInternal + Copy,
+        S: GrowStrategy,
+    {
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+    }
+
+    fn contents(frozen_buffer: &FrozenBuffer<u8>) -> Vec<u8> {
+        frozen_buffer.as_slice().to_vec()
+    }
+
+    #[test]
+    fn returned_then_used_from_the_stack_buffer() {
+        let mut growable_buffer = small_buffer();
+        let mut mock_api = MockApi::new().writes([42; 10]);
+        run(&mut growable_buffer, &mut mock_api);
+        let observation = mock_api.observations()[0];
+        assert!(observation.capacity == 64);
+        let frozen_buffer = growable_buffer.freeze();
+        assert!(contents(&frozen_buffer) == [42; 10]);
+        assert!(frozen_buffer.pointer() == Some(observation.pointer));
+    }
+
+    #[test]
+    fn returned_then_grown_to_the_heap() {
+        let mut growable_buffer = small_buffer();
+        let mut mock_api = MockApi::new().needs(300).writes([42; 300]);
+        run(&mut growable_buffer, &mut mock_api);
+        assert!(mock_api.observations()[0].capacity == 64);
+        assert!(mock_api.observations()[1].capacity >= 300);
+        let frozen_buffer = growable_buffer.freeze();
+        assert!(contents(&frozen_buffer) == [42; 300]);
+        assert!(frozen_buffer.stats().switched_to_heap);
+    }
+
+    #[test]
+    fn moved_between_calls() {
+        let mut growable_buffer = small_buffer();
+        let argument = growable_buffer.argument();
+        let before = argument.pointer();
+        argument.dismiss();
+        // The StackBuffer moves with the OwnedGrowableBuffer.
+        let mut moved = Box::new(growable_buffer);
+        let mut mock_api = MockApi::new().writes([42; 10]);
+        run(&mut *moved, &mut mock_api);
+        let observation = mock_api.observations()[0];
+        assert!(observation.capacity == 64);
+        assert!(observation.pointer != before);
+        let frozen_buffer = moved.freeze();
+        assert!(contents(&frozen_buffer) == [42; 10]);
+        assert!(frozen_buffer.pointer() == Some(observation.pointer));
+    }
+
+    #[test]
+    fn starts_over_after_freeze() {
+        let mut growable_buffer = small_buffer();
+        let mut mock_api = MockApi::new().needs(300).writes([42; 300]).writes([7; 10]);
+        run(&mut growable_buffer, &mut mock_api);
+        let owned = growable_buffer.freeze().into_owned().unwrap();
+        run(&mut growable_buffer, &mut mock_api);
+        assert!(mock_api.observations()[2].capacity == 64);
+        assert!(contents(&growable_buffer.freeze()) == [7; 10]);
+        assert!(contents(&owned) == [42; 300]);
+    }
+
+    #[test]
+    fn path_buffer_starts_with_capacity_for_paths() {
+        let mut growable_buffer = PathGrowableBuffer::default();
+        let mut mock_api = MockApi::new().writes([0x41, 0, 0x42, 0]);
+        run(&mut growable_buffer, &mut mock_api);
+        assert!(mock_api.observations()[0].capacity as usize == CAPACITY_FOR_PATHS);
+        assert!(growable_buffer.freeze().size() == 2);
+    }
+}
+
+mod frozen_as_slice {
+    use windows::core::PWSTR;
+
+    use grob::testing::MockApi;
+    use grob::{ElementCount, FrozenBuffer, GrowForSmallBinary, GrowableBuffer, RawToInternal};
+    use grob::{StackBuffer, ToResult};
+
+    fn run<FT, IT>(mock_api: &mut MockApi) -> FrozenBuffer<'static, FT>
+    where
+        IT: RawToInternal + Copy,
+    {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<FT, IT>::new(&mut initial_buffer, &grow_strategy);
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+        growable_buffer.freeze().into_owned().unwrap()
+    }
+
+    fn words(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_ne_bytes()).collect()
+    }
+
+    #[test]
+    fn size_in_bytes() {
+        let mut mock_api = MockApi::new().writes(words(&[7, 11, 13]));
+        let frozen_buffer = run::<u32, *mut u32>(&mut mock_api);
+        assert!(frozen_buffer.size() == 12);
+        assert!(frozen_buffer.as_slice() == [7, 11, 13]);
+    }
+
+    #[test]
+    fn size_in_elements() {
+        let mut mock_api = MockApi::new().writes(words(&[7, 11, 13]));
+        let frozen_buffer = run::<u32, ElementCount<u32>>(&mut mock_api);
+        assert!(frozen_buffer.size() == 3);
+        assert!(frozen_buffer.as_slice() == [7, 11, 13]);
+    }
+
+    #[test]
+    fn size_in_wchars() {
+        let mut mock_api = MockApi::new().writes_wide(&[0x41, 0x42, 0]);
+        let frozen_buffer = run::<u16, PWSTR>(&mut mock_api);
+        assert!(frozen_buffer.as_slice() == [0x41, 0x42, 0]);
+    }
+
+    #[test]
+    fn partial_element_is_dropped() {
+        let mut bytes = words(&[7, 11]);
+        bytes.extend([1, 2]);
+        let mut mock_api = MockApi::new().writes(bytes);
+        let frozen_buffer = run::<u32, *mut u32>(&mut mock_api);
+        assert!(frozen_buffer.as_slice() == [7, 11]);
+    }
+
+    #[test]
+    fn heap_buffer() {
+        let values = (0..100).collect::<Vec<u32>>();
+        let mut mock_api = MockApi::new().needs(400).writes(words(&values));
+        let frozen_buffer = run::<u32, *mut u32>(&mut mock_api);
+        assert!(frozen_buffer.stats().switched_to_heap);
+        assert!(frozen_buffer.as_slice() == values);
+    }
+
+    #[test]
+    fn arrays() {
+        let mut mock_api = MockApi::new().writes(words(&[1, 2, 3, 4]));
+        let frozen_buffer = run::<[u32; 2], *mut [u32; 2]>(&mut mock_api);
+        assert!(frozen_buffer.as_slice() == [[1, 2], [3, 4]]);
+    }
+
+    #[test]
+    fn nothing_stored_is_empty() {
+        let mut mock_api = MockApi::new().writes([]);
+        let frozen_buffer = run::<u8, *mut u8>(&mut mock_api);
+        assert!(frozen_buffer.as_slice().is_empty());
+    }
+}
+
+mod frozen_buffer_bytes {
+    use windows::core::PWSTR;
+
+    use grob::testing::MockApi;
+    use grob::ToResult;
+    use grob::{FrozenBuffer, GrowForSmallBinary, GrowableBuffer, RawToInternal, StackBuffer};
+
+    fn run<FT, IT>(mock_api: &mut MockApi) -> FrozenBuffer<'static, FT>
+    where
+        IT: RawToInternal + Copy,
+    {
+        let mut initial_buffer = StackBuffer::<128>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<FT, IT>::new(&mut initial_buffer, &grow_strategy);
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+        growable_buffer.freeze().into_owned().unwrap()
+    }
+
+    #[test]
+    fn bytes_of_wide_data() {
+        let wide = [0x41_u16, 0x42];
+        let mut mock_api = MockApi::new().writes_wide(&wide);
+        let frozen_buffer = run::<u16, PWSTR>(&mut mock_api);
+        // The size is in WCHARs; the bytes are all of them.
+        assert!(frozen_buffer.size() == 2);
+        let expected = wide
+            .iter()
+            .flat_map(|c| c.to_ne_bytes())
+            .collect::<Vec<u8>>();
+        assert!(frozen_buffer.bytes() == expected);
+    }
+
+    #[test]
+    fn nothing_frozen_is_empty() {
+        let mut initial_buffer = StackBuffer::<128>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let growable_buffer =
+            GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+        let frozen_buffer = growable_buffer.freeze();
+        assert!(frozen_buffer.bytes().is_empty());
+        assert!(
+            format!("{:?}", frozen_buffer)
+                == "FrozenBuffer { element_type: \"u8\", backing: \"Initial\", size: 0, \
+                    last_os_error_hint: None, bytes: [] }"
+        );
+    }
+
+    #[test]
+    fn debug_small() {
+        let mut mock_api = MockApi::new().writes([0x41, 0x42, 0x00, 0xff]);
+        let frozen_buffer = run::<u8, *mut u8>(&mut mock_api);
+        assert!(
+            format!("{:?}", frozen_buffer)
+                == "FrozenBuffer { element_type: \"u8\", backing: \"Heap\", size: 4, \
+                    last_os_error_hint: None, bytes: [41 42 00 ff] }"
+        );
+    }
+
+    #[test]
+    fn debug_is_bounded() {
+        let mut mock_api = MockApi::new().needs(200).writes([0xab; 200]);
+        let frozen_buffer = run::<u8, *mut u8>(&mut mock_api);
+        let s = format!("{:?}", frozen_buffer);
+        let dump = ["ab"; 64].join(" ");
+        assert!(s.ends_with(&format!("bytes: [{} ...] }}", dump)));
+    }
+}
+
+mod record_iterators {
+    use grob::{FrozenBuffer, GrowForSmallBinary, GrowableBuffer, StackBuffer};
+
+    #[repr(C)]
+    struct Node {
+        next: *const Node,
+        value: u32,
+    }
+
+    const NODE: usize = std::mem::size_of::<Node>();
+
+    // Writes nodes at the given slots.  Each link is a slot number or None for the end of the
+    // list.  An offset past the end of the data makes a pointer that escapes the buffer.
+    fn linked<'sb>(
+        initial_buffer: &'sb mut StackBuffer<256>,
+        links: &[Option<usize>],
+        committed: usize,
+    ) -> FrozenBuffer<'sb, Node> {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<Node, *mut Node>::new(initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        let base = argument.pointer();
+        for (slot, link) in links.iter().enumerate() {
+            let next = match link {
+                Some(l) => base.cast::<u8>().wrapping_add(l * NODE).cast::<Node>(),
+                None => std::ptr::null_mut(),
+            };
+            unsafe {
+                base.add(slot).write(Node {
+                    next,
+                    value: slot as u32,
+                })
+            };
+        }
+        *argument.size_mut() = committed as u32;
+        argument.commit();
+        growable_buffer.freeze()
+    }
+
+    fn values<'a>(nodes: impl Iterator<Item = &'a Node>) -> Vec<u32> {
+        nodes.map(|n| n.value).collect()
+    }
+
+    #[test]
+    fn linked_follows_next() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        let frozen_buffer = linked(&mut initial_buffer, &[Some(2), None, Some(1)], 3 * NODE);
+        assert!(values(unsafe { frozen_buffer.iter_linked(|n| n.next) }) == [0, 2, 1]);
+    }
+
+    #[test]
+    fn linked_next_out_of_bounds() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        // The second node points past the committed data.  The memory is inside the StackBuffer
+        // but was not committed.
+        let frozen_buffer = linked(&mut initial_buffer, &[Some(1), Some(3), None], 3 * NODE);
+        assert!(values(unsafe { frozen_buffer.iter_linked(|n| n.next) }) == [0, 1]);
+    }
+
+    #[test]
+    fn linked_next_outside_the_buffer() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        let frozen_buffer = linked(&mut initial_buffer, &[None], NODE);
+        let outside = Node {
+            next: std::ptr::null(),
+            value: 99,
+        };
+        let escape = |_: &Node| &outside as *const Node;
+        assert!(values(unsafe { frozen_buffer.iter_linked(escape) }) == [0]);
+    }
+
+    #[test]
+    fn linked_next_misaligned() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        let frozen_buffer = linked(&mut initial_buffer, &[None, None], 2 * NODE);
+        let misaligned = |n: &Node| (n as *const Node).cast::<u8>().wrapping_add(1).cast();
+        assert!(values(unsafe { frozen_buffer.iter_linked(misaligned) }) == [0]);
+    }
+
+    #[test]
+    fn linked_cycle_ends() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        let frozen_buffer = linked(&mut initial_buffer, &[Some(1), Some(0)], 2 * NODE);
+        assert!(values(unsafe { frozen_buffer.iter_linked(|n| n.next) }) == [0, 1]);
+    }
+
+    #[test]
+    fn linked_nothing_stored() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        let frozen_buffer = linked(&mut initial_buffer, &[], 0);
+        assert!(unsafe { frozen_buffer.iter_linked(|n| n.next) }
+            .next()
+            .is_none());
+    }
+
+    #[repr(C)]
+    struct Record {
+        size: u32,
+        value: u32,
+        // Only present in some records.
+        extra: [u32; 2],
+    }
+
+    // Writes records of the given sizes back to back.
+    fn sized<'sb>(
+        initial_buffer: &'sb mut StackBuffer<256>,
+        sizes: &[u32],
+        committed: u32,
+    ) -> FrozenBuffer<'sb, Record> {
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<Record, *mut Record>::new(initial_buffer, &grow_strategy);
+        let mut argument = growable_buffer.argument();
+        let base = argument.pointer().cast::<u8>();
+        let mut offset = 0;
+        for (i, size) in sizes.iter().enumerate() {
+            let header = [*size, i as u32];
+            unsafe { std::ptr::copy_nonoverlapping(header.as_ptr(), base.add(offset).cast(), 2) };
+            offset += *size as usize;
+        }
+        *argument.size_mut() = committed;
+        argument.commit();
+        growable_buffer.freeze()
+    }
+
+    // Every field of a Record is a u32 so any committed bytes are a valid Record.
+    fn record_values(frozen_buffer: &FrozenBuffer<Record>) -> Vec<u32> {
+        let records = unsafe { frozen_buffer.iter_sized(|r| r.size as usize) };
+        records.map(|r| r.value).collect()
+    }
+
+    #[test]
+    fn sized_advances_by_size() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        let frozen_buffer = sized(&mut initial_buffer, &[16, 8, 16], 40);
+        assert!(record_values(&frozen_buffer) == [0, 1, 2]);
+        // The larger records are whole.
+        let records = unsafe { frozen_buffer.iter_sized(|r| r.size as usize) };
+        assert!(records.filter(|r| r.size == 16).count() == 2);
+    }
+
+    #[test]
+    fn sized_small_record_at_the_end() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        // The last record is whole but a Record at its start would run past the committed data.
+        let frozen_buffer = sized(&mut initial_buffer, &[16, 8], 24);
+        assert!(record_values(&frozen_buffer) == [0]);
+    }
+
+    #[test]
+    fn sized_record_past_the_data() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        // The last record claims 16 bytes but only 8 were committed.
+        let frozen_buffer = sized(&mut initial_buffer, &[8, 16], 16);
+        assert!(record_values(&frozen_buffer) == [0]);
+    }
+
+    #[test]
+    fn sized_zero_size_ends() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        let frozen_buffer = sized(&mut initial_buffer, &[16, 0], 32);
+        assert!(record_values(&frozen_buffer) == [0]);
+    }
+
+    #[test]
+    fn sized_misaligned_ends() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        let frozen_buffer = sized(&mut initial_buffer, &[16, 6], 48);
+        assert!(record_values(&frozen_buffer) == [0, 1]);
+    }
+
+    #[test]
+    fn sized_nothing_stored() {
+        let mut initial_buffer = StackBuffer::<256>::new();
+        let frozen_buffer = sized(&mut initial_buffer, &[], 0);
+        assert!(record_values(&frozen_buffer).is_empty());
+    }
+}
+
+mod frozen_string_list {
+    use std::ffi::OsString;
+
+    use windows::core::PWSTR;
+
+    use grob::testing::MockApi;
+    use grob::{FrozenBuffer, GrowForSmallBinary, GrowableBuffer, StackBuffer, ToResult};
+
+    fn run(block: &str) -> FrozenBuffer<'static, u16> {
+        let mut mock_api = MockApi::new().writes_wide(&block.encode_utf16().collect::<Vec<_>>());
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+        growable_buffer.freeze().into_owned().unwrap()
+    }
+
+    #[test]
+    fn empty() {
+        let frozen_buffer = run("");
+        assert!(frozen_buffer.to_string_list().is_empty());
+        assert!(frozen_buffer.strings().next().is_none());
+        let frozen_buffer = run("\0\0");
+        assert!(frozen_buffer.to_string_list().is_empty());
+    }
+
+    #[test]
+    fn no_data() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+        growable_buffer.argument().commit_no_data();
+        let frozen_buffer = growable_buffer.freeze();
+        assert!(frozen_buffer.to_string_list().is_empty());
+        assert!(frozen_buffer.strings().count() == 0);
+    }
+
+    #[test]
+    fn single_string() {
+        let frozen_buffer = run("C:\\\0\0");
+        assert!(frozen_buffer.to_string_list() == [OsString::from("C:\\")]);
+        let strings: Vec<&[u16]> = frozen_buffer.strings().collect();
+        assert!(strings == [&[0x43, 0x3A, 0x5C][..]]);
+    }
+
+    #[test]
+    fn two_strings() {
+        let frozen_buffer = run("one\0two\0\0");
+        let list = frozen_buffer.to_string_list();
+        assert!(list == [OsString::from("one"), OsString::from("two")]);
+        assert!(frozen_buffer.try_to_multi_sz().unwrap() == list);
+    }
+
+    #[test]
+    fn missing_terminator() {
+        let frozen_buffer = run("one\0two");
+        let list = frozen_buffer.to_string_list();
+        assert!(list == [OsString::from("one"), OsString::from("two")]);
+        let frozen_buffer = run("one");
+        assert!(frozen_buffer.to_string_list() == [OsString::from("one")]);
+    }
+
+    #[test]
+    fn stops_at_the_empty_string() {
+        let frozen_buffer = run("one\0\0ignored\0\0");
+        assert!(frozen_buffer.to_string_list() == [OsString::from("one")]);
+        assert!(frozen_buffer.strings().count() == 1);
+    }
+}
+
+mod nul_policy {
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+
+    use windows::core::PWSTR;
+
+    use grob::testing::MockApi;
+    use grob::{
+        FrozenBuffer, GrowForSmallBinary, GrowableBuffer, NulPolicy, StackBuffer, ToResult,
+    };
+
+    fn run(text: &str) -> FrozenBuffer<'static, u16> {
+        let mut mock_api = MockApi::new().writes_wide(&text.encode_utf16().collect::<Vec<_>>());
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+        loop {
+            let mut argument = growable_buffer.argument();
+            let rv = mock_api.call_with_error_rv(&mut argument);
+            let result = rv.to_result(&mut argument).unwrap();
+            if argument.try_apply(result).unwrap() {
+                break;
+            }
+        }
+        growable_buffer.freeze().into_owned().unwrap()
+    }
+
+    fn check(text: &str, policy: NulPolicy, expected: &str) {
+        let frozen_buffer = run(text);
+        assert!(frozen_buffer.to_os_string_with(policy) == Some(OsString::from(expected)));
+        assert!(frozen_buffer.to_path_buf_with(policy) == Some(PathBuf::from(expected)));
+        assert!(frozen_buffer.to_string_with(false, policy) == Ok(expected.to_string()));
+    }
+
+    #[test]
+    fn strip_one() {
+        check("ab\0\0\0", NulPolicy::StripOne, "ab\0\0");
+        check("a\0b\0", NulPolicy::StripOne, "a\0b");
+    }
+
+    #[test]
+    fn strip_all() {
+        check("ab\0\0\0", NulPolicy::StripAll, "ab");
+        check("a\0b\0", NulPolicy::StripAll, "a\0b");
+        check("\0\0", NulPolicy::StripAll, "");
+    }
+
+    #[test]
+    fn truncate_at_first() {
+        check("ab\0\0\0", NulPolicy::TruncateAtFirst, "ab");
+        check("a\0b\0", NulPolicy::TruncateAtFirst, "a");
+        check("ab", NulPolicy::TruncateAtFirst, "ab");
+    }
+
+    #[test]
+    fn keep() {
+        check("ab\0\0\0", NulPolicy::Keep, "ab\0\0\0");
+        check("a\0b\0", NulPolicy::Keep, "a\0b\0");
+    }
+
+    #[test]
+    fn strip_one_is_the_default() {
+        assert!(NulPolicy::default() == NulPolicy::StripOne);
+        for text in ["ab\0\0\0", "a\0b\0", "ab"] {
+            let frozen_buffer = run(text);
+            let with = frozen_buffer.to_os_string_with(NulPolicy::StripOne);
+            assert!(frozen_buffer.to_os_string() == with);
+        }
+    }
+
+    #[test]
+    fn nothing_stored() {
+        let mut initial_buffer = StackBuffer::<64>::new();
+        let grow_strategy = GrowForSmallBinary::new();
+        let mut growable_buffer =
+            GrowableBuffer::<u16, PWSTR>::new(&mut initial_buffer, &grow_strategy);
+        growable_buffer.argument().commit_no_data();
+        let frozen_buffer = growable_buffer.freeze();
+        for policy in [
+            NulPolicy::StripOne,
+            NulPolicy::StripAll,
+            NulPolicy::TruncateAtFirst,
+            NulPolicy::Keep,
+        ] {
+            assert!(frozen_buffer.to_os_string_with(policy).is_none());
+            assert!(frozen_buffer.to_string_with(false, policy) == Ok(String::new()));
+        }
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
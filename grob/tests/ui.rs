@@ -0,0 +1,42 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Compile tests for the grob_call! macro.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn grob_call() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/grob-call-apply.rs");
+    t.pass("tests/ui/grob-call-match.rs");
+    t.compile_fail("tests/ui/grob-call-missing-call.rs");
+    t.compile_fail("tests/ui/grob-call-body-not-bool.rs");
+}
+
+// Compile tests pinning which types are Send and Sync.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn send_sync() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/send-sync.rs");
+}
+
+// Compile tests pinning the alignments a StackBuffer can be declared with.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn stack_buffer_align() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/stack-buffer-align.rs");
+    #[cfg(all(windows, target_pointer_width = "64"))]
+    t.compile_fail("tests/ui/stack-buffer-align-8.rs");
+}
@@ -0,0 +1,31 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grob::{grob_call, GrowForSmallBinary, GrowableBuffer, RvIsSize, StackBuffer};
+
+fn run() -> Result<u32, std::io::Error> {
+    let mut initial_buffer = StackBuffer::<0>::new();
+    let grow_strategy = GrowForSmallBinary::new();
+    let frozen_buffer = grob_call!(
+        GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy),
+        RvIsSize::new,
+        |argument| *argument.size_mut(),
+        |action| action
+    );
+    Ok(frozen_buffer.size())
+}
+
+fn main() {
+    let _ = run();
+}
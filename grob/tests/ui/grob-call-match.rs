@@ -0,0 +1,55 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grob::StackBuffer;
+use grob::{grob_call, FillBufferAction, GrowForSmallBinary, GrowableBuffer, RvIsSize};
+
+fn mimic(pointer: *mut u8, size: *mut u32) -> u32 {
+    if unsafe { *size } > 4 {
+        unsafe { pointer.write(42) };
+        1
+    } else {
+        unsafe { *size }
+    }
+}
+
+fn run() -> Result<u32, Box<dyn std::error::Error>> {
+    let mut initial_buffer = StackBuffer::<0>::new();
+    let grow_strategy = GrowForSmallBinary::new();
+    let growable_buffer = GrowableBuffer::<u8, *mut u8>::new(&mut initial_buffer, &grow_strategy);
+    let frozen_buffer = grob_call!(
+        growable_buffer,
+        RvIsSize::new,
+        |argument| mimic(argument.pointer(), argument.size_mut()),
+        |action| match action {
+            FillBufferAction::Commit => {
+                argument.commit();
+                true
+            }
+            FillBufferAction::Grow => {
+                argument.grow()?;
+                false
+            }
+            FillBufferAction::NoData => {
+                argument.commit_no_data();
+                true
+            }
+        },
+    );
+    Ok(frozen_buffer.size())
+}
+
+fn main() {
+    let _ = run();
+}
@@ -0,0 +1,44 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grob::{FrozenBuffer, HeapBuffer, StackBuffer, WindowsPathString, WindowsString};
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+// A FrozenBuffer that borrows the initial buffer is Send and Sync too.
+fn borrowed<'sb>() {
+    assert_send::<FrozenBuffer<'sb, u8>>();
+    assert_sync::<FrozenBuffer<'sb, u8>>();
+}
+
+fn main() {
+    borrowed();
+
+    assert_send::<HeapBuffer>();
+    assert_sync::<HeapBuffer>();
+
+    assert_send::<FrozenBuffer<'static, u8>>();
+    assert_sync::<FrozenBuffer<'static, u8>>();
+    assert_send::<FrozenBuffer<'static, u16>>();
+    assert_sync::<FrozenBuffer<'static, u16>>();
+
+    assert_send::<StackBuffer<64>>();
+    assert_sync::<StackBuffer<64>>();
+
+    assert_send::<WindowsString<64>>();
+    assert_sync::<WindowsString<64>>();
+    assert_send::<WindowsPathString>();
+    assert_sync::<WindowsPathString>();
+}
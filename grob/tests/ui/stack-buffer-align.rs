@@ -0,0 +1,24 @@
+// Copyright 2024 Brian Cook (a.k.a. Coding-Badly)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grob::{StackBuffer, ALIGNMENT};
+
+fn main() {
+    let _ = StackBuffer::<64>::new();
+    let _ = StackBuffer::<64, ALIGNMENT>::new();
+    let _ = StackBuffer::<64, 16>::new();
+    let _ = StackBuffer::<64, 32>::new();
+    let _ = StackBuffer::<64, 64>::new();
+    let _ = StackBuffer::<64, 128>::new();
+}